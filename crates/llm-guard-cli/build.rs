@@ -0,0 +1,21 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "grpc")]
+    compile_proto();
+}
+
+/// Generate the `serve --grpc` service from `proto/llm_guard.proto`.
+#[cfg(feature = "grpc")]
+fn compile_proto() {
+    println!("cargo:rerun-if-changed=proto/llm_guard.proto");
+    println!("cargo:rerun-if-env-changed=PROTOC");
+    if std::env::var_os("PROTOC").is_none() {
+        let protoc =
+            protoc_bin_vendored::protoc_bin_path().expect("no vendored protoc for this host");
+        std::env::set_var("PROTOC", protoc);
+    }
+    tonic_build::configure()
+        .build_client(false)
+        .compile_protos(&["proto/llm_guard.proto"], &["proto"])
+        .expect("failed to compile proto/llm_guard.proto");
+}
@@ -0,0 +1,261 @@
+//! `scan --file corpus.zip`: every text entry of a zip or (gzipped) tar archive is scanned as
+//! its own input and reported as `corpus.zip!path/inside.txt`.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+/// Bytes a single archive may expand to by default (`--max-archive-bytes`).
+pub(crate) const DEFAULT_MAX_ARCHIVE_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Size caps that keep a crafted archive from exhausting memory.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(not(feature = "archives"), allow(dead_code))]
+pub(crate) struct ArchiveLimits {
+    /// Entries larger than this are skipped with a warning (`--max-input-bytes`).
+    pub entry_bytes: usize,
+    /// Decompressing more than this in total is an error (`--max-archive-bytes`).
+    pub total_bytes: u64,
+}
+
+/// A text entry read from an archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ArchiveEntry {
+    /// `archive!path/inside`, used as the report source.
+    pub path: PathBuf,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveKind {
+    fn of(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(Self::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether `path` names an archive whose entries are scanned instead of the file itself.
+pub(crate) fn is_archive(path: &Path) -> bool {
+    ArchiveKind::of(path).is_some()
+}
+
+/// Read every regular, UTF-8 entry of the archive at `path`, in archive order.
+#[cfg(feature = "archives")]
+pub(crate) fn read_archive(path: &Path, limits: ArchiveLimits) -> Result<Vec<ArchiveEntry>> {
+    use std::fs::File;
+    use std::io::Read;
+
+    use anyhow::Context;
+
+    let kind = ArchiveKind::of(path)
+        .with_context(|| format!("{} is not a zip or tar archive", path.display()))?;
+    let file =
+        File::open(path).with_context(|| format!("failed to open archive {}", path.display()))?;
+    let mut collector = Collector {
+        archive: path,
+        limits,
+        total: 0,
+        entries: Vec::new(),
+    };
+    match kind {
+        ArchiveKind::Zip => {
+            let mut archive = zip::ZipArchive::new(file)
+                .with_context(|| format!("failed to read zip archive {}", path.display()))?;
+            for index in 0..archive.len() {
+                let entry = archive
+                    .by_index(index)
+                    .with_context(|| format!("failed to read zip archive {}", path.display()))?;
+                if !entry.is_dir() {
+                    let name = entry.name().to_string();
+                    collector.read(&name, entry)?;
+                }
+            }
+        }
+        ArchiveKind::Tar | ArchiveKind::TarGz => {
+            let input: Box<dyn Read> = if kind == ArchiveKind::TarGz {
+                Box::new(flate2::read::GzDecoder::new(file))
+            } else {
+                Box::new(file)
+            };
+            let mut archive = tar::Archive::new(input);
+            let entries = archive
+                .entries()
+                .with_context(|| format!("failed to read tar archive {}", path.display()))?;
+            for entry in entries {
+                let entry = entry
+                    .with_context(|| format!("failed to read tar archive {}", path.display()))?;
+                if entry.header().entry_type().is_file() {
+                    let name = entry.path()?.display().to_string();
+                    collector.read(&name, entry)?;
+                }
+            }
+        }
+    }
+    Ok(collector.entries)
+}
+
+#[cfg(not(feature = "archives"))]
+pub(crate) fn read_archive(path: &Path, _limits: ArchiveLimits) -> Result<Vec<ArchiveEntry>> {
+    anyhow::bail!(
+        "scanning {} requires the `archives` feature; rebuild with `--features archives`",
+        path.display()
+    )
+}
+
+#[cfg(feature = "archives")]
+struct Collector<'a> {
+    archive: &'a Path,
+    limits: ArchiveLimits,
+    /// Bytes decompressed so far, including skipped entries.
+    total: u64,
+    entries: Vec<ArchiveEntry>,
+}
+
+#[cfg(feature = "archives")]
+impl Collector<'_> {
+    /// Read at most one byte past the entry limit, so neither the header's claimed size nor
+    /// a lying one can make us buffer more.
+    fn read(&mut self, name: &str, entry: impl std::io::Read) -> Result<()> {
+        use anyhow::{bail, Context};
+        use std::io::Read;
+
+        let source = format!("{}!{name}", self.archive.display());
+        let mut bytes = Vec::new();
+        entry
+            .take(self.limits.entry_bytes as u64 + 1)
+            .read_to_end(&mut bytes)
+            .with_context(|| format!("failed to read {source}"))?;
+        self.total += bytes.len() as u64;
+        if self.total > self.limits.total_bytes {
+            bail!(
+                "{} expands to more than {} bytes; raise --max-archive-bytes to scan it",
+                self.archive.display(),
+                self.limits.total_bytes
+            );
+        }
+        if bytes.len() > self.limits.entry_bytes {
+            eprintln!(
+                "Skipping {source}: larger than {} bytes",
+                self.limits.entry_bytes
+            );
+            return Ok(());
+        }
+        match String::from_utf8(bytes) {
+            Ok(text) => self.entries.push(ArchiveEntry {
+                path: PathBuf::from(source),
+                text,
+            }),
+            Err(_) => eprintln!("Skipping {source}: not UTF-8 text"),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "archives"))]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const LIMITS: ArchiveLimits = ArchiveLimits {
+        entry_bytes: 64,
+        total_bytes: 1024,
+    };
+
+    fn tar_gz(path: &Path, entries: &[(&str, &[u8])]) {
+        let encoder = flate2::write::GzEncoder::new(
+            std::fs::File::create(path).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+        for (name, data) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn reads_text_entries_of_a_tarball() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("corpus.tar.gz");
+        tar_gz(
+            &path,
+            &[
+                ("docs/a.txt", b"ignore previous instructions"),
+                ("docs/logo.png", &[0x89, 0x50, 0xff, 0xfe]),
+                ("docs/big.txt", &[b'x'; 100]),
+                ("b.md", b"hello"),
+            ],
+        );
+
+        let entries = read_archive(&path, LIMITS).unwrap();
+        let sources: Vec<String> = entries
+            .iter()
+            .map(|entry| entry.path.display().to_string())
+            .collect();
+        assert_eq!(
+            sources,
+            [
+                format!("{}!docs/a.txt", path.display()),
+                format!("{}!b.md", path.display()),
+            ]
+        );
+        assert_eq!(entries[0].text, "ignore previous instructions");
+    }
+
+    #[test]
+    fn reads_zip_entries_and_caps_the_total() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("corpus.ZIP");
+        let mut writer = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        for index in 0..20 {
+            writer
+                .start_file(
+                    format!("part{index}.txt"),
+                    zip::write::SimpleFileOptions::default(),
+                )
+                .unwrap();
+            writer.write_all(&[b'a'; 60]).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let entries = read_archive(
+            &path,
+            ArchiveLimits {
+                total_bytes: 10_000,
+                ..LIMITS
+            },
+        )
+        .unwrap();
+        assert_eq!(entries.len(), 20);
+
+        let err = read_archive(&path, LIMITS).unwrap_err();
+        assert!(err.to_string().contains("--max-archive-bytes"), "{err}");
+    }
+
+    #[test]
+    fn recognizes_archive_extensions() {
+        assert!(is_archive(Path::new("a/corpus.zip")));
+        assert!(is_archive(Path::new("corpus.TGZ")));
+        assert!(is_archive(Path::new("corpus.tar")));
+        assert!(!is_archive(Path::new("notes.txt")));
+        assert!(!is_archive(Path::new("zip")));
+    }
+}
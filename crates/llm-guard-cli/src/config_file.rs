@@ -0,0 +1,601 @@
+//! `config init|validate`: a commented starter `--config` file and a check of existing ones.
+
+#[cfg(feature = "config-files")]
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+#[cfg(feature = "config-files")]
+use llm_guard_core::{
+    AzureAuth, BandDefinition, BandSet, GrayZone, LabelSet, ModelPrice, OpenAiApi, RiskConfig,
+    RiskConfigError, SafetySetting, VerdictBlend, VerdictLabel,
+};
+use serde::Serialize;
+#[cfg(feature = "config-files")]
+use serde_json::Value;
+
+#[cfg(feature = "config-files")]
+use crate::ExitPolicy;
+
+const STARTER_TOML: &str = include_str!("config_file/starter.toml");
+const STARTER_YAML: &str = include_str!("config_file/starter.yaml");
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ConfigFormat {
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// Format implied by the file extension, if any.
+    fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+
+    fn starter(self) -> &'static str {
+        match self {
+            Self::Toml => STARTER_TOML,
+            Self::Yaml => STARTER_YAML,
+        }
+    }
+}
+
+/// Write the starter config to `output` (stdout without one). The format follows `format`,
+/// then the output extension, then defaults to TOML.
+pub(crate) fn init(format: Option<ConfigFormat>, output: Option<&Path>, force: bool) -> Result<()> {
+    let format = format
+        .or_else(|| output.and_then(ConfigFormat::from_path))
+        .unwrap_or(ConfigFormat::Toml);
+    let Some(output) = output else {
+        print!("{}", format.starter());
+        return Ok(());
+    };
+    if output.exists() && !force {
+        bail!(
+            "{} already exists; pass --force to overwrite it",
+            output.display()
+        );
+    }
+    std::fs::write(output, format.starter())
+        .with_context(|| format!("failed to write {}", output.display()))?;
+    eprintln!("Wrote {}", output.display());
+    Ok(())
+}
+
+/// One problem found in a config file, located by its dotted key path.
+#[derive(Debug, Serialize)]
+pub(crate) struct ConfigIssue {
+    pub key: String,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    #[cfg(feature = "config-files")]
+    fn new(key: &str, message: impl Into<String>) -> Self {
+        Self {
+            key: key.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.key, self.message)
+    }
+}
+
+/// Expected shape of a config value.
+#[cfg(feature = "config-files")]
+#[derive(Clone, Copy)]
+enum Kind {
+    /// A string (or number), checked by the parser of the matching `LLM_GUARD_*` variable.
+    Text(fn(&str) -> Result<(), String>),
+    /// A whole number (or a string holding one) of at least `min`.
+    Count {
+        min: u64,
+    },
+    /// A number (or a string holding one) within the inclusive range.
+    Number {
+        min: f64,
+        max: f64,
+    },
+    /// A positive number (or a string holding one).
+    Factor,
+    Flag,
+    Texts,
+    Statuses,
+    Bands,
+    Labels,
+    Prices,
+}
+
+#[cfg(feature = "config-files")]
+const ANY_TEXT: Kind = Kind::Text(|_| Ok(()));
+#[cfg(feature = "config-files")]
+const COUNT: Kind = Kind::Count { min: 0 };
+#[cfg(feature = "config-files")]
+const POSITIVE: Kind = Kind::Count { min: 1 };
+
+/// Tables that only group other keys.
+#[cfg(feature = "config-files")]
+const SECTIONS: &[&str] = &["rules", "scanner", "scanner.thresholds", "llm"];
+
+/// Every key `--config` understands; keep in sync with `apply_config_overrides` and
+/// `config_rules`.
+#[cfg(feature = "config-files")]
+const KEYS: &[(&str, Kind)] = &[
+    ("rules.dirs", Kind::Texts),
+    ("rules.globs", Kind::Texts),
+    ("rules.strict", Kind::Flag),
+    ("scanner.fail_on", ANY_TEXT),
+    ("scanner.max_input_bytes", POSITIVE),
+    ("scanner.max_findings", COUNT),
+    ("scanner.bands", Kind::Bands),
+    (
+        "scanner.thresholds.medium",
+        Kind::Number {
+            min: 0.0,
+            max: 100.0,
+        },
+    ),
+    (
+        "scanner.thresholds.high",
+        Kind::Number {
+            min: 0.0,
+            max: 100.0,
+        },
+    ),
+    ("scanner.baseline_chars", COUNT),
+    ("scanner.min_length_factor", Kind::Factor),
+    ("scanner.max_length_factor", Kind::Factor),
+    (
+        "scanner.family_dampening",
+        Kind::Number { min: 0.0, max: 1.0 },
+    ),
+    ("llm.provider", ANY_TEXT),
+    ("llm.api_key", ANY_TEXT),
+    ("llm.endpoint", ANY_TEXT),
+    ("llm.model", ANY_TEXT),
+    ("llm.providers", Kind::Texts),
+    ("llm.deployment", ANY_TEXT),
+    ("llm.api_version", ANY_TEXT),
+    (
+        "llm.azure_auth",
+        Kind::Text(|v| v.parse::<AzureAuth>().map(drop)),
+    ),
+    (
+        "llm.openai_api",
+        Kind::Text(|v| v.parse::<OpenAiApi>().map(drop)),
+    ),
+    ("llm.project", ANY_TEXT),
+    ("llm.workspace", ANY_TEXT),
+    ("llm.region", ANY_TEXT),
+    ("llm.response_mime_type", ANY_TEXT),
+    (
+        "llm.safety_settings",
+        Kind::Text(|v| SafetySetting::parse_list(v).map(drop)),
+    ),
+    ("llm.prompt_template", ANY_TEXT),
+    ("llm.temperature", Kind::Number { min: 0.0, max: 2.0 }),
+    ("llm.max_tokens", POSITIVE),
+    ("llm.structured_output", Kind::Flag),
+    ("llm.stream", Kind::Flag),
+    ("llm.stream_idle_timeout_secs", POSITIVE),
+    ("llm.prompt_cache", Kind::Flag),
+    ("llm.scrub_pii", Kind::Flag),
+    ("llm.audit_log", ANY_TEXT),
+    (
+        "llm.blend",
+        Kind::Text(|v| v.parse::<VerdictBlend>().map(drop)),
+    ),
+    (
+        "llm.gray_zone",
+        Kind::Text(|v| v.parse::<GrayZone>().map(drop)),
+    ),
+    ("llm.timeout_secs", COUNT),
+    ("llm.max_retries", COUNT),
+    ("llm.retry_base_delay_ms", COUNT),
+    ("llm.retry_max_delay_ms", COUNT),
+    ("llm.retry_jitter", Kind::Flag),
+    ("llm.retry_on_status", Kind::Statuses),
+    ("llm.requests_per_minute", POSITIVE),
+    ("llm.max_concurrency", POSITIVE),
+    ("llm.breaker_failures", POSITIVE),
+    ("llm.breaker_cooldown_secs", COUNT),
+    ("llm.proxy", ANY_TEXT),
+    ("llm.ca_bundle", ANY_TEXT),
+    ("llm.client_cert", ANY_TEXT),
+    ("llm.client_key", ANY_TEXT),
+    ("llm.labels", Kind::Labels),
+    ("llm.prices", Kind::Prices),
+];
+
+/// Load `path` like `--config` does and report every unknown key and invalid value. Files that
+/// do not parse at all are an error.
+#[cfg(feature = "config-files")]
+pub(crate) fn validate(path: &Path) -> Result<Vec<ConfigIssue>> {
+    let settings = config::Config::builder()
+        .add_source(config::File::from(path))
+        .build()
+        .with_context(|| format!("failed to load {}", path.display()))?;
+    let root: Value = settings
+        .try_deserialize()
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(check_config(&root))
+}
+
+#[cfg(not(feature = "config-files"))]
+pub(crate) fn validate(path: &Path) -> Result<Vec<ConfigIssue>> {
+    bail!(
+        "cannot load {}: `config validate` requires the `config-files` feature",
+        path.display()
+    )
+}
+
+/// Print the result of [`validate`]; exits 1 when the file has problems.
+pub(crate) fn run_validate(path: &Path, json: bool) -> Result<i32> {
+    let issues = validate(path)?;
+    if json {
+        let result = serde_json::json!({
+            "file": path.display().to_string(),
+            "valid": issues.is_empty(),
+            "issues": issues,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else if issues.is_empty() {
+        println!("{}: OK", path.display());
+    } else {
+        println!("{}: {} problem(s)", path.display(), issues.len());
+        for issue in &issues {
+            println!("  {issue}");
+        }
+    }
+    Ok(if issues.is_empty() { 0 } else { 1 })
+}
+
+#[cfg(feature = "config-files")]
+fn check_config(root: &Value) -> Vec<ConfigIssue> {
+    let mut issues = Vec::new();
+    let mut bands = None;
+    match root {
+        Value::Object(entries) => check_table("", entries, &mut issues, &mut bands),
+        Value::Null => {}
+        other => issues.push(ConfigIssue::new(
+            "(root)",
+            format!("expected a table, found {}", describe(other)),
+        )),
+    }
+
+    // Settings that are only valid together are checked once every key has been seen; single
+    // out-of-range values were already reported by their key.
+    let scoring = |pointer: &str| root.pointer(pointer).and_then(number);
+    let mut risk_config = RiskConfig {
+        bands,
+        ..RiskConfig::default()
+    };
+    if let Some(medium) = scoring("/scanner/thresholds/medium") {
+        risk_config.thresholds.medium = medium as f32;
+    }
+    if let Some(high) = scoring("/scanner/thresholds/high") {
+        risk_config.thresholds.high = high as f32;
+    }
+    if let Some(factor) = scoring("/scanner/min_length_factor") {
+        risk_config.min_length_factor = factor as f32;
+    }
+    if let Some(factor) = scoring("/scanner/max_length_factor") {
+        risk_config.max_length_factor = factor as f32;
+    }
+    match risk_config.validate() {
+        Err(err @ RiskConfigError::InvertedThresholds { .. }) => {
+            issues.push(ConfigIssue::new("scanner.thresholds", err.to_string()));
+        }
+        Err(err @ RiskConfigError::InvertedLengthFactors { .. }) => {
+            issues.push(ConfigIssue::new(
+                "scanner.min_length_factor",
+                err.to_string(),
+            ));
+        }
+        _ => {}
+    }
+
+    // `fail_on` may name a custom band, so it is checked once the bands are known.
+    if let Some(spec) = root.pointer("/scanner/fail_on").and_then(text) {
+        if let Err(err) = ExitPolicy::resolve(Some(&spec), &risk_config) {
+            issues.push(ConfigIssue::new("scanner.fail_on", format!("{err:#}")));
+        }
+    }
+    issues
+}
+
+/// Check the keys of the table at `prefix` (empty for the root), descending into sections.
+#[cfg(feature = "config-files")]
+fn check_table(
+    prefix: &str,
+    entries: &serde_json::Map<String, Value>,
+    issues: &mut Vec<ConfigIssue>,
+    bands: &mut Option<BandSet>,
+) {
+    for (key, value) in entries {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        if !SECTIONS.contains(&path.as_str()) {
+            if prefix.is_empty() {
+                issues.push(ConfigIssue::new(&path, "unknown section"));
+            } else if let Some(parsed) = check_key(&path, value, issues) {
+                *bands = Some(parsed);
+            }
+            continue;
+        }
+        match value {
+            // A YAML section whose keys are all commented out.
+            Value::Null => {}
+            Value::Object(keys) => check_table(&path, keys, issues, bands),
+            other => issues.push(ConfigIssue::new(
+                &path,
+                format!("expected a table, found {}", describe(other)),
+            )),
+        }
+    }
+}
+
+/// Check one key; returns the parsed band set for `scanner.bands`.
+#[cfg(feature = "config-files")]
+fn check_key(path: &str, value: &Value, issues: &mut Vec<ConfigIssue>) -> Option<BandSet> {
+    let Some((_, kind)) = KEYS.iter().find(|(key, _)| *key == path) else {
+        issues.push(ConfigIssue::new(path, "unknown key"));
+        return None;
+    };
+    let result = match *kind {
+        Kind::Text(check) => match text(value) {
+            Some(text) => check(text.trim()),
+            None => Err(format!("expected a string, found {}", describe(value))),
+        },
+        Kind::Count { min } => match count(value) {
+            Some(n) if n >= min => Ok(()),
+            _ if min > 0 => Err(format!(
+                "expected a positive whole number, found {}",
+                describe(value)
+            )),
+            _ => Err(format!(
+                "expected a whole number, found {}",
+                describe(value)
+            )),
+        },
+        Kind::Number { min, max } => match number(value) {
+            Some(n) if (min..=max).contains(&n) => Ok(()),
+            _ => Err(format!(
+                "expected a number from {min} to {max}, found {}",
+                describe(value)
+            )),
+        },
+        Kind::Factor => match number(value) {
+            Some(n) if n.is_finite() && n > 0.0 => Ok(()),
+            _ => Err(format!(
+                "expected a positive number, found {}",
+                describe(value)
+            )),
+        },
+        Kind::Flag => match value {
+            Value::Bool(_) => Ok(()),
+            Value::String(text) if flag(text).is_some() => Ok(()),
+            other => Err(format!("expected true or false, found {}", describe(other))),
+        },
+        Kind::Texts => match value {
+            Value::Array(items) if items.iter().all(Value::is_string) => Ok(()),
+            other => Err(format!(
+                "expected a list of strings, found {}",
+                describe(other)
+            )),
+        },
+        Kind::Statuses => match value {
+            Value::Array(items) => items
+                .iter()
+                .find(|item| !item.as_u64().is_some_and(|code| (100..600).contains(&code)))
+                .map_or(Ok(()), |item| {
+                    Err(format!("invalid HTTP status {}", describe(item)))
+                }),
+            other => Err(format!(
+                "expected a list of HTTP status codes, found {}",
+                describe(other)
+            )),
+        },
+        Kind::Bands => {
+            match serde_json::from_value::<Vec<BandDefinition>>(value.clone())
+                .map_err(|err| err.to_string())
+                .and_then(|definitions| BandSet::new(definitions).map_err(|err| err.to_string()))
+            {
+                Ok(bands) => return Some(bands),
+                Err(err) => Err(err),
+            }
+        }
+        Kind::Labels => serde_json::from_value::<Vec<VerdictLabel>>(value.clone())
+            .map_err(|err| err.to_string())
+            .and_then(|labels| {
+                LabelSet::new(labels)
+                    .map(drop)
+                    .map_err(|err| err.to_string())
+            }),
+        Kind::Prices => serde_json::from_value::<HashMap<String, ModelPrice>>(value.clone())
+            .map(drop)
+            .map_err(|err| err.to_string()),
+    };
+    if let Err(message) = result {
+        issues.push(ConfigIssue::new(path, message));
+    }
+    None
+}
+
+/// Strings and numbers, which the loader reads as strings alike.
+#[cfg(feature = "config-files")]
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "config-files")]
+fn count(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "config-files")]
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Accepts the spellings `LlmSettings` does for boolean variables.
+#[cfg(feature = "config-files")]
+fn flag(text: &str) -> Option<bool> {
+    match text.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "config-files")]
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Null => "nothing".to_string(),
+        Value::Array(_) => "a list".to_string(),
+        Value::Object(_) => "a table".to_string(),
+        scalar => scalar.to_string(),
+    }
+}
+
+#[cfg(all(test, feature = "config-files"))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn accepts_an_empty_config_and_typed_values() {
+        assert!(check_config(&json!({})).is_empty());
+        assert!(check_config(&json!({"rules": null, "llm": null})).is_empty());
+        let config = json!({
+            "rules": {"dirs": ["rules"], "strict": "yes"},
+            "scanner": {
+                "max_input_bytes": "2048",
+                "fail_on": "warn",
+                "bands": [
+                    {"name": "ok", "min_score": 0.0},
+                    {"name": "warn", "min_score": 30.0, "exit_code": 2}
+                ]
+            },
+            "llm": {
+                "temperature": 0.5,
+                "retry_on_status": [429, 503],
+                "blend": "escalate",
+                "prices": {"gpt-4o-mini": {"input_per_million": 0.15, "output_per_million": 0.6}}
+            }
+        });
+        let issues = check_config(&config);
+        assert!(issues.is_empty(), "{issues:?}");
+    }
+
+    #[test]
+    fn reports_each_problem_with_its_key_path() {
+        let config = json!({
+            "scanner": {"max_input_bytes": 0, "fail_on": "severe", "max_findngs": 5},
+            "llm": {
+                "timeout_secs": "soon",
+                "temperature": 3,
+                "retry_on_status": [429, 42],
+                "openai_api": "assistants",
+                "labels": []
+            },
+            "extra": {}
+        });
+        let issues: Vec<String> = check_config(&config)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        for expected in [
+            "extra: unknown section",
+            "scanner.max_findngs: unknown key",
+            "scanner.max_input_bytes: expected a positive whole number, found 0",
+            "llm.timeout_secs: expected a whole number, found \"soon\"",
+            "llm.temperature: expected a number from 0 to 2, found 3",
+            "llm.retry_on_status: invalid HTTP status 42",
+            "llm.openai_api: ",
+            "llm.labels: ",
+            "scanner.fail_on: ",
+        ] {
+            assert!(
+                issues.iter().any(|issue| issue.starts_with(expected)),
+                "missing `{expected}` in {issues:#?}"
+            );
+        }
+        assert_eq!(issues.len(), 9, "{issues:#?}");
+    }
+
+    #[test]
+    fn checks_scoring_settings_together() {
+        let config = json!({
+            "scanner": {
+                "thresholds": {"medium": 70, "high": 40, "low": 10},
+                "min_length_factor": 2.0,
+                "family_dampening": 1.5
+            }
+        });
+        let mut issues: Vec<String> = check_config(&config)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        issues.sort();
+        assert_eq!(
+            issues,
+            [
+                "scanner.family_dampening: expected a number from 0 to 1, found 1.5",
+                "scanner.thresholds.low: unknown key",
+                "scanner.thresholds: medium threshold (70) must not exceed the high threshold (40)",
+            ],
+        );
+
+        let config = json!({"scanner": {"thresholds": {"medium": 10}, "max_length_factor": 0.2}});
+        let issues: Vec<String> = check_config(&config)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            issues,
+            ["scanner.min_length_factor: min_length_factor (0.5) must not exceed max_length_factor (0.2)"],
+        );
+    }
+
+    #[test]
+    fn starter_configs_validate_cleanly() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, format) in [
+            ("starter.toml", ConfigFormat::Toml),
+            ("starter.yaml", ConfigFormat::Yaml),
+        ] {
+            let path = dir.path().join(name);
+            init(Some(format), Some(&path), false).unwrap();
+            let issues = validate(&path).unwrap();
+            assert!(issues.is_empty(), "{name}: {issues:?}");
+            assert!(init(None, Some(&path), false).is_err());
+        }
+    }
+}
@@ -0,0 +1,32 @@
+//! `docs man`: reference pages generated from the CLI definitions, so they never drift from
+//! `--help`.
+
+use std::path::Path;
+
+use anyhow::Result;
+
+/// Write `llm-guard.1` plus one page per subcommand (`llm-guard-scan.1`,
+/// `llm-guard-rules-stats.1`, ...) into `out_dir`, or print the top-level page to stdout.
+#[cfg(feature = "man")]
+pub(crate) fn man_pages(out_dir: Option<&Path>) -> Result<()> {
+    use anyhow::Context;
+    use clap::CommandFactory;
+
+    let command = super::Cli::command();
+    let Some(out_dir) = out_dir else {
+        return clap_mangen::Man::new(command)
+            .render(&mut std::io::stdout().lock())
+            .context("failed to write the man page");
+    };
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("failed to create {}", out_dir.display()))?;
+    clap_mangen::generate_to(command, out_dir)
+        .with_context(|| format!("failed to write man pages to {}", out_dir.display()))?;
+    eprintln!("Wrote man pages to {}", out_dir.display());
+    Ok(())
+}
+
+#[cfg(not(feature = "man"))]
+pub(crate) fn man_pages(_out_dir: Option<&Path>) -> Result<()> {
+    anyhow::bail!("`docs man` requires the `man` feature; rebuild with `--features man`")
+}
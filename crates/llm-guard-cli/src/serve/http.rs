@@ -0,0 +1,156 @@
+//! HTTP front end: `POST /v1/scan`, `GET /v1/rules`, `GET /healthz`.
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use axum::{
+    body::Bytes,
+    extract::{DefaultBodyLimit, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use llm_guard_core::{render_jsonl, RuleRepository};
+use serde::Deserialize;
+use serde_json::json;
+
+#[cfg(unix)]
+use super::bind_unix;
+use super::{shutdown_signal, Bind, Guard, ScanError, ScanInput, ServeOptions};
+use crate::TranscriptMessage;
+
+pub(super) async fn serve(options: ServeOptions) -> Result<()> {
+    let app = router(options.guard, options.max_input_bytes);
+    match options.bind {
+        Bind::Tcp(addr) => {
+            let listener = tokio::net::TcpListener::bind(addr)
+                .await
+                .with_context(|| format!("failed to listen on {addr}"))?;
+            eprintln!("Listening on http://{}", listener.local_addr()?);
+            axum::serve(listener, app)
+                .with_graceful_shutdown(shutdown_signal())
+                .await
+                .context("HTTP server failed")
+        }
+        #[cfg(unix)]
+        Bind::Unix { path, mode } => {
+            let listener = bind_unix(&path, mode)?;
+            eprintln!("Listening on unix:{}", path.display());
+            let served = axum::serve(listener, app)
+                .with_graceful_shutdown(shutdown_signal())
+                .await
+                .context("HTTP server failed");
+            let _ = std::fs::remove_file(&path);
+            served
+        }
+    }
+}
+
+fn router(guard: Guard, max_input_bytes: usize) -> Router {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/v1/rules", get(rules))
+        .route("/v1/scan", post(scan))
+        .layer(DefaultBodyLimit::max(max_input_bytes))
+        .with_state(Arc::new(guard))
+}
+
+/// `POST /v1/scan` JSON body: either `text` or chat `messages`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ScanRequest {
+    Chat {
+        messages: Vec<TranscriptMessage>,
+        #[serde(default)]
+        source: Option<String>,
+    },
+    Text {
+        text: String,
+        #[serde(default)]
+        source: Option<String>,
+    },
+}
+
+/// An error answered as `{"error": "..."}`.
+struct ApiError(StatusCode, String);
+
+impl ApiError {
+    fn internal(err: anyhow::Error) -> Self {
+        Self(StatusCode::INTERNAL_SERVER_ERROR, format!("{err:#}"))
+    }
+}
+
+impl From<ScanError> for ApiError {
+    fn from(err: ScanError) -> Self {
+        match err {
+            ScanError::Invalid(message) => Self(StatusCode::BAD_REQUEST, message),
+            ScanError::Internal(err) => Self::internal(err),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+async fn healthz(State(guard): State<Arc<Guard>>) -> Result<Response, ApiError> {
+    let rules = guard
+        .scanner
+        .rule_repository()
+        .load_rules()
+        .await
+        .map_err(ApiError::internal)?;
+    Ok(Json(json!({ "status": "ok", "rules": rules.len() })).into_response())
+}
+
+async fn rules(State(guard): State<Arc<Guard>>) -> Result<Response, ApiError> {
+    let mut rules = guard
+        .scanner
+        .rule_repository()
+        .load_rules()
+        .await
+        .map_err(ApiError::internal)?
+        .to_vec();
+    rules.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(Json(rules).into_response())
+}
+
+async fn scan(
+    State(guard): State<Arc<Guard>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, ApiError> {
+    let is_json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    let (input, source) = if is_json {
+        let request = serde_json::from_slice(&body).map_err(|err| {
+            ApiError(
+                StatusCode::BAD_REQUEST,
+                format!("expected {{\"text\": ...}} or {{\"messages\": [...]}}: {err}"),
+            )
+        })?;
+        match request {
+            ScanRequest::Text { text, source } => (ScanInput::Text(text), source),
+            ScanRequest::Chat { messages, source } => (ScanInput::Chat(messages), source),
+        }
+    } else {
+        let text = String::from_utf8(body.to_vec()).map_err(|_| {
+            ApiError(
+                StatusCode::BAD_REQUEST,
+                "request body is not valid UTF-8".into(),
+            )
+        })?;
+        (ScanInput::Text(text), None)
+    };
+
+    let report = guard
+        .scan(input, source.unwrap_or_else(|| "http".to_string()))
+        .await?;
+    let body = render_jsonl(&report).map_err(ApiError::internal)?;
+    Ok(([(header::CONTENT_TYPE, "application/json")], body).into_response())
+}
@@ -0,0 +1,213 @@
+//! `worker kafka`: consume prompts from a topic and produce one JSON report per message.
+//!
+//! Offsets are committed only after the report (or dead letter, or error result) has been
+//! acknowledged by the brokers, so a crash or restart rescans rather than drops messages
+//! (at-least-once).
+
+use std::{borrow::Cow, time::Duration};
+
+use anyhow::{Context, Result};
+use llm_guard_core::render_jsonl;
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::message::{BorrowedMessage, Header, Message, OwnedHeaders};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use serde_json::json;
+
+use super::{decode_job, shutdown_signal, KafkaOptions, ScanError};
+
+/// How long a produced record may wait in the local queue before delivery counts as failed.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub(super) async fn run(options: KafkaOptions) -> Result<()> {
+    let mut consumer_config = ClientConfig::new();
+    consumer_config
+        .set("bootstrap.servers", &options.brokers)
+        .set("group.id", &options.group_id)
+        .set("enable.auto.commit", "false")
+        .set("auto.offset.reset", "earliest");
+    let mut producer_config = ClientConfig::new();
+    producer_config
+        .set("bootstrap.servers", &options.brokers)
+        .set("enable.idempotence", "true");
+    for (key, value) in &options.properties {
+        consumer_config.set(key, value);
+        producer_config.set(key, value);
+    }
+    let consumer: StreamConsumer = consumer_config
+        .create()
+        .context("failed to create Kafka consumer")?;
+    let producer: FutureProducer = producer_config
+        .create()
+        .context("failed to create Kafka producer")?;
+    consumer
+        .subscribe(&[options.input_topic.as_str()])
+        .with_context(|| format!("failed to subscribe to {}", options.input_topic))?;
+    eprintln!(
+        "Consuming {} from {} (group {})",
+        options.input_topic, options.brokers, options.group_id
+    );
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    loop {
+        let message = tokio::select! {
+            _ = &mut shutdown => break,
+            message = consumer.recv() => message.context("failed to consume from Kafka")?,
+        };
+        handle(&options, &producer, &message).await?;
+        consumer
+            .commit_message(&message, CommitMode::Async)
+            .context("failed to commit Kafka offset")?;
+    }
+    // Flush the asynchronous commits; failing here only means a few messages are rescanned.
+    let _ = consumer.commit_consumer_state(CommitMode::Sync);
+    Ok(())
+}
+
+/// Scan one message and produce its report (or the reason the scan failed) to the output topic,
+/// or its payload to the dead-letter topic. An error means nothing was delivered and the offset
+/// must not be committed.
+async fn handle(
+    options: &KafkaOptions,
+    producer: &FutureProducer,
+    message: &BorrowedMessage<'_>,
+) -> Result<()> {
+    let source = format!(
+        "kafka:{}/{}@{}",
+        message.topic(),
+        message.partition(),
+        message.offset()
+    );
+    let payload = message.payload().unwrap_or_default();
+    let scanned = match decode_job(payload, options.max_input_bytes) {
+        Ok((input, job_source)) => {
+            options
+                .guard
+                .scan(input, job_source.unwrap_or_else(|| source.clone()))
+                .await
+        }
+        Err(err) => Err(err),
+    };
+    let error = match scanned {
+        Ok(report) => {
+            let body = render_jsonl(&report)?;
+            let record = FutureRecord::to(&options.output_topic)
+                .payload(body.trim_end().as_bytes())
+                .headers(OwnedHeaders::new().insert(Header {
+                    key: "llm-guard-source",
+                    value: Some(source.as_str()),
+                }));
+            return deliver(producer, keyed(record, message.key())).await;
+        }
+        Err(error) => error,
+    };
+    let (topic, body, reason) = match rejection(error, options.dead_letter_topic.as_deref()) {
+        Rejection::DeadLetter { topic, reason } => (topic, Cow::Borrowed(payload), reason),
+        Rejection::Failed(reason) => {
+            eprintln!("Failed to scan {source}: {reason}");
+            let body = json!({ "error": reason, "source": source }).to_string();
+            (
+                options.output_topic.as_str(),
+                Cow::Owned(body.into_bytes()),
+                reason,
+            )
+        }
+        Rejection::Skip(reason) => {
+            eprintln!("Skipping {source}: {reason}");
+            return Ok(());
+        }
+    };
+    let record = FutureRecord::to(topic).payload(body.as_ref()).headers(
+        OwnedHeaders::new()
+            .insert(Header {
+                key: "llm-guard-source",
+                value: Some(source.as_str()),
+            })
+            .insert(Header {
+                key: "llm-guard-error",
+                value: Some(reason.as_str()),
+            }),
+    );
+    deliver(producer, keyed(record, message.key())).await
+}
+
+/// What becomes of a message that produced no report.
+#[derive(Debug, PartialEq)]
+enum Rejection<'a> {
+    DeadLetter {
+        topic: &'a str,
+        reason: String,
+    },
+    Skip(String),
+    /// An error result takes the report's place on the output topic.
+    Failed(String),
+}
+
+/// Unusable input is dead-lettered, or skipped without a dead-letter topic. A failed scan (e.g.
+/// an unreachable LLM provider) is reported on the output topic instead, so one bad message
+/// cannot stall its partition.
+fn rejection(error: ScanError, dead_letter_topic: Option<&str>) -> Rejection<'_> {
+    let reason = match error {
+        ScanError::Invalid(reason) => reason,
+        ScanError::Internal(err) => return Rejection::Failed(format!("{err:#}")),
+    };
+    match dead_letter_topic {
+        Some(topic) => Rejection::DeadLetter { topic, reason },
+        None => Rejection::Skip(reason),
+    }
+}
+
+fn keyed<'a>(
+    record: FutureRecord<'a, [u8], [u8]>,
+    key: Option<&'a [u8]>,
+) -> FutureRecord<'a, [u8], [u8]> {
+    match key {
+        Some(key) => record.key(key),
+        None => record,
+    }
+}
+
+async fn deliver(producer: &FutureProducer, record: FutureRecord<'_, [u8], [u8]>) -> Result<()> {
+    let topic = record.topic.to_string();
+    producer
+        .send(record, DELIVERY_TIMEOUT)
+        .await
+        .map_err(|(err, _)| err)
+        .with_context(|| format!("failed to produce to Kafka topic {topic}"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_invalid_messages_are_dead_lettered_or_skipped() {
+        let invalid = || ScanError::Invalid("message is not valid UTF-8".into());
+        assert_eq!(
+            rejection(invalid(), Some("prompts.dlq")),
+            Rejection::DeadLetter {
+                topic: "prompts.dlq",
+                reason: "message is not valid UTF-8".into(),
+            }
+        );
+        assert_eq!(
+            rejection(invalid(), None),
+            Rejection::Skip("message is not valid UTF-8".into())
+        );
+    }
+
+    #[test]
+    fn failed_scans_become_error_results() {
+        for topic in [Some("prompts.dlq"), None] {
+            let error = ScanError::Internal(
+                anyhow::anyhow!("connection refused").context("provider unreachable"),
+            );
+            assert_eq!(
+                rejection(error, topic),
+                Rejection::Failed("provider unreachable: connection refused".into())
+            );
+        }
+    }
+}
@@ -0,0 +1,288 @@
+//! `worker redis`: pop scan jobs from a Redis list or stream and write one JSON result per job
+//! to a result stream and/or a result key.
+//!
+//! Stream jobs are acknowledged only after their result is written, and entries a previous run
+//! left unacknowledged are processed first, so stream jobs are delivered at least once.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use ::redis::aio::MultiplexedConnection;
+use ::redis::streams::StreamReadReply;
+use anyhow::{Context, Result};
+use llm_guard_core::render_jsonl;
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use super::{decode_job, shutdown_signal, RedisJobs, RedisOptions, ScanError};
+
+/// Seconds a pop blocks before the worker checks for Ctrl-C again.
+const POP_TIMEOUT_SECS: u64 = 1;
+
+/// A job taken off the queue.
+struct Job {
+    /// Stream entry id, or the `id` field of a JSON list job.
+    id: Option<String>,
+    payload: Vec<u8>,
+    source: String,
+}
+
+pub(super) async fn run(options: RedisOptions) -> Result<()> {
+    let client = ::redis::Client::open(options.url.as_str())
+        .with_context(|| format!("invalid Redis URL `{}`", options.url))?;
+    // Blocking pops get a connection of their own so they never hold up result writes.
+    let mut jobs_conn = client
+        .get_multiplexed_async_connection()
+        .await
+        .with_context(|| format!("failed to connect to {}", options.url))?;
+    let results_conn = client
+        .get_multiplexed_async_connection()
+        .await
+        .with_context(|| format!("failed to connect to {}", options.url))?;
+    if let RedisJobs::Stream { key, group, .. } = &options.jobs {
+        create_group(&mut jobs_conn, key, group).await?;
+    }
+
+    let stopping = Arc::new(AtomicBool::new(false));
+    tokio::spawn({
+        let stopping = stopping.clone();
+        async move {
+            shutdown_signal().await;
+            stopping.store(true, Ordering::SeqCst);
+        }
+    });
+    let limit = Arc::new(Semaphore::new(options.concurrency.get()));
+    let options = Arc::new(options);
+    let mut tasks = JoinSet::new();
+    // Streams first replay the entries this consumer left pending, then take new ones.
+    let mut pending_after = Some("0".to_string());
+    eprintln!("Waiting for jobs on {}", describe(&options.jobs));
+    while !stopping.load(Ordering::SeqCst) {
+        while let Some(finished) = tasks.try_join_next() {
+            finished.context("scan task panicked")??;
+        }
+        let permit = limit
+            .clone()
+            .acquire_owned()
+            .await
+            .context("job limiter closed")?;
+        let Some(job) = pop(&mut jobs_conn, &options.jobs, &mut pending_after).await? else {
+            continue;
+        };
+        let options = options.clone();
+        let mut results_conn = results_conn.clone();
+        tasks.spawn(async move {
+            let _permit = permit;
+            process(&options, &mut results_conn, job).await
+        });
+    }
+    // Let the jobs in flight finish and record their results before exiting.
+    while let Some(finished) = tasks.join_next().await {
+        finished.context("scan task panicked")??;
+    }
+    Ok(())
+}
+
+fn describe(jobs: &RedisJobs) -> String {
+    match jobs {
+        RedisJobs::List(key) => format!("list {key}"),
+        RedisJobs::Stream {
+            key,
+            group,
+            consumer,
+        } => format!("stream {key} (group {group}, consumer {consumer})"),
+    }
+}
+
+/// Create the consumer group (and the stream) unless it exists; new groups start at the end of
+/// the stream.
+async fn create_group(conn: &mut MultiplexedConnection, key: &str, group: &str) -> Result<()> {
+    let created: ::redis::RedisResult<()> = ::redis::cmd("XGROUP")
+        .arg("CREATE")
+        .arg(key)
+        .arg(group)
+        .arg("$")
+        .arg("MKSTREAM")
+        .query_async(conn)
+        .await;
+    match created {
+        Err(err) if err.code() != Some("BUSYGROUP") => {
+            Err(err).with_context(|| format!("failed to create consumer group {group} on {key}"))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Wait up to [`POP_TIMEOUT_SECS`] for the next job. While `pending_after` is set, stream
+/// entries delivered to this consumer before (and still unacknowledged) come first, in order.
+async fn pop(
+    conn: &mut MultiplexedConnection,
+    jobs: &RedisJobs,
+    pending_after: &mut Option<String>,
+) -> Result<Option<Job>> {
+    match jobs {
+        RedisJobs::List(key) => {
+            let popped: Option<(String, Vec<u8>)> = ::redis::cmd("BLPOP")
+                .arg(key)
+                .arg(POP_TIMEOUT_SECS)
+                .query_async(conn)
+                .await
+                .with_context(|| format!("failed to pop a job from {key}"))?;
+            Ok(popped.map(|(_, payload)| {
+                let id = list_job_id(&payload);
+                let source = match &id {
+                    Some(id) => format!("redis:{key}/{id}"),
+                    None => format!("redis:{key}"),
+                };
+                Job {
+                    id,
+                    payload,
+                    source,
+                }
+            }))
+        }
+        RedisJobs::Stream {
+            key,
+            group,
+            consumer,
+        } => {
+            let mut read = ::redis::cmd("XREADGROUP");
+            read.arg("GROUP")
+                .arg(group)
+                .arg(consumer)
+                .arg("COUNT")
+                .arg(1);
+            if pending_after.is_none() {
+                read.arg("BLOCK").arg(POP_TIMEOUT_SECS * 1000);
+            }
+            let reply: Option<StreamReadReply> = read
+                .arg("STREAMS")
+                .arg(key)
+                .arg(pending_after.as_deref().unwrap_or(">"))
+                .query_async(conn)
+                .await
+                .with_context(|| format!("failed to read a job from {key}"))?;
+            let entry = reply
+                .into_iter()
+                .flat_map(|reply| reply.keys)
+                .flat_map(|stream| stream.ids)
+                .next();
+            let Some(entry) = entry else {
+                *pending_after = None;
+                return Ok(None);
+            };
+            if pending_after.is_some() {
+                *pending_after = Some(entry.id.clone());
+            }
+            Ok(Some(Job {
+                source: format!("redis:{key}/{}", entry.id),
+                payload: entry.get("job").unwrap_or_default(),
+                id: Some(entry.id),
+            }))
+        }
+    }
+}
+
+/// The `id` of a JSON list job, as a string or number.
+fn list_job_id(payload: &[u8]) -> Option<String> {
+    #[derive(Deserialize)]
+    struct JobId {
+        id: Option<serde_json::Value>,
+    }
+
+    match serde_json::from_slice::<JobId>(payload).ok()?.id? {
+        serde_json::Value::String(id) => Some(id),
+        serde_json::Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+/// Scan `job`, write its report (or the reason it could not be scanned), then acknowledge it.
+/// An error means the result could not be recorded and stops the worker.
+async fn process(options: &RedisOptions, conn: &mut MultiplexedConnection, job: Job) -> Result<()> {
+    let scanned = match decode_job(&job.payload, options.max_input_bytes) {
+        Ok((input, source)) => {
+            options
+                .guard
+                .scan(input, source.unwrap_or_else(|| job.source.clone()))
+                .await
+        }
+        Err(err) => Err(err),
+    };
+    let (field, value) = match scanned {
+        Ok(report) => ("report", render_jsonl(&report)?.trim_end().to_string()),
+        Err(err) => {
+            let reason = match err {
+                ScanError::Invalid(message) => message,
+                ScanError::Internal(err) => format!("{err:#}"),
+            };
+            eprintln!("Failed to scan {}: {reason}", job.source);
+            (
+                "error",
+                json!({ "error": reason, "source": job.source }).to_string(),
+            )
+        }
+    };
+
+    if let Some(stream) = &options.result_stream {
+        let mut add = ::redis::cmd("XADD");
+        add.arg(stream).arg("*");
+        if let Some(id) = &job.id {
+            add.arg("id").arg(id);
+        }
+        let _: () = add
+            .arg(field)
+            .arg(&value)
+            .query_async(conn)
+            .await
+            .with_context(|| format!("failed to add the result of {} to {stream}", job.source))?;
+    }
+    if let Some(prefix) = &options.result_prefix {
+        let Some(id) = &job.id else {
+            eprintln!(
+                "Not storing the result of {}: the job has no `id`",
+                job.source
+            );
+            return Ok(());
+        };
+        let _: () = ::redis::cmd("SET")
+            .arg(format!("{prefix}{id}"))
+            .arg(&value)
+            .arg("EX")
+            .arg(options.result_ttl)
+            .query_async(conn)
+            .await
+            .with_context(|| format!("failed to store the result of {}", job.source))?;
+    }
+    if let (RedisJobs::Stream { key, group, .. }, Some(id)) = (&options.jobs, &job.id) {
+        let _: () = ::redis::cmd("XACK")
+            .arg(key)
+            .arg(group)
+            .arg(id)
+            .query_async(conn)
+            .await
+            .with_context(|| format!("failed to acknowledge {}", job.source))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_jobs_carry_string_or_numeric_ids() {
+        assert_eq!(
+            list_job_id(br#"{"id": "req-1", "text": "hi"}"#).as_deref(),
+            Some("req-1")
+        );
+        assert_eq!(
+            list_job_id(br#"{"id": 42, "text": "hi"}"#).as_deref(),
+            Some("42")
+        );
+        assert_eq!(list_job_id(br#"{"text": "hi"}"#), None);
+        assert_eq!(list_job_id(b"plain prompt"), None);
+    }
+}
@@ -0,0 +1,453 @@
+//! `scan --syslog-udp/--syslog-tcp/--journald`: scan log messages as they arrive, so the logs
+//! of a chat application can be monitored without custom plumbing.
+
+use std::net::SocketAddr;
+
+use anyhow::{bail, Context, Result};
+use llm_guard_core::{DefaultScanner, FileRuleRepository, RuleStats, Scanner};
+use serde::Deserialize;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+use tokio::signal;
+use tokio::sync::mpsc;
+
+use super::{LlmStage, ReportStream};
+
+pub(crate) struct SyslogOptions {
+    pub udp: Option<SocketAddr>,
+    pub tcp: Option<SocketAddr>,
+    /// Follow the systemd journal through `journalctl`.
+    pub journald: bool,
+    /// `--journald-unit`: units to follow; the whole journal when empty.
+    pub units: Vec<String>,
+    /// Reports scoring below this are scanned but not printed (`--alert-at`).
+    pub alert_at: f32,
+}
+
+/// A received log message and where it came from.
+#[derive(Debug)]
+struct LogMessage {
+    source: String,
+    text: String,
+}
+
+/// Queued messages waiting to be scanned; receivers pause when it is full.
+const QUEUE_CAPACITY: usize = 1024;
+
+pub(crate) async fn run(
+    scanner: &DefaultScanner<FileRuleRepository>,
+    llm: Option<&LlmStage>,
+    options: SyslogOptions,
+    stream: ReportStream<'_>,
+    mut stats: Option<&mut RuleStats>,
+    max_input_bytes: usize,
+) -> Result<i32> {
+    let mut stream = stream.alerting_at(options.alert_at);
+    let (tx, mut rx) = mpsc::channel(QUEUE_CAPACITY);
+    if let Some(addr) = options.udp {
+        let socket = UdpSocket::bind(addr)
+            .await
+            .with_context(|| format!("failed to listen on udp://{addr}"))?;
+        eprintln!("Listening for syslog on udp://{}", socket.local_addr()?);
+        tokio::spawn(receive_udp(socket, tx.clone(), max_input_bytes));
+    }
+    if let Some(addr) = options.tcp {
+        let listener = TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("failed to listen on tcp://{addr}"))?;
+        eprintln!("Listening for syslog on tcp://{}", listener.local_addr()?);
+        tokio::spawn(accept_tcp(listener, tx.clone(), max_input_bytes));
+    }
+    if options.journald {
+        follow_journal(&options.units, tx.clone(), max_input_bytes)?;
+        eprintln!("Following the systemd journal");
+    }
+    drop(tx);
+
+    loop {
+        tokio::select! {
+            message = rx.recv() => {
+                let Some(LogMessage { source, text }) = message else {
+                    break;
+                };
+                let mut report = scanner.scan(&text).await?;
+                report.source = Some(source);
+                stream
+                    .emit(scanner, llm, &text, None, report, stats.as_deref_mut())
+                    .await?;
+            }
+            _ = signal::ctrl_c() => {
+                eprintln!("Stopping log ingestion");
+                break;
+            }
+        }
+    }
+    Ok(stream.exit_code)
+}
+
+/// One message per datagram.
+async fn receive_udp(socket: UdpSocket, tx: mpsc::Sender<LogMessage>, max_input_bytes: usize) {
+    let mut buffer = vec![0; 65_536];
+    loop {
+        let (length, peer) = match socket.recv_from(&mut buffer).await {
+            Ok(received) => received,
+            Err(err) => {
+                eprintln!("Syslog receive error: {err}");
+                continue;
+            }
+        };
+        if length > max_input_bytes {
+            eprintln!(
+                "Skipping syslog message from {peer}: {length} bytes exceed {max_input_bytes}"
+            );
+            continue;
+        }
+        let line = String::from_utf8_lossy(&buffer[..length]);
+        if tx.send(log_message(&line, peer)).await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn accept_tcp(listener: TcpListener, tx: mpsc::Sender<LogMessage>, max_input_bytes: usize) {
+    loop {
+        match listener.accept().await {
+            Ok((connection, peer)) => {
+                tokio::spawn(receive_tcp(connection, peer, tx.clone(), max_input_bytes));
+            }
+            Err(err) => eprintln!("Syslog accept error: {err}"),
+        }
+    }
+}
+
+async fn receive_tcp(
+    connection: TcpStream,
+    peer: SocketAddr,
+    tx: mpsc::Sender<LogMessage>,
+    max_input_bytes: usize,
+) {
+    let mut reader = BufReader::new(connection);
+    loop {
+        match read_frame(&mut reader, max_input_bytes).await {
+            Ok(Some(frame)) => {
+                let line = String::from_utf8_lossy(&frame);
+                if line.trim().is_empty() {
+                    continue;
+                }
+                if tx.send(log_message(&line, peer)).await.is_err() {
+                    return;
+                }
+            }
+            Ok(None) => return,
+            Err(err) => {
+                eprintln!("Closing syslog connection from {peer}: {err:#}");
+                return;
+            }
+        }
+    }
+}
+
+/// Read the next message of a TCP syslog stream, framed by octet counting (`LEN MSG`, RFC
+/// 6587) or terminated by a newline; `None` at the end of the stream.
+async fn read_frame<R>(reader: &mut R, max_input_bytes: usize) -> Result<Option<Vec<u8>>>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let Some(&first) = reader.fill_buf().await?.first() else {
+        return Ok(None);
+    };
+    let mut frame = Vec::new();
+    if first.is_ascii_digit() {
+        let mut length = Vec::new();
+        (&mut *reader)
+            .take(11)
+            .read_until(b' ', &mut length)
+            .await?;
+        let length: usize = std::str::from_utf8(&length)
+            .ok()
+            .and_then(|length| length.trim_end().parse().ok())
+            .context("invalid octet count")?;
+        if length > max_input_bytes {
+            bail!("message of {length} bytes exceeds {max_input_bytes}");
+        }
+        frame.resize(length, 0);
+        reader.read_exact(&mut frame).await?;
+    } else {
+        (&mut *reader)
+            .take(max_input_bytes as u64 + 1)
+            .read_until(b'\n', &mut frame)
+            .await?;
+        if frame.len() > max_input_bytes {
+            bail!("message exceeds {max_input_bytes} bytes");
+        }
+    }
+    Ok(Some(frame))
+}
+
+fn log_message(line: &str, peer: SocketAddr) -> LogMessage {
+    let line = parse_syslog(line.trim_end_matches(['\r', '\n']));
+    let host = line
+        .host
+        .map_or_else(|| peer.ip().to_string(), str::to_string);
+    LogMessage {
+        source: match line.app {
+            Some(app) => format!("syslog:{host}/{app}"),
+            None => format!("syslog:{host}"),
+        },
+        text: line.message.to_string(),
+    }
+}
+
+/// The parts of a syslog line the report needs.
+#[derive(Debug, PartialEq, Eq)]
+struct SyslogLine<'a> {
+    host: Option<&'a str>,
+    app: Option<&'a str>,
+    message: &'a str,
+}
+
+/// Split an RFC 5424 or BSD (RFC 3164) syslog line; a line in neither format is all message.
+fn parse_syslog(line: &str) -> SyslogLine<'_> {
+    let rest = line
+        .strip_prefix('<')
+        .and_then(|rest| {
+            let end = rest.find('>')?;
+            rest[..end]
+                .bytes()
+                .all(|b| b.is_ascii_digit())
+                .then(|| &rest[end + 1..])
+        })
+        .unwrap_or(line);
+    parse_rfc5424(rest)
+        .or_else(|| parse_rfc3164(rest))
+        .unwrap_or(SyslogLine {
+            host: None,
+            app: None,
+            message: rest,
+        })
+}
+
+/// `VERSION TIMESTAMP HOST APP PROCID MSGID [SD] MSG`, with `-` for absent fields.
+fn parse_rfc5424(line: &str) -> Option<SyslogLine<'_>> {
+    let mut fields = line.splitn(7, ' ');
+    let version = fields.next()?;
+    if version.is_empty() || !version.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let _timestamp = fields.next()?;
+    let host = fields.next()?;
+    let app = fields.next()?;
+    let _procid = fields.next()?;
+    let _msgid = fields.next()?;
+    let rest = fields.next().unwrap_or_default();
+    let message = match rest.strip_prefix('-') {
+        Some(message) => message,
+        None => skip_structured_data(rest)?,
+    };
+    Some(SyslogLine {
+        host: (host != "-").then_some(host),
+        app: (app != "-").then_some(app),
+        message: message
+            .strip_prefix(' ')
+            .unwrap_or(message)
+            .trim_start_matches('\u{feff}'),
+    })
+}
+
+/// The text after the `[id key="value" ...]` elements at the start of `rest`.
+fn skip_structured_data(rest: &str) -> Option<&str> {
+    let mut in_element = false;
+    let mut in_value = false;
+    let mut escaped = false;
+    for (index, c) in rest.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_value => escaped = true,
+            '"' if in_element => in_value = !in_value,
+            '[' if !in_element => in_element = true,
+            ']' if in_element && !in_value => in_element = false,
+            _ if !in_element => return Some(&rest[index..]),
+            _ => {}
+        }
+    }
+    (!in_element).then_some("")
+}
+
+/// `Mmm dd hh:mm:ss HOST TAG: MSG`, where the tag may carry a `[pid]`.
+fn parse_rfc3164(line: &str) -> Option<SyslogLine<'_>> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let timestamp = line.get(..15)?;
+    if !MONTHS.contains(&timestamp.get(..3)?) || line.as_bytes().get(15) != Some(&b' ') {
+        return None;
+    }
+    let (host, rest) = line[16..].split_once(' ')?;
+    let (app, message) = match rest.split_once(' ') {
+        Some((tag, message)) if tag.ends_with(':') => {
+            let tag = tag.trim_end_matches(':');
+            (Some(tag.split('[').next().unwrap_or(tag)), message)
+        }
+        _ => (None, rest),
+    };
+    Some(SyslogLine {
+        host: Some(host),
+        app,
+        message,
+    })
+}
+
+/// Run `journalctl --follow` and queue every new entry with a message.
+fn follow_journal(
+    units: &[String],
+    tx: mpsc::Sender<LogMessage>,
+    max_input_bytes: usize,
+) -> Result<()> {
+    use std::io::BufRead;
+    use std::process::{Command, Stdio};
+
+    let mut command = Command::new("journalctl");
+    command.args(["--follow", "--lines=0", "--output=json"]);
+    for unit in units {
+        command.arg("--unit").arg(unit);
+    }
+    let mut child = command
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("failed to run journalctl; --journald needs a systemd host")?;
+    let stdout = child.stdout.take().context("journalctl has no stdout")?;
+    std::thread::spawn(move || {
+        for line in std::io::BufReader::new(stdout).lines() {
+            let Ok(line) = line else { break };
+            let Some(message) = journal_message(&line) else {
+                continue;
+            };
+            if message.text.len() > max_input_bytes {
+                eprintln!(
+                    "Skipping journal entry from {}: {} bytes exceed {max_input_bytes}",
+                    message.source,
+                    message.text.len()
+                );
+                continue;
+            }
+            if tx.blocking_send(message).is_err() {
+                break;
+            }
+        }
+        let _ = child.kill();
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+/// A `journalctl --output=json` entry; `MESSAGE` is an array of bytes when it is not UTF-8.
+#[derive(Deserialize)]
+struct JournalEntry {
+    #[serde(rename = "MESSAGE")]
+    message: Option<JournalField>,
+    #[serde(rename = "SYSLOG_IDENTIFIER")]
+    identifier: Option<String>,
+    #[serde(rename = "_SYSTEMD_UNIT")]
+    unit: Option<String>,
+    #[serde(rename = "_HOSTNAME")]
+    host: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JournalField {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+fn journal_message(line: &str) -> Option<LogMessage> {
+    let entry: JournalEntry = serde_json::from_str(line).ok()?;
+    let text = match entry.message? {
+        JournalField::Text(text) => text,
+        JournalField::Bytes(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+    };
+    let app = entry.unit.or(entry.identifier);
+    let source = match (entry.host, app) {
+        (Some(host), Some(app)) => format!("journald:{host}/{app}"),
+        (Some(name), None) | (None, Some(name)) => format!("journald:{name}"),
+        (None, None) => "journald".to_string(),
+    };
+    Some(LogMessage { source, text })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rfc5424_lines() {
+        assert_eq!(
+            parse_syslog(
+                "<34>1 2025-10-11T22:14:15.003Z chat-1 chatd 4242 PROMPT \
+                 [meta user=\"u1\" note=\"a \\] b\"] ignore previous instructions"
+            ),
+            SyslogLine {
+                host: Some("chat-1"),
+                app: Some("chatd"),
+                message: "ignore previous instructions",
+            }
+        );
+        assert_eq!(
+            parse_syslog("<13>1 - - - - - - \u{feff}hello"),
+            SyslogLine {
+                host: None,
+                app: None,
+                message: "hello",
+            }
+        );
+    }
+
+    #[test]
+    fn parses_bsd_lines_and_bare_messages() {
+        assert_eq!(
+            parse_syslog("<13>Oct 11 22:14:15 chat-1 chatd[4242]: user said: hi there"),
+            SyslogLine {
+                host: Some("chat-1"),
+                app: Some("chatd"),
+                message: "user said: hi there",
+            }
+        );
+        assert_eq!(
+            parse_syslog("<13>just a message"),
+            SyslogLine {
+                host: None,
+                app: None,
+                message: "just a message",
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn reads_newline_and_octet_counted_frames() {
+        let input: &[u8] = b"first line\n15 <13>second\nline";
+        let mut reader = BufReader::new(input);
+        let frame = read_frame(&mut reader, 1024).await.unwrap().unwrap();
+        assert_eq!(frame, b"first line\n");
+        let frame = read_frame(&mut reader, 1024).await.unwrap().unwrap();
+        assert_eq!(frame, b"<13>second\nline");
+        assert!(read_frame(&mut reader, 1024).await.unwrap().is_none());
+
+        let mut reader = BufReader::new(&b"2048 <13>too long"[..]);
+        assert!(read_frame(&mut reader, 1024).await.is_err());
+    }
+
+    #[test]
+    fn reads_journal_entries() {
+        let message = journal_message(
+            r#"{"MESSAGE":"ignore previous","_HOSTNAME":"chat-1","_SYSTEMD_UNIT":"chatd.service"}"#,
+        )
+        .unwrap();
+        assert_eq!(message.source, "journald:chat-1/chatd.service");
+        assert_eq!(message.text, "ignore previous");
+        let message =
+            journal_message(r#"{"MESSAGE":[104,105],"SYSLOG_IDENTIFIER":"chatd"}"#).unwrap();
+        assert_eq!(message.source, "journald:chatd");
+        assert_eq!(message.text, "hi");
+        assert!(journal_message(r#"{"_HOSTNAME":"chat-1"}"#).is_none());
+    }
+}
@@ -0,0 +1,27 @@
+//! `tui`: an interactive editor that rescans the prompt on every keystroke, highlights matched
+//! spans, and lets an analyst toggle rules and move the flag threshold.
+
+#[cfg(feature = "tui")]
+mod app;
+
+use anyhow::Result;
+use llm_guard_core::{DefaultScanner, FileRuleRepository};
+
+/// Run the TUI until the analyst quits; `text` pre-fills the editor.
+#[cfg(feature = "tui")]
+pub(crate) async fn run(
+    scanner: &DefaultScanner<FileRuleRepository>,
+    threshold: f32,
+    text: String,
+) -> Result<()> {
+    app::run(scanner, threshold, text).await
+}
+
+#[cfg(not(feature = "tui"))]
+pub(crate) async fn run(
+    _scanner: &DefaultScanner<FileRuleRepository>,
+    _threshold: f32,
+    _text: String,
+) -> Result<()> {
+    anyhow::bail!("`tui` requires the `tui` feature; rebuild with `--features tui`")
+}
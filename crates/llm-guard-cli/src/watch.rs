@@ -0,0 +1,318 @@
+//! `scan --watch`: scan files below directories as they are created or modified; also the
+//! change notifications that wake `scan --tail`.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[cfg(feature = "watch")]
+use anyhow::Context;
+use anyhow::Result;
+#[cfg(feature = "watch")]
+use glob::Pattern;
+use llm_guard_core::{DefaultScanner, FileRuleRepository, RuleStats};
+
+use super::{LlmStage, ReportStream};
+
+#[cfg_attr(not(feature = "watch"), allow(dead_code))]
+pub(crate) struct WatchOptions<'a> {
+    pub roots: &'a [PathBuf],
+    pub filter: WatchFilter,
+    /// A file is scanned once it has been quiet this long, so a burst of writes yields one
+    /// report.
+    pub debounce: Duration,
+    /// Read each file as a chat transcript (`--transcript`).
+    pub transcript: bool,
+}
+
+/// `--include` / `--exclude` globs, matched against paths relative to the watched directory.
+#[derive(Debug, Default)]
+pub(crate) struct WatchFilter {
+    #[cfg(feature = "watch")]
+    include: Vec<Pattern>,
+    #[cfg(feature = "watch")]
+    exclude: Vec<Pattern>,
+}
+
+impl WatchFilter {
+    #[cfg(feature = "watch")]
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self> {
+        let parse = |flag: &str, patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|pattern| {
+                    Pattern::new(pattern)
+                        .with_context(|| format!("invalid {flag} pattern `{pattern}`"))
+                })
+                .collect::<Result<Vec<_>>>()
+        };
+        Ok(Self {
+            include: parse("--include", include)?,
+            exclude: parse("--exclude", exclude)?,
+        })
+    }
+
+    #[cfg(not(feature = "watch"))]
+    pub fn new(_include: &[String], _exclude: &[String]) -> Result<Self> {
+        Ok(Self {})
+    }
+
+    /// Whether `path`, below one of `roots`, should be scanned. `*` also matches `/`, so
+    /// `*.txt` selects text files at any depth.
+    #[cfg(feature = "watch")]
+    fn accepts(&self, roots: &[PathBuf], path: &Path) -> bool {
+        let relative = roots
+            .iter()
+            .find_map(|root| path.strip_prefix(root).ok())
+            .unwrap_or(path);
+        (self.include.is_empty() || self.include.iter().any(|p| p.matches_path(relative)))
+            && !self.exclude.iter().any(|p| p.matches_path(relative))
+    }
+}
+
+#[cfg(feature = "watch")]
+pub(crate) async fn run(
+    scanner: &DefaultScanner<FileRuleRepository>,
+    llm: Option<&LlmStage>,
+    options: WatchOptions<'_>,
+    mut stream: ReportStream<'_>,
+    mut stats: Option<&mut RuleStats>,
+) -> Result<i32> {
+    use std::collections::HashMap;
+
+    use anyhow::bail;
+    use notify::{Event, RecursiveMode, Watcher};
+    use tokio::{signal, sync::mpsc, time::Instant};
+
+    let mut roots = Vec::with_capacity(options.roots.len());
+    for root in options.roots {
+        // Events carry canonical paths on some platforms; match them against canonical roots.
+        let canonical = root
+            .canonicalize()
+            .with_context(|| format!("cannot watch {}", root.display()))?;
+        if !canonical.is_dir() {
+            bail!(
+                "--watch expects a directory ({} is not one)",
+                root.display()
+            );
+        }
+        roots.push(canonical);
+    }
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<notify::Result<Event>>();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        let _ = tx.send(event);
+    })
+    .context("failed to start the file watcher")?;
+    for root in &roots {
+        watcher
+            .watch(root, RecursiveMode::Recursive)
+            .with_context(|| format!("failed to watch {}", root.display()))?;
+    }
+    let watched: Vec<String> = roots
+        .iter()
+        .map(|root| root.display().to_string())
+        .collect();
+    eprintln!("Watching {} for changes", watched.join(", "));
+
+    // Changed files and when they last changed.
+    let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+    loop {
+        let next_due = pending
+            .values()
+            .min()
+            .map(|changed| *changed + options.debounce);
+        tokio::select! {
+            event = rx.recv() => match event {
+                Some(Ok(event)) if is_change(&event.kind) => {
+                    let now = Instant::now();
+                    for path in event.paths {
+                        if options.filter.accepts(&roots, &path) {
+                            pending.insert(path, now);
+                        }
+                    }
+                }
+                Some(Ok(_)) => {}
+                Some(Err(err)) => eprintln!("Watch error: {err}"),
+                None => break,
+            },
+            _ = tokio::time::sleep_until(next_due.unwrap_or_else(Instant::now)), if next_due.is_some() => {
+                let now = Instant::now();
+                let mut due: Vec<PathBuf> = pending
+                    .iter()
+                    .filter(|(_, changed)| **changed + options.debounce <= now)
+                    .map(|(path, _)| path.clone())
+                    .collect();
+                due.sort();
+                for path in due {
+                    pending.remove(&path);
+                    scan_file(scanner, llm, &path, options.transcript, &mut stream, stats.as_deref_mut()).await?;
+                }
+            }
+            _ = signal::ctrl_c() => {
+                eprintln!("Stopping watch");
+                break;
+            }
+        }
+    }
+    Ok(stream.exit_code)
+}
+
+#[cfg(not(feature = "watch"))]
+pub(crate) async fn run(
+    _scanner: &DefaultScanner<FileRuleRepository>,
+    _llm: Option<&LlmStage>,
+    _options: WatchOptions<'_>,
+    _stream: ReportStream<'_>,
+    _stats: Option<&mut RuleStats>,
+) -> Result<i32> {
+    anyhow::bail!("`scan --watch` requires the `watch` feature; rebuild with `--features watch`")
+}
+
+/// Wakes `scan --tail` when its file changes. The parent directory is watched so that a
+/// rotated file's replacement is noticed too; without the `watch` feature, or where the
+/// platform watcher cannot be set up, it never fires and the tail falls back to polling.
+pub(crate) struct FileChanges {
+    #[cfg(feature = "watch")]
+    inner: Option<(
+        notify::RecommendedWatcher,
+        tokio::sync::mpsc::UnboundedReceiver<()>,
+    )>,
+}
+
+impl FileChanges {
+    #[cfg(feature = "watch")]
+    pub fn new(path: &Path) -> Self {
+        use notify::{Event, RecursiveMode, Watcher};
+
+        let name = path.file_name().map(|name| name.to_os_string());
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let Ok(event) = event else { return };
+            if event
+                .paths
+                .iter()
+                .any(|changed| changed.file_name() == name.as_deref())
+            {
+                let _ = tx.send(());
+            }
+        })
+        .and_then(|mut watcher| {
+            watcher
+                .watch(parent, RecursiveMode::NonRecursive)
+                .map(|()| watcher)
+        });
+        match watcher {
+            Ok(watcher) => Self {
+                inner: Some((watcher, rx)),
+            },
+            Err(err) => {
+                eprintln!(
+                    "Cannot watch {} for changes ({err}); polling instead",
+                    parent.display()
+                );
+                Self { inner: None }
+            }
+        }
+    }
+
+    #[cfg(not(feature = "watch"))]
+    pub fn new(_path: &Path) -> Self {
+        Self {}
+    }
+
+    /// Resolves once the file may have changed; a burst of events resolves once.
+    pub async fn changed(&mut self) {
+        #[cfg(feature = "watch")]
+        if let Some((_, rx)) = self.inner.as_mut() {
+            if rx.recv().await.is_some() {
+                while rx.try_recv().is_ok() {}
+                return;
+            }
+            // The watcher stopped; rely on polling from here on.
+            self.inner = None;
+        }
+        std::future::pending::<()>().await
+    }
+}
+
+/// Writes, creations, and renames into place; metadata-only changes and reads are ignored.
+#[cfg(feature = "watch")]
+fn is_change(kind: &notify::EventKind) -> bool {
+    use notify::event::{EventKind, ModifyKind};
+
+    matches!(kind, EventKind::Create(_) | EventKind::Modify(_))
+        && !matches!(kind, EventKind::Modify(ModifyKind::Metadata(_)))
+}
+
+/// Scan one settled file; unreadable files are skipped with a warning so the watch goes on.
+#[cfg(feature = "watch")]
+async fn scan_file(
+    scanner: &DefaultScanner<FileRuleRepository>,
+    llm: Option<&LlmStage>,
+    path: &Path,
+    transcript: bool,
+    stream: &mut ReportStream<'_>,
+    stats: Option<&mut RuleStats>,
+) -> Result<()> {
+    use llm_guard_core::Scanner;
+
+    // Removed again before it settled, or a directory.
+    if !path.is_file() {
+        return Ok(());
+    }
+    let text = match super::read_input(Some(path), stream.options.max_input_bytes).await {
+        Ok(text) => text,
+        Err(err) => {
+            eprintln!("Skipping {}: {err:#}", path.display());
+            return Ok(());
+        }
+    };
+    let (text, context) = if transcript {
+        match super::split_transcript(scanner, &text).await {
+            Ok((text, context)) => (text, Some(context)),
+            Err(err) => {
+                eprintln!("Skipping {}: {err:#}", path.display());
+                return Ok(());
+            }
+        }
+    } else {
+        (text, None)
+    };
+    let mut report = scanner.scan(&text).await?;
+    report.source = Some(path.display().to_string());
+    stream
+        .emit(scanner, llm, &text, context.as_ref(), report, stats)
+        .await
+}
+
+#[cfg(all(test, feature = "watch"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_matches_paths_below_the_watched_root() {
+        let roots = [PathBuf::from("/srv/prompts")];
+        let filter = WatchFilter::new(
+            &["*.txt".to_string(), "chats/*.json".to_string()],
+            &["drafts/**".to_string()],
+        )
+        .unwrap();
+
+        assert!(filter.accepts(&roots, Path::new("/srv/prompts/a.txt")));
+        assert!(filter.accepts(&roots, Path::new("/srv/prompts/team/b.txt")));
+        assert!(filter.accepts(&roots, Path::new("/srv/prompts/chats/c.json")));
+        assert!(!filter.accepts(&roots, Path::new("/srv/prompts/c.json")));
+        assert!(!filter.accepts(&roots, Path::new("/srv/prompts/drafts/d.txt")));
+        assert!(WatchFilter::default().accepts(&roots, Path::new("/srv/prompts/e.bin")));
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        let err = WatchFilter::new(&[], &["[".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("invalid --exclude pattern"));
+    }
+}
@@ -0,0 +1,22 @@
+#![cfg(not(feature = "keyring"))]
+
+use assert_cmd::Command;
+use predicates::str::contains;
+
+#[test]
+fn auth_set_requires_keyring_feature() {
+    let mut cmd = Command::cargo_bin("llm-guard-cli").unwrap();
+    cmd.args(["auth", "set", "openai", "--key", "sk-test"]);
+    cmd.assert()
+        .failure()
+        .stderr(contains("requires the `keyring` feature"));
+}
+
+#[test]
+fn auth_status_requires_keyring_feature() {
+    let mut cmd = Command::cargo_bin("llm-guard-cli").unwrap();
+    cmd.args(["auth", "status", "openai"]);
+    cmd.assert()
+        .failure()
+        .stderr(contains("requires the `keyring` feature"));
+}
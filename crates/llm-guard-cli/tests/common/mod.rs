@@ -0,0 +1,16 @@
+//! Rule packs shared by the integration tests.
+
+use std::fs::write;
+
+/// A pack whose `keywords.txt` flags "ignore previous" as `INSTR_OVERRIDE` with `weight`,
+/// followed by the `extra` keyword rules (`id|weight|description|pattern`).
+pub fn override_pack(weight: u32, extra: &[&str]) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let mut keywords = format!("INSTR_OVERRIDE|{weight}|Override|ignore previous\n");
+    for rule in extra {
+        keywords.push_str(rule);
+        keywords.push('\n');
+    }
+    write(dir.path().join("keywords.txt"), keywords).unwrap();
+    dir
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "config-files")]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs::{read_to_string, write};
+
+#[test]
+fn config_init_writes_a_starter_that_validates() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("llm-guard.yaml");
+
+    Command::cargo_bin("llm-guard-cli")
+        .unwrap()
+        .args(["config", "init", "--output", path.to_str().unwrap()])
+        .assert()
+        .success();
+    let starter = read_to_string(&path).unwrap();
+    assert!(starter.contains("scanner:"), "{starter}");
+    assert!(starter.contains("# provider: openai"), "{starter}");
+
+    Command::cargo_bin("llm-guard-cli")
+        .unwrap()
+        .args(["config", "init", "--output", path.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+
+    Command::cargo_bin("llm-guard-cli")
+        .unwrap()
+        .args(["--config", path.to_str().unwrap(), "config", "validate"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("OK"));
+
+    Command::cargo_bin("llm-guard-cli")
+        .unwrap()
+        .args(["config", "init"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[llm]"));
+}
+
+#[test]
+fn config_validate_reports_problems_by_key_path() {
+    let file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    write(
+        file.path(),
+        "[scanner]\nfail_on = \"severe\"\n\n[llm]\nmodle = \"gpt-4o\"\ntimeout_secs = \"soon\"\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("llm-guard-cli")
+        .unwrap()
+        .args(["config", "validate", file.path().to_str().unwrap()])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("3 problem(s)"))
+        .stdout(predicate::str::contains("llm.modle: unknown key"))
+        .stdout(predicate::str::contains(
+            "llm.timeout_secs: expected a whole number, found \"soon\"",
+        ))
+        .stdout(predicate::str::contains("scanner.fail_on: "));
+
+    let output = Command::cargo_bin("llm-guard-cli")
+        .unwrap()
+        .args([
+            "config",
+            "validate",
+            "--json",
+            file.path().to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1), "{output:?}");
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["valid"], false);
+    assert_eq!(result["issues"].as_array().unwrap().len(), 3);
+}
+
+#[test]
+fn config_file_selects_rule_packs() {
+    let pack = tempfile::tempdir().unwrap();
+    write(
+        pack.path().join("keywords.txt"),
+        "CONFIGURED_PACK|10|From the config file|configured\n",
+    )
+    .unwrap();
+    let file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    write(
+        file.path(),
+        format!("[rules]\ndirs = [{:?}]\n", pack.path().to_str().unwrap()),
+    )
+    .unwrap();
+
+    Command::cargo_bin("llm-guard-cli")
+        .unwrap()
+        .args(["--config", file.path().to_str().unwrap(), "list-rules"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("CONFIGURED_PACK"));
+}
@@ -0,0 +1,32 @@
+#![cfg(feature = "man")]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+#[test]
+fn man_writes_a_page_per_subcommand() {
+    let out = tempfile::tempdir().unwrap();
+    Command::cargo_bin("llm-guard-cli")
+        .unwrap()
+        .args(["docs", "man", "--out-dir", out.path().to_str().unwrap()])
+        .assert()
+        .success();
+
+    for page in ["llm-guard.1", "llm-guard-scan.1", "llm-guard-rules-stats.1"] {
+        let text = std::fs::read_to_string(out.path().join(page))
+            .unwrap_or_else(|err| panic!("{page}: {err}"));
+        assert!(text.contains(".TH "), "{page}: {text}");
+    }
+    let scan = std::fs::read_to_string(out.path().join("llm-guard-scan.1")).unwrap();
+    assert!(scan.contains("overlap"), "{scan}");
+}
+
+#[test]
+fn man_prints_the_top_level_page_without_out_dir() {
+    Command::cargo_bin("llm-guard-cli")
+        .unwrap()
+        .args(["docs", "man"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(".TH ").and(predicate::str::contains("scan")));
+}
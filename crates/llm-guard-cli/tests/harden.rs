@@ -0,0 +1,36 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+#[test]
+fn harden_suggests_refusal_and_delimiters_for_bare_prompt() {
+    let mut cmd = Command::cargo_bin("llm-guard-cli").unwrap();
+    cmd.arg("harden")
+        .write_stdin("You are a helpful assistant for ACME support.")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[refusal_clause]"))
+        .stdout(predicate::str::contains("[delimiter_strategy]"));
+}
+
+#[test]
+fn harden_emits_json_with_llm_refinement() {
+    let mut cmd = Command::cargo_bin("llm-guard-cli").unwrap();
+    let output = cmd
+        .args(["harden", "--json", "--with-llm", "--provider", "noop"])
+        .env_remove("LLM_GUARD_API_KEY")
+        .write_stdin("You may execute shell commands on behalf of the user.")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let categories: Vec<_> = value["suggestions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["category"].as_str().unwrap().to_string())
+        .collect();
+    assert!(categories.contains(&"tool_permissions".to_string()));
+    assert_eq!(value["llm_refinement"]["label"], "unavailable");
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "llm")]
+
 use assert_cmd::Command;
 use once_cell::sync::Lazy;
 use predicates::str::contains;
@@ -77,3 +79,49 @@ fn health_check_with_openai_profile_dry_run() {
 
     reset_env();
 }
+
+#[test]
+fn health_json_reports_each_provider() {
+    let _guard = ENV_LOCK.lock().unwrap();
+    reset_env();
+
+    let file = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+    write(
+        file.path(),
+        "providers:\n  - name: \"noop\"\n    model: \"probe-model\"\n  - name: \"bogus\"\n    api_key: \"test-key\"\n",
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("llm-guard-cli")
+        .unwrap()
+        .args([
+            "--providers-config",
+            file.path().to_str().unwrap(),
+            "health",
+            "--json",
+        ])
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["ok"], false);
+    assert_eq!(report["dry_run"], false);
+
+    let providers = report["providers"].as_array().unwrap();
+    assert_eq!(providers.len(), 2);
+    assert_eq!(providers[0]["provider"], "bogus");
+    assert_eq!(providers[0]["status"], "failed");
+    assert!(providers[0]["error"]
+        .as_str()
+        .unwrap()
+        .contains("unsupported LLM provider `bogus`"));
+    assert!(providers[0]["latency_ms"].is_null());
+
+    assert_eq!(providers[1]["provider"], "noop");
+    assert_eq!(providers[1]["status"], "ok");
+    assert_eq!(providers[1]["model"], "probe-model");
+    assert!(providers[1]["latency_ms"].is_u64());
+    assert!(providers[1]["error"].is_null());
+}
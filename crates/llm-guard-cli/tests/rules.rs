@@ -0,0 +1,179 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs::write;
+
+fn pack_with_deprecated_rule() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    write(
+        dir.path().join("patterns.json"),
+        r#"[
+            {"id": "OLD_RULE", "description": "Legacy detection", "pattern": "old", "weight": 10,
+             "deprecated": true, "replaced_by": "NEW_RULE"},
+            {"id": "NEW_RULE", "description": "Current detection", "pattern": "new", "weight": 10}
+        ]"#,
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn list_rules_shows_deprecation_status() {
+    let dir = pack_with_deprecated_rule();
+    let mut cmd = Command::cargo_bin("llm-guard-cli").unwrap();
+    cmd.args(["--rules-dir", dir.path().to_str().unwrap(), "list-rules"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[deprecated, use NEW_RULE]"));
+}
+
+#[test]
+fn strict_rules_rejects_deprecated_rules() {
+    let dir = pack_with_deprecated_rule();
+    let mut cmd = Command::cargo_bin("llm-guard-cli").unwrap();
+    cmd.args([
+        "--rules-dir",
+        dir.path().to_str().unwrap(),
+        "--strict-rules",
+        "scan",
+    ])
+    .write_stdin("hello world")
+    .assert()
+    .code(1)
+    .stderr(predicate::str::contains("OLD_RULE"));
+}
+
+#[test]
+fn rule_namespaces_allow_overlapping_packs() {
+    let root = tempfile::tempdir().unwrap();
+    for (pack, pattern) in [("base", "ignore previous"), ("team", "disregard previous")] {
+        let dir = root.path().join(pack);
+        std::fs::create_dir_all(&dir).unwrap();
+        write(
+            dir.join("keywords.txt"),
+            format!("INSTR_OVERRIDE|20|Override|{pattern}\n"),
+        )
+        .unwrap();
+    }
+    let base = root.path().join("base");
+    let team = root.path().join("team");
+
+    let mut cmd = Command::cargo_bin("llm-guard-cli").unwrap();
+    cmd.args([
+        "--rules-dir",
+        base.to_str().unwrap(),
+        "--rules-dir",
+        team.to_str().unwrap(),
+        "list-rules",
+    ])
+    .assert()
+    .code(1)
+    .stderr(predicate::str::contains(
+        "duplicate rule id `INSTR_OVERRIDE`",
+    ));
+
+    let mut cmd = Command::cargo_bin("llm-guard-cli").unwrap();
+    cmd.args([
+        "--rules-dir",
+        base.to_str().unwrap(),
+        "--rules-dir",
+        team.to_str().unwrap(),
+        "--rule-namespaces",
+        "list-rules",
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("base:INSTR_OVERRIDE"))
+    .stdout(predicate::str::contains("team:INSTR_OVERRIDE"));
+}
+
+#[test]
+fn rules_glob_merges_fragments_in_sorted_order() {
+    let root = tempfile::tempdir().unwrap();
+    let fragments = root.path().join("fragments");
+    std::fs::create_dir_all(fragments.join("b-team")).unwrap();
+    write(
+        fragments.join("b-team").join("keywords.txt"),
+        "TEAM_SECRET|20|Team secret|launch codes\n",
+    )
+    .unwrap();
+    write(
+        fragments.join("a-base.json"),
+        r#"[{"id": "BASE_LEAK", "description": "Base leak", "pattern": "system prompt", "weight": 20}]"#,
+    )
+    .unwrap();
+    let pattern = format!("{}/*", fragments.display());
+
+    let mut cmd = Command::cargo_bin("llm-guard-cli").unwrap();
+    let output = cmd
+        .args(["--rules-glob", pattern.as_str(), "list-rules", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let ids: Vec<String> = serde_json::from_slice::<Vec<serde_json::Value>>(&output.stdout)
+        .unwrap()
+        .into_iter()
+        .map(|rule| rule["id"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(ids, vec!["BASE_LEAK", "TEAM_SECRET"]);
+
+    let mut cmd = Command::cargo_bin("llm-guard-cli").unwrap();
+    cmd.env("LLM_GUARD_RULES_GLOB", root.path().join("missing-*"))
+        .arg("list-rules")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("matched no rule files"));
+}
+
+#[test]
+fn manifest_provenance_is_embedded_in_reports() {
+    let dir = pack_with_deprecated_rule();
+    let rules_dir = dir.path().to_str().unwrap();
+    let mut cmd = Command::cargo_bin("llm-guard-cli").unwrap();
+    cmd.args([
+        "--rules-dir",
+        rules_dir,
+        "rules",
+        "manifest",
+        "--pack-version",
+        "2024.06",
+        "--author",
+        "Security Team",
+        "--source",
+        "https://example.com/packs/base",
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("manifest.json"));
+
+    let mut cmd = Command::cargo_bin("llm-guard-cli").unwrap();
+    let output = cmd
+        .args(["--rules-dir", rules_dir, "scan", "--json"])
+        .write_stdin("something new")
+        .output()
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["rule_packs"][0]["author"], "Security Team");
+    assert_eq!(report["rule_packs"][0]["version"], "2024.06");
+    assert_eq!(
+        report["metadata"]["engine_version"],
+        env!("CARGO_PKG_VERSION")
+    );
+    assert_eq!(
+        report["metadata"]["rule_set_digest"]
+            .as_str()
+            .unwrap()
+            .len(),
+        64
+    );
+    assert_eq!(
+        report["rule_packs"][0]["digest"].as_str().unwrap().len(),
+        64
+    );
+
+    write(dir.path().join("patterns.json"), "[]").unwrap();
+    let mut cmd = Command::cargo_bin("llm-guard-cli").unwrap();
+    cmd.args(["--rules-dir", rules_dir, "list-rules"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("checksum mismatch"));
+}
@@ -0,0 +1,16 @@
+#![cfg(feature = "tui")]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+#[test]
+fn tui_requires_an_interactive_terminal() {
+    Command::cargo_bin("llm-guard-cli")
+        .unwrap()
+        .arg("tui")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "`tui` needs an interactive terminal",
+        ));
+}
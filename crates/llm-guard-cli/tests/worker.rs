@@ -0,0 +1,86 @@
+use assert_cmd::Command;
+use predicates::str::contains;
+
+#[cfg(not(all(feature = "kafka", feature = "redis")))]
+mod common;
+
+#[cfg(not(feature = "kafka"))]
+#[test]
+fn worker_kafka_requires_kafka_feature() {
+    let pack = common::override_pack(20, &[]);
+    Command::cargo_bin("llm-guard-cli")
+        .unwrap()
+        .args([
+            "--rules-dir",
+            pack.path().to_str().unwrap(),
+            "worker",
+            "kafka",
+            "--brokers",
+            "localhost:9092",
+            "--input-topic",
+            "prompts",
+            "--output-topic",
+            "reports",
+        ])
+        .assert()
+        .code(1)
+        .stderr(contains("requires the `kafka` feature"));
+}
+
+#[test]
+fn worker_kafka_rejects_malformed_options() {
+    Command::cargo_bin("llm-guard-cli")
+        .unwrap()
+        .args([
+            "worker",
+            "kafka",
+            "--brokers",
+            "localhost:9092",
+            "--input-topic",
+            "prompts",
+            "--output-topic",
+            "reports",
+            "--kafka-option",
+            "security.protocol",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("expected KEY=VALUE"));
+}
+
+#[cfg(not(feature = "redis"))]
+#[test]
+fn worker_redis_requires_redis_feature() {
+    let pack = common::override_pack(20, &[]);
+    Command::cargo_bin("llm-guard-cli")
+        .unwrap()
+        .args([
+            "--rules-dir",
+            pack.path().to_str().unwrap(),
+            "worker",
+            "redis",
+            "--list",
+            "scan-jobs",
+            "--result-prefix",
+            "scan-result:",
+        ])
+        .assert()
+        .code(1)
+        .stderr(contains("requires the `redis` feature"));
+}
+
+#[test]
+fn worker_redis_needs_a_job_source_and_a_result_target() {
+    Command::cargo_bin("llm-guard-cli")
+        .unwrap()
+        .args(["worker", "redis", "--result-stream", "scan-results"])
+        .assert()
+        .failure()
+        .stderr(contains("--list"));
+    Command::cargo_bin("llm-guard-cli")
+        .unwrap()
+        .args(["worker", "redis", "--stream", "scan-jobs"])
+        .assert()
+        .failure()
+        .stderr(contains("--result-stream"));
+}
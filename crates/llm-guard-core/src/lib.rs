@@ -1,12 +1,51 @@
+pub mod harden;
 pub mod llm;
 pub mod report;
 pub mod scanner;
+pub mod stats;
 
-pub use llm::{build_client, LlmClient, LlmSettings, NoopLlmClient, OpenAiClient};
-pub use report::{render_report, OutputFormat};
+pub use harden::{
+    analyze_system_prompt, render_hardening, HardeningCategory, HardeningReport,
+    HardeningSuggestion,
+};
+pub use llm::{
+    build_client, combine_verdicts, compress_input, delete_api_key, enrich_concurrently,
+    load_api_key, register_provider, registered_providers, scrub_pii, store_api_key,
+    unregister_provider, AzureAuth, CircuitBreakerLlmClient, CircuitBreakerPolicy,
+    CircuitOpenError, ConversationTurn, EnsembleLlmClient, FallbackLlmClient, LabelSet,
+    LabelSetError, LabeledLlmClient, LlmClient, LlmSettings, MeteredLlmClient, ModelPrice,
+    NoopLlmClient, OpenAiApi, PriceTable, PromptTemplate, ProviderFactory, ProviderUsage,
+    RateLimit, RequestPreview, RetryPolicy, SafetySetting, ScanContext, ThrottledLlmClient,
+    TimedLlmClient, UsageTracker, VerdictLabel, DEFAULT_BATCH_CONCURRENCY, DEFAULT_MAX_TOKENS,
+    DEFAULT_TEMPERATURE, MAX_CONTEXT_CHARS, MAX_PROMPT_INPUT_CHARS,
+};
+#[cfg(feature = "provenance")]
+pub use llm::{AuditLog, AuditedLlmClient, DEFAULT_AUDIT_PROMPT_CHARS};
+#[cfg(feature = "llm")]
+pub use llm::{
+    AzureDeployment, AzureOpenAiClient, OpenAiClient, SecretBackend, SecretRef, SecretResolver,
+};
+pub use report::{
+    parse_report, render_cef, render_csv, render_github, render_html, render_jsonl, render_leef,
+    render_report, render_report_with, render_summary, render_summary_line, render_template,
+    report_schema, sign_report, verify_report, FindingGrouping, OutputFormat, RenderOptions,
+    ReportSignature, ReportSigner, RuleTally, ScanSummary, SiemFields, SignatureAlgorithm,
+    VerificationKey, REPORT_SCHEMA_VERSION, SUMMARY_TOP_RULES,
+};
+#[cfg(feature = "provenance")]
+pub use scanner::baseline::finding_fingerprint;
 pub use scanner::{
-    default_scanner::DefaultScanner, file_repository::FileRuleRepository, FamilyContribution,
-    Finding, FindingValidationError, LlmVerdict, RiskBand, RiskConfig, RiskThresholds, Rule,
-    RuleKind, RuleRepository, RuleValidationError, ScanReport, Scanner, ScoreBreakdown, Span,
-    VerdictProvider,
+    bands::{BandDefinition, BandSet, BandSetError, BandTier},
+    baseline::{Baseline, BaselineEntry},
+    blend::{LlmAdjustment, VerdictBlend},
+    default_scanner::{DefaultScanner, ScanTimings},
+    file_repository::{CollisionPolicy, FileRuleRepository},
+    gray_zone::GrayZone,
+    manifest::{ManifestEntry, PackManifest, PackProvenance},
+    redact::ExcerptRedaction,
+    rule_family, EnrichmentMeta, EnsembleSummary, FamilyContribution, Finding,
+    FindingValidationError, LlmDegradation, LlmVerdict, Position, ProviderVote, ReportMetadata,
+    RiskBand, RiskConfig, RiskConfigError, RiskThresholds, Rule, RuleKind, RuleRepository,
+    RuleValidationError, ScanReport, Scanner, ScoreBreakdown, Span, TokenUsage, VerdictProvider,
 };
+pub use stats::{RuleStats, RuleStatsRow};
@@ -1,11 +1,15 @@
-use super::{LlmClient, LlmSettings};
-use crate::scanner::{LlmVerdict, ScanReport};
+use super::labels::{VERDICT_TOOL, VERDICT_TOOL_DESCRIPTION};
+use super::{
+    compress_input, http, stream, LlmClient, LlmSettings, PromptTemplate, RequestPreview,
+    RetryPolicy, DEFAULT_MAX_TOKENS, MAX_PROMPT_INPUT_CHARS,
+};
+use crate::scanner::{LlmVerdict, ScanReport, TokenUsage};
 use anyhow::{anyhow, bail, Context, Result};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::future;
 use std::time::Duration;
-use tokio::time::sleep;
 
 #[derive(Debug, Clone)]
 pub struct AnthropicClient {
@@ -13,7 +17,17 @@ pub struct AnthropicClient {
     url: String,
     api_key: String,
     model: String,
-    max_retries: u32,
+    retry: RetryPolicy,
+    prompt: PromptTemplate,
+    /// Ask for the verdict through a forced tool call instead of free-text JSON.
+    structured: bool,
+    /// Stream the reply and stop reading once the verdict object is complete.
+    stream: bool,
+    idle_timeout: Duration,
+    /// Mark the system prompt (and the verdict tool before it) as a cacheable prefix.
+    prompt_cache: bool,
+    temperature: Option<f32>,
+    max_tokens: u32,
 }
 
 impl AnthropicClient {
@@ -26,11 +40,7 @@ impl AnthropicClient {
             .clone()
             .unwrap_or_else(|| "https://api.anthropic.com".to_string());
         let url = format!("{}/v1/messages", base.trim_end_matches('/'));
-        let http = Client::builder()
-            .user_agent("llm-guard/0.1")
-            .timeout(Duration::from_secs(settings.timeout_secs.unwrap_or(30)))
-            .build()
-            .context("failed to build Anthropic HTTP client")?;
+        let http = http::client(settings, "Anthropic")?;
         Ok(Self {
             http,
             url,
@@ -39,104 +49,182 @@ impl AnthropicClient {
                 .model
                 .clone()
                 .unwrap_or_else(|| "claude-3-haiku-20240307".to_string()),
-            max_retries: settings.max_retries,
+            retry: RetryPolicy::from_settings(settings),
+            prompt: PromptTemplate::from_settings(settings)?,
+            structured: settings.structured_output.unwrap_or(true),
+            stream: settings.stream.unwrap_or(false),
+            idle_timeout: settings
+                .stream_idle_timeout_secs
+                .map(Duration::from_secs)
+                .unwrap_or(stream::DEFAULT_IDLE_TIMEOUT),
+            prompt_cache: settings.prompt_cache.unwrap_or(true),
+            temperature: settings.temperature,
+            max_tokens: settings.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
         })
     }
-}
 
-#[async_trait]
-impl LlmClient for AnthropicClient {
-    async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
-        let payload = AnthropicRequest {
+    fn request(&self, input: &str, report: &ScanReport) -> AnthropicRequest {
+        let (tools, tool_choice) = if self.structured {
+            (
+                Some(vec![AnthropicTool {
+                    name: VERDICT_TOOL,
+                    description: VERDICT_TOOL_DESCRIPTION,
+                    input_schema: self.prompt.labels().verdict_schema(),
+                }]),
+                Some(AnthropicToolChoice {
+                    kind: "tool",
+                    name: VERDICT_TOOL,
+                }),
+            )
+        } else {
+            (None, None)
+        };
+        AnthropicRequest {
             model: self.model.clone(),
-            system: SYSTEM_PROMPT.to_string(),
+            system: vec![AnthropicSystemBlock {
+                kind: "text",
+                text: self.prompt.system().to_string(),
+                // The cached prefix covers the tools and the system prompt; prompts below the
+                // model's minimum cacheable length are simply not cached.
+                cache_control: self
+                    .prompt_cache
+                    .then_some(AnthropicCacheControl { kind: "ephemeral" }),
+            }],
             messages: vec![AnthropicMessage {
                 role: "user".into(),
-                content: format!(
-                    "Input excerpt:\n{}\n\nScore: {:.1} ({:?})\nTop findings: {}\n",
-                    truncate(input, 2000),
-                    report.risk_score,
-                    report.risk_band,
-                    serde_json::to_string(&report.findings).unwrap_or_default()
+                content: self.prompt.render_user(
+                    &compress_input(input, report, MAX_PROMPT_INPUT_CHARS),
+                    report,
                 ),
             }],
-            max_tokens: 200,
-        };
-
-        let mut attempt = 0u32;
-        let mut backoff = Duration::from_millis(200);
-        loop {
-            let response = self
-                .http
-                .post(&self.url)
-                .header("x-api-key", &self.api_key)
-                .header("anthropic-version", "2023-06-01")
-                .json(&payload)
-                .send()
-                .await;
-
-            let response = match response {
-                Ok(resp) => resp,
-                Err(err) => {
-                    if attempt >= self.max_retries {
-                        return Err(err).context("failed to call Anthropic messages API");
-                    }
-                    sleep(backoff).await;
-                    backoff = (backoff * 2).min(Duration::from_secs(5));
-                    attempt += 1;
-                    continue;
-                }
-            };
-
-            if !response.status().is_success() {
-                if attempt >= self.max_retries {
-                    let status = response.status();
-                    let body = response.text().await.unwrap_or_default();
-                    bail!("Anthropic API error ({}): {}", status, body);
-                }
-                sleep(backoff).await;
-                backoff = (backoff * 2).min(Duration::from_secs(5));
-                attempt += 1;
-                continue;
-            }
+            max_tokens: self.max_tokens,
+            temperature: self.temperature,
+            tools,
+            tool_choice,
+            stream: self.stream,
+        }
+    }
+}
 
-            let message: AnthropicResponse = response
-                .json()
-                .await
-                .context("failed to parse Anthropic response")?;
-            let content = message
-                .content
-                .into_iter()
-                .find_map(|part| part.text)
-                .ok_or_else(|| anyhow!("Anthropic response missing message content"))?;
+#[async_trait]
+impl LlmClient for AnthropicClient {
+    async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
+        let payload = self.request(input, report);
 
-            let verdict: ModelVerdict = serde_json::from_str(&content)
-                .context("expected JSON verdict from Anthropic response")?;
+        let response = self
+            .retry
+            .send("Anthropic", "messages", |_| {
+                future::ready(Ok(self
+                    .http
+                    .post(&self.url)
+                    .header("x-api-key", &self.api_key)
+                    .header("anthropic-version", "2023-06-01")
+                    .json(&payload)))
+            })
+            .await?;
 
+        if self.stream {
+            // Output token counts arrive in `message_delta`, after streaming stops reading.
+            let json = stream::read_verdict(response, "Anthropic", self.idle_timeout, |data| {
+                stream::anthropic_delta(data, self.structured)
+            })
+            .await?;
+            let verdict: ModelVerdict = serde_json::from_str(&json)
+                .context("expected JSON verdict from Anthropic stream")?;
             return Ok(LlmVerdict {
                 label: verdict.label,
                 rationale: verdict.rationale,
                 mitigation: verdict.mitigation,
+                ..Default::default()
             });
         }
-    }
-}
 
-const SYSTEM_PROMPT: &str = "You are an application security assistant. Analyze prompt-injection scan results and respond with strict JSON: {\"label\": \"safe|suspicious|malicious\", \"rationale\": \"...\", \"mitigation\": \"...\"}. The mitigation should advise remediation steps.";
+        let message: AnthropicResponse = response
+            .json()
+            .await
+            .context("failed to parse Anthropic response")?;
+        let usage = message.usage.as_ref().and_then(AnthropicUsage::token_usage);
+        let tool_input = message.content.iter().find_map(|part| {
+            (part.kind == "tool_use" && part.name.as_deref() == Some(VERDICT_TOOL))
+                .then(|| part.input.clone())
+                .flatten()
+        });
+        let verdict: ModelVerdict = match tool_input {
+            Some(input) => serde_json::from_value(input)
+                .context("expected JSON verdict input in Anthropic tool use")?,
+            None => {
+                let content = message
+                    .content
+                    .into_iter()
+                    .find_map(|part| part.text)
+                    .ok_or_else(|| anyhow!("Anthropic response missing message content"))?;
+                serde_json::from_str(&content)
+                    .context("expected JSON verdict from Anthropic response")?
+            }
+        };
+
+        Ok(LlmVerdict {
+            label: verdict.label,
+            rationale: verdict.rationale,
+            mitigation: verdict.mitigation,
+            usage,
+            ..Default::default()
+        })
+    }
 
-fn truncate(input: &str, max_chars: usize) -> String {
-    if input.chars().count() <= max_chars {
-        return input.to_string();
+    fn preview(&self, input: &str, report: &ScanReport) -> Result<Vec<RequestPreview>> {
+        Ok(vec![RequestPreview {
+            provider: "anthropic".into(),
+            model: self.model.clone(),
+            url: Some(self.url.clone()),
+            body: serde_json::to_value(self.request(input, report))?,
+        }])
     }
-    input.chars().take(max_chars).collect::<String>() + "…"
 }
 
 #[derive(Serialize)]
 struct AnthropicRequest {
     model: String,
-    system: String,
+    system: Vec<AnthropicSystemBlock>,
     messages: Vec<AnthropicMessage>,
     max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<AnthropicTool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<AnthropicToolChoice>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+}
+
+#[derive(Serialize)]
+struct AnthropicSystemBlock {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_control: Option<AnthropicCacheControl>,
+}
+
+#[derive(Serialize)]
+struct AnthropicCacheControl {
+    #[serde(rename = "type")]
+    kind: &'static str,
+}
+
+#[derive(Serialize)]
+struct AnthropicTool {
+    name: &'static str,
+    description: &'static str,
+    input_schema: serde_json::Value,
+}
+
+#[derive(Serialize)]
+struct AnthropicToolChoice {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    name: &'static str,
 }
 
 #[derive(Serialize)]
@@ -148,18 +236,52 @@ struct AnthropicMessage {
 #[derive(Deserialize)]
 struct AnthropicResponse {
     content: Vec<AnthropicContent>,
+    #[serde(default)]
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Deserialize)]
+struct AnthropicUsage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    #[serde(default)]
+    cache_creation_input_tokens: u64,
+    #[serde(default)]
+    cache_read_input_tokens: u64,
+}
+
+impl AnthropicUsage {
+    /// `input_tokens` excludes cached tokens, so they are added back into the prompt count.
+    fn token_usage(&self) -> Option<TokenUsage> {
+        let prompt =
+            self.input_tokens + self.cache_creation_input_tokens + self.cache_read_input_tokens;
+        TokenUsage::reported(prompt, self.output_tokens).map(|usage| {
+            usage.with_cache(
+                self.cache_read_input_tokens,
+                self.cache_creation_input_tokens,
+            )
+        })
+    }
 }
 
 #[derive(Deserialize)]
 struct AnthropicContent {
     #[serde(rename = "type")]
-    _type: String,
+    kind: String,
     #[serde(default)]
     text: Option<String>,
+    /// Tool name and arguments of a `tool_use` block.
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    input: Option<serde_json::Value>,
 }
 
 #[derive(Deserialize)]
 struct ModelVerdict {
+    #[serde(deserialize_with = "super::labels::deserialize_label")]
     label: String,
     rationale: String,
     mitigation: String,
@@ -181,9 +303,31 @@ mod tests {
             deployment: None,
             project: None,
             workspace: None,
+            region: None,
             timeout_secs: Some(5),
             max_retries: 0,
             api_version: None,
+            retry_base_delay_ms: None,
+            retry_max_delay_ms: None,
+            retry_jitter: None,
+            retry_on_status: None,
+            prompt_template: None,
+            verdict_labels: None,
+            structured_output: None,
+            stream: None,
+            stream_idle_timeout_secs: None,
+            proxy: None,
+            ca_bundle: None,
+            client_cert: None,
+            client_key: None,
+            azure_auth: None,
+            scrub_pii: None,
+            prompt_cache: None,
+            openai_api: None,
+            temperature: None,
+            max_tokens: None,
+            response_mime_type: None,
+            safety_settings: None,
         }
     }
 
@@ -218,6 +362,117 @@ mod tests {
         mock.assert();
     }
 
+    #[tokio::test]
+    #[ignore = "requires loopback networking"]
+    async fn enrich_reads_tool_use_input() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1/messages")
+                .body_contains("\"tool_choice\"");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"content":[{"type":"text","text":"Recording the verdict."},{"type":"tool_use","id":"toolu_1","name":"record_verdict","input":{"label":"suspicious","rationale":"line one\nline two","mitigation":"review"}}],"usage":{"input_tokens":90,"output_tokens":40}}"#);
+        });
+
+        let client = AnthropicClient::new(&base_settings(server.base_url())).unwrap();
+        let verdict = client.enrich("hello", &empty_report()).await.unwrap();
+        assert_eq!(verdict.label, "suspicious");
+        assert_eq!(verdict.rationale, "line one\nline two");
+        assert_eq!(verdict.usage, Some(TokenUsage::new(90, 40)));
+        mock.assert();
+    }
+
+    #[test]
+    fn structured_request_forces_the_verdict_tool() {
+        let mut settings = base_settings("http://localhost".into());
+        let client = AnthropicClient::new(&settings).unwrap();
+        let body = serde_json::to_value(client.request("hi", &empty_report())).unwrap();
+        assert_eq!(body["tools"][0]["name"], VERDICT_TOOL);
+        assert_eq!(
+            body["tools"][0]["input_schema"]["required"],
+            serde_json::json!(["label", "rationale", "mitigation"])
+        );
+        assert_eq!(body["tool_choice"]["type"], "tool");
+
+        settings.structured_output = Some(false);
+        let client = AnthropicClient::new(&settings).unwrap();
+        let body = serde_json::to_value(client.request("hi", &empty_report())).unwrap();
+        assert!(body.get("tools").is_none());
+    }
+
+    #[test]
+    fn system_prompt_carries_a_cache_marker_unless_disabled() {
+        let mut settings = base_settings("http://localhost".into());
+        let client = AnthropicClient::new(&settings).unwrap();
+        let body = serde_json::to_value(client.request("hi", &empty_report())).unwrap();
+        assert_eq!(body["system"][0]["type"], "text");
+        assert_eq!(body["system"][0]["cache_control"]["type"], "ephemeral");
+        assert!(body["messages"][0].get("cache_control").is_none());
+
+        settings.prompt_cache = Some(false);
+        let client = AnthropicClient::new(&settings).unwrap();
+        let body = serde_json::to_value(client.request("hi", &empty_report())).unwrap();
+        assert!(body["system"][0].get("cache_control").is_none());
+        assert!(!body["system"][0]["text"].as_str().unwrap().is_empty());
+    }
+
+    #[test]
+    fn token_cap_and_temperature_follow_settings() {
+        let mut settings = base_settings("http://localhost".into());
+        let client = AnthropicClient::new(&settings).unwrap();
+        let body = serde_json::to_value(client.request("hi", &empty_report())).unwrap();
+        assert_eq!(body["max_tokens"], DEFAULT_MAX_TOKENS);
+        assert!(body.get("temperature").is_none());
+
+        settings.max_tokens = Some(1024);
+        settings.temperature = Some(0.0);
+        let client = AnthropicClient::new(&settings).unwrap();
+        let body = serde_json::to_value(client.request("hi", &empty_report())).unwrap();
+        assert_eq!(body["max_tokens"], 1024);
+        assert_eq!(body["temperature"], 0.0);
+    }
+
+    #[test]
+    fn usage_counts_cached_prompt_tokens() {
+        let usage: AnthropicUsage = serde_json::from_str(
+            r#"{"input_tokens":20,"cache_creation_input_tokens":0,"cache_read_input_tokens":1500,"output_tokens":40}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            usage.token_usage(),
+            Some(TokenUsage::new(1520, 40).with_cache(1500, 0))
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires loopback networking"]
+    async fn enrich_stops_reading_once_streamed_verdict_is_complete() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1/messages")
+                .body_contains("\"stream\":true");
+            // No `message_stop`: the verdict must be returned without waiting for it.
+            then.status(200)
+                .header("content-type", "text/event-stream")
+                .body(concat!(
+                    "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"usage\":{\"input_tokens\":90}}}\n\n",
+                    "event: content_block_start\ndata: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"tool_use\",\"name\":\"record_verdict\",\"input\":{}}}\n\n",
+                    "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\"{\\\"label\\\": \\\"malicious\\\", \\\"rationale\\\": \\\"override\\\"\"}}\n\n",
+                    "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\", \\\"mitigation\\\": \\\"block\\\"}\"}}\n\n",
+                ));
+        });
+
+        let mut settings = base_settings(server.base_url());
+        settings.stream = Some(true);
+        let client = AnthropicClient::new(&settings).unwrap();
+        let verdict = client.enrich("hello", &empty_report()).await.unwrap();
+        assert_eq!(verdict.label, "malicious");
+        assert_eq!(verdict.mitigation, "block");
+        mock.assert();
+    }
+
     #[tokio::test]
     #[ignore = "requires loopback networking"]
     async fn retries_on_failure() {
@@ -234,15 +489,4 @@ mod tests {
         assert!(err.to_string().contains("Anthropic API error"));
         mock.assert_hits(2);
     }
-
-    #[test]
-    fn truncate_short_strings_return_same() {
-        assert_eq!(truncate("abc", 10), "abc");
-    }
-
-    #[test]
-    fn truncate_long_strings_adds_ellipsis() {
-        let result = truncate("abcdefghijklmnopqrstuvwxyz", 5);
-        assert_eq!(result, "abcde…");
-    }
 }
@@ -1,149 +1,614 @@
-use super::{LlmClient, LlmSettings};
-use crate::scanner::{LlmVerdict, ScanReport};
+use super::{
+    compress_input, http, AzureAuth, LlmClient, LlmSettings, PromptTemplate, RequestPreview,
+    RetryPolicy, DEFAULT_MAX_TOKENS, DEFAULT_TEMPERATURE, MAX_PROMPT_INPUT_CHARS,
+};
+use crate::scanner::{LlmVerdict, ScanReport, TokenUsage};
 use anyhow::{anyhow, bail, Context, Result};
 use async_trait::async_trait;
 use reqwest::Client;
-use serde::{Deserialize, Serialize};
-use std::time::Duration;
-use tokio::time::sleep;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-#[derive(Debug, Clone)]
+/// Entra ID scope (and managed-identity resource) for Azure OpenAI / Cognitive Services.
+const COGNITIVE_SERVICES_RESOURCE: &str = "https://cognitiveservices.azure.com";
+const DEFAULT_AUTHORITY_HOST: &str = "https://login.microsoftonline.com";
+const IMDS_TOKEN_URL: &str = "http://169.254.169.254/metadata/identity/oauth2/token";
+/// Refresh tokens this long before Entra ID reports them as expired.
+const TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+/// Data-plane API version of the deployments listing; later versions dropped the operation.
+const DEPLOYMENTS_API_VERSION: &str = "2022-12-01";
+
+/// How requests to the Azure OpenAI deployment are authenticated.
+#[derive(Clone)]
+enum Credential {
+    /// The resource key, sent as the `api-key` header.
+    ApiKey(String),
+    /// Entra ID access tokens, sent as `Authorization: Bearer` and refreshed before expiry.
+    Entra(TokenSource),
+}
+
+/// Where Entra ID access tokens come from, in the order the Azure SDKs' environment and
+/// managed identity credentials check them.
+#[derive(Clone)]
+enum TokenSource {
+    /// A service principal with a client secret (`AZURE_CLIENT_SECRET`).
+    ClientSecret {
+        token_url: String,
+        client_id: String,
+        client_secret: String,
+    },
+    /// AKS workload identity: a federated service-account token exchanged for an access token.
+    WorkloadIdentity {
+        token_url: String,
+        client_id: String,
+        token_file: PathBuf,
+    },
+    /// The App Service / Functions / Container Apps identity endpoint.
+    AppService {
+        endpoint: String,
+        header: String,
+        client_id: Option<String>,
+    },
+    /// The VM / VM scale set instance metadata service.
+    Imds {
+        url: String,
+        client_id: Option<String>,
+    },
+}
+
+impl std::fmt::Debug for TokenSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ClientSecret { client_id, .. } => f
+                .debug_struct("ClientSecret")
+                .field("client_id", client_id)
+                .finish_non_exhaustive(),
+            Self::WorkloadIdentity {
+                client_id,
+                token_file,
+                ..
+            } => f
+                .debug_struct("WorkloadIdentity")
+                .field("client_id", client_id)
+                .field("token_file", token_file)
+                .finish_non_exhaustive(),
+            Self::AppService {
+                endpoint,
+                client_id,
+                ..
+            } => f
+                .debug_struct("AppService")
+                .field("endpoint", endpoint)
+                .field("client_id", client_id)
+                .finish_non_exhaustive(),
+            Self::Imds { url, client_id } => f
+                .debug_struct("Imds")
+                .field("url", url)
+                .field("client_id", client_id)
+                .finish(),
+        }
+    }
+}
+
+impl TokenSource {
+    /// Resolve Entra ID credentials from the process environment.
+    fn from_env() -> Result<Self> {
+        let vars: HashMap<String, String> = env::vars().collect();
+        Self::from_vars(&vars)
+    }
+
+    /// A service principal when `AZURE_TENANT_ID` and `AZURE_CLIENT_ID` come with a secret or a
+    /// federated token file, else the managed identity (`AZURE_CLIENT_ID` then selects a
+    /// user-assigned identity).
+    fn from_vars(vars: &HashMap<String, String>) -> Result<Self> {
+        let get = |key: &str| {
+            vars.get(key)
+                .map(|v| v.trim())
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        let tenant = get("AZURE_TENANT_ID");
+        let client_id = get("AZURE_CLIENT_ID");
+        let secret = get("AZURE_CLIENT_SECRET");
+        let token_file = get("AZURE_FEDERATED_TOKEN_FILE");
+        if secret.is_some() || token_file.is_some() {
+            let (Some(tenant), Some(client_id)) = (tenant, client_id.clone()) else {
+                bail!(
+                    "AZURE_TENANT_ID and AZURE_CLIENT_ID must be set alongside \
+                     AZURE_CLIENT_SECRET or AZURE_FEDERATED_TOKEN_FILE"
+                );
+            };
+            let authority =
+                get("AZURE_AUTHORITY_HOST").unwrap_or_else(|| DEFAULT_AUTHORITY_HOST.to_string());
+            let token_url = format!(
+                "{}/{tenant}/oauth2/v2.0/token",
+                authority.trim_end_matches('/')
+            );
+            return Ok(match secret {
+                Some(client_secret) => Self::ClientSecret {
+                    token_url,
+                    client_id,
+                    client_secret,
+                },
+                None => Self::WorkloadIdentity {
+                    token_url,
+                    client_id,
+                    token_file: PathBuf::from(token_file.unwrap_or_default()),
+                },
+            });
+        }
+        if let (Some(endpoint), Some(header)) = (get("IDENTITY_ENDPOINT"), get("IDENTITY_HEADER")) {
+            return Ok(Self::AppService {
+                endpoint,
+                header,
+                client_id,
+            });
+        }
+        Ok(Self::Imds {
+            url: IMDS_TOKEN_URL.to_string(),
+            client_id,
+        })
+    }
+
+    /// Fetch a fresh access token and its remaining lifetime.
+    async fn fetch(&self, http: &Client) -> Result<(String, Duration)> {
+        let scope = format!("{COGNITIVE_SERVICES_RESOURCE}/.default");
+        let response = match self {
+            Self::ClientSecret {
+                token_url,
+                client_id,
+                client_secret,
+            } => http
+                .post(token_url)
+                .form(&[
+                    ("grant_type", "client_credentials"),
+                    ("client_id", client_id.as_str()),
+                    ("client_secret", client_secret.as_str()),
+                    ("scope", scope.as_str()),
+                ])
+                .send()
+                .await
+                .context("failed to reach the Entra ID token endpoint")?,
+            Self::WorkloadIdentity {
+                token_url,
+                client_id,
+                token_file,
+            } => {
+                // The projected token is rotated on disk, so read it for every exchange.
+                let assertion = std::fs::read_to_string(token_file).with_context(|| {
+                    format!("failed to read federated token {}", token_file.display())
+                })?;
+                http.post(token_url)
+                    .form(&[
+                        ("grant_type", "client_credentials"),
+                        ("client_id", client_id.as_str()),
+                        (
+                            "client_assertion_type",
+                            "urn:ietf:params:oauth:client-assertion-type:jwt-bearer",
+                        ),
+                        ("client_assertion", assertion.trim()),
+                        ("scope", scope.as_str()),
+                    ])
+                    .send()
+                    .await
+                    .context("failed to reach the Entra ID token endpoint")?
+            }
+            Self::AppService {
+                endpoint,
+                header,
+                client_id,
+            } => {
+                let mut query = vec![
+                    ("api-version", "2019-08-01"),
+                    ("resource", COGNITIVE_SERVICES_RESOURCE),
+                ];
+                if let Some(client_id) = client_id {
+                    query.push(("client_id", client_id.as_str()));
+                }
+                http.get(endpoint)
+                    .query(&query)
+                    .header("X-IDENTITY-HEADER", header)
+                    .send()
+                    .await
+                    .context("failed to reach the App Service managed identity endpoint")?
+            }
+            Self::Imds { url, client_id } => {
+                let mut query = vec![
+                    ("api-version", "2018-02-01"),
+                    ("resource", COGNITIVE_SERVICES_RESOURCE),
+                ];
+                if let Some(client_id) = client_id {
+                    query.push(("client_id", client_id.as_str()));
+                }
+                http.get(url)
+                    .query(&query)
+                    .header("Metadata", "true")
+                    .send()
+                    .await
+                    .context(
+                        "failed to reach the Azure instance metadata service (set \
+                         AZURE_TENANT_ID, AZURE_CLIENT_ID and AZURE_CLIENT_SECRET to use a \
+                         service principal instead)",
+                    )?
+            }
+        };
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            bail!("Entra ID token endpoint error ({}): {}", status, body);
+        }
+        let token: TokenResponse = response
+            .json()
+            .await
+            .context("failed to parse Entra ID token response")?;
+        let lifetime = token.lifetime(SystemTime::now());
+        Ok((token.access_token, lifetime))
+    }
+}
+
+/// Token response shared by the Entra ID v2 endpoint and the managed identity endpoints; the
+/// latter send the numbers as strings and App Service only reports `expires_on`.
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default, deserialize_with = "number_or_string")]
+    expires_in: Option<u64>,
+    #[serde(default, deserialize_with = "number_or_string")]
+    expires_on: Option<u64>,
+}
+
+impl TokenResponse {
+    fn lifetime(&self, now: SystemTime) -> Duration {
+        if let Some(secs) = self.expires_in {
+            return Duration::from_secs(secs);
+        }
+        let now = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        Duration::from_secs(
+            self.expires_on
+                .map(|expires_on| expires_on.saturating_sub(now))
+                .unwrap_or(3600),
+        )
+    }
+}
+
+fn number_or_string<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Value {
+        Number(u64),
+        Text(String),
+    }
+    Ok(match Option::<Value>::deserialize(deserializer)? {
+        Some(Value::Number(n)) => Some(n),
+        Some(Value::Text(text)) => text.trim().parse().ok(),
+        None => None,
+    })
+}
+
+#[derive(Clone)]
+struct CachedToken {
+    value: String,
+    expires_at: Option<Instant>,
+}
+
+#[derive(Clone)]
 pub struct AzureOpenAiClient {
     http: Client,
     url: String,
-    api_key: String,
-    max_retries: u32,
+    deployments_url: String,
+    deployment: String,
+    credential: Credential,
+    cached: Arc<Mutex<Option<CachedToken>>>,
+    retry: RetryPolicy,
+    prompt: PromptTemplate,
+    temperature: f32,
+    max_tokens: u32,
+}
+
+impl std::fmt::Debug for AzureOpenAiClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let auth = match &self.credential {
+            Credential::ApiKey(_) => "api-key".to_string(),
+            Credential::Entra(source) => format!("{source:?}"),
+        };
+        f.debug_struct("AzureOpenAiClient")
+            .field("url", &self.url)
+            .field("auth", &auth)
+            .finish_non_exhaustive()
+    }
 }
 
 impl AzureOpenAiClient {
     pub fn new(settings: &LlmSettings) -> Result<Self> {
-        if settings.api_key.trim().is_empty() {
-            bail!("Azure OpenAI API key must be provided via LLM_GUARD_API_KEY");
-        }
+        let credential = match settings.azure_auth.unwrap_or_default() {
+            AzureAuth::ApiKey => {
+                if settings.api_key.trim().is_empty() {
+                    bail!(
+                        "Azure OpenAI API key must be provided via LLM_GUARD_API_KEY \
+                         (or set LLM_GUARD_AZURE_AUTH=entra)"
+                    );
+                }
+                Credential::ApiKey(settings.api_key.clone())
+            }
+            AzureAuth::Entra => Credential::Entra(TokenSource::from_env()?),
+        };
         let endpoint = settings
             .endpoint
             .clone()
             .ok_or_else(|| anyhow!("LLM_GUARD_ENDPOINT must be set for azure provider"))?;
-        let deployment = settings.model.clone().ok_or_else(|| {
-            anyhow!("LLM_GUARD_MODEL must contain the deployment name for azure provider")
-        })?;
+        let deployment = settings
+            .deployment
+            .clone()
+            .or_else(|| settings.model.clone())
+            .ok_or_else(|| {
+                anyhow!(
+                    "LLM_GUARD_DEPLOYMENT or LLM_GUARD_MODEL must contain the deployment name for azure provider"
+                )
+            })?;
         let api_version = settings
             .api_version
             .clone()
             .unwrap_or_else(|| "2024-02-15-preview".to_string());
 
+        let endpoint = endpoint.trim_end_matches('/');
         let url = format!(
-            "{}/openai/deployments/{}/chat/completions?api-version={}",
-            endpoint.trim_end_matches('/'),
-            deployment,
-            api_version
+            "{endpoint}/openai/deployments/{deployment}/chat/completions?api-version={api_version}"
         );
+        let deployments_url =
+            format!("{endpoint}/openai/deployments?api-version={DEPLOYMENTS_API_VERSION}");
 
-        let http = Client::builder()
-            .user_agent("llm-guard/0.1")
-            .timeout(Duration::from_secs(settings.timeout_secs.unwrap_or(30)))
-            .build()
-            .context("failed to build Azure OpenAI HTTP client")?;
+        let http = http::client(settings, "Azure OpenAI")?;
+        let mut retry = RetryPolicy::from_settings(settings);
+        // A 401 usually means the cached token was revoked early; the retry fetches a new one.
+        if matches!(credential, Credential::Entra(_)) && !retry.should_retry(401) {
+            retry.retry_on.push(401);
+        }
 
         Ok(Self {
             http,
             url,
-            api_key: settings.api_key.clone(),
-            max_retries: settings.max_retries,
+            deployments_url,
+            deployment,
+            credential,
+            cached: Arc::new(Mutex::new(None)),
+            retry,
+            prompt: PromptTemplate::from_settings(settings)?,
+            temperature: settings.temperature.unwrap_or(DEFAULT_TEMPERATURE),
+            max_tokens: settings.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
         })
     }
-}
 
-#[async_trait]
-impl LlmClient for AzureOpenAiClient {
-    async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
-        let payload = ChatCompletionRequest {
+    async fn access_token(&self, tokens: &TokenSource) -> Result<String> {
+        let cached = self.cached.lock().expect("token cache poisoned").clone();
+        if let Some(cached) = cached {
+            let fresh = match cached.expires_at {
+                Some(expires_at) => Instant::now() < expires_at,
+                None => true,
+            };
+            if fresh {
+                return Ok(cached.value);
+            }
+        }
+        let (value, lifetime) = tokens.fetch(&self.http).await?;
+        let expires_at = Instant::now().checked_add(lifetime.saturating_sub(TOKEN_EXPIRY_MARGIN));
+        *self.cached.lock().expect("token cache poisoned") = Some(CachedToken {
+            value: value.clone(),
+            expires_at,
+        });
+        Ok(value)
+    }
+
+    fn payload(&self, input: &str, report: &ScanReport) -> ChatCompletionRequest {
+        ChatCompletionRequest {
             messages: vec![
                 ChatMessage {
                     role: "system".into(),
-                    content: SYSTEM_PROMPT.into(),
+                    content: self.prompt.system().into(),
                 },
                 ChatMessage {
                     role: "user".into(),
-                    content: format!(
-                        "Input excerpt:\n{}\n\nScore: {:.1} ({:?})\nTop findings: {}\n",
-                        truncate(input, 2000),
-                        report.risk_score,
-                        report.risk_band,
-                        serde_json::to_string(&report.findings).unwrap_or_default()
+                    content: self.prompt.render_user(
+                        &compress_input(input, report, MAX_PROMPT_INPUT_CHARS),
+                        report,
                     ),
                 },
             ],
-            temperature: 0.1,
-            max_tokens: 200,
-        };
+            temperature: self.temperature,
+            max_tokens: self.max_tokens,
+        }
+    }
 
-        let mut attempt = 0u32;
-        let mut backoff = Duration::from_millis(200);
-        loop {
-            let response = self
-                .http
-                .post(&self.url)
-                .header("api-key", &self.api_key)
-                .json(&payload)
-                .send()
-                .await;
-
-            let response = match response {
-                Ok(resp) => resp,
-                Err(err) => {
-                    if attempt >= self.max_retries {
-                        return Err(err)
-                            .context("failed to call Azure OpenAI chat completions API");
-                    }
-                    sleep(backoff).await;
-                    backoff = (backoff * 2).min(Duration::from_secs(5));
-                    attempt += 1;
-                    continue;
-                }
-            };
+    async fn request(
+        &self,
+        payload: &ChatCompletionRequest,
+        previous: Option<reqwest::StatusCode>,
+    ) -> Result<reqwest::RequestBuilder> {
+        let request = self.authorize(self.http.post(&self.url), previous).await?;
+        Ok(request.json(payload))
+    }
 
-            if !response.status().is_success() {
-                if attempt >= self.max_retries {
-                    let status = response.status();
-                    let body = response.text().await.unwrap_or_default();
-                    bail!("Azure OpenAI API error ({}): {}", status, body);
+    async fn authorize(
+        &self,
+        request: reqwest::RequestBuilder,
+        previous: Option<reqwest::StatusCode>,
+    ) -> Result<reqwest::RequestBuilder> {
+        Ok(match &self.credential {
+            Credential::ApiKey(key) => request.header("api-key", key),
+            Credential::Entra(tokens) => {
+                if previous == Some(reqwest::StatusCode::UNAUTHORIZED) {
+                    // Force a refresh in case the cached token was revoked early.
+                    *self.cached.lock().expect("token cache poisoned") = None;
                 }
-                sleep(backoff).await;
-                backoff = (backoff * 2).min(Duration::from_secs(5));
-                attempt += 1;
-                continue;
+                request.bearer_auth(self.access_token(tokens).await?)
             }
+        })
+    }
 
-            let chat: ChatCompletionResponse = response
-                .json()
-                .await
-                .context("failed to parse Azure OpenAI response")?;
-            let content = chat
-                .choices
-                .into_iter()
-                .find_map(|choice| choice.message.content)
-                .ok_or_else(|| anyhow!("Azure OpenAI response missing message content"))?;
-
-            let verdict: ModelVerdict = serde_json::from_str(&content)
-                .context("expected JSON verdict from Azure OpenAI response")?;
-
-            return Ok(LlmVerdict {
-                label: verdict.label,
-                rationale: verdict.rationale,
-                mitigation: verdict.mitigation,
-            });
+    /// Deployment name chat completions are sent to.
+    pub fn deployment(&self) -> &str {
+        &self.deployment
+    }
+
+    /// Deployments of the resource and the models behind them, from the data-plane
+    /// deployments listing.
+    pub async fn list_deployments(&self) -> Result<Vec<AzureDeployment>> {
+        let response = self
+            .retry
+            .send("Azure OpenAI", "deployments", move |previous| {
+                self.authorize(self.http.get(&self.deployments_url), previous)
+            })
+            .await?;
+        let listing: DeploymentList = response
+            .json()
+            .await
+            .context("failed to parse Azure OpenAI deployments listing")?;
+        Ok(listing.data)
+    }
+
+    /// Fails with a "did you mean" hint when the configured deployment is not among
+    /// `deployments`, instead of the bare 404 a chat completion would return.
+    pub fn check_deployment(&self, deployments: &[AzureDeployment]) -> Result<()> {
+        if deployments.iter().any(|d| d.id == self.deployment) {
+            return Ok(());
+        }
+        let mut message = format!(
+            "deployment `{}` not found on the Azure OpenAI resource",
+            self.deployment
+        );
+        if let Some(suggestion) = suggest_deployment(&self.deployment, deployments) {
+            message.push_str(&format!("; did you mean `{}`?", suggestion.id));
+        }
+        if deployments.is_empty() {
+            message.push_str(" (the resource has no deployments)");
+        } else {
+            let available: Vec<String> = deployments.iter().map(ToString::to_string).collect();
+            message.push_str(&format!(" (available: {})", available.join(", ")));
+        }
+        message.push_str("; set LLM_GUARD_DEPLOYMENT to one of the deployment names");
+        bail!(message)
+    }
+}
+
+/// One deployment of an Azure OpenAI resource.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AzureDeployment {
+    /// Deployment name, the value `LLM_GUARD_DEPLOYMENT` expects.
+    pub id: String,
+    /// Model deployed under that name, e.g. `gpt-4o`.
+    #[serde(default)]
+    pub model: String,
+}
+
+impl std::fmt::Display for AzureDeployment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.model.is_empty() {
+            write!(f, "{}", self.id)
+        } else {
+            write!(f, "{} ({})", self.id, self.model)
         }
     }
 }
 
-const SYSTEM_PROMPT: &str = "You are an application security assistant. Analyze prompt-injection scan results and respond with strict JSON: {\"label\": \"safe|suspicious|malicious\", \"rationale\": \"...\", \"mitigation\": \"...\"}. The mitigation should advise remediation steps.";
+#[derive(Deserialize)]
+struct DeploymentList {
+    #[serde(default)]
+    data: Vec<AzureDeployment>,
+}
 
-fn truncate(input: &str, max_chars: usize) -> String {
-    if input.chars().count() <= max_chars {
-        return input.to_string();
+/// The deployment `name` most likely meant: the one serving a model called `name` (a model
+/// name is a common mistake for the deployment name), else the closest name by edit distance.
+pub fn suggest_deployment<'a>(
+    name: &str,
+    deployments: &'a [AzureDeployment],
+) -> Option<&'a AzureDeployment> {
+    let name = name.to_lowercase();
+    if let Some(by_model) = deployments
+        .iter()
+        .find(|d| d.model.eq_ignore_ascii_case(&name))
+    {
+        return Some(by_model);
+    }
+    let limit = (name.chars().count() / 3).max(2);
+    deployments
+        .iter()
+        .map(|d| (edit_distance(&name, &d.id.to_lowercase()), d))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, d)| d)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+#[async_trait]
+impl LlmClient for AzureOpenAiClient {
+    async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
+        let payload = &self.payload(input, report);
+        let response = self
+            .retry
+            .send("Azure OpenAI", "chat completions", move |previous| {
+                self.request(payload, previous)
+            })
+            .await?;
+
+        let chat: ChatCompletionResponse = response
+            .json()
+            .await
+            .context("failed to parse Azure OpenAI response")?;
+        let usage = chat
+            .usage
+            .as_ref()
+            .and_then(|usage| TokenUsage::reported(usage.prompt_tokens, usage.completion_tokens));
+        let content = chat
+            .choices
+            .into_iter()
+            .find_map(|choice| choice.message.content)
+            .ok_or_else(|| anyhow!("Azure OpenAI response missing message content"))?;
+
+        let verdict: ModelVerdict = serde_json::from_str(&content)
+            .context("expected JSON verdict from Azure OpenAI response")?;
+
+        Ok(LlmVerdict {
+            label: verdict.label,
+            rationale: verdict.rationale,
+            mitigation: verdict.mitigation,
+            usage,
+            ..Default::default()
+        })
+    }
+
+    fn preview(&self, input: &str, report: &ScanReport) -> Result<Vec<RequestPreview>> {
+        Ok(vec![RequestPreview {
+            provider: "azure".into(),
+            model: self.deployment.clone(),
+            url: Some(self.url.clone()),
+            body: serde_json::to_value(self.payload(input, report))?,
+        }])
     }
-    input.chars().take(max_chars).collect::<String>() + "…"
 }
 
 #[derive(Serialize)]
@@ -162,6 +627,16 @@ struct ChatMessage {
 #[derive(Deserialize)]
 struct ChatCompletionResponse {
     choices: Vec<ChatChoice>,
+    #[serde(default)]
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Deserialize)]
+struct ChatUsage {
+    #[serde(default)]
+    prompt_tokens: u64,
+    #[serde(default)]
+    completion_tokens: u64,
 }
 
 #[derive(Deserialize)]
@@ -176,6 +651,7 @@ struct ChatResponseMessage {
 
 #[derive(Deserialize)]
 struct ModelVerdict {
+    #[serde(deserialize_with = "super::labels::deserialize_label")]
     label: String,
     rationale: String,
     mitigation: String,
@@ -196,9 +672,31 @@ mod tests {
             deployment: None,
             project: None,
             workspace: None,
+            region: None,
             timeout_secs: Some(5),
             max_retries: 0,
             api_version: Some("2024-02-15-preview".into()),
+            retry_base_delay_ms: None,
+            retry_max_delay_ms: None,
+            retry_jitter: None,
+            retry_on_status: None,
+            prompt_template: None,
+            verdict_labels: None,
+            structured_output: None,
+            stream: None,
+            stream_idle_timeout_secs: None,
+            proxy: None,
+            ca_bundle: None,
+            client_cert: None,
+            client_key: None,
+            azure_auth: None,
+            scrub_pii: None,
+            prompt_cache: None,
+            openai_api: None,
+            temperature: None,
+            max_tokens: None,
+            response_mime_type: None,
+            safety_settings: None,
         }
     }
 
@@ -251,14 +749,182 @@ mod tests {
         mock.assert_hits(2);
     }
 
+    #[tokio::test]
+    #[ignore = "requires loopback networking"]
+    async fn entra_tokens_are_cached_and_sent_as_bearer() {
+        let server = MockServer::start();
+        let token = server.mock(|when, then| {
+            when.method(POST)
+                .path("/tenant-id/oauth2/v2.0/token")
+                .body_contains("grant_type=client_credentials")
+                .body_contains("scope=https%3A%2F%2Fcognitiveservices.azure.com%2F.default");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"token_type":"Bearer","expires_in":3599,"access_token":"entra-token"}"#);
+        });
+        let chat = server.mock(|when, then| {
+            when.method(POST)
+                .path("/openai/deployments/deployment-name/chat/completions")
+                .header("authorization", "Bearer entra-token");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"choices":[{"message":{"content":"{\"label\":\"safe\",\"rationale\":\"ok\",\"mitigation\":\"none\"}"}}]}"#);
+        });
+
+        let mut client = AzureOpenAiClient::new(&base_settings(server.base_url())).unwrap();
+        client.credential = Credential::Entra(TokenSource::ClientSecret {
+            token_url: server.url("/tenant-id/oauth2/v2.0/token"),
+            client_id: "app-id".into(),
+            client_secret: "app-secret".into(),
+        });
+        client.enrich("hello", &empty_report()).await.unwrap();
+        client.enrich("again", &empty_report()).await.unwrap();
+        token.assert_hits(1);
+        chat.assert_hits(2);
+    }
+
+    #[test]
+    fn entra_credentials_follow_azure_sdk_environment() {
+        let vars = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+
+        let source = TokenSource::from_vars(&vars(&[
+            ("AZURE_TENANT_ID", "tenant"),
+            ("AZURE_CLIENT_ID", "app"),
+            ("AZURE_CLIENT_SECRET", "secret"),
+        ]))
+        .unwrap();
+        match &source {
+            TokenSource::ClientSecret { token_url, .. } => assert_eq!(
+                token_url,
+                "https://login.microsoftonline.com/tenant/oauth2/v2.0/token"
+            ),
+            other => panic!("unexpected source {other:?}"),
+        }
+        assert!(!format!("{source:?}").contains("secret\""));
+
+        let source = TokenSource::from_vars(&vars(&[
+            ("AZURE_TENANT_ID", "tenant"),
+            ("AZURE_CLIENT_ID", "app"),
+            (
+                "AZURE_FEDERATED_TOKEN_FILE",
+                "/var/run/secrets/azure/tokens/token",
+            ),
+            ("AZURE_AUTHORITY_HOST", "https://login.microsoftonline.us/"),
+        ]))
+        .unwrap();
+        assert!(matches!(
+            source,
+            TokenSource::WorkloadIdentity { ref token_url, .. }
+                if token_url == "https://login.microsoftonline.us/tenant/oauth2/v2.0/token"
+        ));
+
+        let err = TokenSource::from_vars(&vars(&[("AZURE_CLIENT_SECRET", "secret")])).unwrap_err();
+        assert!(err.to_string().contains("AZURE_TENANT_ID"));
+
+        let source = TokenSource::from_vars(&vars(&[
+            ("IDENTITY_ENDPOINT", "http://localhost:42356/msi/token"),
+            ("IDENTITY_HEADER", "header"),
+        ]))
+        .unwrap();
+        assert!(matches!(
+            source,
+            TokenSource::AppService {
+                client_id: None,
+                ..
+            }
+        ));
+
+        let source =
+            TokenSource::from_vars(&vars(&[("AZURE_CLIENT_ID", "user-assigned")])).unwrap();
+        assert!(matches!(
+            source,
+            TokenSource::Imds { client_id: Some(ref id), .. } if id == "user-assigned"
+        ));
+    }
+
+    #[test]
+    fn token_lifetime_accepts_string_fields_and_expires_on() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let imds: TokenResponse = serde_json::from_str(
+            r#"{"access_token":"t","expires_in":"3599","expires_on":"1700003599"}"#,
+        )
+        .unwrap();
+        assert_eq!(imds.lifetime(now), Duration::from_secs(3599));
+        let app_service: TokenResponse =
+            serde_json::from_str(r#"{"access_token":"t","expires_on":"1700001800"}"#).unwrap();
+        assert_eq!(app_service.lifetime(now), Duration::from_secs(1800));
+        let bare: TokenResponse = serde_json::from_str(r#"{"access_token":"t"}"#).unwrap();
+        assert_eq!(bare.lifetime(now), Duration::from_secs(3600));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires loopback networking"]
+    async fn lists_deployments_with_their_models() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/openai/deployments")
+                .query_param("api-version", DEPLOYMENTS_API_VERSION)
+                .header("api-key", "test-key");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"object":"list","data":[{"id":"gpt4o-prod","model":"gpt-4o","status":"succeeded"},{"id":"embeddings","model":"text-embedding-3-small"}]}"#);
+        });
+
+        let client = AzureOpenAiClient::new(&base_settings(server.base_url())).unwrap();
+        let deployments = client.list_deployments().await.unwrap();
+        assert_eq!(deployments.len(), 2);
+        assert_eq!(deployments[0].id, "gpt4o-prod");
+        assert_eq!(deployments[0].model, "gpt-4o");
+        mock.assert();
+    }
+
     #[test]
-    fn truncate_short_strings_return_same() {
-        assert_eq!(truncate("abc", 10), "abc");
+    fn missing_deployment_suggests_the_closest_name() {
+        let deployments = vec![
+            AzureDeployment {
+                id: "gpt4o-prod".into(),
+                model: "gpt-4o".into(),
+            },
+            AzureDeployment {
+                id: "embeddings".into(),
+                model: "text-embedding-3-small".into(),
+            },
+        ];
+        let mut settings = base_settings("https://example.openai.azure.com".into());
+
+        settings.model = Some("gpt4o-prod".into());
+        let client = AzureOpenAiClient::new(&settings).unwrap();
+        client.check_deployment(&deployments).unwrap();
+
+        settings.model = Some("gpt4o-prd".into());
+        let client = AzureOpenAiClient::new(&settings).unwrap();
+        let err = client
+            .check_deployment(&deployments)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("deployment `gpt4o-prd` not found"), "{err}");
+        assert!(err.contains("did you mean `gpt4o-prod`?"), "{err}");
+        assert!(err.contains("gpt4o-prod (gpt-4o), embeddings (text-embedding-3-small)"));
+
+        // A model name given as the deployment points at the deployment serving it.
+        assert_eq!(
+            suggest_deployment("GPT-4o", &deployments).map(|d| d.id.as_str()),
+            Some("gpt4o-prod")
+        );
+        assert!(suggest_deployment("chat", &deployments).is_none());
     }
 
     #[test]
-    fn truncate_long_strings_adds_ellipsis() {
-        let result = truncate("abcdefghijklmnopqrstuvwxyz", 5);
-        assert_eq!(result, "abcde…");
+    fn api_key_mode_requires_a_key() {
+        let mut settings = base_settings("https://example.openai.azure.com".into());
+        settings.api_key.clear();
+        let err = AzureOpenAiClient::new(&settings).unwrap_err();
+        assert!(err.to_string().contains("LLM_GUARD_AZURE_AUTH=entra"));
     }
 }
@@ -0,0 +1,621 @@
+use super::{
+    compress_input, http, LlmClient, LlmSettings, PromptTemplate, RequestPreview, RetryPolicy,
+    DEFAULT_MAX_TOKENS, MAX_PROMPT_INPUT_CHARS,
+};
+use crate::scanner::{LlmVerdict, ScanReport, TokenUsage};
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::future;
+use std::time::SystemTime;
+
+const SERVICE: &str = "bedrock";
+const DEFAULT_MODEL: &str = "anthropic.claude-3-haiku-20240307-v1:0";
+
+/// Model families reachable through Bedrock `InvokeModel`; each expects its own request body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BedrockModelFamily {
+    Anthropic,
+    Titan,
+}
+
+impl BedrockModelFamily {
+    fn from_model(model: &str) -> Result<Self> {
+        // Cross-region inference profiles prefix the model id with a geography (`us.`, `eu.`).
+        let id = model.to_ascii_lowercase();
+        if id.starts_with("anthropic.") || id.contains(".anthropic.") {
+            Ok(Self::Anthropic)
+        } else if id.starts_with("amazon.titan") || id.contains(".amazon.titan") {
+            Ok(Self::Titan)
+        } else {
+            bail!("unsupported Bedrock model `{model}` (expected an anthropic.* or amazon.titan* model id)")
+        }
+    }
+}
+
+/// Static AWS credentials used to sign Bedrock (and Secrets Manager) requests.
+#[derive(Clone)]
+pub(super) struct AwsCredentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+impl std::fmt::Debug for AwsCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AwsCredentials")
+            .field("access_key_id", &self.access_key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AwsCredentials {
+    /// `LLM_GUARD_API_KEY` may carry `ACCESS_KEY_ID:SECRET_ACCESS_KEY[:SESSION_TOKEN]`; otherwise
+    /// the standard `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY` / `AWS_SESSION_TOKEN` are used.
+    pub(super) fn resolve(api_key: &str) -> Result<Self> {
+        let api_key = api_key.trim();
+        if !api_key.is_empty() {
+            let mut parts = api_key.splitn(3, ':');
+            let access_key_id = parts.next().unwrap_or_default().trim();
+            let secret_access_key = parts.next().unwrap_or_default().trim();
+            if access_key_id.is_empty() || secret_access_key.is_empty() {
+                bail!("Bedrock API key must have the form ACCESS_KEY_ID:SECRET_ACCESS_KEY[:SESSION_TOKEN]");
+            }
+            return Ok(Self {
+                access_key_id: access_key_id.to_string(),
+                secret_access_key: secret_access_key.to_string(),
+                session_token: parts
+                    .next()
+                    .map(|token| token.trim().to_string())
+                    .filter(|token| !token.is_empty()),
+            });
+        }
+
+        let var = |key: &str| {
+            env::var(key)
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        match (var("AWS_ACCESS_KEY_ID"), var("AWS_SECRET_ACCESS_KEY")) {
+            (Some(access_key_id), Some(secret_access_key)) => Ok(Self {
+                access_key_id,
+                secret_access_key,
+                session_token: var("AWS_SESSION_TOKEN"),
+            }),
+            _ => bail!(
+                "Bedrock credentials must be provided via LLM_GUARD_API_KEY or AWS_ACCESS_KEY_ID/AWS_SECRET_ACCESS_KEY"
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BedrockClient {
+    http: Client,
+    url: String,
+    model: String,
+    host: String,
+    canonical_uri: String,
+    region: String,
+    family: BedrockModelFamily,
+    credentials: AwsCredentials,
+    retry: RetryPolicy,
+    prompt: PromptTemplate,
+    temperature: Option<f32>,
+    max_tokens: u32,
+}
+
+impl BedrockClient {
+    pub fn new(settings: &LlmSettings) -> Result<Self> {
+        let region = settings
+            .region
+            .clone()
+            .or_else(|| env::var("AWS_REGION").ok())
+            .or_else(|| env::var("AWS_DEFAULT_REGION").ok())
+            .map(|region| region.trim().to_string())
+            .filter(|region| !region.is_empty())
+            .ok_or_else(|| {
+                anyhow!("Bedrock region must be provided via LLM_GUARD_REGION or AWS_REGION")
+            })?;
+        let credentials = AwsCredentials::resolve(&settings.api_key)?;
+        let model = settings
+            .model
+            .clone()
+            .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+        let family = BedrockModelFamily::from_model(&model)?;
+
+        let base = settings
+            .endpoint
+            .clone()
+            .unwrap_or_else(|| format!("https://bedrock-runtime.{region}.amazonaws.com"));
+        // Model ids contain `:`, which Bedrock expects percent-encoded in the path; the canonical
+        // URI used for signing encodes each segment once more.
+        let segments = ["model", model.as_str(), "invoke"];
+        let path: String = segments
+            .iter()
+            .map(|segment| format!("/{}", uri_encode(segment)))
+            .collect();
+        let canonical_uri: String = segments
+            .iter()
+            .map(|segment| format!("/{}", uri_encode(&uri_encode(segment))))
+            .collect();
+        let url = format!("{}{}", base.trim_end_matches('/'), path);
+        let parsed =
+            Url::parse(&url).with_context(|| format!("invalid Bedrock endpoint `{base}`"))?;
+        let host = match (parsed.host_str(), parsed.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            (None, _) => bail!("Bedrock endpoint `{base}` has no host"),
+        };
+
+        let http = http::client(settings, "Bedrock")?;
+        Ok(Self {
+            http,
+            url,
+            model,
+            host,
+            canonical_uri,
+            region,
+            family,
+            credentials,
+            retry: RetryPolicy::from_settings(settings),
+            prompt: PromptTemplate::from_settings(settings)?,
+            temperature: settings.temperature,
+            max_tokens: settings.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+        })
+    }
+
+    fn request_body(&self, input: &str, report: &ScanReport) -> Result<Vec<u8>> {
+        let prompt = self.prompt.render_user(
+            &compress_input(input, report, MAX_PROMPT_INPUT_CHARS),
+            report,
+        );
+        let body = match self.family {
+            BedrockModelFamily::Anthropic => serde_json::to_vec(&AnthropicRequest {
+                anthropic_version: "bedrock-2023-05-31",
+                system: self.prompt.system(),
+                messages: vec![AnthropicMessage {
+                    role: "user",
+                    content: prompt,
+                }],
+                max_tokens: self.max_tokens,
+                temperature: self.temperature,
+            }),
+            BedrockModelFamily::Titan => serde_json::to_vec(&TitanRequest {
+                input_text: format!("{}\n\n{prompt}", self.prompt.system()),
+                text_generation_config: TitanGenerationConfig {
+                    max_token_count: self.max_tokens,
+                    temperature: self.temperature.unwrap_or(0.0),
+                },
+            }),
+        };
+        body.context("failed to encode Bedrock request")
+    }
+
+    fn response_text(&self, body: &[u8]) -> Result<String> {
+        match self.family {
+            BedrockModelFamily::Anthropic => {
+                let message: AnthropicResponse = serde_json::from_slice(body)
+                    .context("failed to parse Bedrock Anthropic response")?;
+                message
+                    .content
+                    .into_iter()
+                    .find_map(|part| part.text)
+                    .ok_or_else(|| anyhow!("Bedrock response missing message content"))
+            }
+            BedrockModelFamily::Titan => {
+                let message: TitanResponse = serde_json::from_slice(body)
+                    .context("failed to parse Bedrock Titan response")?;
+                message
+                    .results
+                    .into_iter()
+                    .map(|result| result.output_text)
+                    .next()
+                    .ok_or_else(|| anyhow!("Bedrock response missing output text"))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl LlmClient for BedrockClient {
+    async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
+        let body = self.request_body(input, report)?;
+
+        let response = self
+            .retry
+            .send("Bedrock", "InvokeModel", |_| {
+                // Sign per attempt so retries carry a fresh timestamp.
+                let signed = sign_v4(
+                    &SigningRequest {
+                        method: "POST",
+                        canonical_uri: &self.canonical_uri,
+                        host: &self.host,
+                        region: &self.region,
+                        service: SERVICE,
+                        content_type: "application/json",
+                        body: &body,
+                    },
+                    &self.credentials,
+                    &amz_timestamp(SystemTime::now()),
+                );
+                let mut request = self
+                    .http
+                    .post(&self.url)
+                    .header("content-type", "application/json")
+                    .header("accept", "application/json")
+                    .body(body.clone());
+                for (name, value) in signed {
+                    request = request.header(name, value);
+                }
+                future::ready(Ok(request))
+            })
+            .await?;
+
+        // Bedrock reports token counts in headers for every model family.
+        let token_count = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .unwrap_or(0)
+        };
+        let usage = TokenUsage::reported(
+            token_count("x-amzn-bedrock-input-token-count"),
+            token_count("x-amzn-bedrock-output-token-count"),
+        );
+        let bytes = response
+            .bytes()
+            .await
+            .context("failed to read Bedrock response")?;
+        let content = self.response_text(&bytes)?;
+
+        let verdict: ModelVerdict = serde_json::from_str(&content)
+            .context("expected JSON verdict from Bedrock response")?;
+
+        Ok(LlmVerdict {
+            label: verdict.label,
+            rationale: verdict.rationale,
+            mitigation: verdict.mitigation,
+            usage,
+            ..Default::default()
+        })
+    }
+
+    fn preview(&self, input: &str, report: &ScanReport) -> Result<Vec<RequestPreview>> {
+        Ok(vec![RequestPreview {
+            provider: "bedrock".into(),
+            model: self.model.clone(),
+            url: Some(self.url.clone()),
+            body: serde_json::from_slice(&self.request_body(input, report)?)?,
+        }])
+    }
+}
+
+/// Inputs to an AWS Signature Version 4 signature over a request without a query string.
+pub(super) struct SigningRequest<'a> {
+    pub(super) method: &'a str,
+    pub(super) canonical_uri: &'a str,
+    pub(super) host: &'a str,
+    pub(super) region: &'a str,
+    pub(super) service: &'a str,
+    pub(super) content_type: &'a str,
+    pub(super) body: &'a [u8],
+}
+
+/// Produce the headers (`x-amz-date`, `x-amz-content-sha256`, optional
+/// `x-amz-security-token`, `authorization`) that authenticate `request`.
+pub(super) fn sign_v4(
+    request: &SigningRequest<'_>,
+    credentials: &AwsCredentials,
+    amz_date: &str,
+) -> Vec<(&'static str, String)> {
+    let date = &amz_date[..8];
+    let payload_hash = hex(&Sha256::digest(request.body));
+
+    let mut headers = vec![
+        ("content-type", request.content_type.to_string()),
+        ("host", request.host.to_string()),
+        ("x-amz-content-sha256", payload_hash.clone()),
+        ("x-amz-date", amz_date.to_string()),
+    ];
+    if let Some(token) = &credentials.session_token {
+        headers.push(("x-amz-security-token", token.clone()));
+    }
+    headers.sort_by(|a, b| a.0.cmp(b.0));
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+
+    let canonical_request = format!(
+        "{}\n{}\n\n{}\n{}\n{}",
+        request.method, request.canonical_uri, canonical_headers, signed_headers, payload_hash
+    );
+    let scope = format!("{date}/{}/{}/aws4_request", request.region, request.service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+    let signing_key = signing_key(
+        &credentials.secret_access_key,
+        date,
+        request.region,
+        request.service,
+    );
+    let signature = hex(&hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+    let mut signed = headers
+        .into_iter()
+        .filter(|(name, _)| *name != "content-type" && *name != "host")
+        .collect::<Vec<_>>();
+    signed.push((
+        "authorization",
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            credentials.access_key_id
+        ),
+    ));
+    signed
+}
+
+fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let k_date = hmac_sha256(format!("AWS4{secret}").as_bytes(), date.as_bytes());
+    let k_region = hmac_sha256(&k_date, region.as_bytes());
+    let k_service = hmac_sha256(&k_region, service.as_bytes());
+    hmac_sha256(&k_service, b"aws4_request")
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// SigV4 URI encoding: everything except unreserved characters is percent-encoded.
+fn uri_encode(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            other => encoded.push_str(&format!("%{other:02X}")),
+        }
+    }
+    encoded
+}
+
+/// `YYYYMMDDTHHMMSSZ`, the timestamp format SigV4 expects in `x-amz-date`.
+pub(super) fn amz_timestamp(now: SystemTime) -> String {
+    humantime::format_rfc3339_seconds(now)
+        .to_string()
+        .chars()
+        .filter(|c| *c != '-' && *c != ':')
+        .collect()
+}
+
+#[derive(Serialize)]
+struct AnthropicRequest<'a> {
+    anthropic_version: &'static str,
+    system: &'a str,
+    messages: Vec<AnthropicMessage>,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+}
+
+#[derive(Serialize)]
+struct AnthropicMessage {
+    role: &'static str,
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct AnthropicResponse {
+    content: Vec<AnthropicContent>,
+}
+
+#[derive(Deserialize)]
+struct AnthropicContent {
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TitanRequest {
+    input_text: String,
+    text_generation_config: TitanGenerationConfig,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TitanGenerationConfig {
+    max_token_count: u32,
+    temperature: f32,
+}
+
+#[derive(Deserialize)]
+struct TitanResponse {
+    results: Vec<TitanResult>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TitanResult {
+    output_text: String,
+}
+
+#[derive(Deserialize)]
+struct ModelVerdict {
+    #[serde(deserialize_with = "super::labels::deserialize_label")]
+    label: String,
+    rationale: String,
+    mitigation: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::settings::LlmSettings;
+    use crate::scanner::{RiskThresholds, ScanReport, ScoreBreakdown};
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    fn base_settings(url: String) -> LlmSettings {
+        LlmSettings {
+            provider: "bedrock".into(),
+            api_key: "AKIDEXAMPLE:wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".into(),
+            endpoint: Some(url),
+            model: Some("anthropic.claude-3-haiku-20240307-v1:0".into()),
+            deployment: None,
+            project: None,
+            workspace: None,
+            region: Some("us-east-1".into()),
+            timeout_secs: Some(5),
+            max_retries: 0,
+            api_version: None,
+            retry_base_delay_ms: None,
+            retry_max_delay_ms: None,
+            retry_jitter: None,
+            retry_on_status: None,
+            prompt_template: None,
+            verdict_labels: None,
+            structured_output: None,
+            stream: None,
+            stream_idle_timeout_secs: None,
+            proxy: None,
+            ca_bundle: None,
+            client_cert: None,
+            client_key: None,
+            azure_auth: None,
+            scrub_pii: None,
+            prompt_cache: None,
+            openai_api: None,
+            temperature: None,
+            max_tokens: None,
+            response_mime_type: None,
+            safety_settings: None,
+        }
+    }
+
+    fn empty_report() -> ScanReport {
+        ScanReport::from_breakdown(
+            vec![],
+            0,
+            None,
+            ScoreBreakdown::default(),
+            &RiskThresholds::default(),
+        )
+    }
+
+    #[tokio::test]
+    async fn enrich_parses_anthropic_response() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/model/anthropic.claude-3-haiku-20240307-v1%3A0/invoke")
+                .header_exists("authorization")
+                .header_exists("x-amz-date");
+            then.status(200)
+                .header("content-type", "application/json")
+                .header("x-amzn-bedrock-input-token-count", "150")
+                .header("x-amzn-bedrock-output-token-count", "25")
+                .json_body(json!({
+                    "content": [
+                        {"type": "text", "text": "{\"label\":\"safe\",\"rationale\":\"ok\",\"mitigation\":\"none\"}"}
+                    ]
+                }));
+        });
+
+        let client = BedrockClient::new(&base_settings(server.base_url())).unwrap();
+        let verdict = client.enrich("hello", &empty_report()).await.unwrap();
+        assert_eq!(verdict.label, "safe");
+        assert_eq!(verdict.rationale, "ok");
+        assert_eq!(verdict.mitigation, "none");
+        assert_eq!(verdict.usage, Some(TokenUsage::new(150, 25)));
+        mock.assert();
+    }
+
+    #[test]
+    fn rejects_unsupported_models() {
+        let mut settings = base_settings("http://localhost".into());
+        settings.model = Some("meta.llama3-8b-instruct-v1:0".into());
+        let err = BedrockClient::new(&settings).unwrap_err();
+        assert!(err.to_string().contains("unsupported Bedrock model"));
+
+        settings.model = Some("amazon.titan-text-express-v1".into());
+        let client = BedrockClient::new(&settings).unwrap();
+        assert_eq!(client.family, BedrockModelFamily::Titan);
+        assert_eq!(
+            client.url,
+            "http://localhost/model/amazon.titan-text-express-v1/invoke"
+        );
+    }
+
+    #[test]
+    fn signing_key_matches_aws_example() {
+        // Published example from the AWS SigV4 documentation.
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex(&key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn sign_v4_builds_authorization_header() {
+        let credentials = AwsCredentials::resolve("AKIDEXAMPLE:secret:session").unwrap();
+        let headers = sign_v4(
+            &SigningRequest {
+                method: "POST",
+                canonical_uri: "/model/x/invoke",
+                host: "bedrock-runtime.us-east-1.amazonaws.com",
+                region: "us-east-1",
+                service: SERVICE,
+                content_type: "application/json",
+                body: b"{}",
+            },
+            &credentials,
+            "20240102T030405Z",
+        );
+        let authorization = &headers
+            .iter()
+            .find(|(name, _)| *name == "authorization")
+            .unwrap()
+            .1;
+        assert!(authorization.starts_with(
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20240102/us-east-1/bedrock/aws4_request, SignedHeaders=content-type;host;x-amz-content-sha256;x-amz-date;x-amz-security-token, Signature="
+        ));
+        assert!(headers
+            .iter()
+            .any(|(name, value)| *name == "x-amz-security-token" && value == "session"));
+    }
+
+    #[test]
+    fn amz_timestamp_uses_compact_utc_format() {
+        let time = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_704_164_645);
+        assert_eq!(amz_timestamp(time), "20240102T030405Z");
+    }
+}
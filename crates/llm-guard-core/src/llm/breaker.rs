@@ -0,0 +1,222 @@
+use super::{LlmClient, RequestPreview};
+use crate::scanner::{LlmVerdict, ScanReport};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// When a provider's circuit opens and for how long it stays open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerPolicy {
+    /// Consecutive failures that open the circuit.
+    pub failure_threshold: u32,
+    /// How long calls are skipped before a single probe call is let through.
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerPolicy {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            cooldown: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Returned instead of calling a provider whose circuit is open.
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+#[error("circuit open for provider `{provider}` after {failures} consecutive failures; retrying in {}s", retry_in.as_secs().max(1))]
+pub struct CircuitOpenError {
+    pub provider: String,
+    pub failures: u32,
+    pub retry_in: Duration,
+}
+
+/// Stops calling a provider after repeated failures.
+///
+/// Once `failure_threshold` consecutive calls fail, further calls fail immediately with
+/// [`CircuitOpenError`] until the cooldown elapses. The next call then probes the provider:
+/// success closes the circuit, failure reopens it for another cooldown.
+pub struct CircuitBreakerLlmClient {
+    provider: String,
+    inner: Arc<dyn LlmClient>,
+    policy: CircuitBreakerPolicy,
+    state: Mutex<BreakerState>,
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    probing: bool,
+}
+
+impl CircuitBreakerLlmClient {
+    pub fn new(
+        provider: impl Into<String>,
+        inner: Arc<dyn LlmClient>,
+        policy: CircuitBreakerPolicy,
+    ) -> Self {
+        Self {
+            provider: provider.into(),
+            inner,
+            policy: CircuitBreakerPolicy {
+                failure_threshold: policy.failure_threshold.max(1),
+                ..policy
+            },
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    /// Whether calls are currently being skipped.
+    pub fn is_open(&self) -> bool {
+        let state = self.state.lock().expect("circuit breaker poisoned");
+        state
+            .open_until
+            .is_some_and(|until| state.probing || Instant::now() < until)
+    }
+
+    fn admit(&self) -> Result<(), CircuitOpenError> {
+        let mut state = self.state.lock().expect("circuit breaker poisoned");
+        let Some(until) = state.open_until else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        if now < until || state.probing {
+            return Err(CircuitOpenError {
+                provider: self.provider.clone(),
+                failures: state.consecutive_failures,
+                retry_in: until.saturating_duration_since(now),
+            });
+        }
+        state.probing = true;
+        Ok(())
+    }
+
+    fn settle(&self, succeeded: bool) {
+        let mut state = self.state.lock().expect("circuit breaker poisoned");
+        state.probing = false;
+        if succeeded {
+            *state = BreakerState::default();
+            return;
+        }
+        state.consecutive_failures += 1;
+        if state.consecutive_failures >= self.policy.failure_threshold {
+            if state.open_until.is_none() {
+                tracing::warn!(
+                    "provider {} failed {} times in a row; skipping it for {:?}",
+                    self.provider,
+                    state.consecutive_failures,
+                    self.policy.cooldown
+                );
+            }
+            state.open_until = Some(Instant::now() + self.policy.cooldown);
+        }
+    }
+}
+
+#[async_trait]
+impl LlmClient for CircuitBreakerLlmClient {
+    async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
+        self.admit()?;
+        let result = self.inner.enrich(input, report).await;
+        self.settle(result.is_ok());
+        result
+    }
+
+    fn preview(&self, input: &str, report: &ScanReport) -> Result<Vec<RequestPreview>> {
+        self.inner.preview(input, report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{RiskThresholds, ScoreBreakdown};
+    use anyhow::bail;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct Flaky {
+        healthy: AtomicBool,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl LlmClient for Flaky {
+        async fn enrich(&self, _input: &str, _report: &ScanReport) -> Result<LlmVerdict> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if !self.healthy.load(Ordering::SeqCst) {
+                bail!("provider offline");
+            }
+            Ok(LlmVerdict::default())
+        }
+    }
+
+    fn report() -> ScanReport {
+        ScanReport::from_breakdown(
+            vec![],
+            0,
+            None,
+            ScoreBreakdown::default(),
+            &RiskThresholds::default(),
+        )
+    }
+
+    #[tokio::test]
+    async fn opens_after_threshold_and_recovers_after_cooldown() {
+        let inner = Arc::new(Flaky::default());
+        let breaker = CircuitBreakerLlmClient::new(
+            "openai",
+            inner.clone(),
+            CircuitBreakerPolicy {
+                failure_threshold: 2,
+                cooldown: Duration::from_millis(50),
+            },
+        );
+        let report = report();
+
+        for _ in 0..2 {
+            let err = breaker.enrich("input", &report).await.unwrap_err();
+            assert!(err.downcast_ref::<CircuitOpenError>().is_none());
+        }
+        assert!(breaker.is_open());
+        let err = breaker.enrich("input", &report).await.unwrap_err();
+        let open = err.downcast_ref::<CircuitOpenError>().unwrap();
+        assert_eq!(open.provider, "openai");
+        assert_eq!(open.failures, 2);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+
+        // The probe after the cooldown fails and reopens the circuit immediately.
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(breaker.enrich("input", &report).await.is_err());
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+        assert!(breaker.is_open());
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        inner.healthy.store(true, Ordering::SeqCst);
+        breaker.enrich("input", &report).await.unwrap();
+        assert!(!breaker.is_open());
+    }
+
+    #[tokio::test]
+    async fn success_resets_the_failure_count() {
+        let inner = Arc::new(Flaky::default());
+        let breaker = CircuitBreakerLlmClient::new(
+            "openai",
+            inner.clone(),
+            CircuitBreakerPolicy {
+                failure_threshold: 2,
+                cooldown: Duration::from_secs(60),
+            },
+        );
+        let report = report();
+        assert!(breaker.enrich("input", &report).await.is_err());
+        inner.healthy.store(true, Ordering::SeqCst);
+        breaker.enrich("input", &report).await.unwrap();
+        inner.healthy.store(false, Ordering::SeqCst);
+        assert!(breaker.enrich("input", &report).await.is_err());
+        assert!(!breaker.is_open());
+    }
+}
@@ -0,0 +1,307 @@
+use super::{
+    compress_input, http, LlmClient, LlmSettings, PromptTemplate, RequestPreview, RetryPolicy,
+    DEFAULT_MAX_TOKENS, DEFAULT_TEMPERATURE, MAX_PROMPT_INPUT_CHARS,
+};
+use crate::scanner::{LlmVerdict, ScanReport, TokenUsage};
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::future;
+
+#[derive(Debug, Clone)]
+pub struct CohereClient {
+    http: Client,
+    url: String,
+    api_key: String,
+    model: String,
+    retry: RetryPolicy,
+    prompt: PromptTemplate,
+    temperature: f32,
+    max_tokens: u32,
+}
+
+impl CohereClient {
+    pub fn new(settings: &LlmSettings) -> Result<Self> {
+        if settings.api_key.trim().is_empty() {
+            bail!("Cohere API key must be provided via LLM_GUARD_API_KEY");
+        }
+        let base = settings
+            .endpoint
+            .clone()
+            .unwrap_or_else(|| "https://api.cohere.com".to_string());
+        let url = format!("{}/v2/chat", base.trim_end_matches('/'));
+        let http = http::client(settings, "Cohere")?;
+        Ok(Self {
+            http,
+            url,
+            api_key: settings.api_key.clone(),
+            model: settings
+                .model
+                .clone()
+                .unwrap_or_else(|| "command-r-08-2024".to_string()),
+            retry: RetryPolicy::from_settings(settings),
+            prompt: PromptTemplate::from_settings(settings)?,
+            temperature: settings.temperature.unwrap_or(DEFAULT_TEMPERATURE),
+            max_tokens: settings.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+        })
+    }
+
+    fn request(&self, input: &str, report: &ScanReport) -> CohereChatRequest {
+        CohereChatRequest {
+            model: self.model.clone(),
+            messages: vec![
+                CohereMessage {
+                    role: "system",
+                    content: self.prompt.system().to_string(),
+                },
+                CohereMessage {
+                    role: "user",
+                    content: self.prompt.render_user(
+                        &compress_input(input, report, MAX_PROMPT_INPUT_CHARS),
+                        report,
+                    ),
+                },
+            ],
+            response_format: CohereResponseFormat {
+                kind: "json_object",
+            },
+            temperature: self.temperature,
+            max_tokens: self.max_tokens,
+        }
+    }
+}
+
+#[async_trait]
+impl LlmClient for CohereClient {
+    async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
+        let payload = self.request(input, report);
+
+        let response = self
+            .retry
+            .send("Cohere", "chat", |_| {
+                future::ready(Ok(self
+                    .http
+                    .post(&self.url)
+                    .bearer_auth(&self.api_key)
+                    .json(&payload)))
+            })
+            .await?;
+
+        let chat: CohereChatResponse = response
+            .json()
+            .await
+            .context("failed to parse Cohere response")?;
+        let usage = chat
+            .usage
+            .as_ref()
+            .and_then(|usage| usage.billed_units.as_ref().or(usage.tokens.as_ref()))
+            .and_then(|tokens| TokenUsage::reported(tokens.input_tokens, tokens.output_tokens));
+        let content = chat
+            .message
+            .content
+            .into_iter()
+            .find_map(|part| part.text)
+            .ok_or_else(|| anyhow!("Cohere response missing message content"))?;
+
+        let verdict: ModelVerdict =
+            serde_json::from_str(&content).context("expected JSON verdict from Cohere response")?;
+
+        Ok(LlmVerdict {
+            label: verdict.label,
+            rationale: verdict.rationale,
+            mitigation: verdict.mitigation,
+            usage,
+            ..Default::default()
+        })
+    }
+
+    fn preview(&self, input: &str, report: &ScanReport) -> Result<Vec<RequestPreview>> {
+        Ok(vec![RequestPreview {
+            provider: "cohere".into(),
+            model: self.model.clone(),
+            url: Some(self.url.clone()),
+            body: serde_json::to_value(self.request(input, report))?,
+        }])
+    }
+}
+
+#[derive(Serialize)]
+struct CohereChatRequest {
+    model: String,
+    messages: Vec<CohereMessage>,
+    response_format: CohereResponseFormat,
+    temperature: f32,
+    max_tokens: u32,
+}
+
+#[derive(Serialize)]
+struct CohereMessage {
+    role: &'static str,
+    content: String,
+}
+
+#[derive(Serialize)]
+struct CohereResponseFormat {
+    #[serde(rename = "type")]
+    kind: &'static str,
+}
+
+#[derive(Deserialize)]
+struct CohereChatResponse {
+    message: CohereResponseMessage,
+    #[serde(default)]
+    usage: Option<CohereUsage>,
+}
+
+#[derive(Deserialize)]
+struct CohereUsage {
+    /// What Cohere charges for; `tokens` also counts its hidden chat template.
+    #[serde(default)]
+    billed_units: Option<CohereTokens>,
+    #[serde(default)]
+    tokens: Option<CohereTokens>,
+}
+
+#[derive(Deserialize)]
+struct CohereTokens {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+}
+
+#[derive(Deserialize)]
+struct CohereResponseMessage {
+    #[serde(default)]
+    content: Vec<CohereContent>,
+}
+
+#[derive(Deserialize)]
+struct CohereContent {
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ModelVerdict {
+    #[serde(deserialize_with = "super::labels::deserialize_label")]
+    label: String,
+    rationale: String,
+    mitigation: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::settings::LlmSettings;
+    use crate::scanner::{RiskThresholds, ScanReport, ScoreBreakdown};
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    fn base_settings(url: String) -> LlmSettings {
+        LlmSettings {
+            provider: "cohere".into(),
+            api_key: "test-key".into(),
+            endpoint: Some(url),
+            model: Some("command-test".into()),
+            deployment: None,
+            project: None,
+            workspace: None,
+            region: None,
+            timeout_secs: Some(5),
+            max_retries: 0,
+            api_version: None,
+            retry_base_delay_ms: None,
+            retry_max_delay_ms: None,
+            retry_jitter: None,
+            retry_on_status: None,
+            prompt_template: None,
+            verdict_labels: None,
+            structured_output: None,
+            stream: None,
+            stream_idle_timeout_secs: None,
+            proxy: None,
+            ca_bundle: None,
+            client_cert: None,
+            client_key: None,
+            azure_auth: None,
+            scrub_pii: None,
+            prompt_cache: None,
+            openai_api: None,
+            temperature: None,
+            max_tokens: None,
+            response_mime_type: None,
+            safety_settings: None,
+        }
+    }
+
+    fn empty_report() -> ScanReport {
+        ScanReport::from_breakdown(
+            vec![],
+            0,
+            None,
+            ScoreBreakdown::default(),
+            &RiskThresholds::default(),
+        )
+    }
+
+    #[tokio::test]
+    async fn enrich_parses_successful_response() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v2/chat")
+                .header("authorization", "Bearer test-key")
+                .json_body_partial(r#"{"model":"command-test","response_format":{"type":"json_object"}}"#);
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "id": "abc",
+                    "finish_reason": "COMPLETE",
+                    "message": {
+                        "role": "assistant",
+                        "content": [
+                            {"type": "text", "text": "{\"label\":\"safe\",\"rationale\":\"ok\",\"mitigation\":\"none\"}"}
+                        ]
+                    },
+                    "usage": {
+                        "billed_units": {"input_tokens": 80, "output_tokens": 20},
+                        "tokens": {"input_tokens": 95, "output_tokens": 20}
+                    }
+                }));
+        });
+
+        let client = CohereClient::new(&base_settings(server.base_url())).unwrap();
+        let verdict = client.enrich("hello", &empty_report()).await.unwrap();
+        assert_eq!(verdict.label, "safe");
+        assert_eq!(verdict.rationale, "ok");
+        assert_eq!(verdict.mitigation, "none");
+        assert_eq!(verdict.usage, Some(TokenUsage::new(80, 20)));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn retries_on_failure() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/v2/chat");
+            then.status(429);
+        });
+
+        let mut settings = base_settings(server.base_url());
+        settings.max_retries = 1;
+        let client = CohereClient::new(&settings).unwrap();
+        let err = client.enrich("hello", &empty_report()).await.unwrap_err();
+        assert!(err.to_string().contains("Cohere API error"));
+        mock.assert_hits(2);
+    }
+
+    #[test]
+    fn requires_api_key() {
+        let mut settings = base_settings("http://localhost".into());
+        settings.api_key = "  ".into();
+        let err = CohereClient::new(&settings).unwrap_err();
+        assert!(err.to_string().contains("Cohere API key"));
+    }
+}
@@ -0,0 +1,108 @@
+//! Provider API keys kept in the operating system keyring (macOS Keychain, Windows Credential
+//! Manager, the Secret Service on Linux) instead of environment variables or profiles.
+
+use anyhow::Result;
+
+/// Keyring service name under which every provider key is stored.
+pub const KEYRING_SERVICE: &str = "llm-guard";
+
+/// Keyring account for a provider: its trimmed, lowercased name.
+fn account(provider: &str) -> String {
+    provider.trim().to_ascii_lowercase()
+}
+
+/// Store `api_key` for `provider`, replacing any existing entry.
+#[cfg(feature = "keyring")]
+pub fn store_api_key(provider: &str, api_key: &str) -> Result<()> {
+    use anyhow::{bail, Context};
+    if api_key.trim().is_empty() {
+        bail!(
+            "refusing to store an empty API key for `{}`",
+            account(provider)
+        );
+    }
+    keyring::Entry::new(KEYRING_SERVICE, &account(provider))
+        .and_then(|entry| entry.set_password(api_key.trim()))
+        .with_context(|| {
+            format!(
+                "failed to store API key for `{}` in the keyring",
+                account(provider)
+            )
+        })
+}
+
+/// The stored API key for `provider`, or `None` when the keyring has no entry.
+#[cfg(feature = "keyring")]
+pub fn load_api_key(provider: &str) -> Result<Option<String>> {
+    use anyhow::Context;
+    let entry = keyring::Entry::new(KEYRING_SERVICE, &account(provider))
+        .with_context(|| format!("failed to open keyring entry for `{}`", account(provider)))?;
+    match entry.get_password() {
+        Ok(key) => Ok(Some(key)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) => Err(err).with_context(|| {
+            format!(
+                "failed to read API key for `{}` from the keyring",
+                account(provider)
+            )
+        }),
+    }
+}
+
+/// Remove the stored API key for `provider`; returns whether an entry existed.
+#[cfg(feature = "keyring")]
+pub fn delete_api_key(provider: &str) -> Result<bool> {
+    use anyhow::Context;
+    let entry = keyring::Entry::new(KEYRING_SERVICE, &account(provider))
+        .with_context(|| format!("failed to open keyring entry for `{}`", account(provider)))?;
+    match entry.delete_password() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(err) => Err(err).with_context(|| {
+            format!(
+                "failed to delete API key for `{}` from the keyring",
+                account(provider)
+            )
+        }),
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn store_api_key(provider: &str, _api_key: &str) -> Result<()> {
+    anyhow::bail!(
+        "storing the `{}` API key requires the `keyring` feature",
+        account(provider)
+    )
+}
+
+/// Without the `keyring` feature there is nothing to fall back to.
+#[cfg(not(feature = "keyring"))]
+pub fn load_api_key(_provider: &str) -> Result<Option<String>> {
+    Ok(None)
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn delete_api_key(provider: &str) -> Result<bool> {
+    anyhow::bail!(
+        "deleting the `{}` API key requires the `keyring` feature",
+        account(provider)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accounts_are_case_insensitive() {
+        assert_eq!(account(" OpenAI "), "openai");
+    }
+
+    #[cfg(not(feature = "keyring"))]
+    #[test]
+    fn store_requires_feature() {
+        let err = store_api_key("openai", "sk-test").unwrap_err();
+        assert!(err.to_string().contains("`keyring` feature"));
+        assert_eq!(load_api_key("openai").unwrap(), None);
+    }
+}
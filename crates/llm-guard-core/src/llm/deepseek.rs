@@ -0,0 +1,360 @@
+use super::{
+    compress_input, http, LlmClient, LlmSettings, PromptTemplate, RequestPreview, RetryPolicy,
+    DEFAULT_MAX_TOKENS, DEFAULT_TEMPERATURE, MAX_PROMPT_INPUT_CHARS,
+};
+use crate::scanner::{LlmVerdict, ScanReport, TokenUsage};
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::future;
+
+/// DeepSeek model used when `LLM_GUARD_MODEL` is unset.
+const DEFAULT_MODEL: &str = "deepseek-chat";
+/// Reasoning models count chain-of-thought against `max_tokens`, so they need a larger budget.
+const REASONER_MAX_TOKENS: u32 = 4096;
+
+#[derive(Debug, Clone)]
+pub struct DeepSeekClient {
+    http: Client,
+    url: String,
+    api_key: String,
+    model: String,
+    retry: RetryPolicy,
+    prompt: PromptTemplate,
+    temperature: Option<f32>,
+    max_tokens: Option<u32>,
+}
+
+impl DeepSeekClient {
+    pub fn new(settings: &LlmSettings) -> Result<Self> {
+        if settings.api_key.trim().is_empty() {
+            bail!("DeepSeek API key must be provided via LLM_GUARD_API_KEY");
+        }
+        let base = settings
+            .endpoint
+            .clone()
+            .unwrap_or_else(|| "https://api.deepseek.com".to_string());
+        let url = format!("{}/chat/completions", base.trim_end_matches('/'));
+        let http = http::client(settings, "DeepSeek")?;
+        Ok(Self {
+            http,
+            url,
+            api_key: settings.api_key.clone(),
+            model: settings
+                .model
+                .clone()
+                .unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+            retry: RetryPolicy::from_settings(settings),
+            prompt: PromptTemplate::from_settings(settings)?,
+            temperature: settings.temperature,
+            max_tokens: settings.max_tokens,
+        })
+    }
+
+    fn request(&self, input: &str, report: &ScanReport) -> ChatCompletionRequest {
+        // `deepseek-reasoner` ignores sampling parameters and rejects JSON mode.
+        let reasoner = is_reasoner(&self.model);
+        ChatCompletionRequest {
+            model: self.model.clone(),
+            messages: vec![
+                ChatMessage {
+                    role: "system",
+                    content: self.prompt.system().to_string(),
+                },
+                ChatMessage {
+                    role: "user",
+                    content: self.prompt.render_user(
+                        &compress_input(input, report, MAX_PROMPT_INPUT_CHARS),
+                        report,
+                    ),
+                },
+            ],
+            response_format: (!reasoner).then_some(ResponseFormat {
+                kind: "json_object",
+            }),
+            temperature: (!reasoner).then(|| self.temperature.unwrap_or(DEFAULT_TEMPERATURE)),
+            max_tokens: self.max_tokens.unwrap_or(if reasoner {
+                REASONER_MAX_TOKENS
+            } else {
+                DEFAULT_MAX_TOKENS
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl LlmClient for DeepSeekClient {
+    async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
+        let payload = self.request(input, report);
+
+        let response = self
+            .retry
+            .send("DeepSeek", "chat completions", |_| {
+                future::ready(Ok(self
+                    .http
+                    .post(&self.url)
+                    .bearer_auth(&self.api_key)
+                    .json(&payload)))
+            })
+            .await?;
+
+        let chat: ChatCompletionResponse = response
+            .json()
+            .await
+            .context("failed to parse DeepSeek response")?;
+        let usage = chat
+            .usage
+            .as_ref()
+            .and_then(|usage| TokenUsage::reported(usage.prompt_tokens, usage.completion_tokens));
+        let content = chat
+            .choices
+            .into_iter()
+            .find_map(|choice| choice.message.content.filter(|c| !c.trim().is_empty()))
+            .ok_or_else(|| anyhow!("DeepSeek response missing message content"))?;
+
+        let verdict: ModelVerdict = serde_json::from_str(final_answer(&content))
+            .context("expected JSON verdict from DeepSeek response")?;
+
+        Ok(LlmVerdict {
+            label: verdict.label,
+            rationale: verdict.rationale,
+            mitigation: verdict.mitigation,
+            usage,
+            ..Default::default()
+        })
+    }
+
+    fn preview(&self, input: &str, report: &ScanReport) -> Result<Vec<RequestPreview>> {
+        Ok(vec![RequestPreview {
+            provider: "deepseek".into(),
+            model: self.model.clone(),
+            url: Some(self.url.clone()),
+            body: serde_json::to_value(self.request(input, report))?,
+        }])
+    }
+}
+
+fn is_reasoner(model: &str) -> bool {
+    let model = model.to_ascii_lowercase();
+    model.contains("reasoner") || model.contains("-r1")
+}
+
+/// The answer portion of a reply. The official API returns chain-of-thought separately in
+/// `reasoning_content`, but self-hosted R1 deployments inline it as `<think>…</think>` and
+/// reasoning models often wrap the JSON in a Markdown fence.
+fn final_answer(content: &str) -> &str {
+    let answer = match content.rfind("</think>") {
+        Some(end) => &content[end + "</think>".len()..],
+        None => content,
+    };
+    let answer = answer.trim();
+    match answer.strip_prefix("```") {
+        Some(fenced) => {
+            let body = fenced.trim_start_matches("json");
+            body.strip_suffix("```").unwrap_or(body).trim()
+        }
+        None => answer,
+    }
+}
+
+#[derive(Serialize)]
+struct ChatCompletionRequest {
+    model: String,
+    messages: Vec<ChatMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<ResponseFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    max_tokens: u32,
+}
+
+#[derive(Serialize)]
+struct ResponseFormat {
+    #[serde(rename = "type")]
+    kind: &'static str,
+}
+
+#[derive(Serialize)]
+struct ChatMessage {
+    role: &'static str,
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct ChatCompletionResponse {
+    choices: Vec<ChatChoice>,
+    #[serde(default)]
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Deserialize)]
+struct ChatUsage {
+    #[serde(default)]
+    prompt_tokens: u64,
+    #[serde(default)]
+    completion_tokens: u64,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatResponseMessage,
+}
+
+/// `reasoning_content` (the `deepseek-reasoner` chain-of-thought) is deliberately not
+/// deserialized: only `content` carries the verdict.
+#[derive(Deserialize)]
+struct ChatResponseMessage {
+    content: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ModelVerdict {
+    #[serde(deserialize_with = "super::labels::deserialize_label")]
+    label: String,
+    rationale: String,
+    mitigation: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::settings::LlmSettings;
+    use crate::scanner::{RiskThresholds, ScanReport, ScoreBreakdown};
+    use httpmock::prelude::*;
+
+    fn base_settings(url: String) -> LlmSettings {
+        LlmSettings {
+            provider: "deepseek".into(),
+            api_key: "test-key".into(),
+            endpoint: Some(url),
+            model: Some("deepseek-chat".into()),
+            deployment: None,
+            project: None,
+            workspace: None,
+            region: None,
+            timeout_secs: Some(5),
+            max_retries: 0,
+            api_version: None,
+            retry_base_delay_ms: None,
+            retry_max_delay_ms: None,
+            retry_jitter: None,
+            retry_on_status: None,
+            prompt_template: None,
+            verdict_labels: None,
+            structured_output: None,
+            stream: None,
+            stream_idle_timeout_secs: None,
+            proxy: None,
+            ca_bundle: None,
+            client_cert: None,
+            client_key: None,
+            azure_auth: None,
+            scrub_pii: None,
+            prompt_cache: None,
+            openai_api: None,
+            temperature: None,
+            max_tokens: None,
+            response_mime_type: None,
+            safety_settings: None,
+        }
+    }
+
+    fn empty_report() -> ScanReport {
+        ScanReport::from_breakdown(
+            vec![],
+            0,
+            None,
+            ScoreBreakdown::default(),
+            &RiskThresholds::default(),
+        )
+    }
+
+    #[tokio::test]
+    async fn enrich_parses_successful_response() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/chat/completions")
+                .header("authorization", "Bearer test-key")
+                .json_body_partial(r#"{"model":"deepseek-chat","response_format":{"type":"json_object"}}"#);
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"choices":[{"message":{"content":"{\"label\":\"safe\",\"rationale\":\"ok\",\"mitigation\":\"none\"}"}}]}"#);
+        });
+
+        let client = DeepSeekClient::new(&base_settings(server.base_url())).unwrap();
+        let verdict = client.enrich("hello", &empty_report()).await.unwrap();
+        assert_eq!(verdict.label, "safe");
+        assert_eq!(verdict.rationale, "ok");
+        assert_eq!(verdict.mitigation, "none");
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn retries_on_failure() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/chat/completions");
+            then.status(500);
+        });
+
+        let mut settings = base_settings(server.base_url());
+        settings.max_retries = 1;
+        let client = DeepSeekClient::new(&settings).unwrap();
+        let err = client.enrich("hello", &empty_report()).await.unwrap_err();
+        assert!(err.to_string().contains("DeepSeek API error"));
+        mock.assert_hits(2);
+    }
+
+    #[tokio::test]
+    async fn reasoner_ignores_reasoning_content() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/chat/completions")
+                .json_body_partial(r#"{"model":"deepseek-reasoner","max_tokens":4096}"#);
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(serde_json::json!({
+                    "choices": [{
+                        "message": {
+                            "role": "assistant",
+                            "reasoning_content": "The input asks to {ignore} rules...",
+                            "content": "```json\n{\"label\":\"malicious\",\"rationale\":\"override\",\"mitigation\":\"block\"}\n```"
+                        }
+                    }]
+                }));
+        });
+
+        let mut settings = base_settings(server.base_url());
+        settings.model = Some("deepseek-reasoner".into());
+        let client = DeepSeekClient::new(&settings).unwrap();
+        let verdict = client.enrich("hello", &empty_report()).await.unwrap();
+        assert_eq!(verdict.label, "malicious");
+        mock.assert();
+    }
+
+    #[test]
+    fn final_answer_strips_think_blocks_and_fences() {
+        let json = r#"{"label":"safe","rationale":"ok","mitigation":"none"}"#;
+        assert_eq!(final_answer(json), json);
+        assert_eq!(
+            final_answer(&format!("<think>weigh {{the}} input</think>\n\n{json}")),
+            json
+        );
+        assert_eq!(final_answer(&format!("```json\n{json}\n```")), json);
+        assert!(is_reasoner("deepseek-reasoner"));
+        assert!(is_reasoner("DeepSeek-R1-Distill-Qwen-32B"));
+        assert!(!is_reasoner("deepseek-chat"));
+    }
+
+    #[test]
+    fn defaults_to_chat_model() {
+        let mut settings = base_settings("http://localhost".into());
+        settings.model = None;
+        let client = DeepSeekClient::new(&settings).unwrap();
+        assert_eq!(client.model, DEFAULT_MODEL);
+        assert_eq!(client.url, "http://localhost/chat/completions");
+    }
+}
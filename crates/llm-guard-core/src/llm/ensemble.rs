@@ -0,0 +1,300 @@
+use super::{LabelSet, LlmClient, RequestPreview};
+use crate::scanner::{EnsembleSummary, LlmVerdict, ProviderVote, ScanReport, TokenUsage};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::task::JoinSet;
+
+/// Fans `enrich` out to several providers concurrently and combines their verdicts by
+/// majority vote.
+///
+/// Ties go to the most severe label of the configured [`LabelSet`] (by default `malicious` >
+/// `suspicious` > `safe` > anything else), so a split ensemble never downgrades a warning.
+/// Members that fail are recorded in the vote summary; the call only errors when every member
+/// fails.
+#[derive(Clone)]
+pub struct EnsembleLlmClient {
+    members: Vec<(String, Arc<dyn LlmClient>)>,
+    labels: LabelSet,
+}
+
+impl EnsembleLlmClient {
+    /// `members` pairs a provider name (reported in the votes) with its client.
+    pub fn new(members: Vec<(String, Arc<dyn LlmClient>)>) -> Result<Self> {
+        if members.is_empty() {
+            bail!("an LLM ensemble needs at least one provider");
+        }
+        Ok(Self {
+            members,
+            labels: LabelSet::default(),
+        })
+    }
+
+    /// Rank tied votes by `labels` instead of the default set.
+    pub fn with_labels(mut self, labels: LabelSet) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    pub fn providers(&self) -> impl Iterator<Item = &str> {
+        self.members.iter().map(|(name, _)| name.as_str())
+    }
+}
+
+#[async_trait]
+impl LlmClient for EnsembleLlmClient {
+    async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
+        let mut tasks = JoinSet::new();
+        for (index, (_, client)) in self.members.iter().enumerate() {
+            let client = Arc::clone(client);
+            let input = input.to_string();
+            let report = report.clone();
+            tasks.spawn(async move { (index, client.enrich(&input, &report).await) });
+        }
+
+        let mut results: Vec<Option<Result<LlmVerdict>>> =
+            self.members.iter().map(|_| None).collect();
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((index, result)) => results[index] = Some(result),
+                Err(err) => return Err(anyhow!("ensemble member task failed: {err}")),
+            }
+        }
+
+        combine_verdicts(
+            self.members
+                .iter()
+                .zip(results)
+                .map(|((name, _), result)| {
+                    (
+                        name.clone(),
+                        result.unwrap_or_else(|| Err(anyhow!("no result"))),
+                    )
+                })
+                .collect(),
+            &self.labels,
+        )
+    }
+
+    fn preview(&self, input: &str, report: &ScanReport) -> Result<Vec<RequestPreview>> {
+        let mut previews = Vec::new();
+        for (_, client) in &self.members {
+            previews.extend(client.preview(input, report)?);
+        }
+        Ok(previews)
+    }
+}
+
+/// Combine per-provider results (in configuration order) into one verdict.
+///
+/// The rationale lists every successful member's reasoning prefixed with its provider; the
+/// mitigation comes from the first member that voted for the winning label. Ties go to the
+/// label ranked most severe in `labels`.
+pub fn combine_verdicts(
+    results: Vec<(String, Result<LlmVerdict>)>,
+    labels: &LabelSet,
+) -> Result<LlmVerdict> {
+    let mut votes = Vec::with_capacity(results.len());
+    let mut answered = Vec::new();
+    for (provider, result) in results {
+        match result {
+            Ok(verdict) => {
+                let label = match labels.get(&verdict.label) {
+                    Some(known) => known.name.clone(),
+                    None => verdict.label.trim().to_ascii_lowercase(),
+                };
+                votes.push(ProviderVote {
+                    provider: provider.clone(),
+                    label: Some(label.clone()),
+                    error: None,
+                });
+                answered.push((provider, label, verdict));
+            }
+            Err(err) => votes.push(ProviderVote {
+                provider,
+                label: None,
+                error: Some(format!("{err:#}")),
+            }),
+        }
+    }
+    if answered.is_empty() {
+        let errors = votes
+            .iter()
+            .filter_map(|vote| {
+                vote.error
+                    .as_ref()
+                    .map(|err| format!("{}: {err}", vote.provider))
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        bail!("every ensemble provider failed ({errors})");
+    }
+
+    let mut tally: HashMap<&str, usize> = HashMap::new();
+    for (_, label, _) in &answered {
+        *tally.entry(label.as_str()).or_default() += 1;
+    }
+    let disagreement = tally.len() > 1;
+    let winner = tally
+        .iter()
+        .max_by_key(|(label, count)| {
+            let severity = labels.rank(label).map_or(0, |rank| rank + 1);
+            (**count, severity, Reverse(**label))
+        })
+        .map(|(label, _)| label.to_string())
+        .expect("at least one provider answered");
+
+    let rationale = answered
+        .iter()
+        .map(|(provider, label, verdict)| format!("[{provider}: {label}] {}", verdict.rationale))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mitigation = answered
+        .iter()
+        .find(|(_, label, _)| *label == winner)
+        .map(|(_, _, verdict)| verdict.mitigation.clone())
+        .unwrap_or_default();
+    let usage = answered
+        .iter()
+        .filter_map(|(_, _, verdict)| verdict.usage.as_ref())
+        .fold(None, |total: Option<TokenUsage>, usage| {
+            let mut total = total.unwrap_or_default();
+            total.accumulate(usage);
+            Some(total)
+        });
+
+    Ok(LlmVerdict {
+        label: winner,
+        rationale,
+        mitigation,
+        ensemble: Some(EnsembleSummary {
+            votes,
+            disagreement,
+        }),
+        usage,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{RiskThresholds, ScoreBreakdown};
+
+    struct Fixed(&'static str);
+
+    #[async_trait]
+    impl LlmClient for Fixed {
+        async fn enrich(&self, _input: &str, _report: &ScanReport) -> Result<LlmVerdict> {
+            Ok(verdict(self.0))
+        }
+    }
+
+    struct Failing;
+
+    #[async_trait]
+    impl LlmClient for Failing {
+        async fn enrich(&self, _input: &str, _report: &ScanReport) -> Result<LlmVerdict> {
+            bail!("provider offline")
+        }
+    }
+
+    fn verdict(label: &str) -> LlmVerdict {
+        LlmVerdict {
+            label: label.into(),
+            rationale: format!("{label} because"),
+            mitigation: format!("handle {label}"),
+            usage: Some(TokenUsage::new(10, 5)),
+            ..Default::default()
+        }
+    }
+
+    fn report() -> ScanReport {
+        ScanReport::from_breakdown(
+            vec![],
+            0,
+            None,
+            ScoreBreakdown::default(),
+            &RiskThresholds::default(),
+        )
+    }
+
+    #[tokio::test]
+    async fn majority_label_wins_and_flags_disagreement() {
+        let client = EnsembleLlmClient::new(vec![
+            ("a".into(), Arc::new(Fixed("safe")) as Arc<dyn LlmClient>),
+            ("b".into(), Arc::new(Fixed("Malicious"))),
+            ("c".into(), Arc::new(Fixed("malicious"))),
+        ])
+        .unwrap();
+        let verdict = client.enrich("input", &report()).await.unwrap();
+        assert_eq!(verdict.label, "malicious");
+        assert_eq!(verdict.mitigation, "handle Malicious");
+        assert!(verdict.rationale.contains("[a: safe] safe because"));
+        let summary = verdict.ensemble.unwrap();
+        assert!(summary.disagreement);
+        assert_eq!(
+            summary
+                .votes
+                .iter()
+                .map(|vote| vote.provider.as_str())
+                .collect::<Vec<_>>(),
+            ["a", "b", "c"]
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_members_are_recorded_but_do_not_vote() {
+        let client = EnsembleLlmClient::new(vec![
+            ("a".into(), Arc::new(Failing) as Arc<dyn LlmClient>),
+            ("b".into(), Arc::new(Fixed("safe"))),
+        ])
+        .unwrap();
+        let verdict = client.enrich("input", &report()).await.unwrap();
+        assert_eq!(verdict.label, "safe");
+        let summary = verdict.ensemble.unwrap();
+        assert!(!summary.disagreement);
+        assert_eq!(summary.votes[0].error.as_deref(), Some("provider offline"));
+
+        let all_failed = EnsembleLlmClient::new(vec![("a".into(), Arc::new(Failing) as _)])
+            .unwrap()
+            .enrich("input", &report())
+            .await
+            .unwrap_err();
+        assert!(all_failed.to_string().contains("a: provider offline"));
+    }
+
+    #[test]
+    fn ties_resolve_to_the_more_severe_label() {
+        let verdict = combine_verdicts(
+            vec![
+                ("a".into(), Ok(verdict("safe"))),
+                ("b".into(), Ok(verdict("suspicious"))),
+            ],
+            &LabelSet::default(),
+        )
+        .unwrap();
+        assert_eq!(verdict.label, "suspicious");
+        assert_eq!(verdict.usage, Some(TokenUsage::new(20, 10)));
+        assert!(EnsembleLlmClient::new(Vec::new()).is_err());
+    }
+
+    #[tokio::test]
+    async fn custom_labels_rank_ties() {
+        let labels: LabelSet = "allow,review,block".parse().unwrap();
+        let client = EnsembleLlmClient::new(vec![
+            ("a".into(), Arc::new(Fixed("Review")) as Arc<dyn LlmClient>),
+            ("b".into(), Arc::new(Fixed("allow"))),
+        ])
+        .unwrap()
+        .with_labels(labels);
+        let verdict = client.enrich("input", &report()).await.unwrap();
+        assert_eq!(verdict.label, "review");
+        assert_eq!(
+            verdict.ensemble.unwrap().votes[0].label.as_deref(),
+            Some("review")
+        );
+    }
+}
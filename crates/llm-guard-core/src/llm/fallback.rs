@@ -0,0 +1,132 @@
+use super::{LlmClient, RequestPreview};
+use crate::scanner::{LlmVerdict, ScanReport};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Tries providers in order until one returns a verdict.
+///
+/// A provider that errors (including HTTP timeouts, which surface as errors once its own
+/// retries are exhausted) hands the request to the next one. The verdict records which
+/// provider produced it in `LlmVerdict::provider`.
+#[derive(Clone)]
+pub struct FallbackLlmClient {
+    chain: Vec<(String, Arc<dyn LlmClient>)>,
+}
+
+impl FallbackLlmClient {
+    /// `chain` pairs a provider name (recorded in the verdict) with its client, primary first.
+    pub fn new(chain: Vec<(String, Arc<dyn LlmClient>)>) -> Result<Self> {
+        if chain.is_empty() {
+            bail!("a provider fallback chain needs at least one provider");
+        }
+        Ok(Self { chain })
+    }
+
+    pub fn providers(&self) -> impl Iterator<Item = &str> {
+        self.chain.iter().map(|(name, _)| name.as_str())
+    }
+}
+
+#[async_trait]
+impl LlmClient for FallbackLlmClient {
+    async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
+        let mut errors = Vec::new();
+        for (name, client) in &self.chain {
+            match client.enrich(input, report).await {
+                Ok(mut verdict) => {
+                    verdict.provider.get_or_insert_with(|| name.clone());
+                    return Ok(verdict);
+                }
+                Err(err) => {
+                    tracing::warn!("provider {name} failed, trying next in chain: {err:#}");
+                    errors.push(format!("{name}: {err:#}"));
+                }
+            }
+        }
+        bail!(
+            "every provider in the fallback chain failed ({})",
+            errors.join("; ")
+        )
+    }
+
+    fn preview(&self, input: &str, report: &ScanReport) -> Result<Vec<RequestPreview>> {
+        let mut previews = Vec::new();
+        for (_, client) in &self.chain {
+            previews.extend(client.preview(input, report)?);
+        }
+        Ok(previews)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{RiskThresholds, ScoreBreakdown};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Fixed(&'static str);
+
+    #[async_trait]
+    impl LlmClient for Fixed {
+        async fn enrich(&self, _input: &str, _report: &ScanReport) -> Result<LlmVerdict> {
+            Ok(LlmVerdict {
+                label: self.0.into(),
+                ..Default::default()
+            })
+        }
+    }
+
+    #[derive(Default)]
+    struct Failing(AtomicUsize);
+
+    #[async_trait]
+    impl LlmClient for Failing {
+        async fn enrich(&self, _input: &str, _report: &ScanReport) -> Result<LlmVerdict> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            bail!("provider offline")
+        }
+    }
+
+    fn report() -> ScanReport {
+        ScanReport::from_breakdown(
+            vec![],
+            0,
+            None,
+            ScoreBreakdown::default(),
+            &RiskThresholds::default(),
+        )
+    }
+
+    #[tokio::test]
+    async fn falls_through_to_the_first_working_provider() {
+        let failing = Arc::new(Failing::default());
+        let client = FallbackLlmClient::new(vec![
+            ("openai".into(), failing.clone() as Arc<dyn LlmClient>),
+            ("azure".into(), Arc::new(Fixed("suspicious"))),
+            ("noop".into(), Arc::new(Fixed("unavailable"))),
+        ])
+        .unwrap();
+        let verdict = client.enrich("input", &report()).await.unwrap();
+        assert_eq!(verdict.label, "suspicious");
+        assert_eq!(verdict.provider.as_deref(), Some("azure"));
+        assert_eq!(failing.0.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn reports_every_failure_when_the_chain_is_exhausted() {
+        let client = FallbackLlmClient::new(vec![
+            (
+                "a".into(),
+                Arc::new(Failing::default()) as Arc<dyn LlmClient>,
+            ),
+            ("b".into(), Arc::new(Failing::default())),
+        ])
+        .unwrap();
+        let err = client.enrich("input", &report()).await.unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("a: provider offline; b: provider offline"));
+        assert!(FallbackLlmClient::new(Vec::new()).is_err());
+    }
+}
@@ -1,19 +1,47 @@
-use super::{LlmClient, LlmSettings};
-use crate::scanner::{LlmVerdict, ScanReport};
+use super::{
+    compress_input, http, LlmClient, LlmSettings, PromptTemplate, RequestPreview, RetryPolicy,
+    SafetySetting, MAX_PROMPT_INPUT_CHARS,
+};
+use crate::scanner::{LlmVerdict, ScanReport, TokenUsage};
 use anyhow::{anyhow, bail, Context, Result};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::env;
-use std::time::Duration;
-use tokio::time::sleep;
+use std::future;
 
 #[derive(Debug, Clone)]
 pub struct GeminiClient {
     http: Client,
     url: String,
+    model: String,
     api_key: String,
-    max_retries: u32,
+    retry: RetryPolicy,
+    prompt: PromptTemplate,
+    generation: GenerationOptions,
+}
+
+/// `generationConfig` and `safetySettings` shared by the Gemini API and Vertex AI clients.
+#[derive(Debug, Clone)]
+pub(super) struct GenerationOptions {
+    temperature: Option<f32>,
+    max_output_tokens: Option<u32>,
+    response_mime_type: String,
+    safety_settings: Vec<SafetySetting>,
+}
+
+impl GenerationOptions {
+    pub(super) fn from_settings(settings: &LlmSettings) -> Self {
+        Self {
+            temperature: settings.temperature,
+            max_output_tokens: settings.max_tokens,
+            response_mime_type: settings
+                .response_mime_type
+                .clone()
+                .unwrap_or_else(|| "application/json".to_string()),
+            safety_settings: settings.safety_settings.clone().unwrap_or_default(),
+        }
+    }
 }
 
 impl GeminiClient {
@@ -34,16 +62,15 @@ impl GeminiClient {
             base.trim_end_matches('/'),
             model
         );
-        let http = Client::builder()
-            .user_agent("llm-guard/0.1")
-            .timeout(Duration::from_secs(settings.timeout_secs.unwrap_or(30)))
-            .build()
-            .context("failed to build Gemini HTTP client")?;
+        let http = http::client(settings, "Gemini")?;
         Ok(Self {
             http,
             url,
+            model,
             api_key: settings.api_key.clone(),
-            max_retries: settings.max_retries,
+            retry: RetryPolicy::from_settings(settings),
+            prompt: PromptTemplate::from_settings(settings)?,
+            generation: GenerationOptions::from_settings(settings),
         })
     }
 }
@@ -51,124 +78,177 @@ impl GeminiClient {
 #[async_trait]
 impl LlmClient for GeminiClient {
     async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
-        let payload = GeminiRequest {
-            contents: vec![GeminiRequestContent {
-                role: "user".into(),
-                parts: vec![GeminiRequestPart {
-                    text: Some(format!(
-                        "{}\n\nInput excerpt:\n{}\n\nScore: {:.1} ({:?})\nTop findings: {}\n",
-                        SYSTEM_PROMPT,
-                        truncate(input, 2000),
-                        report.risk_score,
-                        report.risk_band,
-                        serde_json::to_string(&report.findings).unwrap_or_default()
-                    )),
-                }],
-            }],
-            generation_config: Some(GeminiGenerationConfig {
-                response_mime_type: "application/json".to_string(),
-            }),
-        };
-
-        let mut attempt = 0u32;
-        let mut backoff = Duration::from_millis(200);
-        loop {
-            let response = self
-                .http
-                .post(&self.url)
-                .query(&[("key", &self.api_key)])
-                .json(&payload)
-                .send()
-                .await;
-
-            let response = match response {
-                Ok(resp) => resp,
-                Err(err) => {
-                    if attempt >= self.max_retries {
-                        return Err(err).context("failed to call Gemini generateContent API");
-                    }
-                    sleep(backoff).await;
-                    backoff = (backoff * 2).min(Duration::from_secs(5));
-                    attempt += 1;
-                    continue;
-                }
-            };
-
-            if !response.status().is_success() {
-                if attempt >= self.max_retries {
-                    let status = response.status();
-                    let body = response.text().await.unwrap_or_default();
-                    bail!("Gemini API error ({}): {}", status, body);
-                }
-                sleep(backoff).await;
-                backoff = (backoff * 2).min(Duration::from_secs(5));
-                attempt += 1;
-                continue;
-            }
-
-            let message: GeminiResponse = response
-                .json()
-                .await
-                .context("failed to parse Gemini response")?;
-
-            // Always log raw response when debug is enabled
-            if debug_enabled() {
-                tracing::warn!(
-                    "gemini raw response: {}",
-                    serde_json::to_string_pretty(&message).unwrap_or_default()
-                );
-            }
-
-            let content = message
-                .candidates
-                .into_iter()
-                .flat_map(|candidate| candidate.content.parts)
-                .filter_map(|part| part.text)
-                .next()
-                .ok_or_else(|| anyhow!("Gemini response missing message content"))?;
-
-            // Log extracted content when debug is enabled
-            if debug_enabled() {
-                tracing::warn!("gemini extracted content: {}", content);
-            }
-
-            let verdict: ModelVerdict = serde_json::from_str(&content)
-                .context("expected JSON verdict from Gemini response")?;
-
-            return Ok(LlmVerdict {
-                label: verdict.label,
-                rationale: verdict.rationale,
-                mitigation: verdict.mitigation,
-            });
+        let payload = verdict_request(&self.prompt, &self.generation, input, report);
+
+        let response = self
+            .retry
+            .send("Gemini", "generateContent", |_| {
+                future::ready(Ok(self
+                    .http
+                    .post(&self.url)
+                    .query(&[("key", &self.api_key)])
+                    .json(&payload)))
+            })
+            .await?;
+
+        let message: GeminiResponse = response
+            .json()
+            .await
+            .context("failed to parse Gemini response")?;
+
+        // Always log raw response when debug is enabled
+        if debug_enabled() {
+            tracing::warn!(
+                "gemini raw response: {}",
+                serde_json::to_string_pretty(&message).unwrap_or_default()
+            );
         }
+
+        extract_verdict(message, "Gemini")
+    }
+
+    fn preview(&self, input: &str, report: &ScanReport) -> Result<Vec<RequestPreview>> {
+        Ok(vec![RequestPreview {
+            provider: "gemini".into(),
+            model: self.model.clone(),
+            url: Some(self.url.clone()),
+            body: serde_json::to_value(verdict_request(
+                &self.prompt,
+                &self.generation,
+                input,
+                report,
+            ))?,
+        }])
     }
 }
 
-const SYSTEM_PROMPT: &str = "You are an application security assistant. Analyze prompt-injection scan results and respond with strict JSON: {\"label\": \"safe|suspicious|malicious\", \"rationale\": \"...\", \"mitigation\": \"...\"}. The mitigation should advise remediation steps.";
+/// `generateContent` payload shared by the Gemini API and Vertex AI clients.
+pub(super) fn verdict_request(
+    prompt: &PromptTemplate,
+    generation: &GenerationOptions,
+    input: &str,
+    report: &ScanReport,
+) -> GeminiRequest {
+    GeminiRequest {
+        contents: vec![GeminiRequestContent {
+            role: "user".into(),
+            parts: vec![GeminiRequestPart {
+                text: Some(format!(
+                    "{}\n\n{}",
+                    prompt.system(),
+                    prompt.render_user(
+                        &compress_input(input, report, MAX_PROMPT_INPUT_CHARS),
+                        report
+                    )
+                )),
+            }],
+        }],
+        generation_config: Some(GeminiGenerationConfig {
+            response_mime_type: generation.response_mime_type.clone(),
+            temperature: generation.temperature,
+            max_output_tokens: generation.max_output_tokens,
+        }),
+        safety_settings: generation
+            .safety_settings
+            .iter()
+            .map(|setting| GeminiSafetySetting {
+                category: setting.category.clone(),
+                threshold: setting.threshold.clone(),
+            })
+            .collect(),
+    }
+}
 
-fn truncate(input: &str, max_chars: usize) -> String {
-    if input.chars().count() <= max_chars {
-        return input.to_string();
+/// Parse the JSON verdict from the first text part of a `generateContent` response.
+pub(super) fn extract_verdict(message: GeminiResponse, provider: &str) -> Result<LlmVerdict> {
+    let usage = message.usage_metadata.as_ref().and_then(|usage| {
+        TokenUsage::reported(
+            usage.prompt_token_count,
+            usage.candidates_token_count + usage.thoughts_token_count,
+        )
+    });
+    if let Some(reason) = message
+        .prompt_feedback
+        .as_ref()
+        .and_then(|feedback| feedback.block_reason.as_deref())
+    {
+        bail!("{provider} blocked the prompt ({reason}); adjust LLM_GUARD_SAFETY_SETTINGS to allow it");
     }
-    input.chars().take(max_chars).collect::<String>() + "…"
+    let blocked = message
+        .candidates
+        .iter()
+        .find_map(|candidate| candidate.finish_reason.as_deref())
+        .filter(|reason| BLOCKING_FINISH_REASONS.contains(reason))
+        .map(str::to_string);
+    let content = message
+        .candidates
+        .into_iter()
+        .filter_map(|candidate| candidate.content)
+        .flat_map(|content| content.parts)
+        .filter_map(|part| part.text)
+        .next();
+    let content = match (content, blocked) {
+        (Some(content), _) => content,
+        (None, Some(reason)) => bail!(
+            "{provider} withheld the verdict (finishReason {reason}); adjust LLM_GUARD_SAFETY_SETTINGS to allow it"
+        ),
+        (None, None) => bail!("{provider} response missing message content"),
+    };
+
+    // Log extracted content when debug is enabled
+    if debug_enabled() {
+        tracing::warn!("{} extracted content: {}", provider.to_lowercase(), content);
+    }
+
+    let verdict: ModelVerdict = serde_json::from_str(&content)
+        .with_context(|| format!("expected JSON verdict from {provider} response"))?;
+
+    Ok(LlmVerdict {
+        label: verdict.label,
+        rationale: verdict.rationale,
+        mitigation: verdict.mitigation,
+        usage,
+        ..Default::default()
+    })
 }
 
-fn debug_enabled() -> bool {
+/// Finish reasons that mean a filter suppressed the answer.
+const BLOCKING_FINISH_REASONS: [&str; 5] = [
+    "SAFETY",
+    "RECITATION",
+    "BLOCKLIST",
+    "PROHIBITED_CONTENT",
+    "SPII",
+];
+
+pub(super) fn debug_enabled() -> bool {
     matches!(env::var("LLM_GUARD_DEBUG"), Ok(val) if !val.is_empty() && val != "0")
 }
 
 #[derive(Serialize)]
-struct GeminiRequest {
+pub(super) struct GeminiRequest {
     contents: Vec<GeminiRequestContent>,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "generationConfig")]
     generation_config: Option<GeminiGenerationConfig>,
+    #[serde(rename = "safetySettings", skip_serializing_if = "Vec::is_empty")]
+    safety_settings: Vec<GeminiSafetySetting>,
 }
 
 #[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
 struct GeminiGenerationConfig {
-    #[serde(rename = "responseMimeType")]
     response_mime_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_output_tokens: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct GeminiSafetySetting {
+    category: String,
+    threshold: String,
 }
 
 #[derive(Serialize)]
@@ -184,13 +264,50 @@ struct GeminiRequestPart {
 }
 
 #[derive(Deserialize, Serialize)]
-struct GeminiResponse {
+pub(super) struct GeminiResponse {
+    #[serde(default)]
     candidates: Vec<GeminiCandidate>,
+    #[serde(
+        default,
+        rename = "promptFeedback",
+        skip_serializing_if = "Option::is_none"
+    )]
+    prompt_feedback: Option<GeminiPromptFeedback>,
+    #[serde(
+        default,
+        rename = "usageMetadata",
+        skip_serializing_if = "Option::is_none"
+    )]
+    usage_metadata: Option<GeminiUsage>,
 }
 
 #[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiUsage {
+    #[serde(default)]
+    prompt_token_count: u64,
+    #[serde(default)]
+    candidates_token_count: u64,
+    /// Thinking models bill their reasoning as output tokens.
+    #[serde(default)]
+    thoughts_token_count: u64,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiPromptFeedback {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    block_reason: Option<String>,
+}
+
+/// Candidates stopped by a safety filter carry a `finishReason` but no content.
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 struct GeminiCandidate {
-    content: GeminiResponseContent,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content: Option<GeminiResponseContent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    finish_reason: Option<String>,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -206,6 +323,7 @@ struct GeminiResponsePart {
 
 #[derive(Deserialize)]
 struct ModelVerdict {
+    #[serde(deserialize_with = "super::labels::deserialize_label")]
     label: String,
     rationale: String,
     mitigation: String,
@@ -228,9 +346,31 @@ mod tests {
             deployment: None,
             project: None,
             workspace: None,
+            region: None,
             timeout_secs: Some(5),
             max_retries: 0,
             api_version: None,
+            retry_base_delay_ms: None,
+            retry_max_delay_ms: None,
+            retry_jitter: None,
+            retry_on_status: None,
+            prompt_template: None,
+            verdict_labels: None,
+            structured_output: None,
+            stream: None,
+            stream_idle_timeout_secs: None,
+            proxy: None,
+            ca_bundle: None,
+            client_cert: None,
+            client_key: None,
+            azure_auth: None,
+            scrub_pii: None,
+            prompt_cache: None,
+            openai_api: None,
+            temperature: None,
+            max_tokens: None,
+            response_mime_type: None,
+            safety_settings: None,
         }
     }
 
@@ -264,7 +404,13 @@ mod tests {
                                 ]
                             }
                         }
-                    ]
+                    ],
+                    "usageMetadata": {
+                        "promptTokenCount": 200,
+                        "candidatesTokenCount": 40,
+                        "thoughtsTokenCount": 10,
+                        "totalTokenCount": 250
+                    }
                 }));
         });
 
@@ -273,9 +419,66 @@ mod tests {
         assert_eq!(verdict.label, "safe");
         assert_eq!(verdict.rationale, "ok");
         assert_eq!(verdict.mitigation, "none");
+        assert_eq!(verdict.usage, Some(TokenUsage::new(200, 50)));
         mock.assert();
     }
 
+    #[test]
+    fn request_carries_generation_config_and_safety_settings() {
+        let mut settings = base_settings("http://localhost".into());
+        let client = GeminiClient::new(&settings).unwrap();
+        let body = serde_json::to_value(verdict_request(
+            &client.prompt,
+            &client.generation,
+            "hi",
+            &empty_report(),
+        ))
+        .unwrap();
+        assert_eq!(
+            body["generationConfig"],
+            json!({"responseMimeType": "application/json"})
+        );
+        assert!(body.get("safetySettings").is_none());
+
+        settings.temperature = Some(0.0);
+        settings.max_tokens = Some(1024);
+        settings.safety_settings =
+            Some(SafetySetting::parse_list("dangerous_content=block_none").unwrap());
+        let client = GeminiClient::new(&settings).unwrap();
+        let body = serde_json::to_value(verdict_request(
+            &client.prompt,
+            &client.generation,
+            "hi",
+            &empty_report(),
+        ))
+        .unwrap();
+        assert_eq!(body["generationConfig"]["temperature"], 0.0);
+        assert_eq!(body["generationConfig"]["maxOutputTokens"], 1024);
+        assert_eq!(
+            body["safetySettings"],
+            json!([{"category": "HARM_CATEGORY_DANGEROUS_CONTENT", "threshold": "BLOCK_NONE"}])
+        );
+    }
+
+    #[test]
+    fn safety_blocks_are_reported_as_such() {
+        let blocked: GeminiResponse = serde_json::from_value(json!({
+            "candidates": [{"finishReason": "SAFETY", "safetyRatings": []}]
+        }))
+        .unwrap();
+        let err = extract_verdict(blocked, "Gemini").unwrap_err().to_string();
+        assert!(err.contains("finishReason SAFETY"), "{err}");
+
+        let prompt_blocked: GeminiResponse = serde_json::from_value(json!({
+            "promptFeedback": {"blockReason": "SAFETY"}
+        }))
+        .unwrap();
+        let err = extract_verdict(prompt_blocked, "Gemini")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("blocked the prompt"), "{err}");
+    }
+
     #[tokio::test]
     #[ignore = "requires loopback networking"]
     async fn retries_on_failure() {
@@ -294,15 +497,4 @@ mod tests {
         assert!(err.to_string().contains("Gemini API error"));
         mock.assert_hits(2);
     }
-
-    #[test]
-    fn truncate_short_strings_return_same() {
-        assert_eq!(truncate("abc", 10), "abc");
-    }
-
-    #[test]
-    fn truncate_long_strings_adds_ellipsis() {
-        let result = truncate("abcdefghijklmnopqrstuvwxyz", 5);
-        assert_eq!(result, "abcde…");
-    }
 }
@@ -0,0 +1,401 @@
+use super::{LlmClient, RequestPreview};
+use crate::scanner::{LlmVerdict, ScanReport};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+#[cfg(feature = "llm")]
+use serde::Deserializer;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Label a provider may return when it could not produce a verdict at all; always accepted.
+pub const UNKNOWN_LABEL: &str = "unknown";
+
+/// Name of the tool/function through which structured-output requests return the verdict.
+#[cfg(feature = "llm")]
+pub(crate) const VERDICT_TOOL: &str = "record_verdict";
+#[cfg(feature = "llm")]
+pub(crate) const VERDICT_TOOL_DESCRIPTION: &str =
+    "Record the verdict for the scanned input: its label, a rationale, and mitigation advice.";
+
+/// One verdict label a provider may answer with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VerdictLabel {
+    /// Label name as it appears in prompts and reports (e.g. `block`, or `3` on a numeric scale).
+    pub name: String,
+    /// Meaning of the label, listed in the system prompt when set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Points added to the heuristic risk score (clamped to 0–100) when the verdict carries
+    /// this label; the band is re-derived from the adjusted score.
+    #[serde(default)]
+    pub score_adjustment: f32,
+}
+
+impl VerdictLabel {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            score_adjustment: 0.0,
+        }
+    }
+
+    pub fn with_score_adjustment(mut self, points: f32) -> Self {
+        self.score_adjustment = points;
+        self
+    }
+}
+
+/// Verdict labels in ascending severity order.
+///
+/// The default set is `safe`/`suspicious`/`malicious` with no score adjustments. Severity
+/// breaks ensemble ties, and a provider answering with a label outside the set is treated as a
+/// failed call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Vec<VerdictLabel>", into = "Vec<VerdictLabel>")]
+pub struct LabelSet {
+    labels: Vec<VerdictLabel>,
+}
+
+impl LabelSet {
+    /// Build a label set from labels ordered least to most severe.
+    pub fn new(labels: Vec<VerdictLabel>) -> Result<Self, LabelSetError> {
+        if labels.is_empty() {
+            return Err(LabelSetError::Empty);
+        }
+        for (idx, label) in labels.iter().enumerate() {
+            let name = label.name.trim();
+            if name.is_empty() || name.contains(['|', ',']) {
+                return Err(LabelSetError::InvalidName {
+                    label: label.name.clone(),
+                });
+            }
+            if name.eq_ignore_ascii_case(UNKNOWN_LABEL) {
+                return Err(LabelSetError::Reserved);
+            }
+            if !(-100.0..=100.0).contains(&label.score_adjustment) {
+                return Err(LabelSetError::InvalidAdjustment {
+                    label: label.name.clone(),
+                    score_adjustment: label.score_adjustment,
+                });
+            }
+            if labels[..idx]
+                .iter()
+                .any(|other| other.name.trim().eq_ignore_ascii_case(name))
+            {
+                return Err(LabelSetError::DuplicateName {
+                    label: label.name.clone(),
+                });
+            }
+        }
+        Ok(Self {
+            labels: labels
+                .into_iter()
+                .map(|label| VerdictLabel {
+                    name: label.name.trim().to_string(),
+                    ..label
+                })
+                .collect(),
+        })
+    }
+
+    /// Look up a label by name (case-insensitive, surrounding whitespace ignored).
+    pub fn get(&self, name: &str) -> Option<&VerdictLabel> {
+        let name = name.trim();
+        self.labels
+            .iter()
+            .find(|label| label.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Position of the named label in ascending severity order.
+    pub fn rank(&self, name: &str) -> Option<usize> {
+        let name = name.trim();
+        self.labels
+            .iter()
+            .position(|label| label.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Labels in ascending severity order.
+    pub fn labels(&self) -> &[VerdictLabel] {
+        &self.labels
+    }
+
+    /// Label names joined with `|`, as shown to the model.
+    pub fn choices(&self) -> String {
+        self.labels
+            .iter()
+            .map(|label| label.name.as_str())
+            .collect::<Vec<_>>()
+            .join("|")
+    }
+
+    /// One line per described label, or an empty string when none carry a description.
+    pub fn guide(&self) -> String {
+        let mut guide = String::new();
+        for label in &self.labels {
+            if let Some(description) = label.description.as_deref() {
+                let _ = write!(guide, "\n- {}: {}", label.name, description.trim());
+            }
+        }
+        if guide.is_empty() {
+            return guide;
+        }
+        format!(" Labels, from least to most severe:{guide}")
+    }
+
+    /// JSON Schema of a verdict object restricted to these labels, for tool calling.
+    pub fn verdict_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "label": {
+                    "type": "string",
+                    "enum": self.labels.iter().map(|label| label.name.as_str()).collect::<Vec<_>>(),
+                },
+                "rationale": { "type": "string" },
+                "mitigation": { "type": "string" },
+            },
+            "required": ["label", "rationale", "mitigation"],
+            "additionalProperties": false,
+        })
+    }
+
+    /// Score adjustment for `label`, if it is known and non-zero.
+    pub fn score_adjustment(&self, label: &str) -> Option<f32> {
+        self.get(label)
+            .map(|label| label.score_adjustment)
+            .filter(|points| *points != 0.0)
+    }
+
+    /// Replace the verdict's label with its canonical spelling, rejecting labels outside the set.
+    pub fn validate(&self, mut verdict: LlmVerdict) -> Result<LlmVerdict> {
+        if verdict.label.trim().eq_ignore_ascii_case(UNKNOWN_LABEL) {
+            verdict.label = UNKNOWN_LABEL.to_string();
+            return Ok(verdict);
+        }
+        match self.get(&verdict.label) {
+            Some(label) => {
+                verdict.label = label.name.clone();
+                Ok(verdict)
+            }
+            None => bail!(
+                "model returned unknown verdict label `{}` (expected one of {})",
+                verdict.label,
+                self.choices()
+            ),
+        }
+    }
+}
+
+impl Default for LabelSet {
+    fn default() -> Self {
+        Self {
+            labels: vec![
+                VerdictLabel::new("safe"),
+                VerdictLabel::new("suspicious"),
+                VerdictLabel::new("malicious"),
+            ],
+        }
+    }
+}
+
+impl TryFrom<Vec<VerdictLabel>> for LabelSet {
+    type Error = LabelSetError;
+
+    fn try_from(labels: Vec<VerdictLabel>) -> Result<Self, Self::Error> {
+        Self::new(labels)
+    }
+}
+
+impl From<LabelSet> for Vec<VerdictLabel> {
+    fn from(set: LabelSet) -> Self {
+        set.labels
+    }
+}
+
+/// Parses either a JSON array of labels or a comma-separated list of names ordered least to
+/// most severe, each optionally followed by `=<score adjustment>` (e.g. `allow=-10,review,block=25`).
+impl FromStr for LabelSet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let labels = if s.starts_with('[') {
+            serde_json::from_str::<Vec<VerdictLabel>>(s).map_err(|err| err.to_string())?
+        } else {
+            s.split(',')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty())
+                .map(|entry| match entry.split_once('=') {
+                    Some((name, points)) => points
+                        .trim()
+                        .parse::<f32>()
+                        .map(|points| VerdictLabel::new(name).with_score_adjustment(points))
+                        .map_err(|_| {
+                            format!("label `{name}` has invalid score adjustment `{points}`")
+                        }),
+                    None => Ok(VerdictLabel::new(entry)),
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        };
+        Self::new(labels).map_err(|err| err.to_string())
+    }
+}
+
+/// Errors emitted while validating a verdict label configuration.
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum LabelSetError {
+    #[error("label set must contain at least one label")]
+    Empty,
+    #[error("label `{label}` must be non-blank and must not contain `|` or `,`")]
+    InvalidName { label: String },
+    #[error("label `unknown` is reserved for verdicts a provider could not produce")]
+    Reserved,
+    #[error(
+        "label `{label}` score_adjustment must be within -100.0..=100.0 (got {score_adjustment})"
+    )]
+    InvalidAdjustment {
+        label: String,
+        score_adjustment: f32,
+    },
+    #[error("label `{label}` is defined more than once")]
+    DuplicateName { label: String },
+}
+
+/// Accept a verdict label given as a JSON string or number, so numeric scales parse either way.
+#[cfg(feature = "llm")]
+pub(crate) fn deserialize_label<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Text(String),
+        Number(serde_json::Number),
+    }
+
+    Ok(match Raw::deserialize(deserializer)? {
+        Raw::Text(text) => text,
+        Raw::Number(number) => number.to_string(),
+    })
+}
+
+/// Checks every verdict from the wrapped client against a [`LabelSet`], normalising the label's
+/// spelling and failing the call when the model answers outside the set.
+pub struct LabeledLlmClient {
+    inner: Box<dyn LlmClient>,
+    labels: LabelSet,
+}
+
+impl LabeledLlmClient {
+    pub fn new(inner: Box<dyn LlmClient>, labels: LabelSet) -> Self {
+        Self { inner, labels }
+    }
+}
+
+#[async_trait]
+impl LlmClient for LabeledLlmClient {
+    async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
+        let verdict = self.inner.enrich(input, report).await?;
+        self.labels.validate(verdict)
+    }
+
+    fn preview(&self, input: &str, report: &ScanReport) -> Result<Vec<RequestPreview>> {
+        self.inner.preview(input, report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verdict(label: &str) -> LlmVerdict {
+        LlmVerdict {
+            label: label.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn default_set_matches_builtin_labels() {
+        let labels = LabelSet::default();
+        assert_eq!(labels.choices(), "safe|suspicious|malicious");
+        assert_eq!(labels.guide(), "");
+        assert_eq!(labels.rank("Malicious "), Some(2));
+        assert_eq!(labels.score_adjustment("malicious"), None);
+    }
+
+    #[test]
+    fn parses_compact_and_json_specs() {
+        let compact: LabelSet = "allow=-10, review ,block=+25".parse().unwrap();
+        assert_eq!(compact.choices(), "allow|review|block");
+        assert_eq!(compact.score_adjustment("BLOCK"), Some(25.0));
+        assert_eq!(compact.score_adjustment("review"), None);
+
+        let json: LabelSet =
+            r#"[{"name":"1","description":"benign"},{"name":"5","score_adjustment":40}]"#
+                .parse()
+                .unwrap();
+        assert_eq!(json.rank("5"), Some(1));
+        assert_eq!(
+            json.guide(),
+            " Labels, from least to most severe:\n- 1: benign"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_sets() {
+        assert_eq!(LabelSet::new(Vec::new()), Err(LabelSetError::Empty));
+        assert!("allow,ALLOW"
+            .parse::<LabelSet>()
+            .unwrap_err()
+            .contains("more than once"));
+        assert!("a|b".parse::<LabelSet>().is_err());
+        assert!("unknown".parse::<LabelSet>().is_err());
+        assert!("block=200".parse::<LabelSet>().is_err());
+        assert!("block=high".parse::<LabelSet>().is_err());
+    }
+
+    #[test]
+    fn validate_canonicalises_and_rejects_unlisted_labels() {
+        let labels: LabelSet = "allow,review,block".parse().unwrap();
+        assert_eq!(labels.validate(verdict(" Block ")).unwrap().label, "block");
+        assert_eq!(
+            labels.validate(verdict("Unknown")).unwrap().label,
+            "unknown"
+        );
+        let err = labels.validate(verdict("malicious")).unwrap_err();
+        assert!(err.to_string().contains("allow|review|block"), "{err}");
+    }
+
+    #[test]
+    fn verdict_schema_enumerates_labels() {
+        let schema = "allow,review,block"
+            .parse::<LabelSet>()
+            .unwrap()
+            .verdict_schema();
+        assert_eq!(
+            schema["properties"]["label"]["enum"],
+            serde_json::json!(["allow", "review", "block"])
+        );
+        assert_eq!(schema["additionalProperties"], false);
+        assert_eq!(schema["required"].as_array().unwrap().len(), 3);
+    }
+
+    #[cfg(feature = "llm")]
+    #[test]
+    fn numeric_labels_deserialize_from_numbers() {
+        #[derive(Deserialize)]
+        struct Verdict {
+            #[serde(deserialize_with = "deserialize_label")]
+            label: String,
+        }
+        let parsed: Verdict = serde_json::from_str(r#"{"label": 4}"#).unwrap();
+        assert_eq!(parsed.label, "4");
+        let parsed: Verdict = serde_json::from_str(r#"{"label": "4"}"#).unwrap();
+        assert_eq!(parsed.label, "4");
+    }
+}
@@ -0,0 +1,274 @@
+use super::{
+    compress_input, LlmClient, LlmSettings, PromptTemplate, RequestPreview, MAX_PROMPT_INPUT_CHARS,
+};
+use crate::scanner::{LlmVerdict, ScanReport, TokenUsage};
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use llama_cpp_2::context::params::LlamaContextParams;
+use llama_cpp_2::llama_backend::LlamaBackend;
+use llama_cpp_2::llama_batch::LlamaBatch;
+use llama_cpp_2::model::params::LlamaModelParams;
+use llama_cpp_2::model::{AddBos, LlamaChatMessage, LlamaModel, Special};
+use llama_cpp_2::sampling::LlamaSampler;
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use std::num::NonZeroU32;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Context window allocated per verdict; prompts that do not fit are rejected.
+const CONTEXT_TOKENS: u32 = 4096;
+/// Upper bound on generated tokens for one verdict when `LLM_GUARD_MAX_TOKENS` is unset.
+const MAX_NEW_TOKENS: i32 = 256;
+
+/// llama.cpp may only be initialised once per process.
+static BACKEND: OnceCell<LlamaBackend> = OnceCell::new();
+
+fn backend() -> Result<&'static LlamaBackend> {
+    BACKEND.get_or_try_init(|| {
+        let mut backend = LlamaBackend::init().context("failed to initialise llama.cpp")?;
+        backend.void_logs();
+        Ok(backend)
+    })
+}
+
+/// Runs a local GGUF model through llama.cpp, so verdicts never leave the machine.
+///
+/// `LLM_GUARD_MODEL` is the path to the `.gguf` file; no API key or network access is used.
+#[derive(Clone)]
+pub struct LocalLlmClient {
+    model: Arc<LlamaModel>,
+    path: PathBuf,
+    prompt: PromptTemplate,
+    max_new_tokens: i32,
+}
+
+impl LocalLlmClient {
+    pub fn new(settings: &LlmSettings) -> Result<Self> {
+        let path = settings
+            .model
+            .as_deref()
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .ok_or_else(|| {
+                anyhow!("local provider requires LLM_GUARD_MODEL to point at a .gguf model file")
+            })?;
+        let max_new_tokens = match settings.max_tokens {
+            Some(tokens) => i32::try_from(tokens)
+                .ok()
+                .filter(|tokens| *tokens < CONTEXT_TOKENS as i32)
+                .with_context(|| {
+                    format!("LLM_GUARD_MAX_TOKENS must be below the {CONTEXT_TOKENS}-token context")
+                })?,
+            None => MAX_NEW_TOKENS,
+        };
+        if !path.is_file() {
+            bail!("local model file {} does not exist", path.display());
+        }
+        let model = LlamaModel::load_from_file(backend()?, &path, &LlamaModelParams::default())
+            .with_context(|| format!("failed to load GGUF model {}", path.display()))?;
+        Ok(Self {
+            model: Arc::new(model),
+            path,
+            prompt: PromptTemplate::from_settings(settings)?,
+            max_new_tokens,
+        })
+    }
+
+    fn user_prompt(&self, input: &str, report: &ScanReport) -> String {
+        self.prompt.render_user(
+            &compress_input(input, report, MAX_PROMPT_INPUT_CHARS),
+            report,
+        )
+    }
+}
+
+#[async_trait]
+impl LlmClient for LocalLlmClient {
+    async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
+        let user = self.user_prompt(input, report);
+        let system = self.prompt.system().to_string();
+        let model = Arc::clone(&self.model);
+        let max_new_tokens = self.max_new_tokens;
+        // Inference is CPU-bound and the llama.cpp context is not `Send`; keep it off the runtime.
+        let (content, usage) =
+            tokio::task::spawn_blocking(move || generate(&model, &system, &user, max_new_tokens))
+                .await
+                .context("local inference task panicked")?
+                .with_context(|| format!("local inference failed for {}", self.path.display()))?;
+
+        let verdict: ModelVerdict = serde_json::from_str(extract_json(&content))
+            .context("expected JSON verdict from local model output")?;
+        Ok(LlmVerdict {
+            label: verdict.label,
+            rationale: verdict.rationale,
+            mitigation: verdict.mitigation,
+            usage: Some(usage),
+            ..Default::default()
+        })
+    }
+
+    fn preview(&self, input: &str, report: &ScanReport) -> Result<Vec<RequestPreview>> {
+        Ok(vec![RequestPreview {
+            provider: "local".into(),
+            model: self.path.display().to_string(),
+            url: None,
+            body: serde_json::json!({
+                "system": self.prompt.system(),
+                "user": self.user_prompt(input, report),
+                "max_new_tokens": self.max_new_tokens,
+            }),
+        }])
+    }
+}
+
+/// Greedy decoding of the model's answer to the system/user prompt pair, with the prompt and
+/// generated token counts.
+fn generate(
+    model: &LlamaModel,
+    system: &str,
+    user: &str,
+    max_new_tokens: i32,
+) -> Result<(String, TokenUsage)> {
+    let prompt = chat_prompt(model, system, user)?;
+    let context_params = LlamaContextParams::default().with_n_ctx(NonZeroU32::new(CONTEXT_TOKENS));
+    let mut context = model
+        .new_context(backend()?, context_params)
+        .context("failed to create llama.cpp context")?;
+
+    let tokens = model
+        .str_to_token(&prompt, AddBos::Always)
+        .context("failed to tokenize prompt")?;
+    let prompt_len = i32::try_from(tokens.len()).context("prompt too long")?;
+    if prompt_len + max_new_tokens > CONTEXT_TOKENS as i32 {
+        bail!("prompt of {prompt_len} tokens does not fit the {CONTEXT_TOKENS}-token context");
+    }
+
+    let mut batch = LlamaBatch::new(tokens.len().max(1), 1);
+    let last = tokens.len().saturating_sub(1);
+    for (index, token) in tokens.into_iter().enumerate() {
+        batch.add(token, index as i32, &[0], index == last)?;
+    }
+    context
+        .decode(&mut batch)
+        .context("failed to evaluate prompt")?;
+
+    let mut sampler = LlamaSampler::greedy();
+    let mut output = String::new();
+    let mut position = prompt_len;
+    while position < prompt_len + max_new_tokens {
+        let token = sampler.sample(&context, batch.n_tokens() - 1);
+        sampler.accept(token);
+        if model.is_eog_token(token) {
+            break;
+        }
+        output.push_str(
+            &model
+                .token_to_str(token, Special::Tokenize)
+                .context("failed to detokenize output")?,
+        );
+        batch.clear();
+        batch.add(token, position, &[0], true)?;
+        context
+            .decode(&mut batch)
+            .context("failed to decode token")?;
+        position += 1;
+    }
+    let usage = TokenUsage::new(prompt_len as u64, (position - prompt_len) as u64);
+    Ok((output, usage))
+}
+
+/// Format the prompt with the model's own chat template, falling back to plain text for
+/// base models that ship without one.
+fn chat_prompt(model: &LlamaModel, system: &str, user: &str) -> Result<String> {
+    let Ok(template) = model.chat_template(None) else {
+        return Ok(format!("{system}\n\n{user}\nJSON verdict:"));
+    };
+    let messages = vec![
+        LlamaChatMessage::new("system".into(), system.into())?,
+        LlamaChatMessage::new("user".into(), user.into())?,
+    ];
+    model
+        .apply_chat_template(&template, &messages, true)
+        .context("failed to apply the model chat template")
+}
+
+/// Local models often wrap JSON in prose or Markdown; take the outermost object.
+fn extract_json(content: &str) -> &str {
+    match (content.find('{'), content.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &content[start..=end],
+        _ => content.trim(),
+    }
+}
+
+#[derive(Deserialize)]
+struct ModelVerdict {
+    #[serde(deserialize_with = "super::labels::deserialize_label")]
+    label: String,
+    rationale: String,
+    mitigation: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(model: Option<&str>) -> LlmSettings {
+        LlmSettings {
+            provider: "local".into(),
+            api_key: String::new(),
+            endpoint: None,
+            model: model.map(str::to_string),
+            deployment: None,
+            project: None,
+            workspace: None,
+            region: None,
+            timeout_secs: None,
+            max_retries: 0,
+            api_version: None,
+            retry_base_delay_ms: None,
+            retry_max_delay_ms: None,
+            retry_jitter: None,
+            retry_on_status: None,
+            prompt_template: None,
+            verdict_labels: None,
+            structured_output: None,
+            stream: None,
+            stream_idle_timeout_secs: None,
+            proxy: None,
+            ca_bundle: None,
+            client_cert: None,
+            client_key: None,
+            azure_auth: None,
+            scrub_pii: None,
+            prompt_cache: None,
+            openai_api: None,
+            temperature: None,
+            max_tokens: None,
+            response_mime_type: None,
+            safety_settings: None,
+        }
+    }
+
+    #[test]
+    fn requires_existing_model_path() {
+        let err = LocalLlmClient::new(&settings(None)).err().unwrap();
+        assert!(err.to_string().contains("LLM_GUARD_MODEL"));
+        let err = LocalLlmClient::new(&settings(Some("/nonexistent/model.gguf")))
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("does not exist"));
+    }
+
+    #[test]
+    fn extract_json_skips_surrounding_prose() {
+        let json = r#"{"label":"safe","rationale":"ok","mitigation":"none"}"#;
+        assert_eq!(extract_json(json), json);
+        assert_eq!(
+            extract_json(&format!("Sure! Here is the verdict:\n```json\n{json}\n```")),
+            json
+        );
+        assert_eq!(extract_json("no verdict"), "no verdict");
+    }
+}
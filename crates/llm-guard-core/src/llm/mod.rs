@@ -1,26 +1,171 @@
+#[cfg(feature = "llm")]
 mod anthropic;
+#[cfg(feature = "provenance")]
+mod audit;
+#[cfg(feature = "llm")]
 mod azure;
+#[cfg(feature = "llm")]
+mod bedrock;
+mod breaker;
+#[cfg(feature = "llm")]
+mod cohere;
+mod compress;
+mod context;
+mod credentials;
+#[cfg(feature = "llm")]
+mod deepseek;
+mod ensemble;
+mod fallback;
+#[cfg(feature = "llm")]
 mod gemini;
+#[cfg(feature = "llm")]
+mod http;
+mod labels;
+#[cfg(feature = "local-llm")]
+mod local;
+#[cfg(feature = "llm")]
 mod openai;
+mod prompt;
+mod registry;
+mod retry;
+#[cfg(feature = "llm")]
 mod rig_adapter;
+mod scrub;
+#[cfg(feature = "llm")]
+mod secrets;
 mod settings;
+#[cfg(feature = "llm")]
+mod stream;
+mod throttle;
+mod timing;
+mod usage;
+#[cfg(feature = "llm")]
+mod vertex;
+#[cfg(feature = "llm")]
+mod xai;
 
 use anyhow::{bail, Result};
 use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
 
 use crate::scanner::{LlmVerdict, ScanReport};
 
+#[cfg(feature = "llm")]
 pub use anthropic::AnthropicClient;
-pub use azure::AzureOpenAiClient;
+#[cfg(feature = "provenance")]
+pub use audit::{AuditLog, AuditedLlmClient, DEFAULT_AUDIT_PROMPT_CHARS};
+#[cfg(feature = "llm")]
+pub use azure::{suggest_deployment, AzureDeployment, AzureOpenAiClient};
+#[cfg(feature = "llm")]
+pub use bedrock::BedrockClient;
+pub use breaker::{CircuitBreakerLlmClient, CircuitBreakerPolicy, CircuitOpenError};
+#[cfg(feature = "llm")]
+pub use cohere::CohereClient;
+pub use compress::{compress_input, MAX_PROMPT_INPUT_CHARS};
+pub use context::{ConversationTurn, ScanContext, MAX_CONTEXT_CHARS};
+pub use credentials::{delete_api_key, load_api_key, store_api_key, KEYRING_SERVICE};
+#[cfg(feature = "llm")]
+pub use deepseek::DeepSeekClient;
+pub use ensemble::{combine_verdicts, EnsembleLlmClient};
+pub use fallback::FallbackLlmClient;
+#[cfg(feature = "llm")]
 pub use gemini::GeminiClient;
+pub use labels::{LabelSet, LabelSetError, LabeledLlmClient, VerdictLabel, UNKNOWN_LABEL};
+#[cfg(feature = "local-llm")]
+pub use local::LocalLlmClient;
+#[cfg(feature = "llm")]
 pub use openai::OpenAiClient;
-pub use settings::LlmSettings;
+pub use prompt::PromptTemplate;
+pub use registry::{register_provider, registered_providers, unregister_provider, ProviderFactory};
+pub use retry::RetryPolicy;
+pub use scrub::scrub_pii;
+#[cfg(feature = "llm")]
+pub use secrets::{
+    AwsSecretsManagerBackend, SecretBackend, SecretRef, SecretResolver, VaultBackend,
+};
+pub use settings::{
+    AzureAuth, LlmSettings, OpenAiApi, SafetySetting, DEFAULT_MAX_TOKENS, DEFAULT_TEMPERATURE,
+};
+pub use throttle::{RateLimit, ThrottledLlmClient};
+pub use timing::TimedLlmClient;
+pub use usage::{MeteredLlmClient, ModelPrice, PriceTable, ProviderUsage, UsageTracker};
+#[cfg(feature = "llm")]
+pub use vertex::VertexAiClient;
+#[cfg(feature = "llm")]
+pub use xai::XaiClient;
 
 /// Client abstraction for invoking large language models to enrich scan results.
 #[async_trait]
 pub trait LlmClient: Send + Sync {
     /// Produce a verdict/rationale given the original input and heuristic scan report.
     async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict>;
+
+    /// Like [`LlmClient::enrich`], but judge `input` as the latest message of the
+    /// conversation in `context`.
+    ///
+    /// The default frames the most recent earlier turns in front of the input (see
+    /// [`ScanContext::frame`]) and calls `enrich`, so every client and decorator supports it.
+    async fn enrich_with_context(
+        &self,
+        input: &str,
+        report: &ScanReport,
+        context: &ScanContext,
+    ) -> Result<LlmVerdict> {
+        if context.is_empty() {
+            return self.enrich(input, report).await;
+        }
+        self.enrich(&context.frame(input, report), report).await
+    }
+
+    /// Enrich several inputs, returning one result per item in the same order.
+    ///
+    /// The default runs up to [`DEFAULT_BATCH_CONCURRENCY`] `enrich` calls at once. None of the
+    /// supported providers offers a synchronous batch endpoint (the OpenAI and Anthropic batch
+    /// APIs complete asynchronously within hours), so adapters keep the default; decorators that
+    /// know a better bound, such as [`ThrottledLlmClient`], override it.
+    async fn enrich_many(&self, items: &[(&str, &ScanReport)]) -> Vec<Result<LlmVerdict>> {
+        enrich_concurrently(self, items, DEFAULT_BATCH_CONCURRENCY).await
+    }
+
+    /// The request(s) `enrich` would send for `input`, built without contacting the provider.
+    ///
+    /// Decorators forward to the client(s) they wrap; ensembles and fallback chains list every
+    /// member in order.
+    fn preview(&self, _input: &str, _report: &ScanReport) -> Result<Vec<RequestPreview>> {
+        bail!("this LLM client cannot preview its requests")
+    }
+}
+
+/// A provider request as [`LlmClient::preview`] builds it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RequestPreview {
+    pub provider: String,
+    pub model: String,
+    /// Where the request is posted; `None` for in-process models.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// The request body as it would be sent. Credentials travel in headers and are never
+    /// part of it.
+    pub body: serde_json::Value,
+}
+
+/// Calls in flight at once for the default [`LlmClient::enrich_many`].
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 4;
+
+/// Run `client.enrich` over `items` with at most `limit` calls in flight, keeping input order.
+pub async fn enrich_concurrently<C: LlmClient + ?Sized>(
+    client: &C,
+    items: &[(&str, &ScanReport)],
+    limit: usize,
+) -> Vec<Result<LlmVerdict>> {
+    // Built up front: a `.map` closure over borrowed items is not general enough for the
+    // `Send` bound `async_trait` puts on the returned future.
+    let calls: Vec<_> = items
+        .iter()
+        .map(|(input, report)| client.enrich(input, report))
+        .collect();
+    stream::iter(calls).buffered(limit.max(1)).collect().await
 }
 
 /// Placeholder implementation used until a concrete adapter is wired in.
@@ -34,22 +179,101 @@ impl LlmClient for NoopLlmClient {
             label: "unavailable".into(),
             rationale: "LLM adapter not configured; returning heuristic-only verdict.".into(),
             mitigation: "Configure Phase 6 LLM client to receive enriched guidance.".into(),
+            ..Default::default()
         })
     }
+    /// The placeholder never sends anything.
+    fn preview(&self, _input: &str, _report: &ScanReport) -> Result<Vec<RequestPreview>> {
+        Ok(Vec::new())
+    }
 }
 
+/// Build the client for `settings.provider`, checking its verdicts against
+/// `settings.verdict_labels` and timing each call (the `noop` placeholder is returned as-is).
+///
+/// Providers added with [`register_provider`] are looked up first.
 pub fn build_client(settings: &LlmSettings) -> Result<Box<dyn LlmClient>> {
-    let kind = ProviderKind::from_provider(settings.provider.trim())?;
+    let inner = match registry::lookup(&settings.provider) {
+        Some(factory) => factory(settings)?,
+        None => {
+            let kind = ProviderKind::from_provider(settings.provider.trim())?;
+            if kind == ProviderKind::Noop {
+                return Ok(Box::new(NoopLlmClient));
+            }
+            provider_client(kind, settings)?
+        }
+    };
+    Ok(Box::new(LabeledLlmClient::new(
+        Box::new(TimedLlmClient::new(inner)),
+        settings.verdict_labels.clone().unwrap_or_default(),
+    )))
+}
+
+#[cfg(feature = "llm")]
+fn standalone(settings: &LlmSettings) -> bool {
+    settings.structured_output == Some(true)
+        || settings.stream == Some(true)
+        || http::customized(settings)
+}
+
+fn provider_client(kind: ProviderKind, settings: &LlmSettings) -> Result<Box<dyn LlmClient>> {
     match kind {
         ProviderKind::Noop => Ok(Box::new(NoopLlmClient)),
+        #[cfg(feature = "local-llm")]
+        ProviderKind::Local => Ok(Box::new(LocalLlmClient::new(settings)?)),
+        #[cfg(not(feature = "local-llm"))]
+        ProviderKind::Local => bail!(
+            "provider `{}` requires the `local-llm` feature",
+            settings.provider.trim()
+        ),
+        #[cfg(feature = "llm")]
         ProviderKind::Gemini => {
             // Use standalone Gemini client to avoid rig deserialization issues
             Ok(Box::new(GeminiClient::new(settings)?))
         }
+        #[cfg(feature = "llm")]
+        ProviderKind::VertexAi => Ok(Box::new(VertexAiClient::new(settings)?)),
+        #[cfg(feature = "llm")]
+        ProviderKind::Cohere => Ok(Box::new(CohereClient::new(settings)?)),
+        #[cfg(feature = "llm")]
+        ProviderKind::Xai => Ok(Box::new(XaiClient::new(settings)?)),
+        #[cfg(feature = "llm")]
+        ProviderKind::DeepSeek => Ok(Box::new(DeepSeekClient::new(settings)?)),
+        #[cfg(feature = "llm")]
+        ProviderKind::Bedrock => Ok(Box::new(BedrockClient::new(settings)?)),
+        #[cfg(feature = "llm")]
         ProviderKind::Rig => {
             bail!("Select a specific rig-enabled provider (e.g. openai) in LLM_GUARD_PROVIDER")
         }
+        // Structured output and streaming need the standalone clients; rig only returns the
+        // complete free-text reply and builds its own HTTP client, so it cannot take a proxy,
+        // CA bundle or client certificate either, and it only speaks chat completions.
+        #[cfg(feature = "llm")]
+        ProviderKind::OpenAi
+            if standalone(settings) || settings.openai_api == Some(OpenAiApi::Responses) =>
+        {
+            Ok(Box::new(OpenAiClient::new(settings)?))
+        }
+        // Only the standalone client sends cache markers, so asking for caching selects it.
+        #[cfg(feature = "llm")]
+        ProviderKind::Anthropic if standalone(settings) || settings.prompt_cache == Some(true) => {
+            Ok(Box::new(AnthropicClient::new(settings)?))
+        }
+        // rig's Azure client only takes a fixed key or token, so Entra ID auth, which refreshes
+        // tokens, needs the standalone client too.
+        #[cfg(feature = "llm")]
+        ProviderKind::Azure
+            if http::customized(settings) || settings.azure_auth == Some(AzureAuth::Entra) =>
+        {
+            Ok(Box::new(AzureOpenAiClient::new(settings)?))
+        }
+        #[cfg(feature = "llm")]
         other => rig_adapter::RigLlmClient::for_kind(other, settings),
+        #[cfg(not(feature = "llm"))]
+        _ => bail!(
+            "provider `{}` requires the `llm` feature; this build only supports `noop`",
+            settings.provider.trim()
+        ),
     }
 }
 
@@ -60,6 +284,12 @@ pub enum ProviderKind {
     Azure,
     Anthropic,
     Gemini,
+    VertexAi,
+    Cohere,
+    Xai,
+    DeepSeek,
+    Bedrock,
+    Local,
     Rig,
 }
 
@@ -71,8 +301,27 @@ impl ProviderKind {
             "azure" | "azure-openai" => Ok(ProviderKind::Azure),
             "anthropic" | "claude" => Ok(ProviderKind::Anthropic),
             "gemini" | "google" | "google-gemini" => Ok(ProviderKind::Gemini),
+            "vertex" | "vertex-ai" | "vertexai" => Ok(ProviderKind::VertexAi),
+            "cohere" => Ok(ProviderKind::Cohere),
+            "xai" | "grok" => Ok(ProviderKind::Xai),
+            "deepseek" => Ok(ProviderKind::DeepSeek),
+            "bedrock" | "aws-bedrock" => Ok(ProviderKind::Bedrock),
+            "local" | "gguf" | "llama-cpp" => Ok(ProviderKind::Local),
             "rig" | "rag" => Ok(ProviderKind::Rig),
             other => bail!("unsupported LLM provider `{}`", other),
         }
     }
+
+    /// Whether `LLM_GUARD_API_KEY` must be set. Vertex AI and Bedrock fall back to ambient
+    /// cloud credentials (ADC / `AWS_*`); `noop` and `local` never call out. Azure needs none
+    /// when [`AzureAuth::Entra`] is selected, which [`LlmSettings::from_env`] checks.
+    pub fn requires_api_key(&self) -> bool {
+        !matches!(
+            self,
+            ProviderKind::Noop
+                | ProviderKind::Local
+                | ProviderKind::VertexAi
+                | ProviderKind::Bedrock
+        )
+    }
 }
@@ -1,11 +1,18 @@
-use super::{LlmClient, LlmSettings};
-use crate::scanner::{LlmVerdict, ScanReport};
+use super::labels::{VERDICT_TOOL, VERDICT_TOOL_DESCRIPTION};
+use super::{
+    compress_input, http, stream, LlmClient, LlmSettings, OpenAiApi, PromptTemplate,
+    RequestPreview, RetryPolicy, DEFAULT_MAX_TOKENS, DEFAULT_TEMPERATURE, MAX_PROMPT_INPUT_CHARS,
+};
+use crate::scanner::{LlmVerdict, ScanReport, TokenUsage};
 use anyhow::{anyhow, bail, Context, Result};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::future;
 use std::time::Duration;
-use tokio::time::sleep;
+
+/// Output budget for reasoning models, whose hidden reasoning tokens count against it.
+const REASONING_MAX_OUTPUT_TOKENS: u32 = 4096;
 
 #[derive(Debug, Clone)]
 pub struct OpenAiClient {
@@ -13,7 +20,16 @@ pub struct OpenAiClient {
     url: String,
     api_key: String,
     model: String,
-    max_retries: u32,
+    retry: RetryPolicy,
+    prompt: PromptTemplate,
+    /// Ask for the verdict through a strict function call instead of free-text JSON.
+    structured: bool,
+    /// Stream the reply and stop reading once the verdict object is complete.
+    stream: bool,
+    idle_timeout: Duration,
+    api: OpenAiApi,
+    temperature: Option<f32>,
+    max_tokens: Option<u32>,
 }
 
 impl OpenAiClient {
@@ -25,12 +41,13 @@ impl OpenAiClient {
             .endpoint
             .clone()
             .unwrap_or_else(|| "https://api.openai.com".to_string());
-        let url = format!("{}/v1/chat/completions", base.trim_end_matches('/'));
-        let http = Client::builder()
-            .user_agent("llm-guard/0.1")
-            .timeout(Duration::from_secs(settings.timeout_secs.unwrap_or(30)))
-            .build()
-            .context("failed to build OpenAI HTTP client")?;
+        let api = settings.openai_api.unwrap_or_default();
+        let path = match api {
+            OpenAiApi::ChatCompletions => "chat/completions",
+            OpenAiApi::Responses => "responses",
+        };
+        let url = format!("{}/v1/{path}", base.trim_end_matches('/'));
+        let http = http::client(settings, "OpenAI")?;
         Ok(Self {
             http,
             url,
@@ -39,101 +56,255 @@ impl OpenAiClient {
                 .model
                 .clone()
                 .unwrap_or_else(|| "gpt-4o-mini".to_string()),
-            max_retries: settings.max_retries,
+            retry: RetryPolicy::from_settings(settings),
+            prompt: PromptTemplate::from_settings(settings)?,
+            structured: settings.structured_output.unwrap_or(true),
+            stream: settings.stream.unwrap_or(false),
+            idle_timeout: settings
+                .stream_idle_timeout_secs
+                .map(Duration::from_secs)
+                .unwrap_or(stream::DEFAULT_IDLE_TIMEOUT),
+            api,
+            temperature: settings.temperature,
+            max_tokens: settings.max_tokens,
         })
     }
-}
 
-#[async_trait]
-impl LlmClient for OpenAiClient {
-    async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
-        let payload = ChatCompletionRequest {
+    fn request(&self, input: &str, report: &ScanReport) -> ChatCompletionRequest {
+        let (tools, tool_choice) = if self.structured {
+            (
+                Some(vec![ChatTool {
+                    kind: "function",
+                    function: ChatFunction {
+                        name: VERDICT_TOOL,
+                        description: VERDICT_TOOL_DESCRIPTION,
+                        parameters: self.prompt.labels().verdict_schema(),
+                        strict: true,
+                    },
+                }]),
+                Some(serde_json::json!({
+                    "type": "function",
+                    "function": { "name": VERDICT_TOOL },
+                })),
+            )
+        } else {
+            (None, None)
+        };
+        ChatCompletionRequest {
             model: self.model.clone(),
             messages: vec![
                 ChatMessage {
                     role: "system",
-                    content: SYSTEM_PROMPT.to_string(),
+                    content: self.prompt.system().to_string(),
                 },
                 ChatMessage {
                     role: "user",
-                    content: format!(
-                        "Input excerpt:\n{}\n\nScore: {:.1} ({:?})\nTop findings: {}\n",
-                        truncate(input, 2000),
-                        report.risk_score,
-                        report.risk_band,
-                        serde_json::to_string(&report.findings).unwrap_or_default()
+                    content: self.prompt.render_user(
+                        &compress_input(input, report, MAX_PROMPT_INPUT_CHARS),
+                        report,
                     ),
                 },
             ],
-            temperature: 0.1,
-            max_tokens: 200,
-        };
+            temperature: self.temperature.unwrap_or(DEFAULT_TEMPERATURE),
+            max_tokens: self.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+            tools,
+            tool_choice,
+            stream: self.stream,
+        }
+    }
 
-        let mut attempt = 0u32;
-        let mut backoff = Duration::from_millis(200);
-        loop {
-            let response = self
-                .http
-                .post(&self.url)
-                .bearer_auth(&self.api_key)
-                .json(&payload)
-                .send()
-                .await;
-
-            let response = match response {
-                Ok(resp) => resp,
-                Err(err) => {
-                    if attempt >= self.max_retries {
-                        return Err(err).context("failed to call OpenAI chat completions API");
+    fn responses_request(&self, input: &str, report: &ScanReport) -> ResponsesRequest {
+        // Reasoning models reject sampling parameters and spend output tokens on reasoning.
+        let reasoning = is_reasoning_model(&self.model);
+        ResponsesRequest {
+            model: self.model.clone(),
+            instructions: self.prompt.system().to_string(),
+            input: self.prompt.render_user(
+                &compress_input(input, report, MAX_PROMPT_INPUT_CHARS),
+                report,
+            ),
+            temperature: (!reasoning).then(|| self.temperature.unwrap_or(DEFAULT_TEMPERATURE)),
+            max_output_tokens: self.max_tokens.unwrap_or(if reasoning {
+                REASONING_MAX_OUTPUT_TOKENS
+            } else {
+                DEFAULT_MAX_TOKENS
+            }),
+            text: self.structured.then(|| {
+                serde_json::json!({
+                    "format": {
+                        "type": "json_schema",
+                        "name": VERDICT_TOOL,
+                        "description": VERDICT_TOOL_DESCRIPTION,
+                        "schema": self.prompt.labels().verdict_schema(),
+                        "strict": true,
                     }
-                    sleep(backoff).await;
-                    backoff = (backoff * 2).min(Duration::from_secs(5));
-                    attempt += 1;
-                    continue;
-                }
-            };
-
-            if !response.status().is_success() {
-                if attempt >= self.max_retries {
-                    let status = response.status();
-                    let body = response.text().await.unwrap_or_default();
-                    bail!("OpenAI API error ({}): {}", status, body);
-                }
-                sleep(backoff).await;
-                backoff = (backoff * 2).min(Duration::from_secs(5));
-                attempt += 1;
-                continue;
+                })
+            }),
+            store: false,
+            stream: self.stream,
+        }
+    }
+
+    async fn send<T: Serialize>(&self, operation: &str, payload: &T) -> Result<reqwest::Response> {
+        self.retry
+            .send("OpenAI", operation, |_| {
+                future::ready(Ok(self
+                    .http
+                    .post(&self.url)
+                    .bearer_auth(&self.api_key)
+                    .json(payload)))
+            })
+            .await
+    }
+
+    async fn enrich_chat(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
+        let response = self
+            .send("chat completions", &self.request(input, report))
+            .await?;
+
+        if self.stream {
+            // Usage arrives in the final chunk, after the point where streaming stops reading.
+            let json = stream::read_verdict(response, "OpenAI", self.idle_timeout, |data| {
+                stream::chat_completion_delta(data, self.structured)
+            })
+            .await?;
+            let verdict: ModelVerdict =
+                serde_json::from_str(&json).context("expected JSON verdict from OpenAI stream")?;
+            return Ok(LlmVerdict {
+                label: verdict.label,
+                rationale: verdict.rationale,
+                mitigation: verdict.mitigation,
+                ..Default::default()
+            });
+        }
+
+        let chat: ChatCompletionResponse = response
+            .json()
+            .await
+            .context("failed to parse OpenAI response")?;
+        let usage = chat
+            .usage
+            .as_ref()
+            .and_then(|usage| TokenUsage::reported(usage.prompt_tokens, usage.completion_tokens));
+        let tool_call = chat
+            .choices
+            .iter()
+            .flat_map(|choice| &choice.message.tool_calls)
+            .find(|call| call.function.name == VERDICT_TOOL);
+        let verdict: ModelVerdict = match tool_call {
+            Some(call) => serde_json::from_str(&call.function.arguments)
+                .context("expected JSON verdict arguments in OpenAI function call")?,
+            None => {
+                let content = chat
+                    .choices
+                    .into_iter()
+                    .find_map(|choice| choice.message.content)
+                    .ok_or_else(|| anyhow!("OpenAI response missing message content"))?;
+                serde_json::from_str(&content)
+                    .context("expected JSON verdict from OpenAI response")?
             }
+        };
 
-            let chat: ChatCompletionResponse = response
-                .json()
-                .await
-                .context("failed to parse OpenAI response")?;
-            let content = chat
-                .choices
-                .into_iter()
-                .find_map(|choice| choice.message.content)
-                .ok_or_else(|| anyhow!("OpenAI response missing message content"))?;
+        Ok(LlmVerdict {
+            label: verdict.label,
+            rationale: verdict.rationale,
+            mitigation: verdict.mitigation,
+            usage,
+            ..Default::default()
+        })
+    }
 
-            let verdict: ModelVerdict = serde_json::from_str(&content)
-                .context("expected JSON verdict from OpenAI response")?;
+    async fn enrich_responses(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
+        let response = self
+            .send("responses", &self.responses_request(input, report))
+            .await?;
 
+        if self.stream {
+            let json = stream::read_verdict(
+                response,
+                "OpenAI Responses",
+                self.idle_timeout,
+                stream::responses_delta,
+            )
+            .await?;
+            let verdict: ModelVerdict = serde_json::from_str(&json)
+                .context("expected JSON verdict from OpenAI Responses stream")?;
             return Ok(LlmVerdict {
                 label: verdict.label,
                 rationale: verdict.rationale,
                 mitigation: verdict.mitigation,
+                ..Default::default()
             });
         }
+
+        let reply: ResponsesResponse = response
+            .json()
+            .await
+            .context("failed to parse OpenAI Responses response")?;
+        let usage = reply.usage.as_ref().and_then(|usage| {
+            let cached = usage
+                .input_tokens_details
+                .as_ref()
+                .map_or(0, |details| details.cached_tokens);
+            TokenUsage::reported(usage.input_tokens, usage.output_tokens)
+                .map(|tokens| tokens.with_cache(cached, 0))
+        });
+        let content = reply
+            .output
+            .iter()
+            .filter(|item| item.kind == "message")
+            .flat_map(|item| &item.content)
+            .collect::<Vec<_>>();
+        if let Some(refusal) = content.iter().find_map(|part| part.refusal.as_deref()) {
+            bail!("OpenAI refused to classify the input: {refusal}");
+        }
+        let text = content
+            .iter()
+            .find(|part| part.kind == "output_text")
+            .and_then(|part| part.text.as_deref())
+            .ok_or_else(|| anyhow!("OpenAI Responses response missing output text"))?;
+        let verdict: ModelVerdict = serde_json::from_str(text)
+            .context("expected JSON verdict from OpenAI Responses response")?;
+
+        Ok(LlmVerdict {
+            label: verdict.label,
+            rationale: verdict.rationale,
+            mitigation: verdict.mitigation,
+            usage,
+            ..Default::default()
+        })
     }
 }
 
-const SYSTEM_PROMPT: &str = "You are an application security assistant. Analyze prompt-injection scan results and respond with strict JSON: {\"label\": \"safe|suspicious|malicious\", \"rationale\": \"...\", \"mitigation\": \"...\"}. The mitigation should advise remediation steps.";
+/// o-series and GPT-5 models, which only accept the default temperature.
+fn is_reasoning_model(model: &str) -> bool {
+    let model = model.to_ascii_lowercase();
+    let mut chars = model.chars();
+    (chars.next() == Some('o') && chars.next().is_some_and(|c| c.is_ascii_digit()))
+        || model.starts_with("gpt-5")
+}
+
+#[async_trait]
+impl LlmClient for OpenAiClient {
+    async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
+        match self.api {
+            OpenAiApi::ChatCompletions => self.enrich_chat(input, report).await,
+            OpenAiApi::Responses => self.enrich_responses(input, report).await,
+        }
+    }
 
-fn truncate(input: &str, max_chars: usize) -> String {
-    if input.chars().count() <= max_chars {
-        return input.to_string();
+    fn preview(&self, input: &str, report: &ScanReport) -> Result<Vec<RequestPreview>> {
+        let body = match self.api {
+            OpenAiApi::ChatCompletions => serde_json::to_value(self.request(input, report))?,
+            OpenAiApi::Responses => serde_json::to_value(self.responses_request(input, report))?,
+        };
+        Ok(vec![RequestPreview {
+            provider: "openai".into(),
+            model: self.model.clone(),
+            url: Some(self.url.clone()),
+            body,
+        }])
     }
-    input.chars().take(max_chars).collect::<String>() + "…"
 }
 
 #[derive(Serialize)]
@@ -142,6 +313,29 @@ struct ChatCompletionRequest {
     messages: Vec<ChatMessage>,
     temperature: f32,
     max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<ChatTool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+}
+
+#[derive(Serialize)]
+struct ResponsesRequest {
+    model: String,
+    instructions: String,
+    input: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    max_output_tokens: u32,
+    /// `json_schema` output format when structured output is on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<serde_json::Value>,
+    /// Scanned input is not kept server-side for later retrieval.
+    store: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
 }
 
 #[derive(Serialize)]
@@ -150,9 +344,77 @@ struct ChatMessage {
     content: String,
 }
 
+#[derive(Serialize)]
+struct ChatTool {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    function: ChatFunction,
+}
+
+#[derive(Serialize)]
+struct ChatFunction {
+    name: &'static str,
+    description: &'static str,
+    parameters: serde_json::Value,
+    strict: bool,
+}
+
 #[derive(Deserialize)]
 struct ChatCompletionResponse {
     choices: Vec<ChatChoice>,
+    #[serde(default)]
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Deserialize)]
+struct ChatUsage {
+    #[serde(default)]
+    prompt_tokens: u64,
+    #[serde(default)]
+    completion_tokens: u64,
+}
+
+#[derive(Deserialize)]
+struct ResponsesResponse {
+    #[serde(default)]
+    output: Vec<ResponsesOutput>,
+    #[serde(default)]
+    usage: Option<ResponsesUsage>,
+}
+
+/// One output item; only `message` items carry the answer, `reasoning` items are skipped.
+#[derive(Deserialize)]
+struct ResponsesOutput {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    content: Vec<ResponsesContent>,
+}
+
+#[derive(Deserialize)]
+struct ResponsesContent {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    refusal: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ResponsesUsage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    #[serde(default)]
+    input_tokens_details: Option<ResponsesInputDetails>,
+}
+
+#[derive(Deserialize)]
+struct ResponsesInputDetails {
+    #[serde(default)]
+    cached_tokens: u64,
 }
 
 #[derive(Deserialize)]
@@ -163,10 +425,25 @@ struct ChatChoice {
 #[derive(Deserialize)]
 struct ChatResponseMessage {
     content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<ChatToolCall>,
+}
+
+#[derive(Deserialize)]
+struct ChatToolCall {
+    function: ChatFunctionCall,
+}
+
+#[derive(Deserialize)]
+struct ChatFunctionCall {
+    name: String,
+    /// JSON-encoded arguments, as a string.
+    arguments: String,
 }
 
 #[derive(Deserialize)]
 struct ModelVerdict {
+    #[serde(deserialize_with = "super::labels::deserialize_label")]
     label: String,
     rationale: String,
     mitigation: String,
@@ -188,9 +465,31 @@ mod tests {
             deployment: None,
             project: None,
             workspace: None,
+            region: None,
             timeout_secs: Some(5),
             max_retries: 0,
             api_version: None,
+            retry_base_delay_ms: None,
+            retry_max_delay_ms: None,
+            retry_jitter: None,
+            retry_on_status: None,
+            prompt_template: None,
+            verdict_labels: None,
+            structured_output: None,
+            stream: None,
+            stream_idle_timeout_secs: None,
+            proxy: None,
+            ca_bundle: None,
+            client_cert: None,
+            client_key: None,
+            azure_auth: None,
+            scrub_pii: None,
+            prompt_cache: None,
+            openai_api: None,
+            temperature: None,
+            max_tokens: None,
+            response_mime_type: None,
+            safety_settings: None,
         }
     }
 
@@ -214,7 +513,7 @@ mod tests {
                 .header("authorization", "Bearer test-key");
             then.status(200)
                 .header("content-type", "application/json")
-                .body(r#"{"choices":[{"message":{"content":"{\"label\":\"safe\",\"rationale\":\"ok\",\"mitigation\":\"none\"}"}}]}"#);
+                .body(r#"{"choices":[{"message":{"content":"{\"label\":\"safe\",\"rationale\":\"ok\",\"mitigation\":\"none\"}"}}],"usage":{"prompt_tokens":120,"completion_tokens":30,"total_tokens":150}}"#);
         });
 
         let client = OpenAiClient::new(&base_settings(server.base_url())).unwrap();
@@ -222,6 +521,173 @@ mod tests {
         assert_eq!(verdict.label, "safe");
         assert_eq!(verdict.rationale, "ok");
         assert_eq!(verdict.mitigation, "none");
+        assert_eq!(verdict.usage, Some(TokenUsage::new(120, 30)));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires loopback networking"]
+    async fn enrich_reads_function_call_arguments() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1/chat/completions")
+                .body_contains("\"tool_choice\"");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"choices":[{"message":{"content":null,"tool_calls":[{"id":"call_1","type":"function","function":{"name":"record_verdict","arguments":"{\"label\":\"malicious\",\"rationale\":\"override \\\"quoted\\\"\",\"mitigation\":\"block\"}"}}]}}]}"#);
+        });
+
+        let client = OpenAiClient::new(&base_settings(server.base_url())).unwrap();
+        let verdict = client.enrich("hello", &empty_report()).await.unwrap();
+        assert_eq!(verdict.label, "malicious");
+        assert_eq!(verdict.rationale, "override \"quoted\"");
+        mock.assert();
+    }
+
+    #[test]
+    fn structured_request_forces_the_verdict_function() {
+        let mut settings = base_settings("http://localhost".into());
+        let client = OpenAiClient::new(&settings).unwrap();
+        let body = serde_json::to_value(client.request("hi", &empty_report())).unwrap();
+        assert_eq!(body["tools"][0]["function"]["name"], VERDICT_TOOL);
+        assert_eq!(body["tools"][0]["function"]["strict"], true);
+        assert_eq!(
+            body["tools"][0]["function"]["parameters"]["properties"]["label"]["enum"],
+            serde_json::json!(["safe", "suspicious", "malicious"])
+        );
+        assert_eq!(body["tool_choice"]["function"]["name"], VERDICT_TOOL);
+
+        settings.structured_output = Some(false);
+        let client = OpenAiClient::new(&settings).unwrap();
+        let body = serde_json::to_value(client.request("hi", &empty_report())).unwrap();
+        assert!(body.get("tools").is_none());
+        assert!(body.get("tool_choice").is_none());
+        assert!(body.get("stream").is_none());
+    }
+
+    #[test]
+    fn responses_request_uses_a_json_schema_format() {
+        let mut settings = base_settings("http://localhost".into());
+        settings.openai_api = Some(OpenAiApi::Responses);
+        let client = OpenAiClient::new(&settings).unwrap();
+        assert_eq!(client.url, "http://localhost/v1/responses");
+        let body = serde_json::to_value(client.responses_request("hi", &empty_report())).unwrap();
+        assert!(body["input"].as_str().unwrap().contains("hi"));
+        assert!(!body["instructions"].as_str().unwrap().is_empty());
+        assert_eq!(body["text"]["format"]["type"], "json_schema");
+        assert_eq!(body["text"]["format"]["name"], VERDICT_TOOL);
+        assert_eq!(body["text"]["format"]["strict"], true);
+        assert_eq!(body["store"], false);
+        assert_eq!(body["max_output_tokens"], 200);
+        assert!(body.get("temperature").is_some());
+
+        settings.model = Some("o4-mini".into());
+        settings.structured_output = Some(false);
+        let client = OpenAiClient::new(&settings).unwrap();
+        let body = serde_json::to_value(client.responses_request("hi", &empty_report())).unwrap();
+        assert!(body.get("text").is_none());
+        assert!(body.get("temperature").is_none());
+        assert_eq!(body["max_output_tokens"], REASONING_MAX_OUTPUT_TOKENS);
+    }
+
+    #[test]
+    fn requests_use_configured_sampling_and_token_cap() {
+        let mut settings = base_settings("http://localhost".into());
+        settings.temperature = Some(0.0);
+        settings.max_tokens = Some(800);
+        let client = OpenAiClient::new(&settings).unwrap();
+        let body = serde_json::to_value(client.request("hi", &empty_report())).unwrap();
+        assert_eq!(body["temperature"], 0.0);
+        assert_eq!(body["max_tokens"], 800);
+
+        settings.model = Some("o4-mini".into());
+        let client = OpenAiClient::new(&settings).unwrap();
+        let body = serde_json::to_value(client.responses_request("hi", &empty_report())).unwrap();
+        assert!(body.get("temperature").is_none());
+        assert_eq!(body["max_output_tokens"], 800);
+    }
+
+    #[test]
+    fn recognises_reasoning_models() {
+        assert!(is_reasoning_model("o3"));
+        assert!(is_reasoning_model("o1-pro"));
+        assert!(is_reasoning_model("GPT-5-mini"));
+        assert!(!is_reasoning_model("gpt-4o-mini"));
+        assert!(!is_reasoning_model("omni-moderation-latest"));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires loopback networking"]
+    async fn enrich_reads_responses_output_text() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1/responses")
+                .header("authorization", "Bearer test-key")
+                .body_contains("\"json_schema\"");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"id":"resp_1","status":"completed","output":[{"type":"reasoning","summary":[]},{"type":"message","role":"assistant","content":[{"type":"output_text","text":"{\"label\":\"malicious\",\"rationale\":\"override\",\"mitigation\":\"block\"}","annotations":[]}]}],"usage":{"input_tokens":1400,"input_tokens_details":{"cached_tokens":1024},"output_tokens":30,"total_tokens":1430}}"#);
+        });
+
+        let mut settings = base_settings(server.base_url());
+        settings.openai_api = Some(OpenAiApi::Responses);
+        let client = OpenAiClient::new(&settings).unwrap();
+        let verdict = client.enrich("hello", &empty_report()).await.unwrap();
+        assert_eq!(verdict.label, "malicious");
+        assert_eq!(verdict.mitigation, "block");
+        assert_eq!(
+            verdict.usage,
+            Some(TokenUsage::new(1400, 30).with_cache(1024, 0))
+        );
+        mock.assert();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires loopback networking"]
+    async fn responses_refusal_is_an_error() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(POST).path("/v1/responses");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"output":[{"type":"message","content":[{"type":"refusal","refusal":"I can't help with that."}]}]}"#);
+        });
+
+        let mut settings = base_settings(server.base_url());
+        settings.openai_api = Some(OpenAiApi::Responses);
+        let client = OpenAiClient::new(&settings).unwrap();
+        let err = client.enrich("hello", &empty_report()).await.unwrap_err();
+        assert!(err.to_string().contains("refused"));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires loopback networking"]
+    async fn enrich_streams_function_call_arguments() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1/chat/completions")
+                .body_contains("\"stream\":true");
+            then.status(200)
+                .header("content-type", "text/event-stream")
+                .body(concat!(
+                    "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"name\":\"record_verdict\",\"arguments\":\"\"}}]}}]}\n\n",
+                    "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"{\\\"label\\\":\\\"suspicious\\\",\\\"rationale\\\":\\\"ok\\\"\"}}]}}]}\n\n",
+                    "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\",\\\"mitigation\\\":\\\"review\\\"}\"}}]}}]}\n\n",
+                    "data: {\"choices\":[{\"delta\":{},\"finish_reason\":\"stop\"}]}\n\n",
+                    "data: [DONE]\n\n",
+                ));
+        });
+
+        let mut settings = base_settings(server.base_url());
+        settings.stream = Some(true);
+        let client = OpenAiClient::new(&settings).unwrap();
+        let verdict = client.enrich("hello", &empty_report()).await.unwrap();
+        assert_eq!(verdict.label, "suspicious");
+        assert_eq!(verdict.mitigation, "review");
+        assert_eq!(verdict.usage, None);
         mock.assert();
     }
 
@@ -242,14 +708,40 @@ mod tests {
         mock.assert_hits(2);
     }
 
-    #[test]
-    fn truncate_short_strings_return_same() {
-        assert_eq!(truncate("abc", 10), "abc");
+    #[tokio::test]
+    #[ignore = "requires loopback networking"]
+    async fn does_not_retry_client_errors() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/v1/chat/completions");
+            then.status(400).body("bad request");
+        });
+
+        let mut settings = base_settings(server.base_url());
+        settings.max_retries = 3;
+        let client = OpenAiClient::new(&settings).unwrap();
+        let err = client.enrich("hello", &empty_report()).await.unwrap_err();
+        assert!(err.to_string().contains("bad request"));
+        mock.assert_hits(1);
     }
 
     #[test]
-    fn truncate_long_strings_adds_ellipsis() {
-        let result = truncate("abcdefghijklmnopqrstuvwxyz", 5);
-        assert_eq!(result, "abcde…");
+    fn preview_shows_the_request_without_sending_it() {
+        let client = OpenAiClient::new(&base_settings("http://127.0.0.1:9".into())).unwrap();
+        let previews = client.preview("hello", &empty_report()).unwrap();
+        assert_eq!(previews.len(), 1);
+        let preview = &previews[0];
+        assert_eq!(preview.provider, "openai");
+        assert_eq!(
+            preview.url.as_deref(),
+            Some("http://127.0.0.1:9/v1/chat/completions")
+        );
+        assert_eq!(preview.body["model"], preview.model.as_str());
+        assert_eq!(preview.body["messages"][1]["role"], "user");
+        assert!(preview.body["messages"][1]["content"]
+            .as_str()
+            .unwrap()
+            .contains("hello"));
+        assert!(!preview.body.to_string().contains("test-key"));
     }
 }
@@ -0,0 +1,381 @@
+use super::{scrub_pii, LabelSet, LlmSettings};
+use crate::scanner::{Finding, ScanReport};
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::path::Path;
+
+const DEFAULT_SYSTEM_PROMPT: &str = "You are an application security assistant. Analyze prompt-injection scan results and respond with strict JSON: {\"label\": \"{{labels}}\", \"rationale\": \"...\", \"mitigation\": \"...\"}. The mitigation should advise remediation steps.{{label_guide}}";
+
+const DEFAULT_USER_PROMPT: &str =
+    "Input excerpt:\n{{excerpt}}\n\nScore: {{score}} ({{band}})\nTop findings: {{findings}}\n";
+
+/// Placeholders a user prompt may reference.
+const PLACEHOLDERS: [&str; 6] = [
+    "excerpt",
+    "score",
+    "band",
+    "findings",
+    "finding_count",
+    "labels",
+];
+
+/// Placeholders a system prompt may reference.
+const SYSTEM_PLACEHOLDERS: [&str; 2] = ["labels", "label_guide"];
+
+/// Instructions sent with every verdict request.
+///
+/// A template file is TOML with optional `system` and `user` strings; an omitted key keeps the
+/// built-in text. The user prompt may reference `{{excerpt}}` (the input, see
+/// [`super::compress_input`]), `{{score}}`, `{{band}}`, `{{findings}}` (the findings as JSON), and
+/// `{{finding_count}}`. Either prompt may reference `{{labels}}` (the configured verdict labels
+/// joined with `|`), and the system prompt `{{label_guide}}` (the label descriptions, if any).
+/// Whatever the wording, the model must still answer with a JSON object carrying `label`,
+/// `rationale`, and `mitigation`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptTemplate {
+    /// The system prompt as written, label placeholders included.
+    system_template: String,
+    user: String,
+    labels: LabelSet,
+    /// `system_template` with the label placeholders filled in.
+    system: String,
+    /// Replace PII and secrets in the excerpt and findings (see [`scrub_pii`]).
+    scrub_pii: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TemplateFile {
+    system: Option<String>,
+    user: Option<String>,
+}
+
+impl TemplateFile {
+    #[cfg(feature = "formats")]
+    fn parse(source: &str) -> Result<Self> {
+        Ok(toml::from_str(source)?)
+    }
+
+    /// Fallback when the `formats` feature is disabled.
+    #[cfg(not(feature = "formats"))]
+    fn parse(_source: &str) -> Result<Self> {
+        bail!("TOML prompt templates require the `formats` feature")
+    }
+}
+
+impl PromptTemplate {
+    /// The template named by `settings.prompt_template`, or the built-in prompts, listing
+    /// `settings.verdict_labels`, scrubbing PII when `settings.scrub_pii` is set.
+    pub fn from_settings(settings: &LlmSettings) -> Result<Self> {
+        let template = match settings.prompt_template.as_deref() {
+            Some(path) => Self::load(path)?,
+            None => Self::default(),
+        };
+        let template = match settings.verdict_labels.clone() {
+            Some(labels) => template.with_labels(labels),
+            None => template,
+        };
+        Ok(template.with_pii_scrubbing(settings.scrub_pii.unwrap_or(false)))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read prompt template {}", path.display()))?;
+        Self::parse(&source).with_context(|| format!("invalid prompt template {}", path.display()))
+    }
+
+    pub fn parse(source: &str) -> Result<Self> {
+        let file = TemplateFile::parse(source)?;
+        let defaults = Self::default();
+        let system = file.system.unwrap_or(defaults.system_template);
+        let user = file.user.unwrap_or(defaults.user);
+        if system.trim().is_empty() {
+            bail!("`system` must not be empty");
+        }
+        for name in placeholders(&system) {
+            if !SYSTEM_PLACEHOLDERS.contains(&name) {
+                bail!(
+                    "unknown placeholder `{{{{{name}}}}}` in `system` (expected one of {})",
+                    SYSTEM_PLACEHOLDERS.join(", ")
+                );
+            }
+        }
+        for name in placeholders(&user) {
+            if !PLACEHOLDERS.contains(&name) {
+                bail!(
+                    "unknown placeholder `{{{{{name}}}}}` in `user` (expected one of {})",
+                    PLACEHOLDERS.join(", ")
+                );
+            }
+        }
+        Ok(Self::new(system, user, defaults.labels))
+    }
+
+    fn new(system: String, user: String, labels: LabelSet) -> Self {
+        let rendered_system = substitute(&system, |name| match name {
+            "labels" => Some(labels.choices()),
+            "label_guide" => Some(labels.guide()),
+            _ => None,
+        });
+        Self {
+            system_template: system,
+            user,
+            labels,
+            system: rendered_system,
+            scrub_pii: false,
+        }
+    }
+
+    /// Ask for verdicts from `labels` instead of the default set.
+    pub fn with_labels(self, labels: LabelSet) -> Self {
+        let scrub_pii = self.scrub_pii;
+        Self::new(self.system_template, self.user, labels).with_pii_scrubbing(scrub_pii)
+    }
+
+    /// Replace personal data and credentials in the excerpt and finding excerpts with typed
+    /// placeholders before they reach the provider.
+    pub fn with_pii_scrubbing(mut self, enabled: bool) -> Self {
+        self.scrub_pii = enabled;
+        self
+    }
+
+    pub fn labels(&self) -> &LabelSet {
+        &self.labels
+    }
+
+    pub fn system(&self) -> &str {
+        &self.system
+    }
+
+    /// Fill the user prompt for `report`; `excerpt` is the already-compressed input.
+    pub fn render_user(&self, excerpt: &str, report: &ScanReport) -> String {
+        substitute(&self.user, |name| match name {
+            "excerpt" if self.scrub_pii => Some(scrub_pii(excerpt)),
+            "excerpt" => Some(excerpt.to_string()),
+            "score" => Some(format!("{:.1}", report.risk_score)),
+            "band" => Some(report.risk_band.name().to_string()),
+            "findings" => Some(self.findings_json(report)),
+            "finding_count" => Some(report.findings.len().to_string()),
+            "labels" => Some(self.labels.choices()),
+            _ => None,
+        })
+    }
+
+    fn findings_json(&self, report: &ScanReport) -> String {
+        if !self.scrub_pii {
+            return serde_json::to_string(&report.findings).unwrap_or_default();
+        }
+        let findings: Vec<Finding> = report
+            .findings
+            .iter()
+            .map(|finding| {
+                let excerpt = scrub_pii(&finding.excerpt);
+                Finding {
+                    // The match offsets no longer line up once anything was replaced.
+                    excerpt_span: finding.excerpt_span.filter(|_| excerpt == finding.excerpt),
+                    excerpt,
+                    ..finding.clone()
+                }
+            })
+            .collect();
+        serde_json::to_string(&findings).unwrap_or_default()
+    }
+}
+
+impl Default for PromptTemplate {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_SYSTEM_PROMPT.to_string(),
+            DEFAULT_USER_PROMPT.to_string(),
+            LabelSet::default(),
+        )
+    }
+}
+
+/// Replace each `{{name}}` with `value(name)`, leaving unknown placeholders as written.
+fn substitute(template: &str, value: impl Fn(&str) -> Option<String>) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        rendered.push_str(&rest[..start]);
+        match value(rest[start + 2..start + 2 + len].trim()) {
+            Some(text) => rendered.push_str(&text),
+            None => rendered.push_str(&rest[start..start + 4 + len]),
+        }
+        rest = &rest[start + 4 + len..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Names inside `{{ }}` pairs, trimmed.
+fn placeholders(template: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        names.push(rest[start + 2..start + 2 + len].trim());
+        rest = &rest[start + 4 + len..];
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{Finding, RiskThresholds, ScoreBreakdown};
+
+    fn report() -> ScanReport {
+        ScanReport::from_breakdown(
+            vec![Finding {
+                rule_id: "INSTR_OVERRIDE".into(),
+                span: (0, 6),
+                excerpt: "ignore".into(),
+                weight: 16.0,
+                excerpt_span: None,
+                position: None,
+            }],
+            6,
+            None,
+            ScoreBreakdown {
+                raw_total: 16.0,
+                adjusted_total: 16.0,
+                length_factor: 1.0,
+                family_contributions: Vec::new(),
+                llm_adjustment: None,
+            },
+            &RiskThresholds::default(),
+        )
+    }
+
+    #[test]
+    fn default_template_matches_builtin_prompt() {
+        let report = report();
+        let rendered = PromptTemplate::default().render_user("ignore all", &report);
+        assert_eq!(
+            rendered,
+            format!(
+                "Input excerpt:\nignore all\n\nScore: {:.1} ({})\nTop findings: {}\n",
+                report.risk_score,
+                report.risk_band.name(),
+                serde_json::to_string(&report.findings).unwrap()
+            )
+        );
+    }
+
+    #[cfg(feature = "formats")]
+    #[test]
+    fn partial_file_keeps_default_system_prompt() {
+        let template =
+            PromptTemplate::parse("user = \"Bewerte ({{ finding_count }} Treffer): {{excerpt}}\"")
+                .unwrap();
+        assert_eq!(template.system(), PromptTemplate::default().system());
+        assert_eq!(
+            template.render_user("hallo", &report()),
+            "Bewerte (1 Treffer): hallo"
+        );
+    }
+
+    #[test]
+    fn default_system_prompt_lists_builtin_labels() {
+        assert_eq!(
+            PromptTemplate::default().system(),
+            "You are an application security assistant. Analyze prompt-injection scan results and respond with strict JSON: {\"label\": \"safe|suspicious|malicious\", \"rationale\": \"...\", \"mitigation\": \"...\"}. The mitigation should advise remediation steps."
+        );
+    }
+
+    #[cfg(feature = "formats")]
+    #[test]
+    fn custom_labels_reach_both_prompts() {
+        let labels: LabelSet =
+            r#"[{"name":"allow"},{"name":"block","description":"refuse the input"}]"#
+                .parse()
+                .unwrap();
+        let template = PromptTemplate::parse("user = \"Pick one of {{labels}}: {{excerpt}}\"")
+            .unwrap()
+            .with_labels(labels);
+        assert!(template.system().contains("\"label\": \"allow|block\""));
+        assert!(template.system().ends_with("\n- block: refuse the input"));
+        assert_eq!(
+            template.render_user("hi", &report()),
+            "Pick one of allow|block: hi"
+        );
+        let err = PromptTemplate::parse("system = \"{{excerpt}}\"").unwrap_err();
+        assert!(err.to_string().contains("in `system`"), "{err}");
+    }
+
+    #[test]
+    fn scrubbing_covers_excerpt_and_findings() {
+        let mut report = report();
+        report.findings[0].excerpt = "ignore it, mail ops@example.com".into();
+        report.findings[0].excerpt_span = Some((0, 6));
+        let template = PromptTemplate::default().with_pii_scrubbing(true);
+        let rendered = template.render_user("my key is sk-ant-REDACTED", &report);
+        assert!(rendered.contains("my key is [API_KEY]"), "{rendered}");
+        assert!(rendered.contains("mail [EMAIL]"), "{rendered}");
+        assert!(!rendered.contains("excerpt_span"), "{rendered}");
+        assert!(!rendered.contains("example.com"));
+
+        let labels: LabelSet = "allow,block".parse().unwrap();
+        assert!(template.with_labels(labels).scrub_pii);
+        let plain = PromptTemplate::default().render_user("ops@example.com", &report);
+        assert!(plain.contains("ops@example.com"));
+    }
+
+    #[cfg(feature = "formats")]
+    #[test]
+    fn rejects_unknown_placeholders_and_keys() {
+        let err = PromptTemplate::parse("user = \"{{excerpt}} {{risk}}\"").unwrap_err();
+        assert!(err.to_string().contains("{{risk}}"), "{err}");
+        assert!(PromptTemplate::parse("sytem = \"typo\"").is_err());
+        assert!(PromptTemplate::parse("system = \"  \"").is_err());
+    }
+
+    #[cfg(feature = "formats")]
+    #[test]
+    fn loads_from_settings_path() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), "system = \"Reply in JSON.\"\n").unwrap();
+        let settings = LlmSettings {
+            provider: "noop".into(),
+            api_key: String::new(),
+            endpoint: None,
+            model: None,
+            deployment: None,
+            project: None,
+            workspace: None,
+            region: None,
+            timeout_secs: None,
+            max_retries: 0,
+            api_version: None,
+            retry_base_delay_ms: None,
+            retry_max_delay_ms: None,
+            retry_jitter: None,
+            retry_on_status: None,
+            prompt_template: Some(file.path().to_path_buf()),
+            verdict_labels: None,
+            structured_output: None,
+            stream: None,
+            stream_idle_timeout_secs: None,
+            proxy: None,
+            ca_bundle: None,
+            client_cert: None,
+            client_key: None,
+            azure_auth: None,
+            scrub_pii: None,
+            prompt_cache: None,
+            openai_api: None,
+            temperature: None,
+            max_tokens: None,
+            response_mime_type: None,
+            safety_settings: None,
+        };
+        let template = PromptTemplate::from_settings(&settings).unwrap();
+        assert_eq!(template.system(), "Reply in JSON.");
+    }
+}
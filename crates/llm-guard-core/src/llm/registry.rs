@@ -0,0 +1,146 @@
+use super::{LlmClient, LlmSettings};
+use anyhow::{bail, Result};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Builds a client for a registered provider from the resolved settings.
+pub type ProviderFactory = Arc<dyn Fn(&LlmSettings) -> Result<Box<dyn LlmClient>> + Send + Sync>;
+
+static REGISTRY: Lazy<RwLock<HashMap<String, ProviderFactory>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+fn normalize(name: &str) -> String {
+    name.trim().to_ascii_lowercase()
+}
+
+/// Make `name` (case-insensitive) available as `LLM_GUARD_PROVIDER`, e.g. for an internal LLM
+/// gateway.
+///
+/// [`super::build_client`] consults registered providers before the built-in ones, so a
+/// registration may also replace a built-in provider; registering a name again replaces the
+/// earlier factory. Registered providers need no `LLM_GUARD_API_KEY`; the factory receives
+/// whatever is set and decides. Their clients get the same label validation and timing as the
+/// built-in ones.
+pub fn register_provider<F>(name: &str, factory: F) -> Result<()>
+where
+    F: Fn(&LlmSettings) -> Result<Box<dyn LlmClient>> + Send + Sync + 'static,
+{
+    let name = normalize(name);
+    if name.is_empty() {
+        bail!("provider name must not be empty");
+    }
+    REGISTRY
+        .write()
+        .expect("provider registry poisoned")
+        .insert(name, Arc::new(factory));
+    Ok(())
+}
+
+/// Remove a registration; returns whether `name` was registered.
+pub fn unregister_provider(name: &str) -> bool {
+    REGISTRY
+        .write()
+        .expect("provider registry poisoned")
+        .remove(&normalize(name))
+        .is_some()
+}
+
+/// Names of the registered providers, sorted.
+pub fn registered_providers() -> Vec<String> {
+    let mut names: Vec<String> = REGISTRY
+        .read()
+        .expect("provider registry poisoned")
+        .keys()
+        .cloned()
+        .collect();
+    names.sort();
+    names
+}
+
+pub(crate) fn lookup(name: &str) -> Option<ProviderFactory> {
+    REGISTRY
+        .read()
+        .expect("provider registry poisoned")
+        .get(&normalize(name))
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::build_client;
+    use crate::scanner::{LlmVerdict, ScanReport};
+    use async_trait::async_trait;
+
+    struct Gateway(String);
+
+    #[async_trait]
+    impl LlmClient for Gateway {
+        async fn enrich(&self, _input: &str, _report: &ScanReport) -> Result<LlmVerdict> {
+            Ok(LlmVerdict {
+                label: "Suspicious".into(),
+                rationale: format!("via {}", self.0),
+                ..Default::default()
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn build_client_prefers_registered_providers() {
+        register_provider(" RegistryTestCorp ", |settings: &LlmSettings| {
+            Ok(
+                Box::new(Gateway(settings.endpoint.clone().unwrap_or_default()))
+                    as Box<dyn LlmClient>,
+            )
+        })
+        .unwrap();
+        assert!(registered_providers().contains(&"registrytestcorp".to_string()));
+
+        let settings = LlmSettings {
+            provider: "registrytestcorp".into(),
+            api_key: String::new(),
+            endpoint: Some("https://llm.internal".into()),
+            model: None,
+            deployment: None,
+            project: None,
+            workspace: None,
+            region: None,
+            timeout_secs: None,
+            max_retries: 0,
+            api_version: None,
+            retry_base_delay_ms: None,
+            retry_max_delay_ms: None,
+            retry_jitter: None,
+            retry_on_status: None,
+            prompt_template: None,
+            verdict_labels: None,
+            structured_output: None,
+            stream: None,
+            stream_idle_timeout_secs: None,
+            proxy: None,
+            ca_bundle: None,
+            client_cert: None,
+            client_key: None,
+            azure_auth: None,
+            scrub_pii: None,
+            prompt_cache: None,
+            openai_api: None,
+            temperature: None,
+            max_tokens: None,
+            response_mime_type: None,
+            safety_settings: None,
+        };
+        let client = build_client(&settings).unwrap();
+        let report = ScanReport::new(0.0, Vec::new(), 0, None, Default::default());
+        let verdict = client.enrich("hi", &report).await.unwrap();
+        // Labels are normalised and the call is timed like any built-in provider.
+        assert_eq!(verdict.label, "suspicious");
+        assert_eq!(verdict.rationale, "via https://llm.internal");
+        assert!(verdict.meta.is_some());
+
+        assert!(unregister_provider("REGISTRYTESTCORP"));
+        assert!(build_client(&settings).is_err());
+        assert!(register_provider("  ", |_: &LlmSettings| bail!("unused")).is_err());
+    }
+}
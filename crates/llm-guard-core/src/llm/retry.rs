@@ -0,0 +1,291 @@
+use super::LlmSettings;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// Backoff schedule shared by the standalone HTTP provider clients.
+///
+/// Transport errors are always retried; HTTP errors only when their status is listed in
+/// `retry_on`. A `Retry-After` (or `retry-after-ms`) header replaces the computed delay,
+/// capped at `max_delay` so a provider cannot stall a scan indefinitely.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    /// Delay before the first retry; doubles on every further attempt.
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Randomise each delay between half and all of its computed value so parallel scans
+    /// do not retry in lockstep.
+    pub jitter: bool,
+    pub retry_on: Vec<u16>,
+}
+
+impl RetryPolicy {
+    pub const DEFAULT_RETRY_ON: [u16; 7] = [408, 425, 429, 500, 502, 503, 504];
+
+    pub fn from_settings(settings: &LlmSettings) -> Self {
+        let defaults = Self::default();
+        let base_delay = settings
+            .retry_base_delay_ms
+            .map(Duration::from_millis)
+            .unwrap_or(defaults.base_delay);
+        let max_delay = settings
+            .retry_max_delay_ms
+            .map(Duration::from_millis)
+            .unwrap_or(defaults.max_delay)
+            .max(base_delay);
+        Self {
+            max_retries: settings.max_retries,
+            base_delay,
+            max_delay,
+            jitter: settings.retry_jitter.unwrap_or(defaults.jitter),
+            retry_on: settings
+                .retry_on_status
+                .clone()
+                .unwrap_or(defaults.retry_on),
+        }
+    }
+
+    pub fn should_retry(&self, status: u16) -> bool {
+        self.retry_on.contains(&status)
+    }
+
+    /// Delay before retry number `attempt` (zero-based), honouring a server hint if present.
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        if let Some(hint) = retry_after {
+            return hint.min(self.max_delay);
+        }
+        let factor = 2u32.saturating_pow(attempt.min(16));
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        if self.jitter {
+            let half = delay / 2;
+            half + half.mul_f64(unit_random())
+        } else {
+            delay
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+            jitter: true,
+            retry_on: Self::DEFAULT_RETRY_ON.to_vec(),
+        }
+    }
+}
+
+/// A value in `[0, 1)` from the std hasher's per-process random keys; good enough for jitter
+/// without pulling in an RNG crate.
+fn unit_random() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos())
+            .unwrap_or_default(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Parse `retry-after-ms` or the integer-seconds form of `Retry-After`.
+#[cfg(feature = "llm")]
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let read = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<f64>().ok())
+    };
+    read("retry-after-ms")
+        .map(|ms| ms / 1000.0)
+        .or_else(|| read("retry-after"))
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+}
+
+#[cfg(feature = "llm")]
+impl RetryPolicy {
+    /// Send the request produced by `request` until it succeeds or the policy gives up.
+    ///
+    /// `request` runs once per attempt (so callers can re-sign or refresh tokens) and receives
+    /// the status of the previous failed attempt, if any. `api` names the provider in errors
+    /// and `operation` the endpoint, e.g. `("OpenAI", "chat completions")`.
+    pub(crate) async fn send<F, Fut>(
+        &self,
+        api: &str,
+        operation: &str,
+        mut request: F,
+    ) -> anyhow::Result<reqwest::Response>
+    where
+        F: FnMut(Option<reqwest::StatusCode>) -> Fut,
+        Fut: std::future::Future<Output = anyhow::Result<reqwest::RequestBuilder>>,
+    {
+        use anyhow::Context;
+
+        let mut attempt = 0u32;
+        let mut previous = None;
+        loop {
+            let response = match request(previous).await?.send().await {
+                Ok(response) => response,
+                Err(err) => {
+                    super::timing::record_attempt(None);
+                    if attempt >= self.max_retries {
+                        return Err(err)
+                            .with_context(|| format!("failed to call {api} {operation} API"));
+                    }
+                    tokio::time::sleep(self.delay(attempt, None)).await;
+                    attempt += 1;
+                    continue;
+                }
+            };
+
+            let status = response.status();
+            super::timing::record_attempt(Some(status.as_u16()));
+            if status.is_success() {
+                return Ok(response);
+            }
+            if attempt >= self.max_retries || !self.should_retry(status.as_u16()) {
+                let body = response.text().await.unwrap_or_default();
+                anyhow::bail!("{} API error ({}): {}", api, status, body);
+            }
+            tokio::time::sleep(self.delay(attempt, retry_after(response.headers()))).await;
+            previous = Some(status);
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> LlmSettings {
+        LlmSettings {
+            provider: "openai".into(),
+            api_key: "key".into(),
+            endpoint: None,
+            model: None,
+            deployment: None,
+            project: None,
+            workspace: None,
+            region: None,
+            timeout_secs: None,
+            max_retries: 3,
+            api_version: None,
+            retry_base_delay_ms: None,
+            retry_max_delay_ms: None,
+            retry_jitter: None,
+            retry_on_status: None,
+            prompt_template: None,
+            verdict_labels: None,
+            structured_output: None,
+            stream: None,
+            stream_idle_timeout_secs: None,
+            proxy: None,
+            ca_bundle: None,
+            client_cert: None,
+            client_key: None,
+            azure_auth: None,
+            scrub_pii: None,
+            prompt_cache: None,
+            openai_api: None,
+            temperature: None,
+            max_tokens: None,
+            response_mime_type: None,
+            safety_settings: None,
+        }
+    }
+
+    #[test]
+    fn defaults_apply_when_settings_leave_fields_unset() {
+        let policy = RetryPolicy::from_settings(&settings());
+        assert_eq!(policy.max_retries, 3);
+        assert_eq!(policy.base_delay, Duration::from_millis(200));
+        assert_eq!(policy.max_delay, Duration::from_secs(5));
+        assert!(policy.jitter);
+        assert!(policy.should_retry(429));
+        assert!(policy.should_retry(503));
+        assert!(!policy.should_retry(400));
+        assert!(!policy.should_retry(401));
+    }
+
+    #[test]
+    fn settings_override_schedule_and_statuses() {
+        let mut settings = settings();
+        settings.retry_base_delay_ms = Some(100);
+        settings.retry_max_delay_ms = Some(50);
+        settings.retry_jitter = Some(false);
+        settings.retry_on_status = Some(vec![418]);
+        let policy = RetryPolicy::from_settings(&settings);
+        // A max below the base is raised to it rather than producing a shrinking schedule.
+        assert_eq!(policy.max_delay, Duration::from_millis(100));
+        assert!(policy.should_retry(418));
+        assert!(!policy.should_retry(429));
+    }
+
+    #[test]
+    fn delay_doubles_up_to_the_cap() {
+        let policy = RetryPolicy {
+            jitter: false,
+            ..RetryPolicy::default()
+        };
+        assert_eq!(policy.delay(0, None), Duration::from_millis(200));
+        assert_eq!(policy.delay(1, None), Duration::from_millis(400));
+        assert_eq!(policy.delay(4, None), Duration::from_millis(3200));
+        assert_eq!(policy.delay(5, None), Duration::from_secs(5));
+        assert_eq!(policy.delay(u32::MAX, None), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn jitter_stays_within_half_to_full_delay() {
+        let policy = RetryPolicy::default();
+        for attempt in 0..6 {
+            let ceiling = RetryPolicy {
+                jitter: false,
+                ..RetryPolicy::default()
+            }
+            .delay(attempt, None);
+            let delay = policy.delay(attempt, None);
+            assert!(
+                delay >= ceiling / 2 && delay <= ceiling,
+                "{delay:?} vs {ceiling:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn retry_after_hint_wins_but_is_capped() {
+        let policy = RetryPolicy::default();
+        assert_eq!(
+            policy.delay(0, Some(Duration::from_secs(2))),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            policy.delay(0, Some(Duration::from_secs(120))),
+            Duration::from_secs(5)
+        );
+    }
+
+    #[cfg(feature = "llm")]
+    #[test]
+    fn parses_retry_after_headers() {
+        use reqwest::header::{HeaderMap, HeaderValue};
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert("retry-after", HeaderValue::from_static("3"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(3)));
+        headers.insert("retry-after-ms", HeaderValue::from_static("250"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_millis(250)));
+
+        let mut dated = HeaderMap::new();
+        dated.insert(
+            "retry-after",
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&dated), None);
+    }
+}
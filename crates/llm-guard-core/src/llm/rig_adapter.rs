@@ -1,5 +1,8 @@
-use super::{LlmClient, LlmSettings, ProviderKind};
-use crate::scanner::{LlmVerdict, ScanReport};
+use super::{
+    compress_input, LlmClient, LlmSettings, PromptTemplate, ProviderKind, RequestPreview,
+    DEFAULT_MAX_TOKENS, DEFAULT_TEMPERATURE, MAX_PROMPT_INPUT_CHARS,
+};
+use crate::scanner::{LlmVerdict, ScanReport, TokenUsage};
 use anyhow::{bail, Context, Result};
 use async_trait::async_trait;
 use rig::client::CompletionClient;
@@ -14,9 +17,6 @@ use std::env;
 
 const DEFAULT_OPENAI_MODEL: &str = "gpt-4o-mini";
 const DEFAULT_ANTHROPIC_MODEL: &str = "claude-3-5-sonnet-latest";
-const MAX_OUTPUT_TOKENS: u64 = 200;
-const TEMPERATURE: f64 = 0.1;
-const SYSTEM_PROMPT: &str = "You are an application security assistant. Analyze prompt-injection scan results and respond with strict JSON: {\"label\": \"safe|suspicious|malicious\", \"rationale\": \"...\", \"mitigation\": \"...\"}. The mitigation should advise remediation steps.";
 
 struct RigCompletionConfig {
     provider_label: &'static str,
@@ -29,21 +29,37 @@ pub struct RigLlmClient {
     model: Box<dyn CompletionModelDyn + Send + Sync>,
     config: RigCompletionConfig,
     model_id: String,
+    prompt: PromptTemplate,
 }
 
 impl RigLlmClient {
     pub fn for_kind(kind: ProviderKind, settings: &LlmSettings) -> Result<Box<dyn LlmClient>> {
-        match kind {
-            ProviderKind::OpenAi => Ok(Box::new(Self::new_openai(settings)?)),
-            ProviderKind::Anthropic => Ok(Box::new(Self::new_anthropic(settings)?)),
+        let mut client = match kind {
+            ProviderKind::OpenAi => Self::new_openai(settings)?,
+            ProviderKind::Anthropic => Self::new_anthropic(settings)?,
             ProviderKind::Gemini => {
                 bail!("Gemini provider should use standalone client, not rig adapter")
             }
-            ProviderKind::Azure => Ok(Box::new(Self::new_azure(settings)?)),
-            ProviderKind::Noop | ProviderKind::Rig => {
+            ProviderKind::Azure => Self::new_azure(settings)?,
+            ProviderKind::VertexAi
+            | ProviderKind::Cohere
+            | ProviderKind::Xai
+            | ProviderKind::DeepSeek
+            | ProviderKind::Bedrock => {
+                bail!("{kind:?} provider should use standalone client, not rig adapter")
+            }
+            ProviderKind::Noop | ProviderKind::Local | ProviderKind::Rig => {
                 bail!("rig adapter does not support provider `{kind:?}` yet")
             }
+        };
+        client.prompt = PromptTemplate::from_settings(settings)?;
+        if let Some(temperature) = settings.temperature {
+            client.config.temperature = Some(widen(temperature));
+        }
+        if let Some(max_tokens) = settings.max_tokens {
+            client.config.max_tokens = max_tokens.into();
         }
+        Ok(Box::new(client))
     }
 
     fn new_openai(settings: &LlmSettings) -> Result<Self> {
@@ -97,7 +113,7 @@ impl RigLlmClient {
             model,
             "anthropic",
             model_id,
-            Some(TEMPERATURE),
+            Some(widen(DEFAULT_TEMPERATURE)),
             false,
         ))
     }
@@ -142,7 +158,7 @@ impl RigLlmClient {
             model,
             "azure",
             deployment,
-            Some(TEMPERATURE),
+            Some(widen(DEFAULT_TEMPERATURE)),
             false,
         ))
     }
@@ -159,10 +175,40 @@ impl RigLlmClient {
             config: RigCompletionConfig {
                 provider_label,
                 temperature,
-                max_tokens: MAX_OUTPUT_TOKENS,
+                max_tokens: DEFAULT_MAX_TOKENS.into(),
                 force_json_mime,
             },
             model_id,
+            prompt: PromptTemplate::default(),
+        }
+    }
+
+    fn prompt_text(&self, input: &str, report: &ScanReport) -> String {
+        format!(
+            "You are validating a prompt injection scan. Respond strictly with a JSON object using keys 'label', 'rationale', and 'mitigation'.\n{}",
+            self.prompt.render_user(&compress_input(input, report, MAX_PROMPT_INPUT_CHARS), report)
+        )
+    }
+
+    fn additional_params(&self) -> Option<serde_json::Value> {
+        if self.config.provider_label == "openai" {
+            // Use simple json_object format instead of json_schema for better compatibility
+            // with reasoning models like gpt-5
+            Some(json!({
+                "response_format": {
+                    "type": "json_object"
+                }
+            }))
+        } else if self.config.force_json_mime {
+            Some(json!({
+                "generationConfig": {
+                    "responseMimeType": "application/json"
+                }
+            }))
+        } else {
+            // Note: Gemini function calling removed due to rig compatibility issues
+            // Gemini will rely on prompt instructions for JSON formatting
+            None
         }
     }
 }
@@ -170,43 +216,18 @@ impl RigLlmClient {
 #[async_trait]
 impl LlmClient for RigLlmClient {
     async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
-        let prompt = format!(
-            "You are validating a prompt injection scan. Respond strictly with a JSON object using keys 'label', 'rationale', and 'mitigation'.\nInput excerpt:\n{}\n\nScore: {:.1} ({:?})\nTop findings: {}\n",
-            truncate(input, 2000),
-            report.risk_score,
-            report.risk_band,
-            serde_json::to_string(&report.findings).unwrap_or_default()
-        );
-
         let mut builder = self
             .model
-            .completion_request(prompt.into())
-            .preamble(SYSTEM_PROMPT.to_string())
+            .completion_request(self.prompt_text(input, report).into())
+            .preamble(self.prompt.system().to_string())
             .max_tokens(self.config.max_tokens);
 
         if let Some(temp) = self.config.temperature {
             builder = builder.temperature(temp);
         }
-
-        if self.config.force_json_mime {
-            builder = builder.additional_params(json!({
-                "generationConfig": {
-                    "responseMimeType": "application/json"
-                }
-            }));
-        }
-
-        if self.config.provider_label == "openai" {
-            // Use simple json_object format instead of json_schema for better compatibility
-            // with reasoning models like gpt-5
-            builder = builder.additional_params(json!({
-                "response_format": {
-                    "type": "json_object"
-                }
-            }));
+        if let Some(params) = self.additional_params() {
+            builder = builder.additional_params(params);
         }
-        // Note: Gemini function calling removed due to rig compatibility issues
-        // Gemini will rely on prompt instructions for JSON formatting
 
         let request = builder.build();
 
@@ -232,6 +253,7 @@ impl LlmClient for RigLlmClient {
             }
         };
 
+        let usage = TokenUsage::reported(response.usage.input_tokens, response.usage.output_tokens);
         let choice = response.choice;
         let verdict = verdict_from_choice(choice, self.config.provider_label, &self.model_id)?;
 
@@ -239,12 +261,37 @@ impl LlmClient for RigLlmClient {
             label: verdict.label,
             rationale: verdict.rationale,
             mitigation: verdict.mitigation,
+            usage,
+            ..Default::default()
         })
     }
+
+    /// rig owns the wire format, so this shows the completion request handed to it.
+    fn preview(&self, input: &str, report: &ScanReport) -> Result<Vec<RequestPreview>> {
+        Ok(vec![RequestPreview {
+            provider: self.config.provider_label.into(),
+            model: self.model_id.clone(),
+            url: None,
+            body: json!({
+                "preamble": self.prompt.system(),
+                "prompt": self.prompt_text(input, report),
+                "temperature": self.config.temperature,
+                "max_tokens": self.config.max_tokens,
+                "additional_params": self.additional_params(),
+            }),
+        }])
+    }
+}
+
+/// rig takes an `f64`; go through the decimal form so `0.1` is sent as `0.1`, not
+/// `0.10000000149011612`.
+fn widen(value: f32) -> f64 {
+    value.to_string().parse().unwrap_or(value.into())
 }
 
 #[derive(Debug, Deserialize)]
 struct ModelVerdict {
+    #[serde(deserialize_with = "super::labels::deserialize_label")]
     label: String,
     rationale: String,
     mitigation: String,
@@ -258,6 +305,7 @@ fn fallback_verdict(provider: &str) -> LlmVerdict {
             provider
         ),
         mitigation: "Inspect provider logs or retry with a model that emits textual output.".into(),
+        ..Default::default()
     }
 }
 
@@ -427,13 +475,6 @@ fn verdict_from_choice(
     parse_verdict_json(&json_payload, provider_label, model_id)
 }
 
-fn truncate(input: &str, max_chars: usize) -> String {
-    if input.chars().count() <= max_chars {
-        return input.to_string();
-    }
-    input.chars().take(max_chars).collect::<String>() + "…"
-}
-
 fn extract_json_payload(raw: &str) -> String {
     let trimmed = raw.trim();
     if let Some(stripped) = strip_code_fence(trimmed) {
@@ -476,9 +517,31 @@ mod tests {
             deployment: None,
             project: None,
             workspace: None,
+            region: None,
             timeout_secs: Some(30),
             max_retries: 0,
             api_version: None,
+            retry_base_delay_ms: None,
+            retry_max_delay_ms: None,
+            retry_jitter: None,
+            retry_on_status: None,
+            prompt_template: None,
+            verdict_labels: None,
+            structured_output: None,
+            stream: None,
+            stream_idle_timeout_secs: None,
+            proxy: None,
+            ca_bundle: None,
+            client_cert: None,
+            client_key: None,
+            azure_auth: None,
+            scrub_pii: None,
+            prompt_cache: None,
+            openai_api: None,
+            temperature: None,
+            max_tokens: None,
+            response_mime_type: None,
+            safety_settings: None,
         }
     }
 
@@ -525,9 +588,31 @@ mod tests {
             deployment: None,
             project: None,
             workspace: None,
+            region: None,
             timeout_secs: Some(30),
             max_retries: 0,
             api_version: Some("2024-02-15-preview".into()),
+            retry_base_delay_ms: None,
+            retry_max_delay_ms: None,
+            retry_jitter: None,
+            retry_on_status: None,
+            prompt_template: None,
+            verdict_labels: None,
+            structured_output: None,
+            stream: None,
+            stream_idle_timeout_secs: None,
+            proxy: None,
+            ca_bundle: None,
+            client_cert: None,
+            client_key: None,
+            azure_auth: None,
+            scrub_pii: None,
+            prompt_cache: None,
+            openai_api: None,
+            temperature: None,
+            max_tokens: None,
+            response_mime_type: None,
+            safety_settings: None,
         }
     }
 
@@ -636,14 +721,6 @@ mod tests {
         assert!(verdict.rationale.contains("openai"));
     }
 
-    #[test]
-    fn truncate_adds_ellipsis_when_exceeding_limit() {
-        let long = "abcdefghijklmnopqrstuvwxyz";
-        let truncated = truncate(long, 10);
-        assert!(truncated.ends_with('…'));
-        assert_eq!(truncated.chars().count(), 11);
-    }
-
     #[test]
     fn verdict_from_choice_parses_valid_json() {
         let choice = OneOrMany::one(AssistantContent::Text(Text {
@@ -0,0 +1,427 @@
+//! Secret references such as `vault://kv/llm/openai#key` that are resolved through pluggable
+//! backends, so provider profiles never hold raw credentials.
+
+use super::bedrock::{amz_timestamp, sign_v4, AwsCredentials, SigningRequest};
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use reqwest::{Client, Url};
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// A parsed `scheme://path#field` reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretRef {
+    pub scheme: String,
+    pub path: String,
+    /// Key to pick out of a structured secret; `None` uses the whole value (or the only key).
+    pub field: Option<String>,
+}
+
+impl SecretRef {
+    /// Parse `value` if it has the `scheme://path[#field]` shape; plain strings yield `None`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (scheme, rest) = value.trim().split_once("://")?;
+        let valid_scheme = !scheme.is_empty()
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '+');
+        if !valid_scheme {
+            return None;
+        }
+        let (path, field) = match rest.split_once('#') {
+            Some((path, field)) => (path, Some(field.trim().to_string())),
+            None => (rest, None),
+        };
+        let path = path.trim().trim_matches('/');
+        if path.is_empty() {
+            return None;
+        }
+        Some(Self {
+            scheme: scheme.to_ascii_lowercase(),
+            path: path.to_string(),
+            field: field.filter(|field| !field.is_empty()),
+        })
+    }
+}
+
+impl std::fmt::Display for SecretRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}://{}", self.scheme, self.path)?;
+        if let Some(field) = &self.field {
+            write!(f, "#{field}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A store that can look up [`SecretRef`]s with one URI scheme.
+#[async_trait]
+pub trait SecretBackend: Send + Sync {
+    /// URI scheme handled by this backend, e.g. `vault`.
+    fn scheme(&self) -> &str;
+    async fn fetch(&self, secret: &SecretRef) -> Result<String>;
+}
+
+/// Resolves secret references through registered backends, caching each value for the run.
+pub struct SecretResolver {
+    backends: Vec<Arc<dyn SecretBackend>>,
+    cache: Mutex<HashMap<String, String>>,
+}
+
+impl Default for SecretResolver {
+    /// Vault (`vault://`) and AWS Secrets Manager (`aws-sm://`).
+    fn default() -> Self {
+        Self::empty()
+            .with_backend(VaultBackend)
+            .with_backend(AwsSecretsManagerBackend)
+    }
+}
+
+impl SecretResolver {
+    /// A resolver without backends; every value passes through unchanged.
+    pub fn empty() -> Self {
+        Self {
+            backends: Vec::new(),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Register `backend`, replacing any earlier backend for the same scheme.
+    pub fn with_backend(mut self, backend: impl SecretBackend + 'static) -> Self {
+        self.backends
+            .retain(|existing| !existing.scheme().eq_ignore_ascii_case(backend.scheme()));
+        self.backends.push(Arc::new(backend));
+        self
+    }
+
+    /// Whether `value` references a secret in one of the registered backends.
+    pub fn is_reference(&self, value: &str) -> bool {
+        self.reference(value).is_some()
+    }
+
+    /// The secret `value` references, or `value` itself when it is not a reference to a
+    /// registered scheme.
+    pub async fn resolve(&self, value: &str) -> Result<String> {
+        let Some((secret, backend)) = self.reference(value) else {
+            return Ok(value.to_string());
+        };
+        let key = secret.to_string();
+        let cached = self
+            .cache
+            .lock()
+            .expect("secret cache poisoned")
+            .get(&key)
+            .cloned();
+        if let Some(cached) = cached {
+            return Ok(cached);
+        }
+        let resolved = backend
+            .fetch(&secret)
+            .await
+            .with_context(|| format!("failed to resolve secret `{key}`"))?;
+        self.cache
+            .lock()
+            .expect("secret cache poisoned")
+            .insert(key, resolved.clone());
+        Ok(resolved)
+    }
+
+    fn reference(&self, value: &str) -> Option<(SecretRef, Arc<dyn SecretBackend>)> {
+        let secret = SecretRef::parse(value)?;
+        let backend = self
+            .backends
+            .iter()
+            .find(|backend| backend.scheme().eq_ignore_ascii_case(&secret.scheme))?;
+        Some((secret, Arc::clone(backend)))
+    }
+}
+
+/// Pick `field` out of a structured secret. Without a field, a string is used as-is and an
+/// object must have exactly one key.
+fn select_field(value: serde_json::Value, field: Option<&str>) -> Result<String> {
+    let value = match (value, field) {
+        (serde_json::Value::Object(mut map), Some(field)) => map
+            .remove(field)
+            .ok_or_else(|| anyhow!("secret has no field `{field}`"))?,
+        (serde_json::Value::Object(map), None) if map.len() == 1 => map
+            .into_iter()
+            .next()
+            .map(|(_, value)| value)
+            .unwrap_or_default(),
+        (serde_json::Value::Object(map), None) => {
+            let mut keys: Vec<_> = map.keys().cloned().collect();
+            keys.sort();
+            bail!(
+                "secret has several fields ({}); select one with `#field`",
+                keys.join(", ")
+            )
+        }
+        (_, Some(field)) => bail!("secret is not a key/value object, cannot select `{field}`"),
+        (value, None) => value,
+    };
+    match value {
+        serde_json::Value::String(text) => Ok(text),
+        other => Ok(other.to_string()),
+    }
+}
+
+fn secret_http_client() -> Result<Client> {
+    Client::builder()
+        .user_agent("llm-guard/0.1")
+        .timeout(Duration::from_secs(30))
+        .build()
+        .context("failed to build secret backend HTTP client")
+}
+
+fn env_var(key: &str) -> Option<String> {
+    env::var(key)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// HashiCorp Vault KV version 2. `vault://<mount>/<path>#<field>` reads
+/// `GET $VAULT_ADDR/v1/<mount>/data/<path>` with `VAULT_TOKEN` (and `VAULT_NAMESPACE` if set).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VaultBackend;
+
+#[async_trait]
+impl SecretBackend for VaultBackend {
+    fn scheme(&self) -> &str {
+        "vault"
+    }
+
+    async fn fetch(&self, secret: &SecretRef) -> Result<String> {
+        let addr =
+            env_var("VAULT_ADDR").context("VAULT_ADDR must be set to resolve vault:// secrets")?;
+        let token = env_var("VAULT_TOKEN")
+            .context("VAULT_TOKEN must be set to resolve vault:// secrets")?;
+        let (mount, path) = secret
+            .path
+            .split_once('/')
+            .ok_or_else(|| anyhow!("vault reference must have the form vault://<mount>/<path>"))?;
+        let url = format!("{}/v1/{mount}/data/{path}", addr.trim_end_matches('/'));
+
+        let mut request = secret_http_client()?
+            .get(&url)
+            .header("x-vault-token", token);
+        if let Some(namespace) = env_var("VAULT_NAMESPACE") {
+            request = request.header("x-vault-namespace", namespace);
+        }
+        let response = request.send().await.context("Vault request failed")?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            bail!("Vault returned {status}: {}", body.trim());
+        }
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .context("failed to parse Vault response")?;
+        let data = body
+            .pointer("/data/data")
+            .cloned()
+            .ok_or_else(|| anyhow!("Vault response has no data; is `{mount}` a KV v2 mount?"))?;
+        select_field(data, secret.field.as_deref())
+    }
+}
+
+/// AWS Secrets Manager. `aws-sm://<secret id or ARN>#<json field>` calls `GetSecretValue` in
+/// `AWS_REGION`/`AWS_DEFAULT_REGION` (or the ARN's region), signed with the standard `AWS_*`
+/// credentials. `AWS_ENDPOINT_URL_SECRETS_MANAGER` overrides the endpoint.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AwsSecretsManagerBackend;
+
+impl AwsSecretsManagerBackend {
+    fn region(secret_id: &str) -> Option<String> {
+        secret_id
+            .strip_prefix("arn:")
+            .and_then(|arn| arn.split(':').nth(2))
+            .map(str::to_string)
+            .filter(|region| !region.is_empty())
+            .or_else(|| env_var("AWS_REGION"))
+            .or_else(|| env_var("AWS_DEFAULT_REGION"))
+    }
+}
+
+#[async_trait]
+impl SecretBackend for AwsSecretsManagerBackend {
+    fn scheme(&self) -> &str {
+        "aws-sm"
+    }
+
+    async fn fetch(&self, secret: &SecretRef) -> Result<String> {
+        let region = Self::region(&secret.path)
+            .context("AWS_REGION must be set (or use a secret ARN) to resolve aws-sm:// secrets")?;
+        let credentials = AwsCredentials::resolve("").map_err(|_| {
+            anyhow!("AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY must be set to resolve aws-sm:// secrets")
+        })?;
+        let endpoint = env_var("AWS_ENDPOINT_URL_SECRETS_MANAGER")
+            .unwrap_or_else(|| format!("https://secretsmanager.{region}.amazonaws.com"));
+        let url = Url::parse(&endpoint)
+            .with_context(|| format!("invalid Secrets Manager endpoint `{endpoint}`"))?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            (None, _) => bail!("Secrets Manager endpoint `{endpoint}` has no host"),
+        };
+
+        let body = serde_json::to_vec(&serde_json::json!({ "SecretId": secret.path }))?;
+        let content_type = "application/x-amz-json-1.1";
+        let signed = sign_v4(
+            &SigningRequest {
+                method: "POST",
+                canonical_uri: "/",
+                host: &host,
+                region: &region,
+                service: "secretsmanager",
+                content_type,
+                body: &body,
+            },
+            &credentials,
+            &amz_timestamp(SystemTime::now()),
+        );
+        let mut request = secret_http_client()?
+            .post(url)
+            .header("content-type", content_type)
+            .header("x-amz-target", "secretsmanager.GetSecretValue")
+            .body(body);
+        for (name, value) in signed {
+            request = request.header(name, value);
+        }
+        let response = request
+            .send()
+            .await
+            .context("Secrets Manager request failed")?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            bail!("Secrets Manager returned {status}: {}", body.trim());
+        }
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .context("failed to parse Secrets Manager response")?;
+        let text = body
+            .get("SecretString")
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| {
+                anyhow!("secret has no SecretString (binary secrets are not supported)")
+            })?;
+        match secret.field.as_deref() {
+            None => Ok(text.to_string()),
+            Some(field) => {
+                let value: serde_json::Value = serde_json::from_str(text)
+                    .context("secret must be a JSON object to select a field")?;
+                select_field(value, Some(field))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+
+    struct StaticBackend;
+
+    #[async_trait]
+    impl SecretBackend for StaticBackend {
+        fn scheme(&self) -> &str {
+            "test"
+        }
+
+        async fn fetch(&self, secret: &SecretRef) -> Result<String> {
+            select_field(
+                serde_json::json!({ "key": format!("value-of-{}", secret.path) }),
+                secret.field.as_deref(),
+            )
+        }
+    }
+
+    #[test]
+    fn parses_references() {
+        assert_eq!(
+            SecretRef::parse("vault://kv/llm/openai#key"),
+            Some(SecretRef {
+                scheme: "vault".into(),
+                path: "kv/llm/openai".into(),
+                field: Some("key".into()),
+            })
+        );
+        assert_eq!(
+            SecretRef::parse("AWS-SM://prod/llm").map(|secret| secret.to_string()),
+            Some("aws-sm://prod/llm".to_string())
+        );
+        assert_eq!(SecretRef::parse("sk-plain-key"), None);
+        assert_eq!(SecretRef::parse("vault://#key"), None);
+    }
+
+    #[tokio::test]
+    async fn resolves_registered_schemes_only() {
+        let resolver = SecretResolver::empty().with_backend(StaticBackend);
+        assert!(resolver.is_reference("test://llm/openai#key"));
+        assert_eq!(
+            resolver.resolve("test://llm/openai#key").await.unwrap(),
+            "value-of-llm/openai"
+        );
+        assert_eq!(
+            resolver.resolve("test://llm/openai").await.unwrap(),
+            "value-of-llm/openai"
+        );
+        // Unregistered schemes and plain keys pass through untouched.
+        assert_eq!(
+            resolver.resolve("vault://kv/x").await.unwrap(),
+            "vault://kv/x"
+        );
+        assert_eq!(resolver.resolve("sk-123").await.unwrap(), "sk-123");
+
+        let err = resolver
+            .resolve("test://llm/openai#token")
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("no field `token`"));
+    }
+
+    #[test]
+    fn ambiguous_objects_need_a_field() {
+        let value = serde_json::json!({ "a": "1", "b": "2" });
+        let err = select_field(value, None).unwrap_err();
+        assert!(err.to_string().contains("a, b"));
+    }
+
+    #[test]
+    fn region_prefers_the_arn() {
+        assert_eq!(
+            AwsSecretsManagerBackend::region(
+                "arn:aws:secretsmanager:eu-central-1:123456789012:secret:llm-AbCdEf"
+            )
+            .as_deref(),
+            Some("eu-central-1")
+        );
+    }
+
+    #[tokio::test]
+    async fn vault_reads_kv2_fields() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v1/kv/data/llm/openai")
+                .header("x-vault-token", "s.test");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"data":{"data":{"key":"sk-from-vault"},"metadata":{"version":3}}}"#);
+        });
+        env::set_var("VAULT_ADDR", server.base_url());
+        env::set_var("VAULT_TOKEN", "s.test");
+        let secret = SecretRef::parse("vault://kv/llm/openai#key").unwrap();
+        let value = VaultBackend.fetch(&secret).await;
+        env::remove_var("VAULT_ADDR");
+        env::remove_var("VAULT_TOKEN");
+        assert_eq!(value.unwrap(), "sk-from-vault");
+        mock.assert();
+    }
+}
@@ -1,8 +1,183 @@
-use anyhow::{Context, Result};
+use super::LabelSet;
+use anyhow::{anyhow, bail, Context, Result};
 use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
 
-/// Environment-driven configuration required for LLM adapters.
+/// Output token cap when `LLM_GUARD_MAX_TOKENS` is unset; room for a short verdict object.
+pub const DEFAULT_MAX_TOKENS: u32 = 200;
+/// Sampling temperature when `LLM_GUARD_TEMPERATURE` is unset.
+pub const DEFAULT_TEMPERATURE: f32 = 0.1;
+
+/// How the Azure OpenAI provider authenticates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AzureAuth {
+    /// The resource key from `LLM_GUARD_API_KEY`, sent as the `api-key` header.
+    #[default]
+    ApiKey,
+    /// Microsoft Entra ID bearer tokens: a service principal (`AZURE_TENANT_ID`,
+    /// `AZURE_CLIENT_ID` and `AZURE_CLIENT_SECRET` or `AZURE_FEDERATED_TOKEN_FILE`), else the
+    /// host's managed identity.
+    Entra,
+}
+
+impl FromStr for AzureAuth {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "api-key" | "api_key" | "key" => Ok(Self::ApiKey),
+            "entra" | "entra-id" | "aad" | "azure-ad" => Ok(Self::Entra),
+            other => Err(format!(
+                "unknown Azure auth mode `{other}` (expected `api-key` or `entra`)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for AzureAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::ApiKey => "api-key",
+            Self::Entra => "entra",
+        })
+    }
+}
+
+/// Which OpenAI endpoint the `openai` provider calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OpenAiApi {
+    /// `/v1/chat/completions`.
+    #[default]
+    ChatCompletions,
+    /// `/v1/responses`, required by some organisation policies and Responses-only models.
+    Responses,
+}
+
+impl FromStr for OpenAiApi {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "chat" | "chat-completions" | "chat_completions" => Ok(Self::ChatCompletions),
+            "responses" => Ok(Self::Responses),
+            other => Err(format!(
+                "unknown OpenAI API `{other}` (expected `chat-completions` or `responses`)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OpenAiApi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::ChatCompletions => "chat-completions",
+            Self::Responses => "responses",
+        })
+    }
+}
+
+/// Harm categories Gemini accepts in `safetySettings`, without the `HARM_CATEGORY_` prefix.
+const HARM_CATEGORIES: [&str; 5] = [
+    "HARASSMENT",
+    "HATE_SPEECH",
+    "SEXUALLY_EXPLICIT",
+    "DANGEROUS_CONTENT",
+    "CIVIC_INTEGRITY",
+];
+
+const HARM_THRESHOLDS: [&str; 5] = [
+    "BLOCK_NONE",
+    "BLOCK_ONLY_HIGH",
+    "BLOCK_MEDIUM_AND_ABOVE",
+    "BLOCK_LOW_AND_ABOVE",
+    "OFF",
+];
+
+/// One Gemini/Vertex AI `safetySettings` entry, written `CATEGORY=THRESHOLD`.
+///
+/// Names are case-insensitive and the `HARM_CATEGORY_` prefix is optional, so
+/// `dangerous_content=block_only_high` is accepted.
 #[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SafetySetting {
+    /// Full category name, e.g. `HARM_CATEGORY_DANGEROUS_CONTENT`.
+    pub category: String,
+    pub threshold: String,
+}
+
+impl SafetySetting {
+    /// Parse a comma-separated list; the category `all` applies its threshold to every
+    /// category, and later entries override earlier ones.
+    pub fn parse_list(value: &str) -> Result<Vec<Self>, String> {
+        let mut settings: Vec<Self> = Vec::new();
+        for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = match entry.split_once('=') {
+                Some((category, threshold)) if category.trim().eq_ignore_ascii_case("all") => {
+                    let threshold = normalize_threshold(threshold)?;
+                    HARM_CATEGORIES
+                        .iter()
+                        .map(|category| Self {
+                            category: format!("HARM_CATEGORY_{category}"),
+                            threshold: threshold.clone(),
+                        })
+                        .collect()
+                }
+                _ => vec![entry.parse::<Self>()?],
+            };
+            for setting in parsed {
+                settings.retain(|existing| existing.category != setting.category);
+                settings.push(setting);
+            }
+        }
+        Ok(settings)
+    }
+}
+
+fn normalize_threshold(value: &str) -> Result<String, String> {
+    let threshold = value.trim().to_ascii_uppercase().replace('-', "_");
+    if HARM_THRESHOLDS.contains(&threshold.as_str()) {
+        Ok(threshold)
+    } else {
+        Err(format!(
+            "unknown safety threshold `{}` (expected one of {})",
+            value.trim(),
+            HARM_THRESHOLDS.join(", ")
+        ))
+    }
+}
+
+impl FromStr for SafetySetting {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (category, threshold) = value
+            .split_once('=')
+            .ok_or_else(|| format!("safety setting `{value}` must be CATEGORY=THRESHOLD"))?;
+        let upper = category.trim().to_ascii_uppercase().replace('-', "_");
+        let short = upper.strip_prefix("HARM_CATEGORY_").unwrap_or(&upper);
+        if !HARM_CATEGORIES.contains(&short) {
+            return Err(format!(
+                "unknown harm category `{}` (expected one of {}, or `all`)",
+                category.trim(),
+                HARM_CATEGORIES.join(", ").to_ascii_lowercase()
+            ));
+        }
+        Ok(Self {
+            category: format!("HARM_CATEGORY_{short}"),
+            threshold: normalize_threshold(threshold)?,
+        })
+    }
+}
+
+impl fmt::Display for SafetySetting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.category, self.threshold)
+    }
+}
+
+/// Environment-driven configuration required for LLM adapters.
+#[derive(Debug, Clone, PartialEq)]
 pub struct LlmSettings {
     pub provider: String,
     pub api_key: String,
@@ -11,9 +186,66 @@ pub struct LlmSettings {
     pub deployment: Option<String>,
     pub project: Option<String>,
     pub workspace: Option<String>,
+    /// Cloud region for region-scoped providers (the AWS region for Bedrock, the location for
+    /// Vertex AI).
+    pub region: Option<String>,
     pub timeout_secs: Option<u64>,
     pub max_retries: u32,
     pub api_version: Option<String>,
+    /// Retry backoff tuning for the HTTP providers; `None` keeps the [`super::RetryPolicy`]
+    /// default.
+    pub retry_base_delay_ms: Option<u64>,
+    pub retry_max_delay_ms: Option<u64>,
+    pub retry_jitter: Option<bool>,
+    /// HTTP statuses worth retrying; transport errors are always retried.
+    pub retry_on_status: Option<Vec<u16>>,
+    /// TOML file overriding the verdict prompts (see [`super::PromptTemplate`]).
+    pub prompt_template: Option<PathBuf>,
+    /// Labels the model may answer with; `None` keeps `safe`/`suspicious`/`malicious`.
+    pub verdict_labels: Option<LabelSet>,
+    /// Request verdicts through tool/function calling with a strict JSON schema. `None` lets
+    /// the OpenAI and Anthropic clients use it while `build_client` keeps those providers on rig;
+    /// `Some(true)` also routes them to the standalone clients.
+    pub structured_output: Option<bool>,
+    /// Stream the verdict over server-sent events and stop reading once its JSON object is
+    /// complete (OpenAI, Anthropic, xAI). `Some(true)` also routes OpenAI and Anthropic to the
+    /// standalone clients.
+    pub stream: Option<bool>,
+    /// Abort a streamed verdict when no event arrives for this long (default 10 seconds).
+    pub stream_idle_timeout_secs: Option<u64>,
+    /// Outbound HTTP(S) proxy URL, optionally with `user:password@`. Without it the standard
+    /// `HTTPS_PROXY`/`HTTP_PROXY` variables still apply.
+    pub proxy: Option<String>,
+    /// PEM file with extra root certificates, e.g. a TLS-intercepting proxy's CA.
+    pub ca_bundle: Option<PathBuf>,
+    /// PEM client certificate for mTLS gateways. It may also contain the private key.
+    pub client_cert: Option<PathBuf>,
+    /// PEM private key for `client_cert` when it is kept in a separate file.
+    pub client_key: Option<PathBuf>,
+    /// Azure OpenAI authentication; `None` means [`AzureAuth::ApiKey`]. With
+    /// [`AzureAuth::Entra`] no API key is needed.
+    pub azure_auth: Option<AzureAuth>,
+    /// Replace PII and secrets in the prompt with typed placeholders (see
+    /// [`super::scrub_pii`]); `None` sends the input as scanned.
+    pub scrub_pii: Option<bool>,
+    /// Mark the static system prompt as cacheable for providers with prompt caching
+    /// (Anthropic); `None` means enabled in the standalone client, and `Some(true)` selects it
+    /// over rig.
+    pub prompt_cache: Option<bool>,
+    /// OpenAI endpoint for the `openai` provider; `None` means [`OpenAiApi::ChatCompletions`].
+    pub openai_api: Option<OpenAiApi>,
+    /// Sampling temperature (0–2); `None` means [`DEFAULT_TEMPERATURE`], or the model's own
+    /// default where the client never sent one (Anthropic, Gemini, Vertex AI). Ignored for
+    /// reasoning models, which reject it, and by the greedy `local` provider.
+    pub temperature: Option<f32>,
+    /// Cap on generated tokens; `None` means [`DEFAULT_MAX_TOKENS`] (a larger budget for
+    /// reasoning models, 256 for `local`, the model's default for Gemini and Vertex AI). Raise
+    /// it when verbose rationales are cut off mid-JSON.
+    pub max_tokens: Option<u32>,
+    /// Gemini/Vertex AI `responseMimeType`; `None` means `application/json`.
+    pub response_mime_type: Option<String>,
+    /// Gemini/Vertex AI `safetySettings`; `None` keeps the model's default filters.
+    pub safety_settings: Option<Vec<SafetySetting>>,
 }
 
 impl LlmSettings {
@@ -24,9 +256,31 @@ impl LlmSettings {
     const DEPLOYMENT_ENV: &'static str = "LLM_GUARD_DEPLOYMENT";
     const PROJECT_ENV: &'static str = "LLM_GUARD_PROJECT";
     const WORKSPACE_ENV: &'static str = "LLM_GUARD_WORKSPACE";
+    const REGION_ENV: &'static str = "LLM_GUARD_REGION";
     const TIMEOUT_ENV: &'static str = "LLM_GUARD_TIMEOUT_SECS";
     const RETRIES_ENV: &'static str = "LLM_GUARD_MAX_RETRIES";
     const API_VERSION_ENV: &'static str = "LLM_GUARD_API_VERSION";
+    const RETRY_BASE_DELAY_ENV: &'static str = "LLM_GUARD_RETRY_BASE_DELAY_MS";
+    const RETRY_MAX_DELAY_ENV: &'static str = "LLM_GUARD_RETRY_MAX_DELAY_MS";
+    const RETRY_JITTER_ENV: &'static str = "LLM_GUARD_RETRY_JITTER";
+    const RETRY_ON_STATUS_ENV: &'static str = "LLM_GUARD_RETRY_ON_STATUS";
+    const PROMPT_TEMPLATE_ENV: &'static str = "LLM_GUARD_PROMPT_TEMPLATE";
+    const VERDICT_LABELS_ENV: &'static str = "LLM_GUARD_VERDICT_LABELS";
+    const STRUCTURED_OUTPUT_ENV: &'static str = "LLM_GUARD_STRUCTURED_OUTPUT";
+    const STREAM_ENV: &'static str = "LLM_GUARD_STREAM";
+    const STREAM_IDLE_TIMEOUT_ENV: &'static str = "LLM_GUARD_STREAM_IDLE_TIMEOUT_SECS";
+    const PROXY_ENV: &'static str = "LLM_GUARD_PROXY";
+    const CA_BUNDLE_ENV: &'static str = "LLM_GUARD_CA_BUNDLE";
+    const CLIENT_CERT_ENV: &'static str = "LLM_GUARD_CLIENT_CERT";
+    const CLIENT_KEY_ENV: &'static str = "LLM_GUARD_CLIENT_KEY";
+    const AZURE_AUTH_ENV: &'static str = "LLM_GUARD_AZURE_AUTH";
+    const SCRUB_PII_ENV: &'static str = "LLM_GUARD_SCRUB_PII";
+    const PROMPT_CACHE_ENV: &'static str = "LLM_GUARD_PROMPT_CACHE";
+    const OPENAI_API_ENV: &'static str = "LLM_GUARD_OPENAI_API";
+    const TEMPERATURE_ENV: &'static str = "LLM_GUARD_TEMPERATURE";
+    const MAX_TOKENS_ENV: &'static str = "LLM_GUARD_MAX_TOKENS";
+    const RESPONSE_MIME_TYPE_ENV: &'static str = "LLM_GUARD_RESPONSE_MIME_TYPE";
+    const SAFETY_SETTINGS_ENV: &'static str = "LLM_GUARD_SAFETY_SETTINGS";
 
     pub fn provider_kind(&self) -> Result<super::ProviderKind> {
         super::ProviderKind::from_provider(&self.provider)
@@ -35,10 +289,78 @@ impl LlmSettings {
     /// Load settings from environment variables.
     ///
     /// * `LLM_GUARD_PROVIDER` — Provider identifier (default: `openai`).
-    /// * `LLM_GUARD_API_KEY`  — API key/token (required).
+    /// * `LLM_GUARD_API_KEY`  — API key/token (required except for `noop`, `vertex`, which falls
+    ///   back to Application Default Credentials, and `bedrock`, which falls back to `AWS_*`).
+    ///   When unset, a key stored with [`super::store_api_key`] is used (`keyring` feature).
     /// * `LLM_GUARD_ENDPOINT` — Optional custom endpoint/base URL.
+    /// * `LLM_GUARD_REGION`   — Optional cloud region (Bedrock) or location (Vertex AI).
+    /// * `LLM_GUARD_RETRY_*`  — Optional backoff tuning: `BASE_DELAY_MS`, `MAX_DELAY_MS`,
+    ///   `JITTER` (`true`/`false`) and `ON_STATUS` (comma-separated HTTP status codes).
+    /// * `LLM_GUARD_PROMPT_TEMPLATE` — Optional TOML file overriding the verdict prompts.
+    /// * `LLM_GUARD_VERDICT_LABELS` — Optional label set, least to most severe: comma-separated
+    ///   names with optional `=<score adjustment>`, or a JSON array (see [`LabelSet`]).
+    /// * `LLM_GUARD_STRUCTURED_OUTPUT` — Optional `true`/`false`: request verdicts via tool
+    ///   calling (OpenAI, Anthropic).
+    /// * `LLM_GUARD_STREAM` — Optional `true`/`false`: stream verdicts (OpenAI, Anthropic, xAI).
+    /// * `LLM_GUARD_STREAM_IDLE_TIMEOUT_SECS` — Optional stall timeout for streamed verdicts.
+    /// * `LLM_GUARD_PROXY`    — Optional outbound proxy URL for provider requests.
+    /// * `LLM_GUARD_CA_BUNDLE` — Optional PEM file of additional trusted root certificates.
+    /// * `LLM_GUARD_CLIENT_CERT`/`LLM_GUARD_CLIENT_KEY` — Optional PEM client certificate and
+    ///   key for mTLS; the key may be bundled into the certificate file instead.
+    /// * `LLM_GUARD_AZURE_AUTH` — Optional `api-key` (default) or `entra`: authenticate to Azure
+    ///   OpenAI with Microsoft Entra ID tokens instead of a key.
+    /// * `LLM_GUARD_SCRUB_PII` — Optional `true`/`false`: redact PII and secrets from the prompt.
+    /// * `LLM_GUARD_PROMPT_CACHE` — Optional `true`/`false` (default `true`): cache the system
+    ///   prompt with providers that support it.
+    /// * `LLM_GUARD_OPENAI_API` — Optional `chat-completions` (default) or `responses`: the
+    ///   OpenAI endpoint used by the `openai` provider.
+    /// * `LLM_GUARD_TEMPERATURE` / `LLM_GUARD_MAX_TOKENS` — Optional sampling temperature (0–2)
+    ///   and output token cap.
+    /// * `LLM_GUARD_RESPONSE_MIME_TYPE` — Optional Gemini `responseMimeType`.
+    /// * `LLM_GUARD_SAFETY_SETTINGS` — Optional Gemini safety settings, e.g.
+    ///   `dangerous_content=block_only_high,harassment=block_none` (see [`SafetySetting`]).
     pub fn from_env() -> Result<Self> {
-        Self::from_map(std::env::vars().collect())
+        let mut vars: HashMap<String, String> = std::env::vars().collect();
+        if let Some(key) = Self::keyring_fallback(&vars) {
+            vars.insert(Self::API_KEY_ENV.to_string(), key);
+        }
+        Self::from_map(vars)
+    }
+
+    /// The keyring entry for the selected provider when the environment has no key. Lookup
+    /// failures (e.g. no Secret Service on a headless host) are treated as a missing entry.
+    fn keyring_fallback(vars: &HashMap<String, String>) -> Option<String> {
+        if vars
+            .get(Self::API_KEY_ENV)
+            .is_some_and(|key| !key.trim().is_empty())
+        {
+            return None;
+        }
+        let provider = vars
+            .get(Self::PROVIDER_ENV)
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .unwrap_or("openai");
+        let needs_key = super::ProviderKind::from_provider(provider)
+            .map(|kind| kind.requires_api_key())
+            .unwrap_or(false);
+        if !needs_key || Self::entra_selected(vars) {
+            return None;
+        }
+        match super::credentials::load_api_key(provider) {
+            Ok(key) => key,
+            Err(err) => {
+                tracing::debug!("keyring lookup for `{provider}` failed: {err:#}");
+                None
+            }
+        }
+    }
+
+    /// Whether `LLM_GUARD_AZURE_AUTH` asks for Entra ID tokens, which replace the API key.
+    fn entra_selected(vars: &HashMap<String, String>) -> bool {
+        vars.get(Self::AZURE_AUTH_ENV)
+            .and_then(|v| v.parse::<AzureAuth>().ok())
+            == Some(AzureAuth::Entra)
     }
 
     fn from_map(vars: HashMap<String, String>) -> Result<Self> {
@@ -54,11 +376,47 @@ impl LlmSettings {
             .filter(|v| !v.is_empty())
             .map(|v| v.to_string())
             .unwrap_or_else(|| "openai".to_string());
-        let provider_lower = provider.to_lowercase();
-        let api_key = match provider_lower.as_str() {
-            "noop" => get_trimmed(Self::API_KEY_ENV).unwrap_or_default(),
-            _ => vars
-                .get(Self::API_KEY_ENV)
+        let azure_auth = get_trimmed(Self::AZURE_AUTH_ENV)
+            .map(|v| {
+                v.parse::<AzureAuth>()
+                    .map_err(|err| anyhow!("{} is invalid: {err}", Self::AZURE_AUTH_ENV))
+            })
+            .transpose()?;
+        let temperature = get_trimmed(Self::TEMPERATURE_ENV)
+            .map(|v| {
+                v.parse::<f32>()
+                    .ok()
+                    .filter(|t| (0.0..=2.0).contains(t))
+                    .with_context(|| {
+                        format!("{} must be a number from 0 to 2", Self::TEMPERATURE_ENV)
+                    })
+            })
+            .transpose()?;
+        let max_tokens = get_trimmed(Self::MAX_TOKENS_ENV)
+            .map(|v| {
+                v.parse::<u32>()
+                    .ok()
+                    .filter(|n| *n > 0)
+                    .with_context(|| format!("{} must be a positive number", Self::MAX_TOKENS_ENV))
+            })
+            .transpose()?;
+        let response_mime_type = get_trimmed(Self::RESPONSE_MIME_TYPE_ENV);
+        let safety_settings = get_trimmed(Self::SAFETY_SETTINGS_ENV)
+            .map(|v| {
+                SafetySetting::parse_list(&v)
+                    .map_err(|err| anyhow!("{} is invalid: {err}", Self::SAFETY_SETTINGS_ENV))
+            })
+            .transpose()?;
+        // Registered providers check their own credentials; unknown providers still require a
+        // key and `build_client` reports them later.
+        let key_required = match super::ProviderKind::from_provider(&provider) {
+            _ if super::registry::lookup(&provider).is_some() => false,
+            Ok(super::ProviderKind::Azure) => azure_auth != Some(AzureAuth::Entra),
+            Ok(kind) => kind.requires_api_key(),
+            Err(_) => true,
+        };
+        let api_key = if key_required {
+            vars.get(Self::API_KEY_ENV)
                 .map(|v| v.trim())
                 .filter(|v| !v.is_empty())
                 .map(|v| v.to_string())
@@ -67,13 +425,16 @@ impl LlmSettings {
                         "environment variable {} must be set when --with-llm is used",
                         Self::API_KEY_ENV
                     )
-                })?,
+                })?
+        } else {
+            get_trimmed(Self::API_KEY_ENV).unwrap_or_default()
         };
         let endpoint = get_trimmed(Self::ENDPOINT_ENV);
         let model = get_trimmed(Self::MODEL_ENV);
         let deployment = get_trimmed(Self::DEPLOYMENT_ENV);
         let project = get_trimmed(Self::PROJECT_ENV);
         let workspace = get_trimmed(Self::WORKSPACE_ENV);
+        let region = get_trimmed(Self::REGION_ENV);
         let timeout_secs = vars
             .get(Self::TIMEOUT_ENV)
             .and_then(|v| v.trim().parse::<u64>().ok());
@@ -82,6 +443,86 @@ impl LlmSettings {
             .and_then(|v| v.trim().parse::<u32>().ok())
             .unwrap_or(2);
         let api_version = get_trimmed(Self::API_VERSION_ENV);
+        let parse_millis = |key: &str| -> Result<Option<u64>> {
+            get_trimmed(key)
+                .map(|v| {
+                    v.parse::<u64>()
+                        .with_context(|| format!("{key} must be a number of milliseconds"))
+                })
+                .transpose()
+        };
+        let retry_base_delay_ms = parse_millis(Self::RETRY_BASE_DELAY_ENV)?;
+        let retry_max_delay_ms = parse_millis(Self::RETRY_MAX_DELAY_ENV)?;
+        let parse_bool = |key: &str| -> Result<Option<bool>> {
+            get_trimmed(key)
+                .map(|v| match v.to_ascii_lowercase().as_str() {
+                    "1" | "true" | "yes" | "on" => Ok(true),
+                    "0" | "false" | "no" | "off" => Ok(false),
+                    _ => bail!("{key} must be true or false"),
+                })
+                .transpose()
+        };
+        let retry_jitter = parse_bool(Self::RETRY_JITTER_ENV)?;
+        let retry_on_status = get_trimmed(Self::RETRY_ON_STATUS_ENV)
+            .map(|v| {
+                v.split(',')
+                    .map(str::trim)
+                    .filter(|code| !code.is_empty())
+                    .map(|code| {
+                        code.parse::<u16>()
+                            .ok()
+                            .filter(|code| (100..600).contains(code))
+                            .with_context(|| {
+                                format!(
+                                    "{} has invalid HTTP status `{code}`",
+                                    Self::RETRY_ON_STATUS_ENV
+                                )
+                            })
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .transpose()?;
+        let prompt_template = get_trimmed(Self::PROMPT_TEMPLATE_ENV).map(PathBuf::from);
+        let verdict_labels = get_trimmed(Self::VERDICT_LABELS_ENV)
+            .map(|v| {
+                v.parse::<LabelSet>()
+                    .map_err(|err| anyhow!("{} is invalid: {err}", Self::VERDICT_LABELS_ENV))
+            })
+            .transpose()?;
+        let structured_output = parse_bool(Self::STRUCTURED_OUTPUT_ENV)?;
+        let stream = parse_bool(Self::STREAM_ENV)?;
+        let scrub_pii = parse_bool(Self::SCRUB_PII_ENV)?;
+        let prompt_cache = parse_bool(Self::PROMPT_CACHE_ENV)?;
+        let openai_api = get_trimmed(Self::OPENAI_API_ENV)
+            .map(|v| {
+                v.parse::<OpenAiApi>()
+                    .map_err(|err| anyhow!("{} is invalid: {err}", Self::OPENAI_API_ENV))
+            })
+            .transpose()?;
+        let stream_idle_timeout_secs = get_trimmed(Self::STREAM_IDLE_TIMEOUT_ENV)
+            .map(|v| {
+                v.parse::<u64>()
+                    .ok()
+                    .filter(|secs| *secs > 0)
+                    .with_context(|| {
+                        format!(
+                            "{} must be a positive number of seconds",
+                            Self::STREAM_IDLE_TIMEOUT_ENV
+                        )
+                    })
+            })
+            .transpose()?;
+        let proxy = get_trimmed(Self::PROXY_ENV);
+        let ca_bundle = get_trimmed(Self::CA_BUNDLE_ENV).map(PathBuf::from);
+        let client_cert = get_trimmed(Self::CLIENT_CERT_ENV).map(PathBuf::from);
+        let client_key = get_trimmed(Self::CLIENT_KEY_ENV).map(PathBuf::from);
+        if client_key.is_some() && client_cert.is_none() {
+            bail!(
+                "{} requires {} to be set",
+                Self::CLIENT_KEY_ENV,
+                Self::CLIENT_CERT_ENV
+            );
+        }
 
         Ok(Self {
             provider,
@@ -91,9 +532,31 @@ impl LlmSettings {
             deployment,
             project,
             workspace,
+            region,
             timeout_secs,
             max_retries,
             api_version,
+            retry_base_delay_ms,
+            retry_max_delay_ms,
+            retry_jitter,
+            retry_on_status,
+            prompt_template,
+            verdict_labels,
+            structured_output,
+            stream,
+            stream_idle_timeout_secs,
+            proxy,
+            ca_bundle,
+            client_cert,
+            client_key,
+            azure_auth,
+            scrub_pii,
+            prompt_cache,
+            openai_api,
+            temperature,
+            max_tokens,
+            response_mime_type,
+            safety_settings,
         })
     }
 }
@@ -195,6 +658,318 @@ mod tests {
         });
     }
 
+    #[test]
+    fn bedrock_reads_region_without_api_key() {
+        let mut vars = HashMap::new();
+        vars.insert(LlmSettings::PROVIDER_ENV.to_string(), "bedrock".to_string());
+        vars.insert(
+            LlmSettings::REGION_ENV.to_string(),
+            " eu-central-1 ".to_string(),
+        );
+        let settings = LlmSettings::from_map(vars).expect("bedrock may use AWS_* credentials");
+        assert!(settings.api_key.is_empty());
+        assert_eq!(settings.region.as_deref(), Some("eu-central-1"));
+    }
+
+    #[test]
+    fn parses_retry_tuning() {
+        let mut vars = HashMap::new();
+        vars.insert(LlmSettings::PROVIDER_ENV.to_string(), "noop".to_string());
+        vars.insert(
+            LlmSettings::RETRY_BASE_DELAY_ENV.to_string(),
+            "500".to_string(),
+        );
+        vars.insert(
+            LlmSettings::RETRY_MAX_DELAY_ENV.to_string(),
+            "30000".to_string(),
+        );
+        vars.insert(LlmSettings::RETRY_JITTER_ENV.to_string(), "off".to_string());
+        vars.insert(
+            LlmSettings::RETRY_ON_STATUS_ENV.to_string(),
+            "429, 503,".to_string(),
+        );
+        let settings = LlmSettings::from_map(vars.clone()).expect("retry tuning should parse");
+        assert_eq!(settings.retry_base_delay_ms, Some(500));
+        assert_eq!(settings.retry_max_delay_ms, Some(30_000));
+        assert_eq!(settings.retry_jitter, Some(false));
+        assert_eq!(settings.retry_on_status, Some(vec![429, 503]));
+
+        vars.insert(
+            LlmSettings::RETRY_ON_STATUS_ENV.to_string(),
+            "429,teapot".to_string(),
+        );
+        let err = LlmSettings::from_map(vars).expect_err("bad status should error");
+        assert!(err.to_string().contains("teapot"));
+    }
+
+    #[test]
+    fn parses_verdict_labels() {
+        let mut vars = HashMap::new();
+        vars.insert(LlmSettings::PROVIDER_ENV.to_string(), "noop".to_string());
+        let settings = LlmSettings::from_map(vars.clone()).unwrap();
+        assert!(settings.verdict_labels.is_none());
+
+        vars.insert(
+            LlmSettings::VERDICT_LABELS_ENV.to_string(),
+            "allow,review=10,block=30".to_string(),
+        );
+        let labels = LlmSettings::from_map(vars.clone())
+            .unwrap()
+            .verdict_labels
+            .expect("labels should parse");
+        assert_eq!(labels.choices(), "allow|review|block");
+        assert_eq!(labels.score_adjustment("block"), Some(30.0));
+
+        vars.insert(
+            LlmSettings::VERDICT_LABELS_ENV.to_string(),
+            "allow,allow".to_string(),
+        );
+        let err = LlmSettings::from_map(vars).expect_err("duplicate labels should error");
+        assert!(err.to_string().contains(LlmSettings::VERDICT_LABELS_ENV));
+    }
+
+    #[test]
+    fn parses_structured_output_flag() {
+        let mut vars = HashMap::new();
+        vars.insert(LlmSettings::PROVIDER_ENV.to_string(), "noop".to_string());
+        assert_eq!(
+            LlmSettings::from_map(vars.clone())
+                .unwrap()
+                .structured_output,
+            None
+        );
+        vars.insert(
+            LlmSettings::STRUCTURED_OUTPUT_ENV.to_string(),
+            "yes".to_string(),
+        );
+        assert_eq!(
+            LlmSettings::from_map(vars.clone())
+                .unwrap()
+                .structured_output,
+            Some(true)
+        );
+        vars.insert(
+            LlmSettings::STRUCTURED_OUTPUT_ENV.to_string(),
+            "sometimes".to_string(),
+        );
+        let err = LlmSettings::from_map(vars).unwrap_err();
+        assert!(err.to_string().contains(LlmSettings::STRUCTURED_OUTPUT_ENV));
+    }
+
+    #[test]
+    fn parses_scrub_pii_flag() {
+        let mut vars = HashMap::new();
+        vars.insert(LlmSettings::PROVIDER_ENV.to_string(), "noop".to_string());
+        assert_eq!(LlmSettings::from_map(vars.clone()).unwrap().scrub_pii, None);
+        vars.insert(LlmSettings::SCRUB_PII_ENV.to_string(), "yes".to_string());
+        assert_eq!(
+            LlmSettings::from_map(vars.clone()).unwrap().scrub_pii,
+            Some(true)
+        );
+        vars.insert(LlmSettings::SCRUB_PII_ENV.to_string(), "maybe".to_string());
+        assert!(LlmSettings::from_map(vars).is_err());
+    }
+
+    #[test]
+    fn parses_openai_api() {
+        let mut vars = HashMap::new();
+        vars.insert(LlmSettings::PROVIDER_ENV.to_string(), "noop".to_string());
+        assert_eq!(
+            LlmSettings::from_map(vars.clone()).unwrap().openai_api,
+            None
+        );
+        vars.insert(
+            LlmSettings::OPENAI_API_ENV.to_string(),
+            " Responses ".to_string(),
+        );
+        assert_eq!(
+            LlmSettings::from_map(vars.clone()).unwrap().openai_api,
+            Some(OpenAiApi::Responses)
+        );
+        vars.insert(
+            LlmSettings::OPENAI_API_ENV.to_string(),
+            "assistants".to_string(),
+        );
+        let err = LlmSettings::from_map(vars).unwrap_err();
+        assert!(err.to_string().contains(LlmSettings::OPENAI_API_ENV));
+        assert_eq!(OpenAiApi::Responses.to_string(), "responses");
+    }
+
+    #[test]
+    fn parses_generation_settings() {
+        let mut vars = HashMap::new();
+        vars.insert(LlmSettings::PROVIDER_ENV.to_string(), "noop".to_string());
+        vars.insert(LlmSettings::TEMPERATURE_ENV.to_string(), "0.4".to_string());
+        vars.insert(LlmSettings::MAX_TOKENS_ENV.to_string(), "512".to_string());
+        vars.insert(
+            LlmSettings::SAFETY_SETTINGS_ENV.to_string(),
+            "all=block_only_high, dangerous-content=BLOCK_NONE".to_string(),
+        );
+        let settings = LlmSettings::from_map(vars.clone()).unwrap();
+        assert_eq!(settings.temperature, Some(0.4));
+        assert_eq!(settings.max_tokens, Some(512));
+        let safety = settings.safety_settings.unwrap();
+        assert_eq!(safety.len(), 5);
+        assert_eq!(
+            safety.last().unwrap().to_string(),
+            "HARM_CATEGORY_DANGEROUS_CONTENT=BLOCK_NONE"
+        );
+        assert!(safety
+            .iter()
+            .filter(|s| s.category != "HARM_CATEGORY_DANGEROUS_CONTENT")
+            .all(|s| s.threshold == "BLOCK_ONLY_HIGH"));
+
+        vars.insert(LlmSettings::TEMPERATURE_ENV.to_string(), "3".to_string());
+        assert!(LlmSettings::from_map(vars.clone()).is_err());
+        vars.remove(LlmSettings::TEMPERATURE_ENV);
+        vars.insert(LlmSettings::MAX_TOKENS_ENV.to_string(), "0".to_string());
+        assert!(LlmSettings::from_map(vars.clone()).is_err());
+        vars.remove(LlmSettings::MAX_TOKENS_ENV);
+        vars.insert(
+            LlmSettings::SAFETY_SETTINGS_ENV.to_string(),
+            "violence=block_none".to_string(),
+        );
+        let err = LlmSettings::from_map(vars).unwrap_err();
+        assert!(err.to_string().contains("unknown harm category"));
+    }
+
+    #[test]
+    fn parses_prompt_cache_flag() {
+        let mut vars = HashMap::new();
+        vars.insert(LlmSettings::PROVIDER_ENV.to_string(), "noop".to_string());
+        assert_eq!(
+            LlmSettings::from_map(vars.clone()).unwrap().prompt_cache,
+            None
+        );
+        vars.insert(
+            LlmSettings::PROMPT_CACHE_ENV.to_string(),
+            "false".to_string(),
+        );
+        assert_eq!(
+            LlmSettings::from_map(vars).unwrap().prompt_cache,
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn registered_providers_need_no_api_key() {
+        use crate::llm::{register_provider, unregister_provider, LlmClient, NoopLlmClient};
+
+        let mut vars = HashMap::new();
+        vars.insert(
+            LlmSettings::PROVIDER_ENV.to_string(),
+            "settingstestgateway".to_string(),
+        );
+        assert!(LlmSettings::from_map(vars.clone()).is_err());
+        register_provider("settingstestgateway", |_: &LlmSettings| {
+            Ok(Box::new(NoopLlmClient) as Box<dyn LlmClient>)
+        })
+        .unwrap();
+        assert_eq!(LlmSettings::from_map(vars).unwrap().api_key, "");
+        unregister_provider("settingstestgateway");
+    }
+
+    #[test]
+    fn parses_stream_settings() {
+        let mut vars = HashMap::new();
+        vars.insert(LlmSettings::PROVIDER_ENV.to_string(), "noop".to_string());
+        let settings = LlmSettings::from_map(vars.clone()).unwrap();
+        assert_eq!(settings.stream, None);
+        assert_eq!(settings.stream_idle_timeout_secs, None);
+
+        vars.insert(LlmSettings::STREAM_ENV.to_string(), "on".to_string());
+        vars.insert(
+            LlmSettings::STREAM_IDLE_TIMEOUT_ENV.to_string(),
+            " 4 ".to_string(),
+        );
+        let settings = LlmSettings::from_map(vars.clone()).unwrap();
+        assert_eq!(settings.stream, Some(true));
+        assert_eq!(settings.stream_idle_timeout_secs, Some(4));
+
+        vars.insert(
+            LlmSettings::STREAM_IDLE_TIMEOUT_ENV.to_string(),
+            "0".to_string(),
+        );
+        let err = LlmSettings::from_map(vars).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains(LlmSettings::STREAM_IDLE_TIMEOUT_ENV));
+    }
+
+    #[test]
+    fn keyring_fallback_skips_present_keys_and_keyless_providers() {
+        let mut vars = HashMap::new();
+        vars.insert(LlmSettings::PROVIDER_ENV.to_string(), "noop".to_string());
+        assert_eq!(LlmSettings::keyring_fallback(&vars), None);
+
+        vars.insert(LlmSettings::PROVIDER_ENV.to_string(), "openai".to_string());
+        vars.insert(LlmSettings::API_KEY_ENV.to_string(), "sk-env".to_string());
+        assert_eq!(LlmSettings::keyring_fallback(&vars), None);
+    }
+
+    #[test]
+    fn azure_entra_auth_needs_no_api_key() {
+        let mut vars = HashMap::new();
+        vars.insert(LlmSettings::PROVIDER_ENV.to_string(), "azure".to_string());
+        assert!(LlmSettings::from_map(vars.clone()).is_err());
+
+        vars.insert(LlmSettings::AZURE_AUTH_ENV.to_string(), "Entra".to_string());
+        assert_eq!(LlmSettings::keyring_fallback(&vars), None);
+        let settings = LlmSettings::from_map(vars.clone()).unwrap();
+        assert_eq!(settings.azure_auth, Some(AzureAuth::Entra));
+        assert!(settings.api_key.is_empty());
+
+        vars.insert(LlmSettings::AZURE_AUTH_ENV.to_string(), "token".to_string());
+        let err = LlmSettings::from_map(vars).unwrap_err();
+        assert!(err.to_string().contains(LlmSettings::AZURE_AUTH_ENV));
+    }
+
+    #[test]
+    fn parses_proxy_and_ca_bundle() {
+        let mut vars = HashMap::new();
+        vars.insert(LlmSettings::PROVIDER_ENV.to_string(), "noop".to_string());
+        vars.insert(
+            LlmSettings::PROXY_ENV.to_string(),
+            " http://proxy.corp:3128 ".to_string(),
+        );
+        vars.insert(
+            LlmSettings::CA_BUNDLE_ENV.to_string(),
+            "/etc/ssl/corp-root.pem".to_string(),
+        );
+        let settings = LlmSettings::from_map(vars).unwrap();
+        assert_eq!(settings.proxy.as_deref(), Some("http://proxy.corp:3128"));
+        assert_eq!(
+            settings.ca_bundle,
+            Some(PathBuf::from("/etc/ssl/corp-root.pem"))
+        );
+    }
+
+    #[test]
+    fn client_key_requires_client_cert() {
+        let mut vars = HashMap::new();
+        vars.insert(LlmSettings::PROVIDER_ENV.to_string(), "noop".to_string());
+        vars.insert(
+            LlmSettings::CLIENT_KEY_ENV.to_string(),
+            "/etc/llm-guard/client.key".to_string(),
+        );
+        let err = LlmSettings::from_map(vars.clone()).unwrap_err();
+        assert!(err.to_string().contains(LlmSettings::CLIENT_CERT_ENV));
+
+        vars.insert(
+            LlmSettings::CLIENT_CERT_ENV.to_string(),
+            "/etc/llm-guard/client.pem".to_string(),
+        );
+        let settings = LlmSettings::from_map(vars).unwrap();
+        assert_eq!(
+            settings.client_cert,
+            Some(PathBuf::from("/etc/llm-guard/client.pem"))
+        );
+        assert_eq!(
+            settings.client_key,
+            Some(PathBuf::from("/etc/llm-guard/client.key"))
+        );
+    }
+
     fn trimmed_string() -> impl Strategy<Value = String> {
         proptest::string::string_regex("[A-Za-z0-9 _\\-]{1,24}").unwrap()
     }
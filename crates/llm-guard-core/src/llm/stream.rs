@@ -0,0 +1,308 @@
+//! Incremental verdict reading for providers that stream server-sent events.
+//!
+//! The model's reply arrives as text deltas. [`read_verdict`] feeds them into a
+//! [`JsonObjectAssembler`] and returns as soon as the first top-level JSON object closes, then
+//! drops the response so the provider stops generating.
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::time::Duration;
+
+/// Stall timeout used when `LLM_GUARD_STREAM_IDLE_TIMEOUT_SECS` is unset.
+pub(crate) const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Read an SSE response until the verdict JSON object is complete.
+///
+/// `delta` maps each event's `data` payload to the text it contributes, if any. The read
+/// fails when no bytes arrive for `idle`, or when the stream ends before the object closes.
+pub(crate) async fn read_verdict<F>(
+    mut response: reqwest::Response,
+    api: &str,
+    idle: Duration,
+    mut delta: F,
+) -> Result<String>
+where
+    F: FnMut(&str) -> Result<Option<String>>,
+{
+    let mut events = SseDecoder::default();
+    let mut json = JsonObjectAssembler::default();
+    loop {
+        let chunk = tokio::time::timeout(idle, response.chunk())
+            .await
+            .map_err(|_| {
+                anyhow!(
+                    "{api} stream stalled for {}s before the verdict was complete",
+                    idle.as_secs()
+                )
+            })?
+            .with_context(|| format!("failed to read {api} stream"))?;
+        let Some(chunk) = chunk else {
+            bail!("{api} stream ended before the verdict JSON was complete");
+        };
+        for data in events.push(&chunk) {
+            if data == "[DONE]" {
+                bail!("{api} stream ended before the verdict JSON was complete");
+            }
+            if let Some(text) = delta(&data)? {
+                if let Some(object) = json.push(&text) {
+                    return Ok(object.to_string());
+                }
+            }
+        }
+    }
+}
+
+/// Text delta of an OpenAI-compatible `chat.completion.chunk` event: function-call argument
+/// fragments when `tool_call` is set, message content otherwise.
+pub(crate) fn chat_completion_delta(data: &str, tool_call: bool) -> Result<Option<String>> {
+    let event: serde_json::Value =
+        serde_json::from_str(data).context("invalid chat completion stream event")?;
+    if let Some(message) = event.pointer("/error/message").and_then(|m| m.as_str()) {
+        bail!("stream error: {message}");
+    }
+    let delta = event.pointer("/choices/0/delta");
+    let text = if tool_call {
+        delta.and_then(|d| d.pointer("/tool_calls/0/function/arguments"))
+    } else {
+        delta.and_then(|d| d.get("content"))
+    };
+    Ok(text.and_then(|t| t.as_str()).map(str::to_string))
+}
+
+/// Text delta of an OpenAI Responses stream event (`response.output_text.delta`); structured
+/// output arrives through the same event.
+pub(crate) fn responses_delta(data: &str) -> Result<Option<String>> {
+    let event: serde_json::Value =
+        serde_json::from_str(data).context("invalid Responses stream event")?;
+    match event.get("type").and_then(|t| t.as_str()) {
+        Some("error") | Some("response.failed") => {
+            let message = event
+                .get("message")
+                .or_else(|| event.pointer("/response/error/message"))
+                .and_then(|m| m.as_str())
+                .unwrap_or("unknown error");
+            bail!("stream error: {message}");
+        }
+        Some("response.output_text.delta") => Ok(event
+            .get("delta")
+            .and_then(|t| t.as_str())
+            .map(str::to_string)),
+        _ => Ok(None),
+    }
+}
+
+/// Text delta of an Anthropic Messages stream event: `input_json_delta` fragments when
+/// `tool_use` is set, `text_delta` text otherwise.
+pub(crate) fn anthropic_delta(data: &str, tool_use: bool) -> Result<Option<String>> {
+    let event: serde_json::Value =
+        serde_json::from_str(data).context("invalid Anthropic stream event")?;
+    match event.get("type").and_then(|t| t.as_str()) {
+        Some("error") => {
+            let message = event
+                .pointer("/error/message")
+                .and_then(|m| m.as_str())
+                .unwrap_or("unknown error");
+            bail!("stream error: {message}");
+        }
+        Some("content_block_delta") => {
+            let (kind, field) = if tool_use {
+                ("input_json_delta", "partial_json")
+            } else {
+                ("text_delta", "text")
+            };
+            let delta = event.get("delta");
+            if delta.and_then(|d| d.get("type")).and_then(|t| t.as_str()) != Some(kind) {
+                return Ok(None);
+            }
+            Ok(delta
+                .and_then(|d| d.get(field))
+                .and_then(|t| t.as_str())
+                .map(str::to_string))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Splits a byte stream into server-sent events and yields their `data` payloads.
+#[derive(Debug, Default)]
+pub(crate) struct SseDecoder {
+    pending: Vec<u8>,
+    data: Vec<String>,
+}
+
+impl SseDecoder {
+    /// Consume a chunk and return the payloads of the events it completed. Multi-line `data`
+    /// fields are joined with newlines; comments and other fields are ignored.
+    pub(crate) fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(chunk);
+        let mut completed = Vec::new();
+        // Lines end at `\n`, which never occurs inside a multi-byte UTF-8 sequence.
+        while let Some(end) = self.pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+            if line.is_empty() {
+                if !self.data.is_empty() {
+                    completed.push(self.data.join("\n"));
+                    self.data.clear();
+                }
+            } else if let Some(value) = line.strip_prefix("data:") {
+                self.data
+                    .push(value.strip_prefix(' ').unwrap_or(value).to_string());
+            }
+        }
+        completed
+    }
+}
+
+/// Accumulates streamed text until the first top-level JSON object is complete.
+///
+/// Text before the opening brace (a Markdown fence, a preamble) is skipped; braces inside
+/// string literals are ignored.
+#[derive(Debug, Default)]
+pub(crate) struct JsonObjectAssembler {
+    buffer: String,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    complete: bool,
+}
+
+impl JsonObjectAssembler {
+    /// Append `text`, returning the whole object once its closing brace arrives.
+    pub(crate) fn push(&mut self, text: &str) -> Option<&str> {
+        if self.complete {
+            return Some(&self.buffer);
+        }
+        for ch in text.chars() {
+            if self.depth == 0 {
+                if ch == '{' {
+                    self.depth = 1;
+                    self.buffer.push(ch);
+                }
+                continue;
+            }
+            self.buffer.push(ch);
+            if self.in_string {
+                match ch {
+                    _ if self.escaped => self.escaped = false,
+                    '\\' => self.escaped = true,
+                    '"' => self.in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match ch {
+                '"' => self.in_string = true,
+                '{' => self.depth += 1,
+                '}' => {
+                    self.depth -= 1;
+                    if self.depth == 0 {
+                        self.complete = true;
+                        return Some(&self.buffer);
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assembler_completes_on_closing_brace() {
+        let mut json = JsonObjectAssembler::default();
+        assert_eq!(json.push("```json\n{\"label\": \"sa"), None);
+        assert_eq!(
+            json.push("fe\", \"rationale\": \"a } in \\\"text\\\" {\""),
+            None
+        );
+        assert_eq!(json.push(", \"extra\": {\"n\": 1}"), None);
+        let object = json.push("}\n```").unwrap();
+        let value: serde_json::Value = serde_json::from_str(object).unwrap();
+        assert_eq!(value["label"], "safe");
+        assert_eq!(value["rationale"], "a } in \"text\" {");
+        assert_eq!(value["extra"]["n"], 1);
+    }
+
+    #[test]
+    fn decoder_handles_split_events_and_crlf() {
+        let mut events = SseDecoder::default();
+        assert!(events.push(b"event: delta\r\ndata: {\"a\":").is_empty());
+        assert_eq!(events.push(b"1}\r\n\r\n: ping\n\n"), vec!["{\"a\":1}"]);
+        assert_eq!(
+            events.push("data: first\ndata: sécond\n\ndata: [DONE]\n\n".as_bytes()),
+            vec!["first\nsécond", "[DONE]"]
+        );
+    }
+
+    #[test]
+    fn decoder_keeps_multibyte_characters_split_across_chunks() {
+        let mut events = SseDecoder::default();
+        let bytes = "data: é\n\n".as_bytes();
+        assert!(events.push(&bytes[..7]).is_empty());
+        assert_eq!(events.push(&bytes[7..]), vec!["é"]);
+    }
+
+    #[test]
+    fn extracts_chat_completion_deltas() {
+        let content = r#"{"choices":[{"delta":{"content":"{\"la"}}]}"#;
+        assert_eq!(
+            chat_completion_delta(content, false).unwrap().as_deref(),
+            Some("{\"la")
+        );
+        let arguments = r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\""}}]}}]}"#;
+        assert_eq!(
+            chat_completion_delta(arguments, true).unwrap().as_deref(),
+            Some("{\"")
+        );
+        assert_eq!(chat_completion_delta(arguments, false).unwrap(), None);
+        let err =
+            chat_completion_delta(r#"{"error":{"message":"overloaded"}}"#, false).unwrap_err();
+        assert!(err.to_string().contains("overloaded"));
+    }
+
+    #[test]
+    fn extracts_responses_deltas() {
+        let text = r#"{"type":"response.output_text.delta","item_id":"msg_1","output_index":0,"content_index":0,"delta":"{\"label\""}"#;
+        assert_eq!(
+            responses_delta(text).unwrap().as_deref(),
+            Some("{\"label\"")
+        );
+        let created = r#"{"type":"response.created","response":{"id":"resp_1"}}"#;
+        assert_eq!(responses_delta(created).unwrap(), None);
+        let failed = r#"{"type":"response.failed","response":{"error":{"code":"server_error","message":"overloaded"}}}"#;
+        assert!(responses_delta(failed)
+            .unwrap_err()
+            .to_string()
+            .contains("overloaded"));
+    }
+
+    #[test]
+    fn extracts_anthropic_deltas() {
+        let text = r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"{\"label\""}}"#;
+        let tool = r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"label\""}}"#;
+        assert_eq!(
+            anthropic_delta(text, false).unwrap().as_deref(),
+            Some("{\"label\"")
+        );
+        assert_eq!(anthropic_delta(text, true).unwrap(), None);
+        assert_eq!(
+            anthropic_delta(tool, true).unwrap().as_deref(),
+            Some("{\"label\"")
+        );
+        assert_eq!(
+            anthropic_delta(r#"{"type":"message_start","message":{}}"#, true).unwrap(),
+            None
+        );
+        let err = anthropic_delta(
+            r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#,
+            false,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Overloaded"));
+    }
+}
@@ -0,0 +1,205 @@
+use super::{enrich_concurrently, LlmClient, RequestPreview, DEFAULT_BATCH_CONCURRENCY};
+use crate::scanner::{LlmVerdict, ScanReport};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::time::{sleep_until, Instant};
+
+/// Request budget for one provider; `None` leaves that dimension unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Sustained request rate. Calls are spaced evenly rather than allowed to burst, so a
+    /// batch never front-loads a provider's per-minute quota.
+    pub requests_per_minute: Option<u32>,
+    /// Calls allowed in flight at once.
+    pub max_concurrency: Option<usize>,
+}
+
+impl RateLimit {
+    pub fn is_unlimited(&self) -> bool {
+        self.requests_per_minute.is_none() && self.max_concurrency.is_none()
+    }
+}
+
+/// Waits for a rate slot and a concurrency permit before every call to the wrapped client.
+///
+/// Clones share the same budget, so one limiter can front every use of a provider in a run.
+#[derive(Clone)]
+pub struct ThrottledLlmClient {
+    inner: Arc<dyn LlmClient>,
+    pacer: Option<Arc<Pacer>>,
+    permits: Option<Arc<Semaphore>>,
+    /// Calls `enrich_many` keeps in flight: the concurrency cap when one is set.
+    batch_concurrency: usize,
+}
+
+impl ThrottledLlmClient {
+    pub fn new(inner: Arc<dyn LlmClient>, limit: RateLimit) -> Self {
+        let max_concurrency = limit.max_concurrency.filter(|max| *max > 0);
+        Self {
+            inner,
+            pacer: limit
+                .requests_per_minute
+                .filter(|rpm| *rpm > 0)
+                .map(|rpm| Arc::new(Pacer::per_minute(rpm))),
+            permits: max_concurrency.map(|max| Arc::new(Semaphore::new(max))),
+            batch_concurrency: max_concurrency.unwrap_or(DEFAULT_BATCH_CONCURRENCY),
+        }
+    }
+}
+
+#[async_trait]
+impl LlmClient for ThrottledLlmClient {
+    async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
+        let _permit = match &self.permits {
+            Some(permits) => Some(
+                permits
+                    .acquire()
+                    .await
+                    .context("LLM concurrency limiter closed")?,
+            ),
+            None => None,
+        };
+        if let Some(pacer) = &self.pacer {
+            pacer.wait().await;
+        }
+        self.inner.enrich(input, report).await
+    }
+
+    async fn enrich_many(&self, items: &[(&str, &ScanReport)]) -> Vec<Result<LlmVerdict>> {
+        enrich_concurrently(self, items, self.batch_concurrency).await
+    }
+
+    fn preview(&self, input: &str, report: &ScanReport) -> Result<Vec<RequestPreview>> {
+        self.inner.preview(input, report)
+    }
+}
+
+/// Hands out evenly spaced start times (a GCRA limiter without burst capacity).
+struct Pacer {
+    interval: Duration,
+    next: Mutex<Option<Instant>>,
+}
+
+impl Pacer {
+    fn per_minute(requests: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(60) / requests,
+            next: Mutex::new(None),
+        }
+    }
+
+    async fn wait(&self) {
+        let slot = {
+            let mut next = self.next.lock().expect("rate limiter poisoned");
+            let now = Instant::now();
+            let slot = next.map_or(now, |next| next.max(now));
+            *next = Some(slot + self.interval);
+            slot
+        };
+        sleep_until(slot).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{RiskThresholds, ScoreBreakdown};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct Slow {
+        in_flight: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl LlmClient for Slow {
+        async fn enrich(&self, input: &str, _report: &ScanReport) -> Result<LlmVerdict> {
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(LlmVerdict {
+                label: input.to_string(),
+                ..Default::default()
+            })
+        }
+    }
+
+    fn report() -> ScanReport {
+        ScanReport::from_breakdown(
+            vec![],
+            0,
+            None,
+            ScoreBreakdown::default(),
+            &RiskThresholds::default(),
+        )
+    }
+
+    #[tokio::test]
+    async fn concurrency_is_capped() {
+        let inner = Arc::new(Slow::default());
+        let client = ThrottledLlmClient::new(
+            inner.clone(),
+            RateLimit {
+                max_concurrency: Some(2),
+                ..RateLimit::default()
+            },
+        );
+        let report = report();
+        futures::future::try_join_all((0..5).map(|_| client.enrich("input", &report)))
+            .await
+            .unwrap();
+        assert_eq!(inner.peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn enrich_many_keeps_order_and_follows_the_cap() {
+        let report = report();
+        let inputs: Vec<String> = (0..10).map(|i| i.to_string()).collect();
+        let items: Vec<(&str, &ScanReport)> =
+            inputs.iter().map(|i| (i.as_str(), &report)).collect();
+
+        let direct = Slow::default();
+        let verdicts = direct.enrich_many(&items).await;
+        let labels: Vec<String> = verdicts.into_iter().map(|v| v.unwrap().label).collect();
+        assert_eq!(labels, inputs);
+        assert_eq!(
+            direct.peak.load(Ordering::SeqCst),
+            DEFAULT_BATCH_CONCURRENCY
+        );
+
+        let inner = Arc::new(Slow::default());
+        let client = ThrottledLlmClient::new(
+            inner.clone(),
+            RateLimit {
+                max_concurrency: Some(6),
+                ..RateLimit::default()
+            },
+        );
+        assert_eq!(client.enrich_many(&items).await.len(), 10);
+        assert_eq!(inner.peak.load(Ordering::SeqCst), 6);
+    }
+
+    #[tokio::test]
+    async fn requests_are_spaced_by_the_rate() {
+        // 1200 requests per minute = one every 50ms.
+        let client = ThrottledLlmClient::new(
+            Arc::new(Slow::default()),
+            RateLimit {
+                requests_per_minute: Some(1200),
+                ..RateLimit::default()
+            },
+        );
+        let report = report();
+        let started = Instant::now();
+        for _ in 0..3 {
+            client.enrich("input", &report).await.unwrap();
+        }
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        assert!(RateLimit::default().is_unlimited());
+    }
+}
@@ -0,0 +1,120 @@
+use super::{LlmClient, RequestPreview};
+use crate::scanner::{EnrichmentMeta, LlmVerdict, ScanReport};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::cell::RefCell;
+use std::time::Instant;
+
+/// HTTP attempts made by [`super::RetryPolicy`] during one `enrich` call.
+#[derive(Debug, Clone, Copy, Default)]
+struct AttemptLog {
+    attempts: u32,
+    last_status: Option<u16>,
+}
+
+tokio::task_local! {
+    static ATTEMPTS: RefCell<AttemptLog>;
+}
+
+/// Note an HTTP attempt and its status (`None` for a transport error) for the enclosing
+/// [`TimedLlmClient`] call; a no-op outside one.
+#[cfg_attr(not(feature = "llm"), allow(dead_code))]
+pub(crate) fn record_attempt(status: Option<u16>) {
+    let _ = ATTEMPTS.try_with(|log| {
+        let mut log = log.borrow_mut();
+        log.attempts += 1;
+        log.last_status = status;
+    });
+}
+
+/// Attaches [`EnrichmentMeta`] (latency, HTTP attempts, final status) to each verdict.
+///
+/// Attempts are only known for clients that send through [`super::RetryPolicy`]; the rig-backed
+/// providers report latency alone.
+pub struct TimedLlmClient {
+    inner: Box<dyn LlmClient>,
+}
+
+impl TimedLlmClient {
+    pub fn new(inner: Box<dyn LlmClient>) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl LlmClient for TimedLlmClient {
+    async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
+        let started = Instant::now();
+        let (result, log) = ATTEMPTS
+            .scope(RefCell::new(AttemptLog::default()), async {
+                let result = self.inner.enrich(input, report).await;
+                (result, ATTEMPTS.with(|log| *log.borrow()))
+            })
+            .await;
+        let mut verdict = result?;
+        verdict.meta = Some(EnrichmentMeta {
+            latency_ms: u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX),
+            attempts: (log.attempts > 0).then_some(log.attempts),
+            http_status: log.last_status,
+        });
+        Ok(verdict)
+    }
+
+    fn preview(&self, input: &str, report: &ScanReport) -> Result<Vec<RequestPreview>> {
+        self.inner.preview(input, report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Flaky;
+
+    #[async_trait]
+    impl LlmClient for Flaky {
+        async fn enrich(&self, _input: &str, _report: &ScanReport) -> Result<LlmVerdict> {
+            record_attempt(Some(503));
+            record_attempt(None);
+            record_attempt(Some(200));
+            Ok(LlmVerdict {
+                label: "safe".into(),
+                ..Default::default()
+            })
+        }
+    }
+
+    struct Untracked;
+
+    #[async_trait]
+    impl LlmClient for Untracked {
+        async fn enrich(&self, _input: &str, _report: &ScanReport) -> Result<LlmVerdict> {
+            Ok(LlmVerdict::default())
+        }
+    }
+
+    fn report() -> ScanReport {
+        ScanReport::new(0.0, Vec::new(), 0, None, Default::default())
+    }
+
+    #[tokio::test]
+    async fn records_attempts_and_final_status() {
+        let verdict = TimedLlmClient::new(Box::new(Flaky))
+            .enrich("hi", &report())
+            .await
+            .unwrap();
+        let meta = verdict.meta.unwrap();
+        assert_eq!(meta.attempts, Some(3));
+        assert_eq!(meta.http_status, Some(200));
+
+        // Outside a timed call the log is not touched.
+        record_attempt(Some(500));
+        let verdict = TimedLlmClient::new(Box::new(Untracked))
+            .enrich("hi", &report())
+            .await
+            .unwrap();
+        let meta = verdict.meta.unwrap();
+        assert_eq!(meta.attempts, None);
+        assert_eq!(meta.http_status, None);
+    }
+}
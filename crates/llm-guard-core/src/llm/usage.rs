@@ -0,0 +1,289 @@
+use super::{LlmClient, RequestPreview};
+use crate::scanner::{LlmVerdict, ScanReport, TokenUsage};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+/// USD price per million tokens for one model.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+impl ModelPrice {
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.prompt_tokens as f64 * self.input_per_million
+            + usage.completion_tokens as f64 * self.output_per_million)
+            / 1_000_000.0
+    }
+}
+
+/// Prices keyed by `provider/model`, bare model id, or provider name, tried in that order.
+///
+/// No prices are built in: list prices change too often to ship, so costs are only
+/// estimated for entries the user configures.
+#[derive(Debug, Clone, Default)]
+pub struct PriceTable {
+    entries: HashMap<String, ModelPrice>,
+}
+
+impl PriceTable {
+    pub fn new(entries: HashMap<String, ModelPrice>) -> Self {
+        Self {
+            entries: entries
+                .into_iter()
+                .map(|(key, price)| (key.to_ascii_lowercase(), price))
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn lookup(&self, provider: &str, model: Option<&str>) -> Option<ModelPrice> {
+        let provider = provider.to_ascii_lowercase();
+        let model = model.map(str::to_ascii_lowercase);
+        model
+            .as_ref()
+            .and_then(|model| self.entries.get(&format!("{provider}/{model}")))
+            .or_else(|| model.as_ref().and_then(|model| self.entries.get(model)))
+            .or_else(|| self.entries.get(&provider))
+            .copied()
+    }
+}
+
+/// Cumulative usage of one provider across a run.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ProviderUsage {
+    pub provider: String,
+    pub calls: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// Prompt tokens served from or written to the provider's prompt cache.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_read_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_write_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+}
+
+/// Shared accumulator fed by every [`MeteredLlmClient`] of a run.
+#[derive(Debug, Default)]
+pub struct UsageTracker {
+    prices: PriceTable,
+    totals: Mutex<BTreeMap<String, ProviderUsage>>,
+}
+
+impl UsageTracker {
+    pub fn new(prices: PriceTable) -> Self {
+        Self {
+            prices,
+            totals: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn prices(&self) -> &PriceTable {
+        &self.prices
+    }
+
+    /// Count one call; `usage` is `None` when the provider did not report tokens.
+    pub fn record(&self, provider: &str, usage: Option<&TokenUsage>) {
+        let mut totals = self.totals.lock().expect("usage tracker poisoned");
+        let entry = totals
+            .entry(provider.to_string())
+            .or_insert_with(|| ProviderUsage {
+                provider: provider.to_string(),
+                ..ProviderUsage::default()
+            });
+        entry.calls += 1;
+        if let Some(usage) = usage {
+            entry.prompt_tokens += usage.prompt_tokens;
+            entry.completion_tokens += usage.completion_tokens;
+            if let Some(tokens) = usage.cache_read_tokens {
+                *entry.cache_read_tokens.get_or_insert(0) += tokens;
+            }
+            if let Some(tokens) = usage.cache_write_tokens {
+                *entry.cache_write_tokens.get_or_insert(0) += tokens;
+            }
+            if let Some(cost) = usage.cost_usd {
+                *entry.cost_usd.get_or_insert(0.0) += cost;
+            }
+        }
+    }
+
+    /// Per-provider totals, sorted by provider name.
+    pub fn snapshot(&self) -> Vec<ProviderUsage> {
+        self.totals
+            .lock()
+            .expect("usage tracker poisoned")
+            .values()
+            .cloned()
+            .collect()
+    }
+}
+
+/// Prices each verdict's token usage and adds it to a shared [`UsageTracker`].
+pub struct MeteredLlmClient {
+    provider: String,
+    model: Option<String>,
+    inner: Arc<dyn LlmClient>,
+    tracker: Arc<UsageTracker>,
+}
+
+impl MeteredLlmClient {
+    /// `model` selects the price entry; `None` falls back to the provider's entry.
+    pub fn new(
+        provider: impl Into<String>,
+        model: Option<String>,
+        inner: Arc<dyn LlmClient>,
+        tracker: Arc<UsageTracker>,
+    ) -> Self {
+        Self {
+            provider: provider.into(),
+            model,
+            inner,
+            tracker,
+        }
+    }
+}
+
+#[async_trait]
+impl LlmClient for MeteredLlmClient {
+    async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
+        let mut verdict = self.inner.enrich(input, report).await?;
+        if let Some(usage) = verdict.usage.as_mut() {
+            if let Some(price) = self
+                .tracker
+                .prices()
+                .lookup(&self.provider, self.model.as_deref())
+            {
+                usage.cost_usd = Some(price.cost(usage));
+            }
+        }
+        self.tracker.record(&self.provider, verdict.usage.as_ref());
+        Ok(verdict)
+    }
+
+    fn preview(&self, input: &str, report: &ScanReport) -> Result<Vec<RequestPreview>> {
+        self.inner.preview(input, report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{RiskThresholds, ScoreBreakdown};
+
+    struct Reporting(u64, u64);
+
+    #[async_trait]
+    impl LlmClient for Reporting {
+        async fn enrich(&self, _input: &str, _report: &ScanReport) -> Result<LlmVerdict> {
+            Ok(LlmVerdict {
+                label: "safe".into(),
+                usage: TokenUsage::reported(self.0, self.1),
+                ..Default::default()
+            })
+        }
+    }
+
+    fn price(input: f64, output: f64) -> ModelPrice {
+        ModelPrice {
+            input_per_million: input,
+            output_per_million: output,
+        }
+    }
+
+    fn report() -> ScanReport {
+        ScanReport::from_breakdown(
+            vec![],
+            0,
+            None,
+            ScoreBreakdown::default(),
+            &RiskThresholds::default(),
+        )
+    }
+
+    #[test]
+    fn lookup_prefers_the_most_specific_key() {
+        let table = PriceTable::new(HashMap::from([
+            ("openai/gpt-4o-mini".to_string(), price(1.0, 1.0)),
+            ("GPT-4O-MINI".to_string(), price(2.0, 2.0)),
+            ("openai".to_string(), price(3.0, 3.0)),
+        ]));
+        assert_eq!(
+            table.lookup("openai", Some("gpt-4o-mini")),
+            Some(price(1.0, 1.0))
+        );
+        assert_eq!(
+            table.lookup("azure", Some("gpt-4o-mini")),
+            Some(price(2.0, 2.0))
+        );
+        assert_eq!(table.lookup("openai", None), Some(price(3.0, 3.0)));
+        assert_eq!(table.lookup("cohere", Some("command-r")), None);
+    }
+
+    #[tokio::test]
+    async fn metered_client_prices_and_accumulates_usage() {
+        let tracker = Arc::new(UsageTracker::new(PriceTable::new(HashMap::from([(
+            "gpt-4o-mini".to_string(),
+            price(0.15, 0.60),
+        )]))));
+        let priced = MeteredLlmClient::new(
+            "openai",
+            Some("gpt-4o-mini".into()),
+            Arc::new(Reporting(1_000_000, 500_000)),
+            Arc::clone(&tracker),
+        );
+        let unpriced = MeteredLlmClient::new(
+            "noop",
+            None,
+            Arc::new(Reporting(0, 0)),
+            Arc::clone(&tracker),
+        );
+
+        let verdict = priced.enrich("input", &report()).await.unwrap();
+        let usage = verdict.usage.unwrap();
+        assert!((usage.cost_usd.unwrap() - 0.45).abs() < 1e-9);
+        priced.enrich("input", &report()).await.unwrap();
+        assert!(unpriced
+            .enrich("input", &report())
+            .await
+            .unwrap()
+            .usage
+            .is_none());
+
+        let totals = tracker.snapshot();
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[0].provider, "noop");
+        assert_eq!(totals[0].calls, 1);
+        assert_eq!(totals[0].cost_usd, None);
+        assert_eq!(totals[1].calls, 2);
+        assert_eq!(totals[1].prompt_tokens, 2_000_000);
+        assert!((totals[1].cost_usd.unwrap() - 0.90).abs() < 1e-9);
+    }
+
+    #[test]
+    fn tracker_sums_prompt_cache_tokens() {
+        let tracker = UsageTracker::default();
+        tracker.record(
+            "anthropic",
+            Some(&TokenUsage::new(1200, 50).with_cache(0, 1100)),
+        );
+        tracker.record(
+            "anthropic",
+            Some(&TokenUsage::new(1200, 50).with_cache(1100, 0)),
+        );
+        tracker.record("openai", Some(&TokenUsage::new(100, 10)));
+
+        let totals = tracker.snapshot();
+        assert_eq!(totals[0].cache_read_tokens, Some(1100));
+        assert_eq!(totals[0].cache_write_tokens, Some(1100));
+        assert_eq!(totals[1].cache_read_tokens, None);
+    }
+}
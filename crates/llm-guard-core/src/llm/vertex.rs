@@ -0,0 +1,572 @@
+use super::gemini::{
+    debug_enabled, extract_verdict, verdict_request, GeminiResponse, GenerationOptions,
+};
+use super::{http, LlmClient, LlmSettings, PromptTemplate, RequestPreview, RetryPolicy};
+use crate::scanner::{LlmVerdict, ScanReport};
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use rsa::pkcs1v15::SigningKey;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::signature::{SignatureEncoding, Signer};
+use rsa::RsaPrivateKey;
+use serde::Deserialize;
+use sha2::Sha256;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const CLOUD_PLATFORM_SCOPE: &str = "https://www.googleapis.com/auth/cloud-platform";
+const DEFAULT_TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
+const METADATA_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+const DEFAULT_LOCATION: &str = "us-central1";
+/// Refresh tokens this long before Google reports them as expired.
+const TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+/// Where Vertex AI access tokens come from, in Application Default Credentials order.
+#[derive(Clone)]
+enum TokenSource {
+    /// A pre-minted OAuth access token (e.g. `gcloud auth print-access-token`).
+    Static(String),
+    /// `gcloud auth application-default login` credentials.
+    AuthorizedUser {
+        client_id: String,
+        client_secret: String,
+        refresh_token: String,
+        token_uri: String,
+    },
+    /// A service-account key file; tokens are obtained with a signed JWT assertion.
+    ServiceAccount {
+        client_email: String,
+        private_key: Box<RsaPrivateKey>,
+        token_uri: String,
+    },
+    /// The GCE/GKE/Cloud Run metadata server.
+    Metadata { url: String },
+}
+
+impl std::fmt::Debug for TokenSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Static(_) => f.write_str("Static"),
+            Self::AuthorizedUser { client_id, .. } => f
+                .debug_struct("AuthorizedUser")
+                .field("client_id", client_id)
+                .finish_non_exhaustive(),
+            Self::ServiceAccount { client_email, .. } => f
+                .debug_struct("ServiceAccount")
+                .field("client_email", client_email)
+                .finish_non_exhaustive(),
+            Self::Metadata { url } => f.debug_struct("Metadata").field("url", url).finish(),
+        }
+    }
+}
+
+/// Subset of the ADC JSON file formats written by `gcloud` and the IAM console.
+#[derive(Deserialize)]
+struct CredentialsFile {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    client_id: Option<String>,
+    #[serde(default)]
+    client_secret: Option<String>,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    client_email: Option<String>,
+    #[serde(default)]
+    private_key: Option<String>,
+    #[serde(default)]
+    token_uri: Option<String>,
+    #[serde(default)]
+    project_id: Option<String>,
+    #[serde(default)]
+    quota_project_id: Option<String>,
+}
+
+impl TokenSource {
+    /// Resolve credentials: an explicit token in `LLM_GUARD_API_KEY`, then
+    /// `GOOGLE_APPLICATION_CREDENTIALS`, then the gcloud ADC file, then the metadata server.
+    /// Also returns the project recorded in the credentials file, if any.
+    fn resolve(api_key: &str) -> Result<(Self, Option<String>)> {
+        if !api_key.trim().is_empty() {
+            return Ok((Self::Static(api_key.trim().to_string()), None));
+        }
+        if let Some(path) = env::var_os("GOOGLE_APPLICATION_CREDENTIALS") {
+            return Self::from_file(Path::new(&path));
+        }
+        if let Some(path) = well_known_adc_file().filter(|path| path.exists()) {
+            return Self::from_file(&path);
+        }
+        Ok((
+            Self::Metadata {
+                url: METADATA_TOKEN_URL.to_string(),
+            },
+            None,
+        ))
+    }
+
+    fn from_file(path: &Path) -> Result<(Self, Option<String>)> {
+        let contents = std::fs::read_to_string(path).with_context(|| {
+            format!("failed to read Google credentials from {}", path.display())
+        })?;
+        Self::from_json(&contents)
+            .with_context(|| format!("invalid Google credentials file {}", path.display()))
+    }
+
+    fn from_json(contents: &str) -> Result<(Self, Option<String>)> {
+        let file: CredentialsFile = serde_json::from_str(contents)?;
+        let token_uri = file
+            .token_uri
+            .unwrap_or_else(|| DEFAULT_TOKEN_URI.to_string());
+        let field = |value: Option<String>, name: &str| {
+            value
+                .filter(|value| !value.trim().is_empty())
+                .ok_or_else(|| anyhow!("`{}` credentials are missing `{name}`", file.kind))
+        };
+        match file.kind.as_str() {
+            "authorized_user" => Ok((
+                Self::AuthorizedUser {
+                    client_id: field(file.client_id, "client_id")?,
+                    client_secret: field(file.client_secret, "client_secret")?,
+                    refresh_token: field(file.refresh_token, "refresh_token")?,
+                    token_uri,
+                },
+                file.quota_project_id,
+            )),
+            "service_account" => {
+                let pem = field(file.private_key, "private_key")?;
+                let private_key = RsaPrivateKey::from_pkcs8_pem(&pem)
+                    .context("service account private_key is not a PKCS#8 RSA key")?;
+                Ok((
+                    Self::ServiceAccount {
+                        client_email: field(file.client_email, "client_email")?,
+                        private_key: Box::new(private_key),
+                        token_uri,
+                    },
+                    file.project_id.or(file.quota_project_id),
+                ))
+            }
+            other => bail!(
+                "unsupported credentials type `{other}` (expected service_account or authorized_user)"
+            ),
+        }
+    }
+
+    /// Fetch a fresh access token and its lifetime.
+    async fn fetch(&self, http: &Client) -> Result<(String, Duration)> {
+        let response = match self {
+            Self::Static(token) => return Ok((token.clone(), Duration::MAX)),
+            Self::AuthorizedUser {
+                client_id,
+                client_secret,
+                refresh_token,
+                token_uri,
+            } => http
+                .post(token_uri)
+                .form(&[
+                    ("grant_type", "refresh_token"),
+                    ("client_id", client_id.as_str()),
+                    ("client_secret", client_secret.as_str()),
+                    ("refresh_token", refresh_token.as_str()),
+                ])
+                .send()
+                .await
+                .context("failed to refresh Google user credentials")?,
+            Self::ServiceAccount {
+                client_email,
+                private_key,
+                token_uri,
+            } => {
+                let assertion =
+                    service_account_jwt(client_email, private_key, token_uri, SystemTime::now())?;
+                http.post(token_uri)
+                    .form(&[
+                        ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                        ("assertion", assertion.as_str()),
+                    ])
+                    .send()
+                    .await
+                    .context("failed to exchange service account assertion")?
+            }
+            Self::Metadata { url } => http
+                .get(url)
+                .header("Metadata-Flavor", "Google")
+                .send()
+                .await
+                .context(
+                    "failed to reach the GCE metadata server (set GOOGLE_APPLICATION_CREDENTIALS \
+                     or run `gcloud auth application-default login`)",
+                )?,
+        };
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            bail!("Google token endpoint error ({}): {}", status, body);
+        }
+        let token: TokenResponse = response
+            .json()
+            .await
+            .context("failed to parse Google token response")?;
+        Ok((
+            token.access_token,
+            Duration::from_secs(token.expires_in.unwrap_or(3600)),
+        ))
+    }
+}
+
+/// `$CLOUDSDK_CONFIG` or the platform default gcloud config directory.
+fn well_known_adc_file() -> Option<PathBuf> {
+    let dir = if let Some(dir) = env::var_os("CLOUDSDK_CONFIG") {
+        PathBuf::from(dir)
+    } else if cfg!(windows) {
+        PathBuf::from(env::var_os("APPDATA")?).join("gcloud")
+    } else {
+        PathBuf::from(env::var_os("HOME")?)
+            .join(".config")
+            .join("gcloud")
+    };
+    Some(dir.join("application_default_credentials.json"))
+}
+
+/// RS256-signed JWT asserting `client_email` for the cloud-platform scope.
+fn service_account_jwt(
+    client_email: &str,
+    private_key: &RsaPrivateKey,
+    token_uri: &str,
+    now: SystemTime,
+) -> Result<String> {
+    let issued_at = now
+        .duration_since(UNIX_EPOCH)
+        .context("system clock is before the Unix epoch")?
+        .as_secs();
+    let header = serde_json::json!({ "alg": "RS256", "typ": "JWT" });
+    let claims = serde_json::json!({
+        "iss": client_email,
+        "scope": CLOUD_PLATFORM_SCOPE,
+        "aud": token_uri,
+        "iat": issued_at,
+        "exp": issued_at + 3600,
+    });
+    let signing_input = format!(
+        "{}.{}",
+        base64url(header.to_string().as_bytes()),
+        base64url(claims.to_string().as_bytes())
+    );
+    let signature = SigningKey::<Sha256>::new(private_key.clone()).sign(signing_input.as_bytes());
+    Ok(format!(
+        "{signing_input}.{}",
+        base64url(&signature.to_bytes())
+    ))
+}
+
+/// Unpadded base64url, as required for JWT segments.
+fn base64url(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..=chunk.len() {
+            out.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+        }
+    }
+    out
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+#[derive(Clone)]
+struct CachedToken {
+    value: String,
+    expires_at: Option<Instant>,
+}
+
+/// Gemini models served from Vertex AI (`aiplatform.googleapis.com`), authenticated with
+/// Application Default Credentials rather than a Gemini API key.
+#[derive(Clone)]
+pub struct VertexAiClient {
+    http: Client,
+    url: String,
+    model: String,
+    tokens: TokenSource,
+    cached: Arc<Mutex<Option<CachedToken>>>,
+    retry: RetryPolicy,
+    prompt: PromptTemplate,
+    generation: GenerationOptions,
+}
+
+impl VertexAiClient {
+    pub fn new(settings: &LlmSettings) -> Result<Self> {
+        let (tokens, credentials_project) = TokenSource::resolve(&settings.api_key)?;
+        let project = settings
+            .project
+            .clone()
+            .or_else(|| env::var("GOOGLE_CLOUD_PROJECT").ok())
+            .or(credentials_project)
+            .map(|project| project.trim().to_string())
+            .filter(|project| !project.is_empty())
+            .ok_or_else(|| {
+                anyhow!("Vertex AI project must be provided via LLM_GUARD_PROJECT or GOOGLE_CLOUD_PROJECT")
+            })?;
+        let location = settings
+            .region
+            .clone()
+            .or_else(|| env::var("GOOGLE_CLOUD_LOCATION").ok())
+            .map(|location| location.trim().to_string())
+            .filter(|location| !location.is_empty())
+            .unwrap_or_else(|| DEFAULT_LOCATION.to_string());
+        let model = settings
+            .model
+            .clone()
+            .unwrap_or_else(|| "gemini-1.5-flash".to_string());
+        let base = settings.endpoint.clone().unwrap_or_else(|| {
+            if location == "global" {
+                "https://aiplatform.googleapis.com".to_string()
+            } else {
+                format!("https://{location}-aiplatform.googleapis.com")
+            }
+        });
+        let url = format!(
+            "{}/v1/projects/{project}/locations/{location}/publishers/google/models/{model}:generateContent",
+            base.trim_end_matches('/')
+        );
+        let http = http::client(settings, "Vertex AI")?;
+        let mut retry = RetryPolicy::from_settings(settings);
+        // A 401 usually means the cached token was revoked early; the retry fetches a new one.
+        if !retry.should_retry(401) {
+            retry.retry_on.push(401);
+        }
+        Ok(Self {
+            http,
+            url,
+            model,
+            tokens,
+            cached: Arc::new(Mutex::new(None)),
+            retry,
+            prompt: PromptTemplate::from_settings(settings)?,
+            generation: GenerationOptions::from_settings(settings),
+        })
+    }
+
+    async fn access_token(&self) -> Result<String> {
+        let cached = self.cached.lock().expect("token cache poisoned").clone();
+        if let Some(cached) = cached {
+            let fresh = match cached.expires_at {
+                Some(expires_at) => Instant::now() < expires_at,
+                None => true,
+            };
+            if fresh {
+                return Ok(cached.value);
+            }
+        }
+        let (value, lifetime) = self.tokens.fetch(&self.http).await?;
+        let expires_at = Instant::now().checked_add(lifetime.saturating_sub(TOKEN_EXPIRY_MARGIN));
+        *self.cached.lock().expect("token cache poisoned") = Some(CachedToken {
+            value: value.clone(),
+            expires_at,
+        });
+        Ok(value)
+    }
+}
+
+#[async_trait]
+impl LlmClient for VertexAiClient {
+    async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
+        let payload = &verdict_request(&self.prompt, &self.generation, input, report);
+
+        let response = self
+            .retry
+            .send("Vertex AI", "generateContent", move |previous| async move {
+                if previous == Some(reqwest::StatusCode::UNAUTHORIZED) {
+                    // Force a refresh in case the cached token was revoked early.
+                    *self.cached.lock().expect("token cache poisoned") = None;
+                }
+                let token = self.access_token().await?;
+                Ok(self.http.post(&self.url).bearer_auth(&token).json(payload))
+            })
+            .await?;
+
+        let message: GeminiResponse = response
+            .json()
+            .await
+            .context("failed to parse Vertex AI response")?;
+        if debug_enabled() {
+            tracing::warn!(
+                "vertex raw response: {}",
+                serde_json::to_string_pretty(&message).unwrap_or_default()
+            );
+        }
+        extract_verdict(message, "Vertex AI")
+    }
+
+    fn preview(&self, input: &str, report: &ScanReport) -> Result<Vec<RequestPreview>> {
+        Ok(vec![RequestPreview {
+            provider: "vertex".into(),
+            model: self.model.clone(),
+            url: Some(self.url.clone()),
+            body: serde_json::to_value(verdict_request(
+                &self.prompt,
+                &self.generation,
+                input,
+                report,
+            ))?,
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::settings::LlmSettings;
+    use crate::scanner::{RiskThresholds, ScanReport, ScoreBreakdown};
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    fn base_settings(url: String) -> LlmSettings {
+        LlmSettings {
+            provider: "vertex".into(),
+            api_key: "ya29.test-token".into(),
+            endpoint: Some(url),
+            model: Some("gemini-test".into()),
+            deployment: None,
+            project: Some("security-project".into()),
+            workspace: None,
+            region: Some("europe-west4".into()),
+            timeout_secs: Some(5),
+            max_retries: 0,
+            api_version: None,
+            retry_base_delay_ms: None,
+            retry_max_delay_ms: None,
+            retry_jitter: None,
+            retry_on_status: None,
+            prompt_template: None,
+            verdict_labels: None,
+            structured_output: None,
+            stream: None,
+            stream_idle_timeout_secs: None,
+            proxy: None,
+            ca_bundle: None,
+            client_cert: None,
+            client_key: None,
+            azure_auth: None,
+            scrub_pii: None,
+            prompt_cache: None,
+            openai_api: None,
+            temperature: None,
+            max_tokens: None,
+            response_mime_type: None,
+            safety_settings: None,
+        }
+    }
+
+    fn empty_report() -> ScanReport {
+        ScanReport::from_breakdown(
+            vec![],
+            0,
+            None,
+            ScoreBreakdown::default(),
+            &RiskThresholds::default(),
+        )
+    }
+
+    #[tokio::test]
+    async fn enrich_uses_bearer_token_and_project_path() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1/projects/security-project/locations/europe-west4/publishers/google/models/gemini-test:generateContent")
+                .header("authorization", "Bearer ya29.test-token");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "candidates": [
+                        {
+                            "content": {
+                                "role": "model",
+                                "parts": [
+                                    {"text": "{\"label\":\"safe\",\"rationale\":\"ok\",\"mitigation\":\"none\"}"}
+                                ]
+                            }
+                        }
+                    ]
+                }));
+        });
+
+        let client = VertexAiClient::new(&base_settings(server.base_url())).unwrap();
+        let verdict = client.enrich("hello", &empty_report()).await.unwrap();
+        assert_eq!(verdict.label, "safe");
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn refreshes_authorized_user_token() {
+        let server = MockServer::start();
+        let token = server.mock(|when, then| {
+            when.method(POST)
+                .path("/token")
+                .body_contains("grant_type=refresh_token");
+            then.status(200)
+                .json_body(json!({"access_token": "fresh", "expires_in": 3600}));
+        });
+        let (source, project) = TokenSource::from_json(
+            &json!({
+                "type": "authorized_user",
+                "client_id": "id",
+                "client_secret": "secret",
+                "refresh_token": "refresh",
+                "token_uri": server.url("/token"),
+                "quota_project_id": "quota-project",
+            })
+            .to_string(),
+        )
+        .unwrap();
+        assert_eq!(project.as_deref(), Some("quota-project"));
+
+        let (value, lifetime) = source.fetch(&Client::new()).await.unwrap();
+        assert_eq!(value, "fresh");
+        assert_eq!(lifetime, Duration::from_secs(3600));
+        token.assert();
+    }
+
+    #[test]
+    fn default_endpoint_is_regional() {
+        let mut settings = base_settings(String::new());
+        settings.endpoint = None;
+        let client = VertexAiClient::new(&settings).unwrap();
+        assert_eq!(
+            client.url,
+            "https://europe-west4-aiplatform.googleapis.com/v1/projects/security-project/locations/europe-west4/publishers/google/models/gemini-test:generateContent"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_credential_types() {
+        let err = TokenSource::from_json(r#"{"type": "external_account"}"#).unwrap_err();
+        assert!(err.to_string().contains("unsupported credentials type"));
+        let err = TokenSource::from_json(r#"{"type": "authorized_user"}"#).unwrap_err();
+        assert!(err.to_string().contains("client_id"));
+    }
+
+    #[test]
+    fn base64url_omits_padding() {
+        assert_eq!(base64url(b""), "");
+        assert_eq!(base64url(b"f"), "Zg");
+        assert_eq!(base64url(b"fo"), "Zm8");
+        assert_eq!(base64url(b"foo"), "Zm9v");
+        assert_eq!(base64url(&[0xfb, 0xff]), "-_8");
+    }
+}
@@ -0,0 +1,310 @@
+use super::{
+    compress_input, http, stream, LlmClient, LlmSettings, PromptTemplate, RequestPreview,
+    RetryPolicy, DEFAULT_MAX_TOKENS, DEFAULT_TEMPERATURE, MAX_PROMPT_INPUT_CHARS,
+};
+use crate::scanner::{LlmVerdict, ScanReport, TokenUsage};
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::future;
+use std::time::Duration;
+
+/// Grok model used when `LLM_GUARD_MODEL` is unset.
+const DEFAULT_MODEL: &str = "grok-3-mini";
+
+#[derive(Debug, Clone)]
+pub struct XaiClient {
+    http: Client,
+    url: String,
+    api_key: String,
+    model: String,
+    retry: RetryPolicy,
+    prompt: PromptTemplate,
+    /// Stream the reply and stop reading once the verdict object is complete.
+    stream: bool,
+    idle_timeout: Duration,
+    temperature: f32,
+    max_tokens: u32,
+}
+
+impl XaiClient {
+    pub fn new(settings: &LlmSettings) -> Result<Self> {
+        if settings.api_key.trim().is_empty() {
+            bail!("xAI API key must be provided via LLM_GUARD_API_KEY");
+        }
+        let base = settings
+            .endpoint
+            .clone()
+            .unwrap_or_else(|| "https://api.x.ai".to_string());
+        let url = format!("{}/v1/chat/completions", base.trim_end_matches('/'));
+        let http = http::client(settings, "xAI")?;
+        Ok(Self {
+            http,
+            url,
+            api_key: settings.api_key.clone(),
+            model: settings
+                .model
+                .clone()
+                .unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+            retry: RetryPolicy::from_settings(settings),
+            prompt: PromptTemplate::from_settings(settings)?,
+            stream: settings.stream.unwrap_or(false),
+            idle_timeout: settings
+                .stream_idle_timeout_secs
+                .map(Duration::from_secs)
+                .unwrap_or(stream::DEFAULT_IDLE_TIMEOUT),
+            temperature: settings.temperature.unwrap_or(DEFAULT_TEMPERATURE),
+            max_tokens: settings.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+        })
+    }
+
+    fn request(&self, input: &str, report: &ScanReport) -> ChatCompletionRequest {
+        ChatCompletionRequest {
+            model: self.model.clone(),
+            messages: vec![
+                ChatMessage {
+                    role: "system",
+                    content: self.prompt.system().to_string(),
+                },
+                ChatMessage {
+                    role: "user",
+                    content: self.prompt.render_user(
+                        &compress_input(input, report, MAX_PROMPT_INPUT_CHARS),
+                        report,
+                    ),
+                },
+            ],
+            response_format: ResponseFormat {
+                kind: "json_object",
+            },
+            temperature: self.temperature,
+            max_tokens: self.max_tokens,
+            stream: self.stream,
+        }
+    }
+}
+
+#[async_trait]
+impl LlmClient for XaiClient {
+    async fn enrich(&self, input: &str, report: &ScanReport) -> Result<LlmVerdict> {
+        let payload = self.request(input, report);
+
+        let response = self
+            .retry
+            .send("xAI", "chat completions", |_| {
+                future::ready(Ok(self
+                    .http
+                    .post(&self.url)
+                    .bearer_auth(&self.api_key)
+                    .json(&payload)))
+            })
+            .await?;
+
+        if self.stream {
+            let json = stream::read_verdict(response, "xAI", self.idle_timeout, |data| {
+                stream::chat_completion_delta(data, false)
+            })
+            .await?;
+            let verdict: ModelVerdict =
+                serde_json::from_str(&json).context("expected JSON verdict from xAI stream")?;
+            return Ok(LlmVerdict {
+                label: verdict.label,
+                rationale: verdict.rationale,
+                mitigation: verdict.mitigation,
+                ..Default::default()
+            });
+        }
+
+        let chat: ChatCompletionResponse = response
+            .json()
+            .await
+            .context("failed to parse xAI response")?;
+        let usage = chat
+            .usage
+            .as_ref()
+            .and_then(|usage| TokenUsage::reported(usage.prompt_tokens, usage.completion_tokens));
+        let content = chat
+            .choices
+            .into_iter()
+            .find_map(|choice| choice.message.content)
+            .ok_or_else(|| anyhow!("xAI response missing message content"))?;
+
+        let verdict: ModelVerdict =
+            serde_json::from_str(&content).context("expected JSON verdict from xAI response")?;
+
+        Ok(LlmVerdict {
+            label: verdict.label,
+            rationale: verdict.rationale,
+            mitigation: verdict.mitigation,
+            usage,
+            ..Default::default()
+        })
+    }
+
+    fn preview(&self, input: &str, report: &ScanReport) -> Result<Vec<RequestPreview>> {
+        Ok(vec![RequestPreview {
+            provider: "xai".into(),
+            model: self.model.clone(),
+            url: Some(self.url.clone()),
+            body: serde_json::to_value(self.request(input, report))?,
+        }])
+    }
+}
+
+#[derive(Serialize)]
+struct ChatCompletionRequest {
+    model: String,
+    messages: Vec<ChatMessage>,
+    response_format: ResponseFormat,
+    temperature: f32,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+}
+
+#[derive(Serialize)]
+struct ResponseFormat {
+    #[serde(rename = "type")]
+    kind: &'static str,
+}
+
+#[derive(Serialize)]
+struct ChatMessage {
+    role: &'static str,
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct ChatCompletionResponse {
+    choices: Vec<ChatChoice>,
+    #[serde(default)]
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Deserialize)]
+struct ChatUsage {
+    #[serde(default)]
+    prompt_tokens: u64,
+    #[serde(default)]
+    completion_tokens: u64,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatResponseMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatResponseMessage {
+    content: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ModelVerdict {
+    #[serde(deserialize_with = "super::labels::deserialize_label")]
+    label: String,
+    rationale: String,
+    mitigation: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::settings::LlmSettings;
+    use crate::scanner::{RiskThresholds, ScanReport, ScoreBreakdown};
+    use httpmock::prelude::*;
+
+    fn base_settings(url: String) -> LlmSettings {
+        LlmSettings {
+            provider: "xai".into(),
+            api_key: "test-key".into(),
+            endpoint: Some(url),
+            model: Some("grok-test".into()),
+            deployment: None,
+            project: None,
+            workspace: None,
+            region: None,
+            timeout_secs: Some(5),
+            max_retries: 0,
+            api_version: None,
+            retry_base_delay_ms: None,
+            retry_max_delay_ms: None,
+            retry_jitter: None,
+            retry_on_status: None,
+            prompt_template: None,
+            verdict_labels: None,
+            structured_output: None,
+            stream: None,
+            stream_idle_timeout_secs: None,
+            proxy: None,
+            ca_bundle: None,
+            client_cert: None,
+            client_key: None,
+            azure_auth: None,
+            scrub_pii: None,
+            prompt_cache: None,
+            openai_api: None,
+            temperature: None,
+            max_tokens: None,
+            response_mime_type: None,
+            safety_settings: None,
+        }
+    }
+
+    fn empty_report() -> ScanReport {
+        ScanReport::from_breakdown(
+            vec![],
+            0,
+            None,
+            ScoreBreakdown::default(),
+            &RiskThresholds::default(),
+        )
+    }
+
+    #[tokio::test]
+    async fn enrich_parses_successful_response() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/v1/chat/completions")
+                .header("authorization", "Bearer test-key")
+                .json_body_partial(r#"{"model":"grok-test","response_format":{"type":"json_object"}}"#);
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"choices":[{"message":{"content":"{\"label\":\"safe\",\"rationale\":\"ok\",\"mitigation\":\"none\"}"}}]}"#);
+        });
+
+        let client = XaiClient::new(&base_settings(server.base_url())).unwrap();
+        let verdict = client.enrich("hello", &empty_report()).await.unwrap();
+        assert_eq!(verdict.label, "safe");
+        assert_eq!(verdict.rationale, "ok");
+        assert_eq!(verdict.mitigation, "none");
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn retries_on_failure() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/v1/chat/completions");
+            then.status(500);
+        });
+
+        let mut settings = base_settings(server.base_url());
+        settings.max_retries = 1;
+        let client = XaiClient::new(&settings).unwrap();
+        let err = client.enrich("hello", &empty_report()).await.unwrap_err();
+        assert!(err.to_string().contains("xAI API error"));
+        mock.assert_hits(2);
+    }
+
+    #[test]
+    fn defaults_to_grok_model() {
+        let mut settings = base_settings("http://localhost".into());
+        settings.model = None;
+        let client = XaiClient::new(&settings).unwrap();
+        assert_eq!(client.model, DEFAULT_MODEL);
+        assert_eq!(client.url, "http://localhost/v1/chat/completions");
+    }
+}
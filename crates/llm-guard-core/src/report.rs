@@ -1,51 +1,594 @@
-use std::fmt::Write;
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt,
+    fmt::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::scanner::{FamilyContribution, Finding, RiskBand, ScanReport};
+pub mod github;
+pub mod html;
+pub mod siem;
+pub mod signing;
+
+pub use github::render_github;
+pub use html::render_html;
+pub use siem::{render_cef, render_leef, SiemFields};
+pub use signing::{
+    sign_report, verify_report, ReportSignature, ReportSigner, SignatureAlgorithm, VerificationKey,
+};
+
+use crate::scanner::{
+    bands::{BandSet, BandTier},
+    manifest::PackProvenance,
+    redact::ExcerptRedaction,
+    rule_family, FamilyContribution, Finding, LlmDegradation, LlmVerdict, ReportMetadata, RiskBand,
+    ScanReport, ScoreBreakdown,
+};
+
+/// Layout version written as `schema_version` by the `json`/`jsonl` formats.
+///
+/// Bump it whenever a change would mislead older readers (renamed, removed, or retyped fields)
+/// and teach [`parse_report`] to upgrade the previous version; new optional fields default
+/// when missing and need no bump. History:
+///
+/// 1. Reports written before `schema_version` existed (the field is absent).
+/// 2. Adds `schema_version`; otherwise identical to 1, whose later optional fields (`source`,
+///    `band`, `rule_packs`, `metadata`, `redaction`, `truncated_findings`, finding `position`)
+///    default when missing.
+/// 3. `risk_band` holds the configured band's name (formerly in `band`, which is gone) and
+///    `risk_band_index` its position in the band set; `breakdown.llm_adjustment` band names
+///    follow the configured bands too.
+pub const REPORT_SCHEMA_VERSION: u32 = 3;
+
+/// Number of rules kept in [`ScanSummary::top_rules`].
+pub const SUMMARY_TOP_RULES: usize = 10;
 
 /// Format styles supported in default reporter implementations.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum OutputFormat {
     Human,
     Json,
+    /// Compact JSON, one report per line, for `jq` and log shippers.
+    Jsonl,
+    /// One row per finding with a header row, for spreadsheets and BI tools.
+    Csv,
+    /// A user-supplied Handlebars template (see [`render_template`]).
+    Template(PathBuf),
+    /// One ArcSight CEF event per scan.
+    Cef,
+    /// One IBM QRadar LEEF event per scan.
+    Leef,
+    /// `score band finding-count source` on one line (see [`render_summary_line`]).
+    Summary,
+    /// GitHub Actions `::error`/`::warning` workflow commands, one per finding.
+    Github,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "human" | "text" => Ok(Self::Human),
+            "json" => Ok(Self::Json),
+            "jsonl" | "ndjson" => Ok(Self::Jsonl),
+            "csv" => Ok(Self::Csv),
+            "cef" => Ok(Self::Cef),
+            "leef" => Ok(Self::Leef),
+            "summary" => Ok(Self::Summary),
+            "github" | "github-actions" => Ok(Self::Github),
+            other => Err(format!(
+                "unknown output format `{other}` (expected human, json, jsonl, csv, cef, leef, \
+                 summary, or github)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Human => "human",
+            Self::Json => "json",
+            Self::Jsonl => "jsonl",
+            Self::Csv => "csv",
+            Self::Template(_) => "template",
+            Self::Cef => "cef",
+            Self::Leef => "leef",
+            Self::Summary => "summary",
+            Self::Github => "github",
+        })
+    }
+}
+
+/// How human output arranges findings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FindingGrouping {
+    /// One flat list in scan order.
+    #[default]
+    None,
+    /// Findings nested under their rule family with per-family subtotals.
+    Family,
+}
+
+impl FromStr for FindingGrouping {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "none" | "flat" => Ok(Self::None),
+            "family" => Ok(Self::Family),
+            other => Err(format!(
+                "unknown grouping `{other}` (expected none or family)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for FindingGrouping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::None => "none",
+            Self::Family => "family",
+        })
+    }
+}
+
+/// Presentation knobs for [`render_report_with`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderOptions<'a> {
+    /// Emit ANSI colors in human output (band, weights, and the matched text in excerpts).
+    pub color: bool,
+    /// Band set the report was classified into, supplying band colors and GitHub annotation
+    /// levels; `None` means the built-in bands (green/yellow/red).
+    pub bands: Option<&'a BandSet>,
+    /// Device vendor/product/version written into CEF and LEEF headers.
+    pub siem: SiemFields<'a>,
+    /// Arrangement of findings in human output.
+    pub group_by: FindingGrouping,
 }
 
 /// Produce a report string from a `ScanReport` using the desired format.
 pub fn render_report(report: &ScanReport, format: OutputFormat) -> anyhow::Result<String> {
+    render_report_with(report, &format, &RenderOptions::default())
+}
+
+/// Like [`render_report`], with presentation options applied where the format supports them.
+pub fn render_report_with(
+    report: &ScanReport,
+    format: &OutputFormat,
+    options: &RenderOptions<'_>,
+) -> anyhow::Result<String> {
     match format {
-        OutputFormat::Human => render_human(report),
+        OutputFormat::Human => render_human(report, options),
         OutputFormat::Json => Ok(serde_json::to_string_pretty(&JsonReport::from(report))?),
+        OutputFormat::Jsonl => render_jsonl(report),
+        OutputFormat::Csv => render_csv(report, true),
+        OutputFormat::Template(path) => render_template(report, path),
+        OutputFormat::Cef => Ok(render_cef(report, &options.siem)),
+        OutputFormat::Leef => Ok(render_leef(report, &options.siem)),
+        OutputFormat::Summary => Ok(render_summary_line(report)),
+        OutputFormat::Github => Ok(render_github(report, options.bands)),
+    }
+}
+
+/// Render `score band finding-count source` on a single newline-terminated line for shell
+/// pipelines; the source comes last so paths containing spaces stay unambiguous.
+pub fn render_summary_line(report: &ScanReport) -> String {
+    format!(
+        "{:.1} {} {} {}\n",
+        report.risk_score,
+        report.risk_band.name(),
+        report.findings.len(),
+        report.source.as_deref().unwrap_or("-")
+    )
+}
+
+/// Render `report` as a single line of compact JSON terminated by a newline.
+pub fn render_jsonl(report: &ScanReport) -> anyhow::Result<String> {
+    let mut line = serde_json::to_string(&JsonReport::from(report))?;
+    line.push('\n');
+    Ok(line)
+}
+
+/// Render `report` through the Handlebars template at `path`.
+///
+/// The template sees the JSON report fields (`risk_score`, `findings`, `family_contributions`,
+/// `llm_verdict`, ...) plus `band_name`. Values are inserted verbatim rather than HTML-escaped,
+/// so templates can target plain text, Slack markdown, or email bodies.
+#[cfg(feature = "templates")]
+pub fn render_template(report: &ScanReport, path: &Path) -> anyhow::Result<String> {
+    use anyhow::Context;
+
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read report template {}", path.display()))?;
+    let mut registry = handlebars::Handlebars::new();
+    registry.register_escape_fn(handlebars::no_escape);
+    let context = TemplateContext {
+        band_name: report.risk_band.name(),
+        report: JsonReport::from(report),
+    };
+    registry
+        .render_template(&source, &context)
+        .with_context(|| format!("failed to render report template {}", path.display()))
+}
+
+/// Fallback when the `templates` feature is disabled.
+#[cfg(not(feature = "templates"))]
+pub fn render_template(_report: &ScanReport, path: &Path) -> anyhow::Result<String> {
+    anyhow::bail!(
+        "template output ({}) requires the `templates` feature",
+        path.display()
+    )
+}
+
+/// JSON Schema (draft 7) of the report emitted by the `json`/`jsonl` formats, pretty-printed.
+#[cfg(feature = "schema")]
+pub fn report_schema() -> anyhow::Result<String> {
+    let schema = schemars::schema_for!(JsonReport<'static>);
+    Ok(serde_json::to_string_pretty(&schema)?)
+}
+
+/// Fallback when the `schema` feature is disabled.
+#[cfg(not(feature = "schema"))]
+pub fn report_schema() -> anyhow::Result<String> {
+    anyhow::bail!("the report JSON Schema requires the `schema` feature")
+}
+
+/// Render findings as CSV rows (`source,rule_id,weight,span_start,span_end,excerpt,band`).
+///
+/// Streams of reports (e.g. tail mode) pass `header = false` after the first report so the output
+/// stays a single table.
+#[cfg(feature = "formats")]
+pub fn render_csv(report: &ScanReport, header: bool) -> anyhow::Result<String> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    if header {
+        writer.write_record([
+            "source",
+            "rule_id",
+            "weight",
+            "span_start",
+            "span_end",
+            "excerpt",
+            "band",
+            "line",
+            "column",
+            "scanned_at",
+            "engine_version",
+            "rule_set_digest",
+        ])?;
+    }
+    let source = report.source.as_deref().unwrap_or("-");
+    let band = report.risk_band.name();
+    let metadata = report.metadata.as_ref();
+    let scanned_at = metadata.map_or("", |meta| meta.scanned_at.as_str());
+    let engine_version = metadata.map_or("", |meta| meta.engine_version.as_str());
+    let rule_set_digest = metadata.map_or("", |meta| meta.rule_set_digest.as_str());
+    for finding in &report.findings {
+        let (line, column) = finding
+            .position
+            .map(|position| (position.line.to_string(), position.column.to_string()))
+            .unwrap_or_default();
+        writer.write_record([
+            source,
+            &finding.rule_id,
+            &format!("{:.1}", finding.weight),
+            &finding.span.0.to_string(),
+            &finding.span.1.to_string(),
+            &finding.excerpt,
+            band,
+            &line,
+            &column,
+            scanned_at,
+            engine_version,
+            rule_set_digest,
+        ])?;
     }
+    Ok(String::from_utf8(writer.into_inner()?)?)
 }
 
-fn render_human(report: &ScanReport) -> anyhow::Result<String> {
+/// Fallback when the `formats` feature is disabled.
+#[cfg(not(feature = "formats"))]
+pub fn render_csv(_report: &ScanReport, _header: bool) -> anyhow::Result<String> {
+    anyhow::bail!("CSV output requires the `formats` feature")
+}
+
+/// Aggregate view over the reports of a multi-input scan.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanSummary {
+    pub inputs: usize,
+    pub total_findings: usize,
+    /// Band name of the highest-scoring input (`low` for an empty batch).
+    pub worst_band: String,
+    /// Source of the highest-scoring input, when reports carry one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worst_source: Option<String>,
+    pub max_score: f32,
+    pub mean_score: f32,
+    pub p50_score: f32,
+    pub p90_score: f32,
+    pub p95_score: f32,
+    /// Most frequent rules, by finding count then id, capped at [`SUMMARY_TOP_RULES`].
+    pub top_rules: Vec<RuleTally>,
+    /// Family contributions summed across inputs, heaviest adjusted weight first.
+    pub families: Vec<FamilyContribution>,
+}
+
+/// How often a rule fired across a batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuleTally {
+    pub rule_id: String,
+    pub findings: usize,
+    /// Number of inputs with at least one finding for the rule.
+    pub inputs: usize,
+}
+
+impl ScanSummary {
+    /// Summarize `reports`; percentiles use the nearest-rank method.
+    pub fn aggregate(reports: &[ScanReport]) -> Self {
+        let mut scores: Vec<f32> = reports.iter().map(|report| report.risk_score).collect();
+        scores.sort_by(f32::total_cmp);
+        let worst = reports.iter().reduce(|worst, report| {
+            if report.risk_score > worst.risk_score {
+                report
+            } else {
+                worst
+            }
+        });
+
+        let mut rules: BTreeMap<&str, RuleTally> = BTreeMap::new();
+        let mut families: BTreeMap<&str, FamilyContribution> = BTreeMap::new();
+        for report in reports {
+            let mut seen = Vec::new();
+            for finding in &report.findings {
+                let tally = rules
+                    .entry(finding.rule_id.as_str())
+                    .or_insert_with(|| RuleTally {
+                        rule_id: finding.rule_id.clone(),
+                        findings: 0,
+                        inputs: 0,
+                    });
+                tally.findings += 1;
+                if !seen.contains(&finding.rule_id.as_str()) {
+                    seen.push(finding.rule_id.as_str());
+                    tally.inputs += 1;
+                }
+            }
+            for family in &report.score_breakdown.family_contributions {
+                let total =
+                    families
+                        .entry(family.family.as_str())
+                        .or_insert_with(|| FamilyContribution {
+                            family: family.family.clone(),
+                            ..FamilyContribution::default()
+                        });
+                total.occurrences += family.occurrences;
+                total.raw_weight += family.raw_weight;
+                total.adjusted_weight += family.adjusted_weight;
+            }
+        }
+        let mut top_rules: Vec<RuleTally> = rules.into_values().collect();
+        top_rules.sort_by(|a, b| b.findings.cmp(&a.findings).then(a.rule_id.cmp(&b.rule_id)));
+        top_rules.truncate(SUMMARY_TOP_RULES);
+        let mut families: Vec<FamilyContribution> = families.into_values().collect();
+        families.sort_by(|a, b| {
+            b.adjusted_weight
+                .total_cmp(&a.adjusted_weight)
+                .then_with(|| a.family.cmp(&b.family))
+        });
+
+        Self {
+            inputs: reports.len(),
+            total_findings: reports.iter().map(|report| report.findings.len()).sum(),
+            worst_band: worst.map_or_else(
+                || RiskBand::from_score(0.0).name,
+                |report| report.risk_band.name.clone(),
+            ),
+            worst_source: worst.and_then(|report| report.source.clone()),
+            max_score: scores.last().copied().unwrap_or(0.0),
+            mean_score: if scores.is_empty() {
+                0.0
+            } else {
+                scores.iter().sum::<f32>() / scores.len() as f32
+            },
+            p50_score: percentile(&scores, 50.0),
+            p90_score: percentile(&scores, 90.0),
+            p95_score: percentile(&scores, 95.0),
+            top_rules,
+            families,
+        }
+    }
+}
+
+fn percentile(sorted: &[f32], pct: f32) -> f32 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (pct / 100.0 * sorted.len() as f32).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Render the human-readable summary section printed after batch scans.
+pub fn render_summary(summary: &ScanSummary) -> String {
     let mut out = String::new();
-    writeln!(
+    let _ = writeln!(
+        out,
+        "Summary: {} input(s), {} finding(s)",
+        summary.inputs, summary.total_findings
+    );
+    let _ = write!(
+        out,
+        "  Worst: {:.1} ({})",
+        summary.max_score, summary.worst_band
+    );
+    if let Some(source) = &summary.worst_source {
+        let _ = write!(out, " in {source}");
+    }
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "  Scores: mean {:.1} • p50 {:.1} • p90 {:.1} • p95 {:.1}",
+        summary.mean_score, summary.p50_score, summary.p90_score, summary.p95_score
+    );
+    if !summary.top_rules.is_empty() {
+        let _ = writeln!(out, "  Top Rules:");
+        for rule in &summary.top_rules {
+            let _ = writeln!(
+                out,
+                "    - {} ({}): {} finding(s) in {} input(s)",
+                rule.rule_id,
+                rule_family(&rule.rule_id),
+                rule.findings,
+                rule.inputs
+            );
+        }
+    }
+    if !summary.families.is_empty() {
+        let _ = writeln!(out, "  Families:");
+        for family in &summary.families {
+            let _ = writeln!(
+                out,
+                "    - {family:>12}: raw {raw:.1}, adjusted {adj:.1} (occurrences: {count})",
+                family = family.family,
+                raw = family.raw_weight,
+                adj = family.adjusted_weight,
+                count = family.occurrences
+            );
+        }
+    }
+    out
+}
+
+/// Findings bucketed by rule family, heaviest family first; ties keep first-seen order.
+fn group_by_family(report: &ScanReport) -> Vec<(&str, Vec<&Finding>)> {
+    let mut groups: Vec<(&str, Vec<&Finding>)> = Vec::new();
+    for finding in &report.findings {
+        let family = rule_family(&finding.rule_id);
+        match groups.iter_mut().find(|(name, _)| *name == family) {
+            Some((_, findings)) => findings.push(finding),
+            None => groups.push((family, vec![finding])),
+        }
+    }
+    let total = |findings: &[&Finding]| -> f32 { findings.iter().map(|f| f.weight).sum() };
+    groups.sort_by(|a, b| total(&b.1).total_cmp(&total(&a.1)));
+    groups
+}
+
+fn write_finding(
+    out: &mut String,
+    finding: &Finding,
+    options: &RenderOptions<'_>,
+    indent: &str,
+) -> fmt::Result {
+    let weight = format!("{:.1}", finding.weight);
+    let weight = if options.color {
+        paint(&weight, weight_color_code(finding.weight))
+    } else {
+        weight
+    };
+    write!(
         out,
-        "Risk Score: {:.1} ({:?})",
-        report.risk_score, report.risk_band
+        "{indent}- {id} [{weight}] @ {start}..{end}",
+        id = finding.rule_id,
+        start = finding.span.0,
+        end = finding.span.1,
     )?;
+    match finding.position {
+        Some(position) => writeln!(out, " (line {}, col {})", position.line, position.column)?,
+        None => writeln!(out)?,
+    }
+    if !finding.excerpt.trim().is_empty() {
+        let excerpt = sanitize_excerpt(&finding.excerpt);
+        let excerpt = match finding.excerpt_span {
+            Some((start, end))
+                if options.color
+                    && start < end
+                    && end <= excerpt.len()
+                    && excerpt.is_char_boundary(start)
+                    && excerpt.is_char_boundary(end) =>
+            {
+                format!(
+                    "{}{}{}",
+                    &excerpt[..start],
+                    paint(&excerpt[start..end], "1;4;31"),
+                    &excerpt[end..]
+                )
+            }
+            _ => excerpt,
+        };
+        writeln!(out, "{indent}  \"{excerpt}\"")?;
+    }
+    Ok(())
+}
+
+fn render_human(report: &ScanReport, options: &RenderOptions<'_>) -> anyhow::Result<String> {
+    let mut out = String::new();
+    let band = report.risk_band.name();
+    let band = if options.color {
+        paint(
+            band,
+            &format!("1;{}", band_color_code(report, options.bands)),
+        )
+    } else {
+        band.to_string()
+    };
+    writeln!(out, "Risk Score: {:.1} ({})", report.risk_score, band)?;
     writeln!(out, "Normalized Length: {} chars", report.normalized_len)?;
+    if let Some(mode) = report.redaction {
+        writeln!(out, "Excerpts: redacted ({mode})")?;
+    }
     writeln!(out)?;
 
     if report.findings.is_empty() {
         writeln!(out, "No findings detected.")?;
-    } else {
-        writeln!(out, "Findings:")?;
-        for finding in &report.findings {
-            writeln!(
+    } else if options.group_by == FindingGrouping::Family {
+        writeln!(out, "Findings by Family:")?;
+        for (family, findings) in group_by_family(report) {
+            let total: f32 = findings.iter().map(|finding| finding.weight).sum();
+            write!(
                 out,
-                "  - {id} [{weight:.1}] @ {start}..{end}",
-                id = finding.rule_id,
-                weight = finding.weight,
-                start = finding.span.0,
-                end = finding.span.1,
+                "  {family}: {} finding(s), weight {total:.1}",
+                findings.len()
             )?;
-            if !finding.excerpt.trim().is_empty() {
-                writeln!(out, "    \"{}\"", sanitize_excerpt(&finding.excerpt))?;
+            match report
+                .score_breakdown
+                .family_contributions
+                .iter()
+                .find(|contribution| contribution.family == family)
+            {
+                Some(contribution) => {
+                    writeln!(out, ", adjusted {:.1}", contribution.adjusted_weight)?
+                }
+                None => writeln!(out)?,
+            }
+            for finding in findings {
+                write_finding(&mut out, finding, options, "    ")?;
             }
         }
+    } else {
+        writeln!(out, "Findings:")?;
+        for finding in &report.findings {
+            write_finding(&mut out, finding, options, "  ")?;
+        }
+    }
+    if !report.truncated_findings.is_empty() {
+        let families: Vec<String> = report
+            .truncated_findings
+            .iter()
+            .map(|(family, count)| format!("{family}: {count}"))
+            .collect();
+        writeln!(
+            out,
+            "  ... {} more finding(s) not shown ({})",
+            report.truncated_count(),
+            families.join(", ")
+        )?;
     }
 
     writeln!(out)?;
@@ -67,15 +610,174 @@ fn render_human(report: &ScanReport) -> anyhow::Result<String> {
         report.score_breakdown.length_factor, report.score_breakdown.adjusted_total
     )?;
 
+    if !report.rule_packs.is_empty() {
+        writeln!(out, "\nRule Packs:")?;
+        for pack in &report.rule_packs {
+            write!(out, "  - {}", pack.name)?;
+            if let Some(version) = &pack.version {
+                write!(out, " {version}")?;
+            }
+            write!(out, " sha256:{}", pack.digest)?;
+            if let Some(author) = &pack.author {
+                write!(out, " by {author}")?;
+            }
+            if let Some(source) = &pack.source {
+                write!(out, " ({source})")?;
+            }
+            writeln!(out)?;
+        }
+    }
+
+    if let Some(meta) = &report.metadata {
+        writeln!(
+            out,
+            "\nScanned {} • llm-guard {} • {} rules (sha256:{}) • input sha256:{}",
+            meta.scanned_at,
+            meta.engine_version,
+            meta.rule_count,
+            short_digest(&meta.rule_set_digest),
+            short_digest(&meta.input_digest)
+        )?;
+    }
+
     if let Some(verdict) = &report.llm_verdict {
-        writeln!(out, "\nLLM Verdict: {}", verdict.label)?;
+        match &verdict.provider {
+            Some(provider) => writeln!(out, "\nLLM Verdict: {} (via {provider})", verdict.label)?,
+            None => writeln!(out, "\nLLM Verdict: {}", verdict.label)?,
+        }
         writeln!(out, "  Rationale: {}", verdict.rationale)?;
         writeln!(out, "  Mitigation: {}", verdict.mitigation)?;
+        if let Some(points) = report.verdict_adjustment {
+            writeln!(
+                out,
+                "  Score adjustment: {points:+.1} (label `{}`)",
+                verdict.label
+            )?;
+        }
+        if let Some(blend) = &report.score_breakdown.llm_adjustment {
+            writeln!(
+                out,
+                "  Blended into score ({}): {:+.1}, {} -> {}",
+                blend.mode, blend.points, blend.from_band, blend.to_band
+            )?;
+        }
+        if let Some(usage) = &verdict.usage {
+            write!(out, "  Usage: {} prompt", usage.prompt_tokens)?;
+            if usage.cache_read_tokens.is_some() || usage.cache_write_tokens.is_some() {
+                write!(
+                    out,
+                    " ({} cache read, {} cache write)",
+                    usage.cache_read_tokens.unwrap_or(0),
+                    usage.cache_write_tokens.unwrap_or(0)
+                )?;
+            }
+            write!(out, " + {} completion tokens", usage.completion_tokens)?;
+            match usage.cost_usd {
+                Some(cost) => writeln!(out, " (~${cost:.4})")?,
+                None => writeln!(out)?,
+            }
+        }
+        if let Some(meta) = &verdict.meta {
+            write!(out, "  Latency: {} ms", meta.latency_ms)?;
+            match (meta.attempts, meta.http_status) {
+                (Some(attempts), Some(status)) => {
+                    writeln!(out, " ({attempts} attempt(s), HTTP {status})")?
+                }
+                (Some(attempts), None) => writeln!(out, " ({attempts} attempt(s))")?,
+                _ => writeln!(out)?,
+            }
+        }
+        if let Some(ensemble) = &verdict.ensemble {
+            let votes = ensemble
+                .votes
+                .iter()
+                .map(|vote| match &vote.label {
+                    Some(label) => format!("{}={label}", vote.provider),
+                    None => format!("{}=error", vote.provider),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(
+                out,
+                "  Ensemble: {votes}{}",
+                if ensemble.disagreement {
+                    " (providers disagree)"
+                } else {
+                    ""
+                }
+            )?;
+        }
+    }
+    if let Some(degraded) = &report.llm_degraded {
+        writeln!(
+            out,
+            "\nLLM Verdict: unavailable ({}; heuristic score only)",
+            if degraded.circuit_open {
+                "circuit open"
+            } else {
+                "provider failed"
+            }
+        )?;
+        writeln!(out, "  Reason: {}", degraded.reason)?;
+    }
+    if report.llm_consulted == Some(false) {
+        writeln!(
+            out,
+            "\nLLM Verdict: not requested (score outside the LLM gray zone)"
+        )?;
     }
 
     Ok(out)
 }
 
+/// First 12 hex digits, enough to tell digests apart at a glance.
+fn short_digest(digest: &str) -> &str {
+    digest.get(..12).unwrap_or(digest)
+}
+
+fn paint(text: &str, code: &str) -> String {
+    format!("\x1b[{code}m{text}\x1b[0m")
+}
+
+/// SGR foreground code for the report's band: its configured color, else green, yellow, or red
+/// by where the band sits in its set.
+fn band_color_code(report: &ScanReport, bands: Option<&BandSet>) -> &'static str {
+    let bands = bands.map_or_else(|| Cow::Owned(BandSet::default()), Cow::Borrowed);
+    let configured = bands
+        .definition(&report.risk_band)
+        .and_then(|band| band.color.as_deref())
+        .and_then(ansi_color_code);
+    configured.unwrap_or(match bands.tier(&report.risk_band) {
+        BandTier::Lowest => "32",
+        BandTier::Intermediate => "33",
+        BandTier::Highest => "31",
+    })
+}
+
+fn weight_color_code(weight: f32) -> &'static str {
+    if weight >= 40.0 {
+        "31"
+    } else if weight >= 20.0 {
+        "33"
+    } else {
+        "32"
+    }
+}
+
+fn ansi_color_code(name: &str) -> Option<&'static str> {
+    Some(match name.to_ascii_lowercase().as_str() {
+        "black" => "30",
+        "red" => "31",
+        "green" => "32",
+        "yellow" => "33",
+        "blue" => "34",
+        "magenta" => "35",
+        "cyan" => "36",
+        "white" => "37",
+        _ => return None,
+    })
+}
+
 fn sanitize_excerpt(input: &str) -> String {
     input
         .chars()
@@ -87,34 +789,187 @@ fn sanitize_excerpt(input: &str) -> String {
 }
 
 #[derive(Debug, Serialize)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(rename = "ScanReport")
+)]
 struct JsonReport<'a> {
+    schema_version: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<&'a str>,
     risk_score: f32,
-    risk_band: RiskBand,
+    risk_band: &'a str,
+    risk_band_index: usize,
     normalized_len: usize,
     findings: &'a [Finding],
     family_contributions: &'a [FamilyContribution],
     breakdown: &'a crate::scanner::ScoreBreakdown,
     llm_verdict: Option<&'a crate::scanner::LlmVerdict>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    llm_degraded: Option<&'a LlmDegradation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    llm_consulted: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verdict_adjustment: Option<f32>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    rule_packs: &'a [PackProvenance],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<&'a ReportMetadata>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    redaction: Option<ExcerptRedaction>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    truncated_findings: &'a BTreeMap<String, usize>,
+}
+
+#[cfg(feature = "templates")]
+#[derive(Serialize)]
+struct TemplateContext<'a> {
+    band_name: &'a str,
+    #[serde(flatten)]
+    report: JsonReport<'a>,
 }
 
 impl<'a> From<&'a ScanReport> for JsonReport<'a> {
     fn from(report: &'a ScanReport) -> Self {
         Self {
+            schema_version: REPORT_SCHEMA_VERSION,
+            source: report.source.as_deref(),
             risk_score: report.risk_score,
-            risk_band: report.risk_band,
+            risk_band: report.risk_band.name(),
+            risk_band_index: report.risk_band.index,
             normalized_len: report.normalized_len,
             findings: &report.findings,
             family_contributions: &report.score_breakdown.family_contributions,
             breakdown: &report.score_breakdown,
             llm_verdict: report.llm_verdict.as_ref(),
+            llm_degraded: report.llm_degraded.as_ref(),
+            llm_consulted: report.llm_consulted,
+            verdict_adjustment: report.verdict_adjustment,
+            rule_packs: &report.rule_packs,
+            metadata: report.metadata.as_ref(),
+            redaction: report.redaction,
+            truncated_findings: &report.truncated_findings,
         }
     }
 }
 
+/// Owned counterpart of [`JsonReport`] at [`REPORT_SCHEMA_VERSION`], read by [`parse_report`].
+#[derive(Deserialize)]
+struct StoredReport {
+    #[serde(default)]
+    source: Option<String>,
+    risk_score: f32,
+    risk_band: String,
+    risk_band_index: usize,
+    normalized_len: usize,
+    findings: Vec<Finding>,
+    breakdown: ScoreBreakdown,
+    #[serde(default)]
+    llm_verdict: Option<LlmVerdict>,
+    #[serde(default)]
+    llm_degraded: Option<LlmDegradation>,
+    #[serde(default)]
+    llm_consulted: Option<bool>,
+    #[serde(default)]
+    verdict_adjustment: Option<f32>,
+    #[serde(default)]
+    rule_packs: Vec<PackProvenance>,
+    #[serde(default)]
+    metadata: Option<ReportMetadata>,
+    #[serde(default)]
+    redaction: Option<ExcerptRedaction>,
+    #[serde(default)]
+    truncated_findings: BTreeMap<String, usize>,
+}
+
+impl From<StoredReport> for ScanReport {
+    fn from(stored: StoredReport) -> Self {
+        let mut report = ScanReport::new(
+            stored.risk_score,
+            stored.findings,
+            stored.normalized_len,
+            stored.llm_verdict,
+            stored.breakdown,
+        );
+        report.risk_band = RiskBand {
+            name: stored.risk_band,
+            index: stored.risk_band_index,
+        };
+        report.llm_degraded = stored.llm_degraded;
+        report.llm_consulted = stored.llm_consulted;
+        report.verdict_adjustment = stored.verdict_adjustment;
+        report.source = stored.source;
+        report.rule_packs = stored.rule_packs;
+        report.metadata = stored.metadata;
+        report.redaction = stored.redaction;
+        report.truncated_findings = stored.truncated_findings;
+        report
+    }
+}
+
+/// Read a report written by the `json`/`jsonl` formats of any llm-guard version.
+///
+/// Older layouts are upgraded step by step to [`REPORT_SCHEMA_VERSION`] before decoding;
+/// reports from a newer llm-guard are rejected rather than misread. The `signature` block of
+/// signed reports is ignored (check it with [`verify_report`] first).
+pub fn parse_report(json: &str) -> anyhow::Result<ScanReport> {
+    use anyhow::{bail, Context};
+
+    let mut value: serde_json::Value =
+        serde_json::from_str(json.trim()).context("report is not valid JSON")?;
+    let object = value
+        .as_object_mut()
+        .context("report must be a JSON object")?;
+    let version = match object.remove("schema_version") {
+        None => 1,
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .context("`schema_version` must be a positive integer")?,
+    };
+    if version > REPORT_SCHEMA_VERSION {
+        bail!(
+            "report schema version {version} is newer than this llm-guard supports \
+             ({REPORT_SCHEMA_VERSION}); upgrade llm-guard to read it"
+        );
+    }
+    // Upgrade steps go here, one per version bump (`if version < 4 { ... }`). Version 1 only
+    // lacked `schema_version`, so it needs no rewriting.
+    if version < 3 {
+        upgrade_band_fields(object);
+    }
+    object.remove("signature");
+    object.remove("family_contributions");
+    let stored: StoredReport =
+        serde_json::from_value(value).context("report does not match the expected layout")?;
+    Ok(stored.into())
+}
+
+/// Version 2 kept a custom band's name in `band` next to the built-in `low`/`medium`/`high`
+/// in `risk_band`, and recorded no band position. The built-in band's position is the best
+/// available index: it matches whenever the report used the default bands.
+fn upgrade_band_fields(object: &mut serde_json::Map<String, serde_json::Value>) {
+    use serde_json::Value;
+
+    let builtin = object
+        .get("risk_band")
+        .and_then(Value::as_str)
+        .and_then(|name| {
+            ["low", "medium", "high"]
+                .iter()
+                .position(|band| *band == name)
+        });
+    object.insert("risk_band_index".into(), builtin.unwrap_or(0).into());
+    if let Some(band @ Value::String(_)) = object.remove("band") {
+        object.insert("risk_band".into(), band);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::scanner::{FamilyContribution, Finding, ScanReport, ScoreBreakdown};
+    use crate::scanner::{FamilyContribution, Finding, Position, ScanReport, ScoreBreakdown};
 
     fn sample_report() -> ScanReport {
         let findings = vec![Finding {
@@ -122,6 +977,8 @@ mod tests {
             span: (0, 10),
             excerpt: "example excerpt".into(),
             weight: 10.0,
+            excerpt_span: Some((0, 7)),
+            position: Some(Position { line: 2, column: 5 }),
         }];
         let breakdown = ScoreBreakdown {
             raw_total: 10.0,
@@ -133,6 +990,7 @@ mod tests {
                 raw_weight: 10.0,
                 adjusted_weight: 10.0,
             }],
+            llm_adjustment: None,
         };
         ScanReport::from_breakdown(
             findings,
@@ -149,9 +1007,323 @@ mod tests {
         let output = render_report(&report, OutputFormat::Human).unwrap();
         assert!(output.contains("Risk Score"));
         assert!(output.contains("TEST_RULE"));
+        assert!(output.contains("@ 0..10 (line 2, col 5)"));
         assert!(output.contains("Family Contributions"));
     }
 
+    #[test]
+    fn verdict_blend_and_meta_are_rendered_and_round_trip() {
+        let mut report = sample_report();
+        report.llm_verdict = Some(LlmVerdict {
+            label: "malicious".into(),
+            ..Default::default()
+        });
+        assert!(report.apply_verdict_blend(
+            crate::scanner::blend::VerdictBlend::Escalate,
+            "malicious",
+            &crate::llm::LabelSet::default(),
+            &crate::scanner::RiskConfig::default(),
+        ));
+        report.llm_verdict.as_mut().unwrap().meta = Some(crate::scanner::EnrichmentMeta {
+            latency_ms: 812,
+            attempts: Some(2),
+            http_status: Some(200),
+        });
+        let output = render_report(&report, OutputFormat::Human).unwrap();
+        assert!(output.contains("Blended into score (escalate): +50.0, low -> high"));
+        assert!(output.contains("Latency: 812 ms (2 attempt(s), HTTP 200)"));
+
+        let json = render_report(&report, OutputFormat::Json).unwrap();
+        let parsed = parse_report(&json).unwrap();
+        assert_eq!(parsed.risk_band, report.risk_band);
+        assert_eq!(
+            parsed.score_breakdown.llm_adjustment,
+            report.score_breakdown.llm_adjustment
+        );
+        assert_eq!(
+            parsed.llm_verdict.unwrap().meta,
+            report.llm_verdict.as_ref().unwrap().meta
+        );
+    }
+
+    #[test]
+    fn skipped_llm_call_is_rendered_and_round_trips() {
+        let mut report = sample_report();
+        assert!(!render_report(&report, OutputFormat::Json)
+            .unwrap()
+            .contains("llm_consulted"));
+
+        report.llm_consulted = Some(false);
+        let output = render_report(&report, OutputFormat::Human).unwrap();
+        assert!(output.contains("LLM Verdict: not requested (score outside the LLM gray zone)"));
+        let json = render_report(&report, OutputFormat::Json).unwrap();
+        assert_eq!(parse_report(&json).unwrap().llm_consulted, Some(false));
+    }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn report_schema_describes_json_output() {
+        let schema: serde_json::Value = serde_json::from_str(&report_schema().unwrap()).unwrap();
+        assert_eq!(schema["title"], "ScanReport");
+        let required = schema["required"].as_array().unwrap();
+        assert!(required.contains(&"risk_score".into()));
+        assert!(required.contains(&"findings".into()));
+        assert!(schema["properties"]["breakdown"].is_object());
+        assert!(schema["definitions"]["Finding"]["properties"]["excerpt"].is_object());
+    }
+
+    #[test]
+    fn parse_report_reads_current_and_legacy_reports() {
+        let mut report = sample_report();
+        report.source = Some("prompt.txt".into());
+        report.risk_band = RiskBand {
+            name: "warning".into(),
+            index: 2,
+        };
+        let json = render_report(&report, OutputFormat::Json).unwrap();
+        assert!(json.contains("\"schema_version\": 3"));
+        let parsed = parse_report(&json).unwrap();
+        assert_eq!(parsed.source.as_deref(), Some("prompt.txt"));
+        assert_eq!(parsed.risk_band, report.risk_band);
+        assert_eq!(parsed.findings[0].position, report.findings[0].position);
+        assert_eq!(render_report(&parsed, OutputFormat::Json).unwrap(), json);
+
+        let legacy = r#"{
+            "risk_score": 30.0, "risk_band": "medium", "normalized_len": 12,
+            "findings": [{"rule_id": "INSTR_OVERRIDE", "span": [0, 6], "excerpt": "ignore",
+                          "weight": 30.0}],
+            "family_contributions": [],
+            "breakdown": {"raw_total": 30.0, "adjusted_total": 30.0, "length_factor": 1.0,
+                          "family_contributions": []},
+            "llm_verdict": null
+        }"#;
+        let parsed = parse_report(legacy).unwrap();
+        assert_eq!(parsed.risk_band, RiskBand::from_score(30.0));
+        assert_eq!(parsed.findings[0].rule_id, "INSTR_OVERRIDE");
+        assert!(parsed.metadata.is_none());
+
+        let v2 = legacy.replacen('{', r#"{"schema_version": 2, "band": "warning","#, 1);
+        let parsed = parse_report(&v2).unwrap();
+        assert_eq!(
+            (parsed.risk_band.name(), parsed.risk_band.index),
+            ("warning", 1)
+        );
+
+        let future = json.replace("\"schema_version\": 3", "\"schema_version\": 99");
+        let err = parse_report(&future).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("newer than this llm-guard supports"));
+    }
+
+    #[test]
+    fn human_report_groups_findings_by_family() {
+        let mut report = sample_report();
+        let extra = |rule_id: &str, weight| Finding {
+            rule_id: "packs:".to_string() + rule_id,
+            weight,
+            position: None,
+            ..report.findings[0].clone()
+        };
+        let findings = vec![extra("LEAK_PROMPT", 30.0), extra("TEST_OTHER", 5.0)];
+        report.findings.extend(findings);
+        let options = RenderOptions {
+            group_by: FindingGrouping::Family,
+            ..RenderOptions::default()
+        };
+        let output = render_report_with(&report, &OutputFormat::Human, &options).unwrap();
+        let leak = output.find("  LEAK: 1 finding(s), weight 30.0\n").unwrap();
+        let test = output
+            .find("  TEST: 2 finding(s), weight 15.0, adjusted 10.0\n")
+            .unwrap();
+        assert!(leak < test, "{output}");
+        assert!(output.contains("    - packs:TEST_OTHER [5.0]"), "{output}");
+        assert!(output.contains("      \"example excerpt\""), "{output}");
+        assert_eq!("family".parse(), Ok(FindingGrouping::Family));
+    }
+
+    #[cfg(feature = "formats")]
+    #[test]
+    fn metadata_is_rendered_in_every_format() {
+        let mut report = sample_report();
+        report.metadata = Some(ReportMetadata {
+            scanned_at: "2024-05-01T12:00:00Z".into(),
+            engine_version: "9.9.9".into(),
+            rule_count: 3,
+            rule_set_digest: "ab".repeat(32),
+            input_digest: "cd".repeat(32),
+        });
+
+        let human = render_report(&report, OutputFormat::Human).unwrap();
+        assert!(human.contains(
+            "Scanned 2024-05-01T12:00:00Z • llm-guard 9.9.9 • 3 rules (sha256:abababababab)"
+        ));
+        let json: serde_json::Value =
+            serde_json::from_str(&render_report(&report, OutputFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["metadata"]["engine_version"], "9.9.9");
+        let csv = render_csv(&report, false).unwrap();
+        assert!(csv
+            .trim_end()
+            .ends_with(&format!("2024-05-01T12:00:00Z,9.9.9,{}", "ab".repeat(32))));
+        let cef = render_report(&report, OutputFormat::Cef).unwrap();
+        assert!(cef.contains("cs5=2024-05-01T12:00:00Z"));
+    }
+
+    #[test]
+    fn custom_band_label_is_rendered() {
+        let mut report = sample_report();
+        let bands = crate::scanner::bands::BandSet::new(vec![
+            crate::scanner::bands::BandDefinition::new("info", 0.0, 0),
+            crate::scanner::bands::BandDefinition::new("notice", 5.0, 0),
+        ])
+        .unwrap();
+        report.apply_bands(&bands);
+        let human = render_report(&report, OutputFormat::Human).unwrap();
+        assert!(human.contains("(notice)"));
+        let json = render_report(&report, OutputFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["risk_band"], "notice");
+        assert_eq!(value["risk_band_index"], 1);
+        assert!(value.get("band").is_none());
+    }
+
+    #[cfg(feature = "formats")]
+    #[test]
+    fn csv_report_quotes_excerpts() {
+        let mut report = sample_report();
+        report.source = Some("prompts/a.txt".into());
+        report.findings[0].excerpt = "ignore, all\n\"previous\"".into();
+        let output = render_report(&report, OutputFormat::Csv).unwrap();
+
+        let mut reader = csv::Reader::from_reader(output.as_bytes());
+        let headers = reader.headers().unwrap().clone();
+        assert_eq!(&headers[0], "source");
+        let rows: Vec<_> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(&rows[0][0], "prompts/a.txt");
+        assert_eq!(&rows[0][1], "TEST_RULE");
+        assert_eq!(&rows[0][5], "ignore, all\n\"previous\"");
+        assert_eq!(&rows[0][6], "low");
+        assert_eq!((&rows[0][7], &rows[0][8]), ("2", "5"));
+
+        let body = render_csv(&report, false).unwrap();
+        assert!(body.starts_with("prompts/a.txt,TEST_RULE"));
+        assert_eq!("CSV".parse::<OutputFormat>(), Ok(OutputFormat::Csv));
+    }
+
+    #[test]
+    fn color_output_highlights_band_weight_and_match() {
+        let mut report = sample_report();
+        let plain = render_report(&report, OutputFormat::Human).unwrap();
+        assert!(!plain.contains('\x1b'));
+
+        let options = RenderOptions {
+            color: true,
+            ..RenderOptions::default()
+        };
+        let colored = render_report_with(&report, &OutputFormat::Human, &options).unwrap();
+        assert!(colored.contains("(\x1b[1;32mlow\x1b[0m)"));
+        assert!(colored.contains("[\x1b[32m10.0\x1b[0m]"));
+        assert!(colored.contains("\"\x1b[1;4;31mexample\x1b[0m excerpt\""));
+
+        let bands =
+            crate::scanner::bands::BandSet::new(vec![crate::scanner::bands::BandDefinition::new(
+                "info", 0.0, 0,
+            )
+            .with_color("blue")])
+            .unwrap();
+        report.apply_bands(&bands);
+        let options = RenderOptions {
+            color: true,
+            bands: Some(&bands),
+            ..RenderOptions::default()
+        };
+        let colored = render_report_with(&report, &OutputFormat::Human, &options).unwrap();
+        assert!(colored.contains("\x1b[1;34minfo\x1b[0m"));
+    }
+
+    #[cfg(feature = "templates")]
+    #[test]
+    fn template_renders_report_fields() {
+        let report = sample_report();
+        let template = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            template.path(),
+            "*{{band_name}}* {{risk_score}}\n{{#each findings}}- {{rule_id}} <{{excerpt}}>\n{{/each}}",
+        )
+        .unwrap();
+        let output = render_report(
+            &report,
+            OutputFormat::Template(template.path().to_path_buf()),
+        )
+        .unwrap();
+        assert!(output.starts_with("*low* "));
+        assert!(output.contains("- TEST_RULE <example excerpt>"));
+
+        let missing = OutputFormat::Template(PathBuf::from("/nonexistent/report.hbs"));
+        assert!(render_report(&report, missing).is_err());
+    }
+
+    #[test]
+    fn summary_aggregates_scores_rules_and_families() {
+        let mut quiet = sample_report();
+        quiet.source = Some("a.txt".into());
+        let mut noisy = sample_report();
+        noisy.source = Some("b.txt".into());
+        noisy.risk_score = 80.0;
+        noisy.risk_band = RiskBand::from_score(80.0);
+        noisy.findings.push(noisy.findings[0].clone());
+        let clean = ScanReport::new(0.0, Vec::new(), 5, None, ScoreBreakdown::default());
+
+        let summary = ScanSummary::aggregate(&[quiet, noisy, clean]);
+        assert_eq!(summary.inputs, 3);
+        assert_eq!(summary.total_findings, 3);
+        assert_eq!(summary.worst_band, "high");
+        assert_eq!(summary.worst_source.as_deref(), Some("b.txt"));
+        assert_eq!(summary.max_score, 80.0);
+        assert_eq!(summary.p50_score, sample_report().risk_score);
+        assert_eq!(summary.p95_score, 80.0);
+        assert_eq!(
+            summary.top_rules,
+            vec![RuleTally {
+                rule_id: "TEST_RULE".into(),
+                findings: 3,
+                inputs: 2,
+            }]
+        );
+        assert_eq!(summary.families[0].occurrences, 2);
+        assert_eq!(summary.families[0].adjusted_weight, 20.0);
+        assert!(render_summary(&summary).contains("Worst: 80.0 (high) in b.txt"));
+
+        let empty = ScanSummary::aggregate(&[]);
+        assert_eq!(empty.worst_band, "low");
+        assert_eq!(empty.mean_score, 0.0);
+    }
+
+    #[test]
+    fn summary_line_lists_score_band_count_and_source() {
+        let mut report = sample_report();
+        assert_eq!(render_summary_line(&report), "10.0 low 1 -\n");
+        report.source = Some("prompts/my file.txt".into());
+        assert_eq!(
+            render_report(&report, OutputFormat::Summary).unwrap(),
+            "10.0 low 1 prompts/my file.txt\n"
+        );
+    }
+
+    #[test]
+    fn jsonl_report_is_a_single_line() {
+        let mut report = sample_report();
+        report.source = Some("a.txt".into());
+        let output = render_report(&report, OutputFormat::Jsonl).unwrap();
+        assert!(output.ends_with('\n'));
+        assert_eq!(output.lines().count(), 1);
+        let value: serde_json::Value = serde_json::from_str(output.trim_end()).unwrap();
+        assert_eq!(value["source"], "a.txt");
+        assert_eq!(value["findings"][0]["rule_id"], "TEST_RULE");
+        assert_eq!("ndjson".parse::<OutputFormat>(), Ok(OutputFormat::Jsonl));
+    }
+
     #[test]
     fn json_report_serializes() {
         let report = sample_report();
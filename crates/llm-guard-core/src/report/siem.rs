@@ -0,0 +1,199 @@
+use std::fmt::Write;
+
+use crate::scanner::ScanReport;
+
+/// Device identity written into CEF/LEEF headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SiemFields<'a> {
+    pub vendor: &'a str,
+    pub product: &'a str,
+    pub version: &'a str,
+}
+
+impl Default for SiemFields<'_> {
+    fn default() -> Self {
+        Self {
+            vendor: "LLM Guard",
+            product: "llm-guard",
+            version: env!("CARGO_PKG_VERSION"),
+        }
+    }
+}
+
+/// Render one ArcSight CEF event per report.
+///
+/// The signature id is `llm-guard:<band>`; severity is the risk score scaled to 0–10.
+pub fn render_cef(report: &ScanReport, fields: &SiemFields<'_>) -> String {
+    let band = report.risk_band.name();
+    let mut out = format!(
+        "CEF:0|{}|{}|{}|{}|{}|{}|",
+        cef_header(fields.vendor),
+        cef_header(fields.product),
+        cef_header(fields.version),
+        cef_header(&format!("llm-guard:{band}")),
+        cef_header(&format!("Prompt injection risk {band}")),
+        severity(report)
+    );
+    let mut extension = vec![
+        ("cs1Label", "riskBand".to_string()),
+        ("cs1", band.to_string()),
+        ("cfp1Label", "riskScore".to_string()),
+        ("cfp1", format!("{:.1}", report.risk_score)),
+        ("cnt", report.findings.len().to_string()),
+    ];
+    if !report.findings.is_empty() {
+        extension.push(("cs2Label", "ruleIds".to_string()));
+        extension.push(("cs2", rule_ids(report)));
+    }
+    if let Some(verdict) = &report.llm_verdict {
+        extension.push(("cs3Label", "llmVerdict".to_string()));
+        extension.push(("cs3", verdict.label.clone()));
+    }
+    if let Some(meta) = &report.metadata {
+        extension.push(("cs4Label", "ruleSetDigest".to_string()));
+        extension.push(("cs4", meta.rule_set_digest.clone()));
+        extension.push(("cs5Label", "scannedAt".to_string()));
+        extension.push(("cs5", meta.scanned_at.clone()));
+        extension.push(("fileHash", meta.input_digest.clone()));
+    }
+    if let Some(source) = &report.source {
+        extension.push(("fname", source.clone()));
+    }
+    let pairs: Vec<String> = extension
+        .into_iter()
+        .map(|(key, value)| format!("{key}={}", cef_extension(&value)))
+        .collect();
+    out.push_str(&pairs.join(" "));
+    out.push('\n');
+    out
+}
+
+/// Render one IBM QRadar LEEF 1.0 event per report (tab-delimited attributes).
+pub fn render_leef(report: &ScanReport, fields: &SiemFields<'_>) -> String {
+    let band = report.risk_band.name();
+    let mut out = format!(
+        "LEEF:1.0|{}|{}|{}|{}|",
+        leef_header(fields.vendor),
+        leef_header(fields.product),
+        leef_header(fields.version),
+        leef_header(&format!("llm-guard:{band}"))
+    );
+    let mut attributes = vec![
+        ("cat", band.to_string()),
+        ("sev", severity(report).max(1).to_string()),
+        ("riskScore", format!("{:.1}", report.risk_score)),
+        ("findingCount", report.findings.len().to_string()),
+    ];
+    if !report.findings.is_empty() {
+        attributes.push(("ruleIds", rule_ids(report)));
+    }
+    if let Some(verdict) = &report.llm_verdict {
+        attributes.push(("llmVerdict", verdict.label.clone()));
+    }
+    if let Some(meta) = &report.metadata {
+        attributes.push(("devTime", meta.scanned_at.clone()));
+        attributes.push(("devTimeFormat", "yyyy-MM-dd'T'HH:mm:ssX".to_string()));
+        attributes.push(("ruleSetDigest", meta.rule_set_digest.clone()));
+        attributes.push(("inputDigest", meta.input_digest.clone()));
+    }
+    if let Some(source) = &report.source {
+        attributes.push(("resource", source.clone()));
+    }
+    for (idx, (key, value)) in attributes.iter().enumerate() {
+        if idx > 0 {
+            out.push('\t');
+        }
+        let _ = write!(out, "{key}={}", leef_value(value));
+    }
+    out.push('\n');
+    out
+}
+
+fn severity(report: &ScanReport) -> u8 {
+    (report.risk_score / 10.0).round().clamp(0.0, 10.0) as u8
+}
+
+fn rule_ids(report: &ScanReport) -> String {
+    let mut ids: Vec<&str> = report
+        .findings
+        .iter()
+        .map(|finding| finding.rule_id.as_str())
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    ids.join(",")
+}
+
+fn cef_header(value: &str) -> String {
+    single_line(value).replace('\\', "\\\\").replace('|', "\\|")
+}
+
+fn cef_extension(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('=', "\\=")
+        .replace('\r', "\\r")
+        .replace('\n', "\\n")
+}
+
+fn leef_header(value: &str) -> String {
+    single_line(value).replace('|', "/")
+}
+
+fn leef_value(value: &str) -> String {
+    single_line(value).replace('\t', " ")
+}
+
+fn single_line(value: &str) -> String {
+    value.replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{Finding, ScoreBreakdown};
+
+    fn report() -> ScanReport {
+        let finding = |rule_id: &str| Finding {
+            rule_id: rule_id.into(),
+            span: (0, 4),
+            excerpt: "x".into(),
+            weight: 30.0,
+            excerpt_span: None,
+            position: None,
+        };
+        let mut report = ScanReport::new(
+            72.0,
+            vec![finding("PROMPT_LEAK"), finding("INSTR_OVERRIDE")],
+            120,
+            None,
+            ScoreBreakdown::default(),
+        );
+        report.source = Some("logs/a=b.txt".into());
+        report
+    }
+
+    #[test]
+    fn cef_event_escapes_header_and_extension() {
+        let fields = SiemFields {
+            vendor: "Acme|Sec",
+            ..SiemFields::default()
+        };
+        let event = render_cef(&report(), &fields);
+        assert!(event.starts_with("CEF:0|Acme\\|Sec|llm-guard|"));
+        assert!(event.contains("|llm-guard:high|Prompt injection risk high|7|"));
+        assert!(event.contains("cfp1=72.0"));
+        assert!(event.contains("cs2=INSTR_OVERRIDE,PROMPT_LEAK"));
+        assert!(event.contains("fname=logs/a\\=b.txt"));
+        assert_eq!(event.lines().count(), 1);
+    }
+
+    #[test]
+    fn leef_event_uses_tab_delimited_attributes() {
+        let event = render_leef(&report(), &SiemFields::default());
+        assert!(event.starts_with("LEEF:1.0|LLM Guard|llm-guard|"));
+        assert!(event.contains("|llm-guard:high|cat=high\tsev=7\triskScore=72.0"));
+        assert!(event.contains("\tresource=logs/a=b.txt"));
+        assert_eq!(event.lines().count(), 1);
+    }
+}
@@ -0,0 +1,417 @@
+use std::{fmt, fs, path::Path, str::FromStr};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{JsonReport, OutputFormat};
+use crate::scanner::ScanReport;
+
+/// Key types accepted by [`ReportSigner`] and [`VerificationKey`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SignatureAlgorithm {
+    /// Shared-secret HMAC-SHA256; the verifier needs the same secret.
+    #[serde(rename = "hmac-sha256")]
+    HmacSha256,
+    /// Ed25519 signature; the verifier only needs the public key.
+    #[serde(rename = "ed25519")]
+    Ed25519,
+}
+
+impl FromStr for SignatureAlgorithm {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "hmac-sha256" | "hmac" => Ok(Self::HmacSha256),
+            "ed25519" => Ok(Self::Ed25519),
+            other => Err(format!(
+                "unknown signature algorithm `{other}` (expected hmac-sha256 or ed25519)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SignatureAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::HmacSha256 => "hmac-sha256",
+            Self::Ed25519 => "ed25519",
+        })
+    }
+}
+
+/// Signature block embedded under `signature` in signed JSON reports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportSignature {
+    pub algorithm: SignatureAlgorithm,
+    /// Hex-encoded Ed25519 public key of the signer (absent for HMAC).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+    /// Hex-encoded MAC or signature over the canonical report (see [`sign_report`]).
+    pub value: String,
+}
+
+/// Secret key material used to sign reports.
+#[derive(Clone)]
+pub struct ReportSigner {
+    algorithm: SignatureAlgorithm,
+    key: Vec<u8>,
+}
+
+impl fmt::Debug for ReportSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReportSigner")
+            .field("algorithm", &self.algorithm)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ReportSigner {
+    /// Build a signer from raw key bytes: any non-empty secret for HMAC, a 32-byte seed for
+    /// Ed25519.
+    pub fn new(algorithm: SignatureAlgorithm, key: Vec<u8>) -> Result<Self> {
+        check_key(algorithm, &key, "signing key")?;
+        Ok(Self { algorithm, key })
+    }
+
+    /// Read a key file: the HMAC secret as text (surrounding whitespace trimmed), or the
+    /// Ed25519 seed as 64 hex characters.
+    pub fn from_file(algorithm: SignatureAlgorithm, path: &Path) -> Result<Self> {
+        let key = read_key(algorithm, path, "signing key")?;
+        Self::new(algorithm, key)
+    }
+
+    pub fn algorithm(&self) -> SignatureAlgorithm {
+        self.algorithm
+    }
+
+    fn sign(&self, payload: &[u8]) -> Result<ReportSignature> {
+        crypto::sign(self.algorithm, &self.key, payload)
+    }
+}
+
+/// Key material used by [`verify_report`].
+#[derive(Clone)]
+pub struct VerificationKey {
+    algorithm: SignatureAlgorithm,
+    key: Vec<u8>,
+}
+
+impl fmt::Debug for VerificationKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VerificationKey")
+            .field("algorithm", &self.algorithm)
+            .finish_non_exhaustive()
+    }
+}
+
+impl VerificationKey {
+    /// Build a key from raw bytes: the shared secret for HMAC, the 32-byte public key for
+    /// Ed25519.
+    pub fn new(algorithm: SignatureAlgorithm, key: Vec<u8>) -> Result<Self> {
+        check_key(algorithm, &key, "verification key")?;
+        Ok(Self { algorithm, key })
+    }
+
+    /// Read a key file in the same layout as [`ReportSigner::from_file`], holding the Ed25519
+    /// public key instead of the seed.
+    pub fn from_file(algorithm: SignatureAlgorithm, path: &Path) -> Result<Self> {
+        let key = read_key(algorithm, path, "verification key")?;
+        Self::new(algorithm, key)
+    }
+}
+
+/// Render `report` as JSON (`Json`) or a single JSON line (`Jsonl`) with an embedded
+/// `signature` block.
+///
+/// The signature covers the report serialized with sorted object keys and no whitespace, with
+/// the `signature` field itself removed, so re-indenting the stored report does not invalidate
+/// it but changing any value does.
+pub fn sign_report(
+    report: &ScanReport,
+    format: &OutputFormat,
+    signer: &ReportSigner,
+) -> Result<String> {
+    let mut value = serde_json::to_value(JsonReport::from(report))?;
+    let signature = signer.sign(canonical_json(&value).as_bytes())?;
+    value
+        .as_object_mut()
+        .expect("reports serialize as JSON objects")
+        .insert("signature".into(), serde_json::to_value(signature)?);
+    match format {
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(&value)?),
+        OutputFormat::Jsonl => {
+            let mut line = serde_json::to_string(&value)?;
+            line.push('\n');
+            Ok(line)
+        }
+        other => bail!("{other} output cannot be signed; use json or jsonl"),
+    }
+}
+
+/// Check the embedded signature of a report produced by [`sign_report`], returning it on
+/// success.
+pub fn verify_report(signed: &str, key: &VerificationKey) -> Result<ReportSignature> {
+    let mut value: Value = serde_json::from_str(signed.trim()).context("report is not JSON")?;
+    let signature = value
+        .as_object_mut()
+        .and_then(|object| object.remove("signature"))
+        .ok_or_else(|| anyhow!("report has no `signature` block"))?;
+    let signature: ReportSignature =
+        serde_json::from_value(signature).context("malformed `signature` block")?;
+    if signature.algorithm != key.algorithm {
+        bail!(
+            "report is signed with {} but a {} key was supplied",
+            signature.algorithm,
+            key.algorithm
+        );
+    }
+    crypto::verify(
+        key.algorithm,
+        &key.key,
+        canonical_json(&value).as_bytes(),
+        &signature,
+    )?;
+    Ok(signature)
+}
+
+fn check_key(algorithm: SignatureAlgorithm, key: &[u8], what: &str) -> Result<()> {
+    match algorithm {
+        SignatureAlgorithm::HmacSha256 if key.is_empty() => bail!("{what} is empty"),
+        SignatureAlgorithm::Ed25519 if key.len() != 32 => {
+            bail!("{what} must be 32 bytes for ed25519 (got {})", key.len())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn read_key(algorithm: SignatureAlgorithm, path: &Path, what: &str) -> Result<Vec<u8>> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("failed to read {what} {}", path.display()))?;
+    let raw = raw.trim();
+    match algorithm {
+        SignatureAlgorithm::HmacSha256 => Ok(raw.as_bytes().to_vec()),
+        SignatureAlgorithm::Ed25519 => {
+            from_hex(raw).ok_or_else(|| anyhow!("{what} {} is not hex-encoded", path.display()))
+        }
+    }
+}
+
+fn from_hex(value: &str) -> Option<Vec<u8>> {
+    value
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| match pair {
+            [high, low] => Some((hex_digit(*high)? << 4) | hex_digit(*low)?),
+            _ => None,
+        })
+        .collect()
+}
+
+fn hex_digit(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|digit| digit as u8)
+}
+
+/// Serialize `value` with object keys sorted and no insignificant whitespace.
+fn canonical_json(value: &Value) -> String {
+    fn write(value: &Value, out: &mut String) {
+        match value {
+            Value::Object(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(b.0));
+                out.push('{');
+                for (idx, (key, value)) in entries.into_iter().enumerate() {
+                    if idx > 0 {
+                        out.push(',');
+                    }
+                    out.push_str(&Value::String(key.clone()).to_string());
+                    out.push(':');
+                    write(value, out);
+                }
+                out.push('}');
+            }
+            Value::Array(items) => {
+                out.push('[');
+                for (idx, item) in items.iter().enumerate() {
+                    if idx > 0 {
+                        out.push(',');
+                    }
+                    write(item, out);
+                }
+                out.push(']');
+            }
+            scalar => out.push_str(&scalar.to_string()),
+        }
+    }
+
+    let mut out = String::new();
+    write(value, &mut out);
+    out
+}
+
+#[cfg(feature = "signing")]
+mod crypto {
+    use anyhow::{anyhow, bail, Result};
+    use ed25519_dalek::{Signer, Verifier};
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    use super::{from_hex, ReportSignature, SignatureAlgorithm};
+    use crate::scanner::manifest::to_hex;
+
+    fn hmac(key: &[u8]) -> Hmac<Sha256> {
+        Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length")
+    }
+
+    fn seed(key: &[u8]) -> [u8; 32] {
+        key.try_into()
+            .expect("ed25519 key length checked on construction")
+    }
+
+    pub(super) fn sign(
+        algorithm: SignatureAlgorithm,
+        key: &[u8],
+        payload: &[u8],
+    ) -> Result<ReportSignature> {
+        Ok(match algorithm {
+            SignatureAlgorithm::HmacSha256 => {
+                let mut mac = hmac(key);
+                mac.update(payload);
+                ReportSignature {
+                    algorithm,
+                    public_key: None,
+                    value: to_hex(&mac.finalize().into_bytes()),
+                }
+            }
+            SignatureAlgorithm::Ed25519 => {
+                let signing_key = ed25519_dalek::SigningKey::from_bytes(&seed(key));
+                ReportSignature {
+                    algorithm,
+                    public_key: Some(to_hex(signing_key.verifying_key().as_bytes())),
+                    value: to_hex(&signing_key.sign(payload).to_bytes()),
+                }
+            }
+        })
+    }
+
+    pub(super) fn verify(
+        algorithm: SignatureAlgorithm,
+        key: &[u8],
+        payload: &[u8],
+        signature: &ReportSignature,
+    ) -> Result<()> {
+        let value = from_hex(&signature.value)
+            .ok_or_else(|| anyhow!("signature value is not hex-encoded"))?;
+        let valid = match algorithm {
+            SignatureAlgorithm::HmacSha256 => {
+                let mut mac = hmac(key);
+                mac.update(payload);
+                mac.verify_slice(&value).is_ok()
+            }
+            SignatureAlgorithm::Ed25519 => {
+                let verifying_key = ed25519_dalek::VerifyingKey::from_bytes(&seed(key))?;
+                let signature = ed25519_dalek::Signature::from_slice(&value)?;
+                verifying_key.verify(payload, &signature).is_ok()
+            }
+        };
+        if !valid {
+            bail!("signature mismatch: the report was modified or signed with a different key");
+        }
+        Ok(())
+    }
+}
+
+/// Fallback when the `signing` feature is disabled.
+#[cfg(not(feature = "signing"))]
+mod crypto {
+    use anyhow::{bail, Result};
+
+    use super::{ReportSignature, SignatureAlgorithm};
+
+    pub(super) fn sign(
+        algorithm: SignatureAlgorithm,
+        _key: &[u8],
+        _payload: &[u8],
+    ) -> Result<ReportSignature> {
+        bail!("{algorithm} report signing requires the `signing` feature")
+    }
+
+    pub(super) fn verify(
+        algorithm: SignatureAlgorithm,
+        _key: &[u8],
+        _payload: &[u8],
+        _signature: &ReportSignature,
+    ) -> Result<()> {
+        bail!("{algorithm} report verification requires the `signing` feature")
+    }
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::scanner::{Finding, ScoreBreakdown};
+
+    fn report() -> ScanReport {
+        let finding = Finding {
+            rule_id: "INSTR_OVERRIDE".into(),
+            span: (0, 15),
+            excerpt: "ignore previous".into(),
+            weight: 20.0,
+            excerpt_span: None,
+            position: None,
+        };
+        let mut report = ScanReport::new(20.0, vec![finding], 28, None, ScoreBreakdown::default());
+        report.source = Some("prompt.txt".into());
+        report
+    }
+
+    #[test]
+    fn hmac_signature_detects_tampering() {
+        let signer = ReportSigner::new(SignatureAlgorithm::HmacSha256, b"s3cret".to_vec()).unwrap();
+        let key = VerificationKey::new(SignatureAlgorithm::HmacSha256, b"s3cret".to_vec()).unwrap();
+        let signed = sign_report(&report(), &OutputFormat::Json, &signer).unwrap();
+        assert!(verify_report(&signed, &key).is_ok());
+
+        let compact: Value = serde_json::from_str(&signed).unwrap();
+        assert!(verify_report(&compact.to_string(), &key).is_ok());
+
+        let tampered = signed.replace("\"risk_score\": 20.0", "\"risk_score\": 5.0");
+        assert_ne!(tampered, signed);
+        let err = verify_report(&tampered, &key).unwrap_err();
+        assert!(err.to_string().contains("signature mismatch"));
+
+        let other =
+            VerificationKey::new(SignatureAlgorithm::HmacSha256, b"other".to_vec()).unwrap();
+        assert!(verify_report(&signed, &other).is_err());
+    }
+
+    #[test]
+    fn ed25519_signature_verifies_with_public_key() {
+        let signer = ReportSigner::new(SignatureAlgorithm::Ed25519, vec![7; 32]).unwrap();
+        let signed = sign_report(&report(), &OutputFormat::Jsonl, &signer).unwrap();
+        assert_eq!(signed.lines().count(), 1);
+
+        let value: Value = serde_json::from_str(&signed).unwrap();
+        let public_key = from_hex(value["signature"]["public_key"].as_str().unwrap()).unwrap();
+        let key = VerificationKey::new(SignatureAlgorithm::Ed25519, public_key).unwrap();
+        assert_eq!(
+            verify_report(&signed, &key).unwrap().algorithm,
+            SignatureAlgorithm::Ed25519
+        );
+
+        let hmac = VerificationKey::new(SignatureAlgorithm::HmacSha256, b"k".to_vec()).unwrap();
+        assert!(verify_report(&signed, &hmac)
+            .unwrap_err()
+            .to_string()
+            .contains("signed with ed25519"));
+    }
+
+    #[test]
+    fn rejects_malformed_keys_and_formats() {
+        assert!(ReportSigner::new(SignatureAlgorithm::Ed25519, vec![1; 16]).is_err());
+        assert!(ReportSigner::new(SignatureAlgorithm::HmacSha256, Vec::new()).is_err());
+        let signer = ReportSigner::new(SignatureAlgorithm::HmacSha256, b"k".to_vec()).unwrap();
+        assert!(sign_report(&report(), &OutputFormat::Csv, &signer).is_err());
+    }
+}
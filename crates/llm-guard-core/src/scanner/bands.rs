@@ -0,0 +1,294 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{RiskBand, RiskThresholds};
+
+/// ANSI color names accepted for band presentation hints.
+const SUPPORTED_COLORS: &[&str] = &[
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+/// A named risk band with its lower score bound and presentation/exit-code policy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BandDefinition {
+    /// Band name shown in reports (e.g. `warning`).
+    pub name: String,
+    /// Inclusive lower bound of the band (0–100).
+    pub min_score: f32,
+    /// Process exit code used by the CLI when a scan lands in this band.
+    #[serde(default)]
+    pub exit_code: i32,
+    /// Optional ANSI color name used by colorized renderers.
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+impl BandDefinition {
+    pub fn new(name: impl Into<String>, min_score: f32, exit_code: i32) -> Self {
+        Self {
+            name: name.into(),
+            min_score,
+            exit_code,
+            color: None,
+        }
+    }
+
+    pub fn with_color(mut self, color: impl Into<String>) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+}
+
+/// Where a band sits in its set, for renderers with a fixed number of severity levels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BandTier {
+    Lowest,
+    Intermediate,
+    Highest,
+}
+
+/// Ordered set of risk bands, sorted ascending by `min_score`.
+///
+/// The default set is the built-in `low`/`medium`/`high`; organisations with a different policy
+/// taxonomy can supply their own names, thresholds, and exit codes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Vec<BandDefinition>", into = "Vec<BandDefinition>")]
+pub struct BandSet {
+    bands: Vec<BandDefinition>,
+}
+
+impl BandSet {
+    /// Build a band set, sorting by threshold and validating invariants.
+    pub fn new(mut bands: Vec<BandDefinition>) -> Result<Self, BandSetError> {
+        if bands.is_empty() {
+            return Err(BandSetError::Empty);
+        }
+        bands.sort_by(|a, b| a.min_score.total_cmp(&b.min_score));
+        for (idx, band) in bands.iter().enumerate() {
+            if band.name.trim().is_empty() {
+                return Err(BandSetError::BlankName);
+            }
+            if !(0.0..=100.0).contains(&band.min_score) {
+                return Err(BandSetError::InvalidThreshold {
+                    band: band.name.clone(),
+                    min_score: band.min_score,
+                });
+            }
+            if let Some(color) = band.color.as_deref() {
+                if !SUPPORTED_COLORS.contains(&color.to_ascii_lowercase().as_str()) {
+                    return Err(BandSetError::UnknownColor {
+                        band: band.name.clone(),
+                        color: color.to_string(),
+                    });
+                }
+            }
+            if let Some(previous) = idx.checked_sub(1).map(|prev| &bands[prev]) {
+                if (previous.min_score - band.min_score).abs() < f32::EPSILON {
+                    return Err(BandSetError::DuplicateThreshold {
+                        first: previous.name.clone(),
+                        second: band.name.clone(),
+                    });
+                }
+            }
+            if bands[..idx]
+                .iter()
+                .any(|other| other.name.eq_ignore_ascii_case(&band.name))
+            {
+                return Err(BandSetError::DuplicateName {
+                    band: band.name.clone(),
+                });
+            }
+        }
+        if bands[0].min_score > 0.0 {
+            return Err(BandSetError::UncoveredFloor {
+                min_score: bands[0].min_score,
+            });
+        }
+        Ok(Self { bands })
+    }
+
+    /// The built-in three-band policy derived from numeric thresholds.
+    pub fn from_thresholds(thresholds: &RiskThresholds) -> Self {
+        Self {
+            bands: vec![
+                BandDefinition::new("low", 0.0, 0).with_color("green"),
+                BandDefinition::new("medium", thresholds.medium, 2).with_color("yellow"),
+                BandDefinition::new("high", thresholds.high, 3).with_color("red"),
+            ],
+        }
+    }
+
+    /// Return the band covering `score`.
+    pub fn classify(&self, score: f32) -> &BandDefinition {
+        &self.bands[self.position(score)]
+    }
+
+    /// The band covering `score`, with its position in this set.
+    pub fn band(&self, score: f32) -> RiskBand {
+        let index = self.position(score);
+        RiskBand {
+            name: self.bands[index].name.clone(),
+            index,
+        }
+    }
+
+    /// Definition of a band produced by [`BandSet::band`], or `None` when it came from another
+    /// set (e.g. a report classified under a different configuration).
+    pub fn definition(&self, band: &RiskBand) -> Option<&BandDefinition> {
+        self.bands
+            .get(band.index)
+            .filter(|definition| definition.name == band.name)
+    }
+
+    /// Whether `band` is the lowest, the highest, or an intermediate band of this set. A band
+    /// from another set is placed by its index, clamped to this set.
+    pub fn tier(&self, band: &RiskBand) -> BandTier {
+        if band.index == 0 {
+            BandTier::Lowest
+        } else if band.index + 1 >= self.bands.len() {
+            BandTier::Highest
+        } else {
+            BandTier::Intermediate
+        }
+    }
+
+    fn position(&self, score: f32) -> usize {
+        self.bands
+            .iter()
+            .rposition(|band| score >= band.min_score)
+            .unwrap_or(0)
+    }
+
+    /// Look up a band by name (case-insensitive).
+    pub fn get(&self, name: &str) -> Option<&BandDefinition> {
+        self.bands
+            .iter()
+            .find(|band| band.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Position of the named band in ascending severity order.
+    pub fn rank(&self, name: &str) -> Option<usize> {
+        self.bands
+            .iter()
+            .position(|band| band.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Bands in ascending severity order.
+    pub fn bands(&self) -> &[BandDefinition] {
+        &self.bands
+    }
+}
+
+impl Default for BandSet {
+    fn default() -> Self {
+        Self::from_thresholds(&RiskThresholds::default())
+    }
+}
+
+impl TryFrom<Vec<BandDefinition>> for BandSet {
+    type Error = BandSetError;
+
+    fn try_from(bands: Vec<BandDefinition>) -> Result<Self, Self::Error> {
+        Self::new(bands)
+    }
+}
+
+impl From<BandSet> for Vec<BandDefinition> {
+    fn from(set: BandSet) -> Self {
+        set.bands
+    }
+}
+
+/// Errors emitted while validating a band configuration.
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum BandSetError {
+    #[error("band set must contain at least one band")]
+    Empty,
+    #[error("band names must not be blank")]
+    BlankName,
+    #[error("band `{band}` min_score must be within 0.0..=100.0 (got {min_score})")]
+    InvalidThreshold { band: String, min_score: f32 },
+    #[error("bands `{first}` and `{second}` share the same min_score")]
+    DuplicateThreshold { first: String, second: String },
+    #[error("band `{band}` is defined more than once")]
+    DuplicateName { band: String },
+    #[error("lowest band must start at 0 (got {min_score})")]
+    UncoveredFloor { min_score: f32 },
+    #[error("band `{band}` uses unsupported color `{color}`")]
+    UnknownColor { band: String, color: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::RiskBand;
+
+    fn four_level() -> BandSet {
+        BandSet::new(vec![
+            BandDefinition::new("critical", 80.0, 4).with_color("red"),
+            BandDefinition::new("info", 0.0, 0),
+            BandDefinition::new("warning", 50.0, 3).with_color("yellow"),
+            BandDefinition::new("notice", 20.0, 0).with_color("blue"),
+        ])
+        .expect("valid band set")
+    }
+
+    #[test]
+    fn classifies_custom_bands_in_order() {
+        let bands = four_level();
+        assert_eq!(bands.classify(0.0).name, "info");
+        assert_eq!(bands.classify(19.9).name, "info");
+        assert_eq!(bands.classify(20.0).name, "notice");
+        assert_eq!(bands.classify(65.0).name, "warning");
+        assert_eq!(bands.classify(100.0).name, "critical");
+        assert_eq!(bands.rank("WARNING"), Some(2));
+        assert_eq!(bands.get("critical").map(|b| b.exit_code), Some(4));
+
+        let warning = bands.band(65.0);
+        assert_eq!((warning.name(), warning.index), ("warning", 2));
+        assert_eq!(bands.definition(&warning).map(|b| b.exit_code), Some(3));
+        assert_eq!(bands.tier(&warning), BandTier::Intermediate);
+        assert_eq!(bands.tier(&bands.band(5.0)), BandTier::Lowest);
+        assert_eq!(bands.tier(&bands.band(95.0)), BandTier::Highest);
+        assert_eq!(BandSet::default().definition(&warning), None);
+    }
+
+    #[test]
+    fn default_matches_risk_band_thresholds() {
+        let bands = BandSet::default();
+        for score in [0.0, 24.9, 25.0, 59.9, 60.0, 100.0] {
+            assert_eq!(bands.band(score), RiskBand::from_score(score));
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_configurations() {
+        assert_eq!(BandSet::new(Vec::new()), Err(BandSetError::Empty));
+        assert!(matches!(
+            BandSet::new(vec![BandDefinition::new("only", 10.0, 0)]),
+            Err(BandSetError::UncoveredFloor { .. })
+        ));
+        assert!(matches!(
+            BandSet::new(vec![
+                BandDefinition::new("a", 0.0, 0),
+                BandDefinition::new("A", 50.0, 1),
+            ]),
+            Err(BandSetError::DuplicateName { .. })
+        ));
+        assert!(matches!(
+            BandSet::new(vec![BandDefinition::new("a", 0.0, 0).with_color("mauve")]),
+            Err(BandSetError::UnknownColor { .. })
+        ));
+    }
+
+    #[test]
+    fn deserializes_from_list() {
+        let bands: BandSet = serde_json::from_str(
+            r#"[{"name":"ok","min_score":0},{"name":"block","min_score":40,"exit_code":9}]"#,
+        )
+        .unwrap();
+        assert_eq!(bands.classify(41.0).exit_code, 9);
+        let err = serde_json::from_str::<BandSet>("[]").unwrap_err();
+        assert!(err.to_string().contains("at least one band"));
+    }
+}
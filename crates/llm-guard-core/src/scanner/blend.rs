@@ -0,0 +1,215 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use super::{RiskConfig, ScanReport};
+use crate::llm::LabelSet;
+
+/// How far below the medium threshold a benign verdict pulls a medium score.
+const DEESCALATION_MARGIN: f32 = 1.0;
+
+/// How an LLM verdict feeds into the final risk score and band.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum VerdictBlend {
+    /// The verdict is reported next to the heuristic score without moving it (label
+    /// `score_adjustment`s still apply).
+    #[default]
+    Off,
+    /// The most severe label lifts the score into the high band and intermediate labels into
+    /// the medium band. Scores are never lowered.
+    Escalate,
+    /// `escalate`, and the least severe label also pulls a medium score down to low. High scores
+    /// are never lowered.
+    Full,
+}
+
+impl FromStr for VerdictBlend {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "off" | "none" => Ok(Self::Off),
+            "escalate" => Ok(Self::Escalate),
+            "full" => Ok(Self::Full),
+            other => Err(format!(
+                "unknown verdict blend mode `{other}` (expected off, escalate or full)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for VerdictBlend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Off => "off",
+            Self::Escalate => "escalate",
+            Self::Full => "full",
+        })
+    }
+}
+
+/// The share of the final score that came from the LLM verdict, recorded in
+/// [`super::ScoreBreakdown::llm_adjustment`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct LlmAdjustment {
+    pub mode: VerdictBlend,
+    /// Verdict label that moved the score.
+    pub label: String,
+    /// Points added to (negative: removed from) the score.
+    pub points: f32,
+    /// Name of the band before and after the adjustment.
+    pub from_band: String,
+    pub to_band: String,
+}
+
+impl ScanReport {
+    /// Blend a verdict `label` into the score under `mode`, using its severity within `labels`.
+    ///
+    /// Labels outside the set (including `unknown`) leave the report untouched. When the score
+    /// moves, the band(s) are re-derived under `config` and the change is recorded in
+    /// `score_breakdown.llm_adjustment`. Returns whether the score changed.
+    pub fn apply_verdict_blend(
+        &mut self,
+        mode: VerdictBlend,
+        label: &str,
+        labels: &LabelSet,
+        config: &RiskConfig,
+    ) -> bool {
+        if mode == VerdictBlend::Off {
+            return false;
+        }
+        let Some(rank) = labels.rank(label) else {
+            return false;
+        };
+        let most_severe = labels.labels().len() - 1;
+        let thresholds = &config.thresholds;
+        let target = if rank > 0 && rank == most_severe {
+            self.risk_score.max(thresholds.high)
+        } else if rank > 0 {
+            self.risk_score.max(thresholds.medium)
+        } else if mode == VerdictBlend::Full
+            && (thresholds.medium..thresholds.high).contains(&self.risk_score)
+        {
+            (thresholds.medium - DEESCALATION_MARGIN).max(0.0)
+        } else {
+            self.risk_score
+        }
+        .clamp(0.0, 100.0);
+        let points = target - self.risk_score;
+        if points == 0.0 {
+            return false;
+        }
+
+        let from_band = std::mem::replace(&mut self.risk_band, config.band(target));
+        self.risk_score = target;
+        self.score_breakdown.llm_adjustment = Some(LlmAdjustment {
+            mode,
+            label: label.trim().to_string(),
+            points,
+            from_band: from_band.name,
+            to_band: self.risk_band.name.clone(),
+        });
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{
+        bands::{BandDefinition, BandSet},
+        ScoreBreakdown,
+    };
+
+    fn report(score: f32) -> ScanReport {
+        ScanReport::new(score, Vec::new(), 10, None, ScoreBreakdown::default())
+    }
+
+    #[test]
+    fn parses_modes() {
+        assert_eq!(
+            " Escalate ".parse::<VerdictBlend>(),
+            Ok(VerdictBlend::Escalate)
+        );
+        assert_eq!("none".parse::<VerdictBlend>(), Ok(VerdictBlend::Off));
+        assert!("boost".parse::<VerdictBlend>().is_err());
+        assert_eq!(VerdictBlend::Full.to_string(), "full");
+    }
+
+    #[test]
+    fn escalate_raises_but_never_lowers() {
+        let labels = LabelSet::default();
+        let config = RiskConfig::default();
+
+        let mut low = report(10.0);
+        assert!(low.apply_verdict_blend(VerdictBlend::Escalate, "malicious", &labels, &config));
+        assert_eq!(low.risk_score, 60.0);
+        assert_eq!(low.risk_band.name(), "high");
+        let adjustment = low.score_breakdown.llm_adjustment.as_ref().unwrap();
+        assert_eq!(adjustment.points, 50.0);
+        assert_eq!(
+            (adjustment.from_band.as_str(), adjustment.to_band.as_str()),
+            ("low", "high")
+        );
+
+        let mut suspicious = report(10.0);
+        suspicious.apply_verdict_blend(VerdictBlend::Escalate, "Suspicious", &labels, &config);
+        assert_eq!(suspicious.risk_band.name(), "medium");
+
+        let mut high = report(80.0);
+        assert!(!high.apply_verdict_blend(VerdictBlend::Escalate, "suspicious", &labels, &config));
+        let mut medium = report(40.0);
+        assert!(!medium.apply_verdict_blend(VerdictBlend::Escalate, "safe", &labels, &config));
+        assert!(!medium.apply_verdict_blend(VerdictBlend::Off, "malicious", &labels, &config));
+        assert!(!medium.apply_verdict_blend(VerdictBlend::Full, "unknown", &labels, &config));
+        assert_eq!(medium.risk_score, 40.0);
+        assert!(medium.score_breakdown.llm_adjustment.is_none());
+    }
+
+    #[test]
+    fn full_deescalates_medium_only() {
+        let labels = LabelSet::default();
+        let config = RiskConfig {
+            bands: Some(
+                BandSet::new(vec![
+                    BandDefinition::new("info", 0.0, 0),
+                    BandDefinition::new("warning", 25.0, 2),
+                    BandDefinition::new("block", 60.0, 3),
+                ])
+                .unwrap(),
+            ),
+            ..RiskConfig::default()
+        };
+
+        let mut medium = report(40.0);
+        medium.apply_bands(config.bands.as_ref().unwrap());
+        assert!(medium.apply_verdict_blend(VerdictBlend::Full, "safe", &labels, &config));
+        assert_eq!(medium.risk_score, 24.0);
+        assert_eq!(medium.risk_band.name(), "info");
+        assert_eq!(
+            medium
+                .score_breakdown
+                .llm_adjustment
+                .as_ref()
+                .unwrap()
+                .from_band,
+            "warning"
+        );
+        assert_eq!(
+            medium
+                .score_breakdown
+                .llm_adjustment
+                .as_ref()
+                .unwrap()
+                .points,
+            -16.0
+        );
+
+        let mut high = report(75.0);
+        assert!(!high.apply_verdict_blend(VerdictBlend::Full, "safe", &labels, &config));
+        assert_eq!(high.risk_band.name(), "high");
+    }
+}
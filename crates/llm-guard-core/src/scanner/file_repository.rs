@@ -1,40 +1,215 @@
-use std::{collections::HashSet, fs, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::{self, Write},
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering as AtomicOrdering},
+        Arc, PoisonError, RwLock,
+    },
+};
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 
-use super::{Rule, RuleKind, RuleRepository};
-use once_cell::sync::OnceCell;
+use super::{
+    manifest::{PackManifest, PackProvenance},
+    Rule, RuleKind, RuleRepository,
+};
+use tracing::warn;
 
-/// Loads rules from filesystem files (`keywords.txt` and `patterns.json`) located under a base directory.
+/// How rules with the same id from different packs are reconciled.
+///
+/// Duplicates inside a single pack are always an error.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CollisionPolicy {
+    /// Refuse to load (the historical behaviour).
+    #[default]
+    Error,
+    /// Later packs replace earlier definitions.
+    Override,
+    /// Keep the first definition and ignore later ones.
+    Skip,
+}
+
+impl FromStr for CollisionPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "error" => Ok(Self::Error),
+            "override" => Ok(Self::Override),
+            "skip" => Ok(Self::Skip),
+            other => Err(format!(
+                "unknown collision policy `{other}` (expected error, override, or skip)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for CollisionPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Error => "error",
+            Self::Override => "override",
+            Self::Skip => "skip",
+        })
+    }
+}
+
+/// Loads rules from filesystem files (`keywords.txt`, `patterns.json`, `rules.toml`, and
+/// `rules.csv`) located under one or more pack directories, or from individual rule files.
 pub struct FileRuleRepository {
-    base_path: PathBuf,
-    cache: OnceCell<Vec<Rule>>,
+    packs: Vec<PathBuf>,
+    cache: RwLock<Option<Arc<RuleIndex>>>,
+    /// Next [`RuleIndex::generation`]; every build, including reloads, takes a fresh one.
+    generations: AtomicU64,
+    strict: bool,
+    namespaced: bool,
+    collisions: CollisionPolicy,
 }
 
 impl FileRuleRepository {
     /// Create a repository rooted at the given directory.
     pub fn new(base_path: impl Into<PathBuf>) -> Self {
+        Self::from_packs([base_path.into()])
+    }
+
+    /// Create a repository merging several pack directories or rule files in the given order.
+    pub fn from_packs(packs: impl IntoIterator<Item = PathBuf>) -> Self {
         Self {
-            base_path: base_path.into(),
-            cache: OnceCell::new(),
+            packs: packs.into_iter().collect(),
+            cache: RwLock::new(None),
+            generations: AtomicU64::new(0),
+            strict: false,
+            namespaced: false,
+            collisions: CollisionPolicy::default(),
         }
     }
 
-    fn keywords_path(&self) -> PathBuf {
-        self.base_path.join("keywords.txt")
+    /// Reject rule packs that still contain deprecated rules instead of only warning.
+    pub fn with_strict_rules(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
     }
 
-    fn patterns_path(&self) -> PathBuf {
-        self.base_path.join("patterns.json")
+    /// Prefix every rule id with its pack name (e.g. `base:INSTR_OVERRIDE`).
+    pub fn with_namespaces(mut self, namespaced: bool) -> Self {
+        self.namespaced = namespaced;
+        self
     }
 
-    fn load_keywords(&self, seen: &mut HashSet<String>) -> Result<Vec<Rule>> {
+    /// Policy applied when two packs define the same (possibly namespaced) rule id.
+    pub fn with_collision_policy(mut self, collisions: CollisionPolicy) -> Self {
+        self.collisions = collisions;
+        self
+    }
+
+    /// Pack directories (or single rule files) in load order.
+    pub fn packs(&self) -> &[PathBuf] {
+        &self.packs
+    }
+
+    fn load_all(&self) -> Result<(Vec<Rule>, Vec<PackProvenance>)> {
+        let mut merged: Vec<Rule> = Vec::new();
+        let mut provenance = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        let mut pack_names = HashSet::new();
+        for (pack_idx, dir) in self.packs.iter().enumerate() {
+            let name = pack_name(dir, pack_idx);
+            if self.namespaced && !pack_names.insert(name.clone()) {
+                bail!(
+                    "duplicate rule pack name `{name}` ({}); namespaced packs need distinct directory names",
+                    dir.display()
+                );
+            }
+            if dir.is_dir() {
+                if let Some(manifest) = PackManifest::load(dir)? {
+                    manifest.verify(dir)?;
+                    provenance.push(manifest.provenance(&name)?);
+                }
+            }
+            for mut rule in Self::load_pack(dir)? {
+                if self.namespaced {
+                    rule.id = format!("{name}:{}", rule.id);
+                    if let Some(replacement) = rule.replaced_by.as_mut() {
+                        if !replacement.contains(':') {
+                            *replacement = format!("{name}:{replacement}");
+                        }
+                    }
+                }
+                let Some(&existing) = index.get(&rule.id) else {
+                    index.insert(rule.id.clone(), merged.len());
+                    merged.push(rule);
+                    continue;
+                };
+                match self.collisions {
+                    CollisionPolicy::Error => bail!(
+                        "duplicate rule id `{}` in pack {} (enable namespacing or choose a collision policy)",
+                        rule.id,
+                        dir.display()
+                    ),
+                    CollisionPolicy::Override => {
+                        warn!(rule_id = %rule.id, pack = %dir.display(), "rule overridden by later pack");
+                        merged[existing] = rule;
+                    }
+                    CollisionPolicy::Skip => {
+                        warn!(rule_id = %rule.id, pack = %dir.display(), "skipping rule already defined by an earlier pack");
+                    }
+                }
+            }
+        }
+        check_deprecations(&merged, self.strict)?;
+        Ok((merged, provenance))
+    }
+
+    /// Load a pack directory, or a single rule file mounted on its own (format chosen by extension).
+    fn load_pack(path: &Path) -> Result<Vec<Rule>> {
+        let mut seen = HashSet::new();
+        let mut variables = BTreeMap::new();
         let mut rules = Vec::new();
-        let path = self.keywords_path();
+        if path.is_file() {
+            rules = Self::load_file(path, &mut seen, &mut variables)?;
+        } else {
+            for name in PACK_FILES {
+                rules.extend(Self::load_file(
+                    &path.join(name),
+                    &mut seen,
+                    &mut variables,
+                )?);
+            }
+        }
+        expand_variables(&mut rules, &variables)?;
+        Ok(rules)
+    }
+
+    fn load_file(
+        path: &Path,
+        seen: &mut HashSet<String>,
+        variables: &mut BTreeMap<String, String>,
+    ) -> Result<Vec<Rule>> {
         if !path.exists() {
-            return Ok(rules);
+            return Ok(Vec::new());
+        }
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("txt") => Self::load_keywords(path, seen),
+            Some("json") => Self::load_patterns(path, seen, variables),
+            Some("toml") => Self::load_toml(path, seen, variables),
+            Some("csv") => Self::load_csv(path, seen),
+            _ => bail!(
+                "unsupported rule file {} (expected .txt, .json, .toml, or .csv)",
+                path.display()
+            ),
         }
-        let content = fs::read_to_string(&path)
+    }
+
+    fn load_keywords(path: &Path, seen: &mut HashSet<String>) -> Result<Vec<Rule>> {
+        let mut rules = Vec::new();
+        let content = fs::read_to_string(path)
             .with_context(|| format!("failed to read keyword rule file at {}", path.display()))?;
         for (idx, line) in content.lines().enumerate() {
             let trimmed = line.trim();
@@ -68,54 +243,429 @@ impl FileRuleRepository {
         Ok(rules)
     }
 
-    fn load_patterns(&self, seen: &mut HashSet<String>) -> Result<Vec<Rule>> {
-        let mut rules = Vec::new();
-        let path = self.patterns_path();
-        if !path.exists() {
-            return Ok(rules);
-        }
-        let raw = fs::read_to_string(&path)
+    fn load_patterns(
+        path: &Path,
+        seen: &mut HashSet<String>,
+        variables: &mut BTreeMap<String, String>,
+    ) -> Result<Vec<Rule>> {
+        let raw = fs::read_to_string(path)
             .with_context(|| format!("failed to read pattern rule file at {}", path.display()))?;
-        let items: Vec<JsonRule> = serde_json::from_str(&raw).with_context(|| {
+        let file: PatternFile = serde_json::from_str(&raw).with_context(|| {
             format!(
                 "invalid JSON structure in pattern rule file at {}",
                 path.display()
             )
         })?;
-        for item in items {
-            if !seen.insert(item.id.clone()) {
-                return Err(anyhow::anyhow!("duplicate rule id `{}`", item.id));
+        let items = match file {
+            PatternFile::Rules(items) => items,
+            PatternFile::Pack {
+                variables: declared,
+                rules,
+            } => {
+                for (name, value) in declared {
+                    define_variable(variables, name, value)?;
+                }
+                rules
+            }
+        };
+        structured_rules(items, seen)
+    }
+
+    #[cfg(feature = "formats")]
+    fn load_toml(
+        path: &Path,
+        seen: &mut HashSet<String>,
+        variables: &mut BTreeMap<String, String>,
+    ) -> Result<Vec<Rule>> {
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("failed to read TOML rule file at {}", path.display()))?;
+        let file: TomlFile = toml::from_str(&raw)
+            .with_context(|| format!("invalid TOML rule file at {}", path.display()))?;
+        for (name, value) in file.variables {
+            define_variable(variables, name, value)?;
+        }
+        structured_rules(file.rules, seen)
+    }
+
+    /// Load `rules.csv` (`id,kind,weight,window,description,pattern` with a header row).
+    ///
+    /// All rows are validated before failing so spreadsheet maintainers see every problem at once.
+    #[cfg(feature = "formats")]
+    fn load_csv(path: &Path, seen: &mut HashSet<String>) -> Result<Vec<Rule>> {
+        let mut rules = Vec::new();
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .comment(Some(b'#'))
+            .trim(csv::Trim::All)
+            .from_path(path)
+            .with_context(|| format!("failed to read CSV rule file at {}", path.display()))?;
+        let headers = reader
+            .headers()
+            .with_context(|| format!("missing header row in CSV rule file at {}", path.display()))?
+            .clone();
+
+        let mut errors = Vec::new();
+        for record in reader.records() {
+            let record = match record {
+                Ok(record) => record,
+                Err(err) => {
+                    let line = err.position().map(|pos| pos.line()).unwrap_or_default();
+                    errors.push(format!("line {line}: {err}"));
+                    continue;
+                }
+            };
+            let line = record.position().map(|pos| pos.line()).unwrap_or_default();
+            let row: CsvRule = match record.deserialize(Some(&headers)) {
+                Ok(row) => row,
+                Err(err) => {
+                    errors.push(format!("line {line}: {err}"));
+                    continue;
+                }
+            };
+            let kind = match row.kind.to_ascii_lowercase().as_str() {
+                "keyword" => RuleKind::Keyword,
+                "regex" => RuleKind::Regex,
+                other => {
+                    errors.push(format!(
+                        "line {line}: unknown rule kind `{other}` for rule `{}` (expected keyword or regex)",
+                        row.id
+                    ));
+                    continue;
+                }
+            };
+            if !seen.insert(row.id.clone()) {
+                errors.push(format!("line {line}: duplicate rule id `{}`", row.id));
+                continue;
+            }
+            let tags = row.tags.as_deref().unwrap_or_default().split(';');
+            match Rule::new(
+                row.id,
+                row.description,
+                kind,
+                row.pattern,
+                row.weight,
+                row.window,
+            ) {
+                Ok(rule) if row.deprecated.unwrap_or(false) => {
+                    rules.push(rule.with_tags(tags).deprecate(row.replaced_by))
+                }
+                Ok(rule) => rules.push(rule.with_tags(tags)),
+                Err(err) => errors.push(format!("line {line}: {err}")),
             }
-            let rule = Rule::new(
-                item.id,
-                item.description,
-                RuleKind::Regex,
-                item.pattern,
-                item.weight,
-                item.window,
-            )?;
-            rules.push(rule);
+        }
+
+        if !errors.is_empty() {
+            return Err(anyhow::anyhow!(
+                "invalid CSV rule file at {} ({} row error(s)):\n  {}",
+                path.display(),
+                errors.len(),
+                errors.join("\n  ")
+            ));
         }
         Ok(rules)
     }
+
+    /// Fallback when the `formats` feature is disabled.
+    #[cfg(not(feature = "formats"))]
+    fn load_toml(
+        path: &Path,
+        _seen: &mut HashSet<String>,
+        _variables: &mut BTreeMap<String, String>,
+    ) -> Result<Vec<Rule>> {
+        bail!(
+            "TOML rule file {} requires the `formats` feature",
+            path.display()
+        )
+    }
+
+    /// Fallback when the `formats` feature is disabled.
+    #[cfg(not(feature = "formats"))]
+    fn load_csv(path: &Path, _seen: &mut HashSet<String>) -> Result<Vec<Rule>> {
+        bail!(
+            "CSV rule file {} requires the `formats` feature",
+            path.display()
+        )
+    }
+}
+
+/// Loaded rules plus lookup tables for id, family, and tag queries.
+struct RuleIndex {
+    rules: Arc<[Rule]>,
+    /// Distinguishes this load from every other one of the same repository.
+    generation: u64,
+    by_id: HashMap<String, usize>,
+    /// Keyed by uppercase family.
+    by_family: HashMap<String, Vec<usize>>,
+    /// Keyed by lowercase tag.
+    by_tag: HashMap<String, Vec<usize>>,
+    provenance: Vec<PackProvenance>,
+}
+
+impl RuleIndex {
+    fn new(rules: Vec<Rule>, provenance: Vec<PackProvenance>, generation: u64) -> Self {
+        let mut by_id = HashMap::with_capacity(rules.len());
+        let mut by_family: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut by_tag: HashMap<String, Vec<usize>> = HashMap::new();
+        for (idx, rule) in rules.iter().enumerate() {
+            by_id.insert(rule.id.clone(), idx);
+            by_family
+                .entry(rule.family().to_ascii_uppercase())
+                .or_default()
+                .push(idx);
+            for tag in &rule.tags {
+                by_tag
+                    .entry(tag.to_ascii_lowercase())
+                    .or_default()
+                    .push(idx);
+            }
+        }
+        Self {
+            rules: rules.into(),
+            generation,
+            by_id,
+            by_family,
+            by_tag,
+            provenance,
+        }
+    }
+
+    fn select(&self, indices: Option<&Vec<usize>>) -> Vec<Rule> {
+        indices
+            .map(|indices| indices.iter().map(|&idx| self.rules[idx].clone()).collect())
+            .unwrap_or_default()
+    }
+}
+
+impl FileRuleRepository {
+    fn index(&self) -> Result<Arc<RuleIndex>> {
+        if let Some(index) = self
+            .cache
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+        {
+            return Ok(Arc::clone(index));
+        }
+        let mut slot = self.cache.write().unwrap_or_else(PoisonError::into_inner);
+        if let Some(index) = slot.as_ref() {
+            return Ok(Arc::clone(index));
+        }
+        let index = Arc::new(self.build_index()?);
+        *slot = Some(Arc::clone(&index));
+        Ok(index)
+    }
+
+    fn build_index(&self) -> Result<RuleIndex> {
+        let (rules, provenance) = self.load_all()?;
+        let generation = self.generations.fetch_add(1, AtomicOrdering::Relaxed);
+        Ok(RuleIndex::new(rules, provenance, generation))
+    }
 }
 
 #[async_trait::async_trait]
 impl RuleRepository for FileRuleRepository {
-    async fn load_rules(&self) -> Result<Vec<Rule>> {
-        let rules = self.cache.get_or_try_init(|| {
-            let mut seen = HashSet::new();
-            let mut rules = self.load_keywords(&mut seen)?;
-            rules.extend(self.load_patterns(&mut seen)?);
-            Ok::<_, anyhow::Error>(rules)
-        })?;
-        Ok(rules.clone())
+    async fn load_rules(&self) -> Result<Arc<[Rule]>> {
+        Ok(Arc::clone(&self.index()?.rules))
+    }
+
+    async fn load_rules_with_generation(&self) -> Result<(Arc<[Rule]>, Option<u64>)> {
+        let index = self.index()?;
+        Ok((Arc::clone(&index.rules), Some(index.generation)))
     }
 
     async fn get_rule(&self, rule_id: &str) -> Result<Option<Rule>> {
-        let rules = self.load_rules().await?;
-        Ok(rules.into_iter().find(|rule| rule.id == rule_id))
+        let index = self.index()?;
+        Ok(index
+            .by_id
+            .get(rule_id)
+            .map(|&idx| index.rules[idx].clone()))
+    }
+
+    async fn rules_by_family(&self, family: &str) -> Result<Vec<Rule>> {
+        let index = self.index()?;
+        Ok(index.select(index.by_family.get(&family.to_ascii_uppercase())))
+    }
+
+    async fn rules_by_tag(&self, tag: &str) -> Result<Vec<Rule>> {
+        let index = self.index()?;
+        Ok(index.select(index.by_tag.get(&tag.to_ascii_lowercase())))
+    }
+
+    /// Re-read every pack; on failure the previously loaded rules stay active.
+    async fn reload(&self) -> Result<usize> {
+        let index = self.build_index()?;
+        let count = index.rules.len();
+        *self.cache.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(index));
+        Ok(count)
+    }
+
+    fn provenance(&self) -> Vec<PackProvenance> {
+        self.cache
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+            .map(|index| index.provenance.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// Pack name used for namespacing: the directory name, or `packN` when it has none.
+fn pack_name(dir: &Path, idx: usize) -> String {
+    let name = if dir.is_file() {
+        dir.file_stem()
+    } else {
+        dir.file_name()
+    };
+    name.and_then(|name| name.to_str())
+        .filter(|name| !name.is_empty() && *name != "." && *name != "..")
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("pack{}", idx + 1))
+}
+
+/// Files read from a pack directory, in load order.
+pub(crate) const PACK_FILES: [&str; 4] =
+    ["keywords.txt", "patterns.json", "rules.toml", "rules.csv"];
+
+/// `patterns.json` is either a bare rule array or a pack object with shared variables.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum PatternFile {
+    Rules(Vec<JsonRule>),
+    Pack {
+        #[serde(default)]
+        variables: BTreeMap<String, String>,
+        rules: Vec<JsonRule>,
+    },
+}
+
+/// `rules.toml` mirrors the JSON pack: an optional `[variables]` table plus `[[rule]]` entries.
+#[cfg(feature = "formats")]
+#[derive(serde::Deserialize)]
+struct TomlFile {
+    #[serde(default)]
+    variables: BTreeMap<String, String>,
+    #[serde(default, rename = "rule")]
+    rules: Vec<JsonRule>,
+}
+
+/// Converts JSON/TOML rule records into regex rules, rejecting ids already seen in the pack.
+fn structured_rules(items: Vec<JsonRule>, seen: &mut HashSet<String>) -> Result<Vec<Rule>> {
+    let mut rules = Vec::with_capacity(items.len());
+    for item in items {
+        if !seen.insert(item.id.clone()) {
+            return Err(anyhow::anyhow!("duplicate rule id `{}`", item.id));
+        }
+        let mut rule = Rule::new(
+            item.id,
+            item.description,
+            RuleKind::Regex,
+            item.pattern,
+            item.weight,
+            item.window,
+        )?
+        .with_tags(&item.tags);
+        if item.deprecated {
+            rule = rule.deprecate(item.replaced_by);
+        }
+        rules.push(rule);
+    }
+    Ok(rules)
+}
+
+fn define_variable(
+    variables: &mut BTreeMap<String, String>,
+    name: String,
+    value: String,
+) -> Result<()> {
+    let valid_name = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_name {
+        bail!("invalid rule variable name `{name}` (use letters, digits, and underscores)");
+    }
+    if value.is_empty() {
+        bail!("rule variable `{name}` must not be empty");
+    }
+    if variables.insert(name.clone(), value).is_some() {
+        bail!("duplicate rule variable `{name}`");
+    }
+    Ok(())
+}
+
+/// Substitute `${NAME}` references in regex rules with their variable values.
+fn expand_variables(rules: &mut [Rule], variables: &BTreeMap<String, String>) -> Result<()> {
+    for rule in rules
+        .iter_mut()
+        .filter(|rule| matches!(rule.kind, RuleKind::Regex))
+    {
+        if rule.pattern.contains("${") {
+            rule.pattern = expand_pattern(&rule.id, &rule.pattern, variables)?;
+        }
+    }
+    Ok(())
+}
+
+/// Each value is wrapped in a non-capturing group so alternations stay scoped to the reference.
+/// A reference preceded by `\` is left untouched.
+fn expand_pattern(
+    rule_id: &str,
+    pattern: &str,
+    variables: &BTreeMap<String, String>,
+) -> Result<String> {
+    let mut expanded = String::with_capacity(pattern.len());
+    let mut rest = pattern;
+    while let Some(pos) = rest.find("${") {
+        let (before, after) = rest.split_at(pos);
+        expanded.push_str(before);
+        if before.ends_with('\\') {
+            expanded.push_str("${");
+            rest = &after[2..];
+            continue;
+        }
+        let end = after.find('}').ok_or_else(|| {
+            anyhow!("unterminated variable reference in pattern for rule `{rule_id}`")
+        })?;
+        let name = &after[2..end];
+        let value = variables.get(name).ok_or_else(|| {
+            anyhow!("rule `{rule_id}` references undefined variable `${{{name}}}`")
+        })?;
+        write!(expanded, "(?:{value})")?;
+        rest = &after[end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+fn check_deprecations(rules: &[Rule], strict: bool) -> Result<()> {
+    let mut rejected = Vec::new();
+    for rule in rules.iter().filter(|rule| rule.deprecated) {
+        if let Some(replacement) = rule.replaced_by.as_deref() {
+            if !rules.iter().any(|other| other.id == replacement) {
+                warn!(rule_id = %rule.id, replaced_by = %replacement, "deprecated rule names an unknown replacement");
+            }
+        }
+        if strict {
+            rejected.push(match rule.replaced_by.as_deref() {
+                Some(replacement) => format!("`{}` (replaced by `{replacement}`)", rule.id),
+                None => format!("`{}`", rule.id),
+            });
+        } else {
+            warn!(
+                rule_id = %rule.id,
+                replaced_by = rule.replaced_by.as_deref().unwrap_or("-"),
+                "loaded deprecated rule"
+            );
+        }
     }
+    if !rejected.is_empty() {
+        bail!(
+            "deprecated rules are not allowed in strict mode: {}",
+            rejected.join(", ")
+        );
+    }
+    Ok(())
 }
 
 #[derive(serde::Deserialize)]
@@ -126,6 +676,32 @@ struct JsonRule {
     weight: f32,
     #[serde(default)]
     window: Option<usize>,
+    #[serde(default)]
+    deprecated: bool,
+    #[serde(default)]
+    replaced_by: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[cfg(feature = "formats")]
+#[derive(serde::Deserialize)]
+struct CsvRule {
+    id: String,
+    kind: String,
+    weight: f32,
+    #[serde(default)]
+    window: Option<usize>,
+    description: String,
+    pattern: String,
+    /// Optional column; blank cells count as `false`.
+    #[serde(default)]
+    deprecated: Option<bool>,
+    #[serde(default)]
+    replaced_by: Option<String>,
+    /// Optional column; tags separated by `;`.
+    #[serde(default)]
+    tags: Option<String>,
 }
 
 #[cfg(test)]
@@ -169,7 +745,9 @@ DATA_EXFIL|30|Tries to exfiltrate secrets|api key
         );
 
         let repo = FileRuleRepository::new(temp.path());
-        let mut rules = futures::executor::block_on(RuleRepository::load_rules(&repo)).unwrap();
+        let mut rules = futures::executor::block_on(RuleRepository::load_rules(&repo))
+            .unwrap()
+            .to_vec();
         rules.sort_by(|a, b| a.id.cmp(&b.id));
 
         assert_eq!(rules.len(), 3);
@@ -192,6 +770,385 @@ DATA_EXFIL|30|Tries to exfiltrate secrets|api key
         assert!(err.to_string().contains("duplicate rule id `DUP`"));
     }
 
+    #[cfg(feature = "formats")]
+    #[test]
+    fn loads_csv_rules_with_quoting() {
+        let temp = tempfile::tempdir().unwrap();
+        write(
+            &temp.path().join("rules.csv"),
+            r#"id,kind,weight,window,description,pattern
+# spreadsheet export
+CSV_KEYWORD,keyword,20,,"Override, with comma",ignore all rules
+CSV_REGEX,Regex,35,48,"Quoted ""role"" swap","(?i)you are now\s+\w+"
+"#,
+        );
+
+        let repo = FileRuleRepository::new(temp.path());
+        let rules = futures::executor::block_on(RuleRepository::load_rules(&repo)).unwrap();
+
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].description, "Override, with comma");
+        assert_eq!(rules[0].kind, RuleKind::Keyword);
+        assert_eq!(rules[0].window, None);
+        assert_eq!(rules[1].description, "Quoted \"role\" swap");
+        assert_eq!(rules[1].pattern, r"(?i)you are now\s+\w+");
+        assert_eq!(rules[1].window, Some(48));
+    }
+
+    #[cfg(feature = "formats")]
+    #[test]
+    fn csv_reports_every_invalid_row() {
+        let temp = tempfile::tempdir().unwrap();
+        write(
+            &temp.path().join("keywords.txt"),
+            "SHARED|10|desc|pattern\n",
+        );
+        write(
+            &temp.path().join("rules.csv"),
+            "id,kind,weight,window,description,pattern\n\
+             OK_ROW,keyword,10,,fine,hello\n\
+             BAD_WEIGHT,keyword,heavy,,desc,pattern\n\
+             BAD_KIND,glob,10,,desc,pattern\n\
+             SHARED,keyword,10,,desc,pattern\n",
+        );
+
+        let repo = FileRuleRepository::new(temp.path());
+        let err = futures::executor::block_on(RuleRepository::load_rules(&repo))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("3 row error(s)"), "{err}");
+        assert!(err.contains("line 3:"), "{err}");
+        assert!(err.contains("unknown rule kind `glob`"), "{err}");
+        assert!(err.contains("line 5: duplicate rule id `SHARED`"), "{err}");
+    }
+
+    #[cfg(feature = "formats")]
+    #[test]
+    fn substitutes_pattern_variables() {
+        let temp = tempfile::tempdir().unwrap();
+        write(
+            &temp.path().join("patterns.json"),
+            r#"{
+                "variables": { "SHELLS": "bash|zsh|sh" },
+                "rules": [
+                    {
+                        "id": "CODE_SHELL",
+                        "description": "Shell execution",
+                        "pattern": "(?i)run\\s+${SHELLS}\\b",
+                        "weight": 40
+                    }
+                ]
+            }"#,
+        );
+        write(
+            &temp.path().join("rules.csv"),
+            "id,kind,weight,window,description,pattern\n\
+             CODE_PIPE,regex,30,,Pipe to shell,curl .*\\| *${SHELLS}\n",
+        );
+
+        let repo = FileRuleRepository::new(temp.path());
+        let rules = futures::executor::block_on(RuleRepository::load_rules(&repo)).unwrap();
+        let shell = rules.iter().find(|rule| rule.id == "CODE_SHELL").unwrap();
+        assert_eq!(shell.pattern, r"(?i)run\s+(?:bash|zsh|sh)\b");
+        let pipe = rules.iter().find(|rule| rule.id == "CODE_PIPE").unwrap();
+        assert_eq!(pipe.pattern, r"curl .*\| *(?:bash|zsh|sh)");
+    }
+
+    #[cfg(feature = "formats")]
+    #[test]
+    fn loads_toml_rule_tables() {
+        let temp = tempfile::tempdir().unwrap();
+        write(
+            &temp.path().join("rules.toml"),
+            r#"
+[variables]
+TARGET = "system|developer"
+
+[[rule]]
+id = "TOML_PROMPT"
+description = "Prompt leak request"
+pattern = '(?i)reveal the ${TARGET} prompt'
+weight = 35.0
+window = 40
+tags = ["Exfiltration"]
+
+[[rule]]
+id = "TOML_OLD"
+description = "Legacy phrasing"
+pattern = "old phrasing"
+weight = 5
+deprecated = true
+replaced_by = "TOML_PROMPT"
+"#,
+        );
+
+        let repo = FileRuleRepository::new(temp.path());
+        let rules = futures::executor::block_on(RuleRepository::load_rules(&repo)).unwrap();
+        assert_eq!(rules.len(), 2);
+        let prompt = rules.iter().find(|rule| rule.id == "TOML_PROMPT").unwrap();
+        assert!(matches!(prompt.kind, RuleKind::Regex));
+        assert_eq!(prompt.pattern, "(?i)reveal the (?:system|developer) prompt");
+        assert_eq!(prompt.window, Some(40));
+        assert_eq!(prompt.tags, vec!["exfiltration".to_string()]);
+        let old = rules.iter().find(|rule| rule.id == "TOML_OLD").unwrap();
+        assert!(old.deprecated);
+        assert_eq!(old.replaced_by.as_deref(), Some("TOML_PROMPT"));
+
+        write(
+            &temp.path().join("patterns.json"),
+            r#"[{"id": "TOML_PROMPT", "description": "d", "pattern": "x", "weight": 1}]"#,
+        );
+        let err = futures::executor::block_on(RuleRepository::load_rules(
+            &FileRuleRepository::new(temp.path()),
+        ))
+        .unwrap_err();
+        assert!(err.to_string().contains("duplicate rule id `TOML_PROMPT`"));
+    }
+
+    #[test]
+    fn undefined_pattern_variable_errors() {
+        let temp = tempfile::tempdir().unwrap();
+        write(
+            &temp.path().join("patterns.json"),
+            r#"[{"id": "X_RULE", "description": "d", "pattern": "run ${MISSING}", "weight": 10}]"#,
+        );
+        let repo = FileRuleRepository::new(temp.path());
+        let err = futures::executor::block_on(RuleRepository::load_rules(&repo)).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("rule `X_RULE` references undefined variable `${MISSING}`"));
+    }
+
+    #[test]
+    fn deprecated_rules_load_unless_strict() {
+        let temp = tempfile::tempdir().unwrap();
+        write(
+            &temp.path().join("patterns.json"),
+            r#"[
+                {"id": "OLD_RULE", "description": "d", "pattern": "old", "weight": 10,
+                 "deprecated": true, "replaced_by": "NEW_RULE"},
+                {"id": "NEW_RULE", "description": "d", "pattern": "new", "weight": 10}
+            ]"#,
+        );
+
+        let repo = FileRuleRepository::new(temp.path());
+        let rules = futures::executor::block_on(RuleRepository::load_rules(&repo)).unwrap();
+        let old = rules.iter().find(|rule| rule.id == "OLD_RULE").unwrap();
+        assert!(old.deprecated);
+        assert_eq!(old.replaced_by.as_deref(), Some("NEW_RULE"));
+
+        let strict = FileRuleRepository::new(temp.path()).with_strict_rules(true);
+        let err = futures::executor::block_on(RuleRepository::load_rules(&strict)).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("`OLD_RULE` (replaced by `NEW_RULE`)"));
+    }
+
+    fn two_packs_sharing_an_id() -> tempfile::TempDir {
+        let temp = tempfile::tempdir().unwrap();
+        write(
+            &temp.path().join("base/keywords.txt"),
+            "INSTR_OVERRIDE|20|Base override|ignore previous\nBASE_ONLY|10|Base only|base\n",
+        );
+        write(
+            &temp.path().join("custom/keywords.txt"),
+            "INSTR_OVERRIDE|40|Custom override|disregard previous\n",
+        );
+        temp
+    }
+
+    fn load_packs(repo: FileRuleRepository) -> Result<Arc<[Rule]>> {
+        futures::executor::block_on(RuleRepository::load_rules(&repo))
+    }
+
+    fn load_rules_from(repo: &FileRuleRepository) -> Arc<[Rule]> {
+        futures::executor::block_on(RuleRepository::load_rules(repo)).unwrap()
+    }
+
+    #[test]
+    fn cross_pack_collisions_follow_policy() {
+        let temp = two_packs_sharing_an_id();
+        let packs = || [temp.path().join("base"), temp.path().join("custom")];
+
+        let err = load_packs(FileRuleRepository::from_packs(packs())).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("duplicate rule id `INSTR_OVERRIDE`"));
+
+        let overridden = load_packs(
+            FileRuleRepository::from_packs(packs())
+                .with_collision_policy(CollisionPolicy::Override),
+        )
+        .unwrap();
+        assert_eq!(overridden.len(), 2);
+        assert_eq!(overridden[0].pattern, "disregard previous");
+
+        let skipped = load_packs(
+            FileRuleRepository::from_packs(packs()).with_collision_policy(CollisionPolicy::Skip),
+        )
+        .unwrap();
+        assert_eq!(skipped[0].pattern, "ignore previous");
+    }
+
+    #[test]
+    fn namespaced_packs_prefix_rule_ids() {
+        let temp = two_packs_sharing_an_id();
+        let rules = load_packs(
+            FileRuleRepository::from_packs([temp.path().join("base"), temp.path().join("custom")])
+                .with_namespaces(true),
+        )
+        .unwrap();
+        let ids: Vec<_> = rules.iter().map(|rule| rule.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "base:INSTR_OVERRIDE",
+                "base:BASE_ONLY",
+                "custom:INSTR_OVERRIDE"
+            ]
+        );
+        assert_eq!(
+            "error".parse::<CollisionPolicy>(),
+            Ok(CollisionPolicy::Error)
+        );
+        assert!("merge".parse::<CollisionPolicy>().is_err());
+    }
+
+    #[cfg(feature = "formats")]
+    #[test]
+    fn loads_individual_rule_files_as_packs() {
+        let temp = tempfile::tempdir().unwrap();
+        let json = temp.path().join("exfil.json");
+        let tools = temp.path().join("tools.toml");
+        write(
+            &json,
+            r#"[{"id": "DATA_TOKEN", "description": "d", "pattern": "token", "weight": 10}]"#,
+        );
+        write(
+            &tools,
+            "[[rule]]\nid = \"TOOL_SHELL\"\ndescription = \"d\"\npattern = \"shell\"\nweight = 20.0\n",
+        );
+
+        let rules = load_packs(FileRuleRepository::from_packs([json, tools]).with_namespaces(true))
+            .unwrap();
+        let ids: Vec<_> = rules.iter().map(|rule| rule.id.as_str()).collect();
+        assert_eq!(ids, vec!["exfil:DATA_TOKEN", "tools:TOOL_SHELL"]);
+
+        let yaml = temp.path().join("rules.yaml");
+        write(&yaml, "- id: X\n");
+        let err = load_packs(FileRuleRepository::from_packs([yaml])).unwrap_err();
+        assert!(err.to_string().contains("unsupported rule file"));
+    }
+
+    #[cfg(feature = "provenance")]
+    #[test]
+    fn manifest_packs_are_verified_and_reported() {
+        let temp = tempfile::tempdir().unwrap();
+        write(
+            &temp.path().join("keywords.txt"),
+            "INSTR_OVERRIDE|20|Override|ignore previous\n",
+        );
+        PackManifest::generate(temp.path(), Some("Security Team".into()), None)
+            .unwrap()
+            .write(temp.path())
+            .unwrap();
+
+        let repo = FileRuleRepository::new(temp.path());
+        assert!(repo.provenance().is_empty());
+        futures::executor::block_on(RuleRepository::load_rules(&repo)).unwrap();
+        let provenance = repo.provenance();
+        assert_eq!(provenance.len(), 1);
+        assert_eq!(provenance[0].author.as_deref(), Some("Security Team"));
+
+        write(
+            &temp.path().join("keywords.txt"),
+            "INSTR_OVERRIDE|90|Override|ignore previous\n",
+        );
+        let err = load_packs(FileRuleRepository::new(temp.path())).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"));
+    }
+
+    #[test]
+    fn reload_refreshes_rules_and_keeps_them_on_error() {
+        let temp = tempfile::tempdir().unwrap();
+        let keywords = temp.path().join("keywords.txt");
+        write(&keywords, "INSTR_OVERRIDE|20|Override|ignore previous\n");
+        let repo = FileRuleRepository::new(temp.path());
+        assert_eq!(load_rules_from(&repo).len(), 1);
+
+        write(
+            &keywords,
+            "INSTR_OVERRIDE|20|Override|ignore previous\nDATA_EXFIL|30|Exfil|api key\n",
+        );
+        assert_eq!(
+            load_rules_from(&repo).len(),
+            1,
+            "rules are cached until reload"
+        );
+        assert_eq!(futures::executor::block_on(repo.reload()).unwrap(), 2);
+        assert_eq!(load_rules_from(&repo).len(), 2);
+
+        write(&keywords, "not a rule\n");
+        assert!(futures::executor::block_on(repo.reload()).is_err());
+        assert_eq!(load_rules_from(&repo).len(), 2);
+    }
+
+    #[test]
+    fn loads_share_rules_until_a_reload_starts_a_new_generation() {
+        let temp = tempfile::tempdir().unwrap();
+        write(
+            &temp.path().join("keywords.txt"),
+            "INSTR_OVERRIDE|20|Override|ignore previous\n",
+        );
+        let repo = FileRuleRepository::new(temp.path());
+        let load = || futures::executor::block_on(repo.load_rules_with_generation()).unwrap();
+
+        let (first, generation) = load();
+        let (second, same) = load();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(generation, same);
+
+        futures::executor::block_on(repo.reload()).unwrap();
+        let (reloaded, next) = load();
+        assert!(!Arc::ptr_eq(&first, &reloaded));
+        assert_ne!(generation, next);
+    }
+
+    #[cfg(feature = "formats")]
+    #[test]
+    fn queries_rules_by_family_and_tag() {
+        let temp = tempfile::tempdir().unwrap();
+        write(
+            &temp.path().join("keywords.txt"),
+            "DATA_EXFIL|30|Exfil|api key\nINSTR_OVERRIDE|25|Override|ignore previous\n",
+        );
+        write(
+            &temp.path().join("patterns.json"),
+            r#"[{"id": "DATA_DUMP", "description": "d", "pattern": "dump\\s+db",
+                 "weight": 20, "tags": ["Exfiltration", "database"]}]"#,
+        );
+        write(
+            &temp.path().join("rules.csv"),
+            "id,kind,weight,window,description,pattern,tags\n\
+             CODE_SHELL,regex,40,,Shell,run\\s+bash,execution; exfiltration\n",
+        );
+
+        let repo = FileRuleRepository::new(temp.path());
+        let ids = |rules: Vec<Rule>| rules.into_iter().map(|rule| rule.id).collect::<Vec<_>>();
+
+        let data = futures::executor::block_on(repo.rules_by_family("data")).unwrap();
+        assert_eq!(ids(data), vec!["DATA_EXFIL", "DATA_DUMP"]);
+        let exfil = futures::executor::block_on(repo.rules_by_tag("EXFILTRATION")).unwrap();
+        assert_eq!(ids(exfil), vec!["DATA_DUMP", "CODE_SHELL"]);
+        assert!(futures::executor::block_on(repo.rules_by_tag("unknown"))
+            .unwrap()
+            .is_empty());
+        let rule = futures::executor::block_on(repo.get_rule("CODE_SHELL"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(rule.tags, vec!["execution", "exfiltration"]);
+    }
+
     #[test]
     fn loads_sample_rule_pack_from_repo() {
         let repo_path = Path::new(env!("CARGO_MANIFEST_DIR"))
@@ -251,9 +1208,9 @@ DATA_EXFIL|30|Tries to exfiltrate secrets|api key
                 .expect("keyword rules should parse");
 
             prop_assert_eq!(rules.len(), entries.len());
-            for rule in rules {
+            for rule in rules.iter() {
                 prop_assert!(rule.weight >= 0.0 && rule.weight <= 100.0);
-                prop_assert_eq!(rule.kind, RuleKind::Keyword);
+                prop_assert_eq!(&rule.kind, &RuleKind::Keyword);
             }
         }
     }
@@ -292,9 +1249,9 @@ DATA_EXFIL|30|Tries to exfiltrate secrets|api key
                 .expect("pattern rules should parse");
 
             prop_assert_eq!(rules.len(), entries.len());
-            for rule in rules {
+            for rule in rules.iter() {
                 prop_assert!(rule.weight >= 0.0 && rule.weight <= 100.0);
-                prop_assert_eq!(rule.kind, RuleKind::Regex);
+                prop_assert_eq!(&rule.kind, &RuleKind::Regex);
             }
         }
     }
@@ -0,0 +1,93 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// Heuristic scores, inclusive, for which the LLM is consulted.
+///
+/// Inputs scoring below `min` are clearly safe and inputs above `max` clearly malicious, so a
+/// verdict would rarely change the outcome; skipping them cuts provider cost and latency in
+/// batch and tail scans.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GrayZone {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl GrayZone {
+    pub fn new(min: f32, max: f32) -> Result<Self, String> {
+        if !(0.0..=100.0).contains(&min) || !(0.0..=100.0).contains(&max) {
+            return Err(format!("gray zone bounds must be 0–100 (got {min}-{max})"));
+        }
+        if min > max {
+            return Err(format!("gray zone minimum {min} exceeds maximum {max}"));
+        }
+        Ok(Self { min, max })
+    }
+
+    pub fn contains(&self, score: f32) -> bool {
+        (self.min..=self.max).contains(&score)
+    }
+}
+
+impl FromStr for GrayZone {
+    type Err = String;
+
+    /// `MIN-MAX` or `MIN..MAX`, e.g. `15-70`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let (min, max) = value
+            .split_once("..")
+            .or_else(|| value.split_once('-'))
+            .ok_or_else(|| format!("invalid gray zone `{value}` (expected MIN-MAX, e.g. 15-70)"))?;
+        let bound = |text: &str| {
+            text.trim()
+                .parse::<f32>()
+                .map_err(|_| format!("invalid gray zone bound `{}`", text.trim()))
+        };
+        Self::new(bound(min)?, bound(max)?)
+    }
+}
+
+impl fmt::Display for GrayZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.min, self.max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ranges() {
+        let zone: GrayZone = "15-70".parse().unwrap();
+        assert_eq!(
+            zone,
+            GrayZone {
+                min: 15.0,
+                max: 70.0
+            }
+        );
+        assert_eq!(" 20.5..60 ".parse::<GrayZone>().unwrap().min, 20.5);
+        assert_eq!(zone.to_string(), "15-70");
+        assert_eq!(zone.to_string().parse::<GrayZone>().unwrap(), zone);
+    }
+
+    #[test]
+    fn rejects_invalid_ranges() {
+        assert!("70-15".parse::<GrayZone>().is_err());
+        assert!("15".parse::<GrayZone>().is_err());
+        assert!("15-120".parse::<GrayZone>().is_err());
+        assert!("low-high".parse::<GrayZone>().is_err());
+    }
+
+    #[test]
+    fn bounds_are_inclusive() {
+        let zone = GrayZone::new(15.0, 70.0).unwrap();
+        assert!(zone.contains(15.0));
+        assert!(zone.contains(70.0));
+        assert!(!zone.contains(14.9));
+        assert!(!zone.contains(70.1));
+    }
+}
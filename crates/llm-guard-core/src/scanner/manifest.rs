@@ -0,0 +1,225 @@
+use std::{
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "provenance")]
+use sha2::{Digest, Sha256};
+
+use super::file_repository::PACK_FILES;
+
+/// File name of the manifest inside a pack directory.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// `manifest.json` describing the rule files of a pack, their checksums, and where the pack
+/// came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackManifest {
+    /// Release of the pack (free-form, e.g. `2024.06` or `1.4.0`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// URL (or other locator) of the upstream pack.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub files: Vec<ManifestEntry>,
+}
+
+/// A rule file covered by a manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// File name relative to the pack directory (e.g. `patterns.json`).
+    pub path: String,
+    /// Lowercase hex SHA-256 of the file contents.
+    pub sha256: String,
+}
+
+/// Provenance of a manifest-backed pack, embedded in scan reports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PackProvenance {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// SHA-256 over the manifest entries; equal digests mean byte-identical rule files.
+    pub digest: String,
+}
+
+impl PackManifest {
+    /// Describe every rule file present in `dir`.
+    pub fn generate(dir: &Path, author: Option<String>, source: Option<String>) -> Result<Self> {
+        let mut files = Vec::new();
+        for name in PACK_FILES {
+            let path = dir.join(name);
+            if path.is_file() {
+                files.push(ManifestEntry {
+                    path: name.to_string(),
+                    sha256: sha256_file(&path)?,
+                });
+            }
+        }
+        if files.is_empty() {
+            bail!("no rule files found in {}", dir.display());
+        }
+        Ok(Self {
+            version: None,
+            author,
+            source,
+            files,
+        })
+    }
+
+    /// Read the manifest shipped with the pack in `dir`, if any.
+    pub fn load(dir: &Path) -> Result<Option<Self>> {
+        let path = dir.join(MANIFEST_FILE);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let raw = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read rule pack manifest at {}", path.display()))?;
+        let manifest = serde_json::from_str(&raw)
+            .with_context(|| format!("invalid rule pack manifest at {}", path.display()))?;
+        Ok(Some(manifest))
+    }
+
+    /// Write the manifest into `dir`, returning the written path.
+    pub fn write(&self, dir: &Path) -> Result<PathBuf> {
+        let path = dir.join(MANIFEST_FILE);
+        let mut body = serde_json::to_string_pretty(self)?;
+        body.push('\n');
+        fs::write(&path, body)
+            .with_context(|| format!("failed to write rule pack manifest at {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Check every listed checksum and reject rule files the manifest does not cover.
+    pub fn verify(&self, dir: &Path) -> Result<()> {
+        for entry in &self.files {
+            if !PACK_FILES.contains(&entry.path.as_str()) {
+                bail!(
+                    "manifest in {} lists unsupported rule file `{}`",
+                    dir.display(),
+                    entry.path
+                );
+            }
+            let path = dir.join(&entry.path);
+            let actual = sha256_file(&path)?;
+            if !actual.eq_ignore_ascii_case(&entry.sha256) {
+                bail!(
+                    "checksum mismatch for {} (manifest {}, actual {actual})",
+                    path.display(),
+                    entry.sha256
+                );
+            }
+        }
+        for name in PACK_FILES {
+            if dir.join(name).is_file() && !self.files.iter().any(|entry| entry.path == name) {
+                bail!(
+                    "rule file {} is not listed in {}",
+                    dir.join(name).display(),
+                    dir.join(MANIFEST_FILE).display()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Provenance record for a pack loaded under `name`.
+    pub fn provenance(&self, name: &str) -> Result<PackProvenance> {
+        let mut listing = String::new();
+        for entry in &self.files {
+            let _ = writeln!(
+                listing,
+                "{} {}",
+                entry.path,
+                entry.sha256.to_ascii_lowercase()
+            );
+        }
+        Ok(PackProvenance {
+            name: name.to_string(),
+            version: self.version.clone(),
+            author: self.author.clone(),
+            source: self.source.clone(),
+            digest: sha256_hex(listing.as_bytes())?,
+        })
+    }
+}
+
+fn sha256_file(path: &Path) -> Result<String> {
+    let bytes =
+        fs::read(path).with_context(|| format!("failed to read rule file {}", path.display()))?;
+    sha256_hex(&bytes)
+}
+
+/// Lowercase hex SHA-256 of `bytes`.
+#[cfg(feature = "provenance")]
+pub(crate) fn sha256_hex(bytes: &[u8]) -> Result<String> {
+    Ok(to_hex(&Sha256::digest(bytes)))
+}
+
+/// Fallback when the `provenance` feature is disabled.
+#[cfg(not(feature = "provenance"))]
+pub(crate) fn sha256_hex(_bytes: &[u8]) -> Result<String> {
+    bail!("rule pack checksums require the `provenance` feature")
+}
+
+#[cfg(any(feature = "provenance", feature = "signing"))]
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut out, byte| {
+            let _ = write!(out, "{byte:02x}");
+            out
+        })
+}
+
+#[cfg(all(test, feature = "provenance"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_manifest_verifies_until_files_change() {
+        let temp = tempfile::tempdir().unwrap();
+        fs::write(temp.path().join("keywords.txt"), "A_RULE|10|d|alpha\n").unwrap();
+        let manifest = PackManifest::generate(
+            temp.path(),
+            Some("Security Team".into()),
+            Some("https://example.com/packs/base".into()),
+        )
+        .unwrap();
+        assert_eq!(manifest.files.len(), 1);
+        assert_eq!(manifest.files[0].sha256.len(), 64);
+        manifest.write(temp.path()).unwrap();
+
+        let loaded = PackManifest::load(temp.path()).unwrap().unwrap();
+        assert_eq!(loaded, manifest);
+        loaded.verify(temp.path()).unwrap();
+        let provenance = loaded.provenance("base").unwrap();
+        assert_eq!(provenance.author.as_deref(), Some("Security Team"));
+        assert_eq!(
+            provenance.digest,
+            manifest.provenance("other").unwrap().digest
+        );
+
+        fs::write(temp.path().join("keywords.txt"), "A_RULE|10|d|beta\n").unwrap();
+        let err = loaded.verify(temp.path()).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"));
+
+        let regenerated = PackManifest::generate(temp.path(), None, None).unwrap();
+        fs::write(
+            temp.path().join("rules.csv"),
+            "id,kind,weight,window,description,pattern\n",
+        )
+        .unwrap();
+        let err = regenerated.verify(temp.path()).unwrap_err();
+        assert!(err.to_string().contains("is not listed"));
+    }
+}
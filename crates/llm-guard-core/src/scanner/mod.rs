@@ -1,11 +1,24 @@
+use std::{borrow::Cow, collections::BTreeMap, fmt, sync::Arc};
+
 use anyhow::Result as AnyResult;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use bands::BandSet;
+use blend::LlmAdjustment;
+use manifest::PackProvenance;
+use redact::ExcerptRedaction;
+
+pub mod bands;
+pub mod baseline;
+pub mod blend;
 pub mod default_scanner;
 /// Byte span within the scanned text `(start, end)` where `start <= end`.
 pub mod file_repository;
+pub mod gray_zone;
+pub mod manifest;
+pub mod redact;
 
 pub type Span = (usize, usize);
 
@@ -25,33 +38,38 @@ impl Default for RiskThresholds {
     }
 }
 
-/// Classification buckets for overall risk scoring.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum RiskBand {
-    Low,
-    Medium,
-    High,
+/// The band a risk score falls in: its name and its position in the [`BandSet`] that
+/// classified it, lowest band first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RiskBand {
+    pub name: String,
+    pub index: usize,
 }
 
 impl RiskBand {
-    /// Map a numeric risk score (0–100) into a risk band.
+    /// Map a numeric risk score (0–100) into the built-in `low`/`medium`/`high` bands.
     pub fn from_score(score: f32) -> Self {
-        Self::from_score_with_thresholds(score, &RiskThresholds::default())
+        BandSet::default().band(score)
     }
 
-    /// Map a numeric risk score using caller-provided thresholds.
+    /// Map a numeric risk score into the built-in bands at caller-provided thresholds.
     pub fn from_score_with_thresholds(score: f32, thresholds: &RiskThresholds) -> Self {
-        if score >= thresholds.high {
-            Self::High
-        } else if score >= thresholds.medium {
-            Self::Medium
-        } else {
-            Self::Low
-        }
+        BandSet::from_thresholds(thresholds).band(score)
+    }
+
+    /// Band name as configured (e.g. `warning`).
+    pub fn name(&self) -> &str {
+        &self.name
     }
 }
 
+/// Family of a rule id: the segment before the first `_`, ignoring any `pack:` namespace.
+pub fn rule_family(rule_id: &str) -> &str {
+    let local = rule_id.rsplit(':').next().unwrap_or(rule_id);
+    local.split('_').next().unwrap_or(local)
+}
+
 /// Distinguishes between literal keyword and regular-expression rules.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -75,6 +93,15 @@ pub struct Rule {
     pub weight: f32,
     /// Optional character window to capture around matches.
     pub window: Option<usize>,
+    /// Marks a retired rule; loaders warn about it and strict mode rejects it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
+    /// Identifier of the rule that supersedes a deprecated rule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replaced_by: Option<String>,
+    /// Free-form labels (e.g. `exfiltration`, `jailbreak`) used for selective queries.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 impl Rule {
@@ -94,11 +121,40 @@ impl Rule {
             pattern: pattern.into(),
             weight,
             window,
+            deprecated: false,
+            replaced_by: None,
+            tags: Vec::new(),
         };
         rule.validate()?;
         Ok(rule)
     }
 
+    /// Attach tags, normalised to trimmed lowercase with blanks dropped.
+    pub fn with_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.tags = tags
+            .into_iter()
+            .map(|tag| tag.as_ref().trim().to_ascii_lowercase())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        self
+    }
+
+    /// Family this rule belongs to (see [`rule_family`]).
+    pub fn family(&self) -> &str {
+        rule_family(&self.id)
+    }
+
+    /// Mark the rule as deprecated, optionally naming its replacement.
+    pub fn deprecate(mut self, replaced_by: Option<String>) -> Self {
+        self.deprecated = true;
+        self.replaced_by = replaced_by;
+        self
+    }
+
     /// Validate invariants for existing rule definitions.
     pub fn validate(&self) -> Result<(), RuleValidationError> {
         if self.id.trim().is_empty() {
@@ -143,11 +199,32 @@ pub enum RuleValidationError {
 
 /// A feature triggered during scanning.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Finding {
     pub rule_id: String,
     pub span: Span,
     pub excerpt: String,
     pub weight: f32,
+    /// Byte range of the match inside `excerpt`, when it survived excerpt truncation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excerpt_span: Option<Span>,
+    /// Line and column where the match starts in the scanned text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<Position>,
+}
+
+/// 1-based line and column of a byte offset; columns count characters, not bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
 }
 
 impl Finding {
@@ -180,26 +257,194 @@ pub enum FindingValidationError {
 }
 
 /// Optional LLM verdict that augments the heuristic risk score.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct LlmVerdict {
     pub label: String,
     pub rationale: String,
     pub mitigation: String,
+    /// Provider that produced the verdict when it came from a fallback chain.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// Per-provider votes when the verdict was combined by an ensemble.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ensemble: Option<EnsembleSummary>,
+    /// Tokens the provider billed for this verdict, when it reports them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
+    /// Latency and retry details of the provider call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<EnrichmentMeta>,
+}
+
+/// Timing and retry details of the provider call behind a verdict.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EnrichmentMeta {
+    /// Wall-clock time of the call, including retries and backoff.
+    pub latency_ms: u64,
+    /// HTTP attempts made; absent for providers that handle their own requests (rig).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attempts: Option<u32>,
+    /// HTTP status of the final attempt; absent alongside `attempts`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_status: Option<u16>,
+}
+
+/// Token counts reported by a provider, with an estimated cost once priced.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// Prompt tokens served from the provider's prompt cache; included in `prompt_tokens`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_tokens: Option<u64>,
+    /// Prompt tokens written to the provider's prompt cache; included in `prompt_tokens`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_tokens: Option<u64>,
+    /// Estimated cost in USD from the configured price table.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+}
+
+impl TokenUsage {
+    pub fn new(prompt_tokens: u64, completion_tokens: u64) -> Self {
+        Self {
+            prompt_tokens,
+            completion_tokens,
+            cache_read_tokens: None,
+            cache_write_tokens: None,
+            cost_usd: None,
+        }
+    }
+
+    /// Record prompt-cache activity; zero counts are kept as `None`.
+    pub fn with_cache(mut self, read_tokens: u64, write_tokens: u64) -> Self {
+        self.cache_read_tokens = (read_tokens > 0).then_some(read_tokens);
+        self.cache_write_tokens = (write_tokens > 0).then_some(write_tokens);
+        self
+    }
+
+    /// `None` when the provider reported no tokens at all, which usually means it omitted
+    /// usage rather than answering for free.
+    pub fn reported(prompt_tokens: u64, completion_tokens: u64) -> Option<Self> {
+        (prompt_tokens > 0 || completion_tokens > 0)
+            .then(|| Self::new(prompt_tokens, completion_tokens))
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+
+    /// Add another call's usage; the cost stays unknown only if neither side was priced.
+    pub fn accumulate(&mut self, other: &TokenUsage) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.cache_read_tokens = add_counts(self.cache_read_tokens, other.cache_read_tokens);
+        self.cache_write_tokens = add_counts(self.cache_write_tokens, other.cache_write_tokens);
+        self.cost_usd = match (self.cost_usd, other.cost_usd) {
+            (None, None) => None,
+            (left, right) => Some(left.unwrap_or(0.0) + right.unwrap_or(0.0)),
+        };
+    }
+}
+
+fn add_counts(left: Option<u64>, right: Option<u64>) -> Option<u64> {
+    match (left, right) {
+        (None, None) => None,
+        (left, right) => Some(left.unwrap_or(0) + right.unwrap_or(0)),
+    }
+}
+
+/// How the providers of an ensemble voted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EnsembleSummary {
+    pub votes: Vec<ProviderVote>,
+    /// Providers that answered did not all return the same label.
+    pub disagreement: bool,
+}
+
+/// One ensemble member's label, or the error that kept it from voting.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ProviderVote {
+    pub provider: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 /// End-to-end report produced by the scanner pipeline.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ScanReport {
     pub risk_score: f32,
     pub findings: Vec<Finding>,
     pub normalized_len: usize,
     pub risk_band: RiskBand,
     pub llm_verdict: Option<LlmVerdict>,
+    /// Why `llm_verdict` is missing when a provider failure was tolerated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub llm_degraded: Option<LlmDegradation>,
+    /// Whether the LLM was asked for a verdict, recorded when a [`gray_zone::GrayZone`] limits
+    /// enrichment to uncertain scores; `Some(false)` means the score fell outside it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub llm_consulted: Option<bool>,
+    /// Points the verdict label added to (or removed from) the heuristic score, when its
+    /// configured `score_adjustment` is non-zero.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verdict_adjustment: Option<f32>,
     pub score_breakdown: ScoreBreakdown,
+    /// Where the scanned text came from (file path, `stdin`, ...), when the caller knows it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Manifest-backed rule packs that produced this report.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rule_packs: Vec<PackProvenance>,
+    /// When and with which engine and rule set the report was produced.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ReportMetadata>,
+    /// Set when finding excerpts were replaced (see [`ScanReport::redact_excerpts`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redaction: Option<ExcerptRedaction>,
+    /// Findings dropped by [`ScanReport::truncate_findings`], counted per rule family.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub truncated_findings: BTreeMap<String, usize>,
+}
+
+/// A report that fell back to the heuristic score because the LLM could not be consulted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct LlmDegradation {
+    /// Provider error, or the open circuit that skipped the call.
+    pub reason: String,
+    /// The call was skipped because the provider's circuit breaker was open.
+    #[serde(default)]
+    pub circuit_open: bool,
+}
+
+/// Audit metadata recorded by the scanner with every report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ReportMetadata {
+    /// Scan time as an RFC 3339 UTC timestamp.
+    pub scanned_at: String,
+    /// Version of the llm-guard engine that produced the report.
+    pub engine_version: String,
+    /// Number of rules active during the scan.
+    pub rule_count: usize,
+    /// SHA-256 over the active rules (id, kind, weight, window, pattern) in load order.
+    pub rule_set_digest: String,
+    /// SHA-256 of the scanned text, identifying the input without storing it.
+    pub input_digest: String,
 }
 
 /// Contribution of a rule family (prefix before `_`) toward the overall score.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct FamilyContribution {
     pub family: String,
     pub occurrences: usize,
@@ -209,11 +454,16 @@ pub struct FamilyContribution {
 
 /// Rich scoring metadata supporting explainability and downstream reporting.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ScoreBreakdown {
     pub raw_total: f32,
     pub adjusted_total: f32,
     pub length_factor: f32,
     pub family_contributions: Vec<FamilyContribution>,
+    /// Score change from blending in the LLM verdict (see [`ScanReport::apply_verdict_blend`]);
+    /// not part of [`ScoreBreakdown::risk_score`], which stays heuristic-only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub llm_adjustment: Option<LlmAdjustment>,
 }
 
 impl ScoreBreakdown {
@@ -230,6 +480,9 @@ pub struct RiskConfig {
     pub min_length_factor: f32,
     pub max_length_factor: f32,
     pub family_dampening: f32,
+    /// Optional custom band taxonomy layered on top of `thresholds`.
+    #[serde(default)]
+    pub bands: Option<BandSet>,
 }
 
 impl Default for RiskConfig {
@@ -240,11 +493,77 @@ impl Default for RiskConfig {
             min_length_factor: 0.5,
             max_length_factor: 1.5,
             family_dampening: 0.5,
+            bands: None,
         }
     }
 }
 
+/// Errors emitted while validating a scoring configuration.
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum RiskConfigError {
+    #[error("threshold `{name}` must be within 0.0..=100.0 (got {value})")]
+    InvalidThreshold { name: &'static str, value: f32 },
+    #[error("medium threshold ({medium}) must not exceed the high threshold ({high})")]
+    InvertedThresholds { medium: f32, high: f32 },
+    #[error("`{name}` must be a positive number (got {value})")]
+    InvalidLengthFactor { name: &'static str, value: f32 },
+    #[error("min_length_factor ({min}) must not exceed max_length_factor ({max})")]
+    InvertedLengthFactors { min: f32, max: f32 },
+    #[error("family_dampening must be within 0.0..=1.0 (got {0})")]
+    InvalidDampening(f32),
+}
+
 impl RiskConfig {
+    /// Bands reports are classified into: the custom `bands`, else the built-in three at
+    /// `thresholds`.
+    pub fn band_set(&self) -> Cow<'_, BandSet> {
+        match &self.bands {
+            Some(bands) => Cow::Borrowed(bands),
+            None => Cow::Owned(BandSet::from_thresholds(&self.thresholds)),
+        }
+    }
+
+    /// The band covering `score` under this configuration.
+    pub fn band(&self, score: f32) -> RiskBand {
+        self.band_set().band(score)
+    }
+
+    /// Check the invariants scoring relies on, e.g. before applying operator-supplied values.
+    pub fn validate(&self) -> Result<(), RiskConfigError> {
+        for (name, value) in [
+            ("medium", self.thresholds.medium),
+            ("high", self.thresholds.high),
+        ] {
+            if !(0.0..=100.0).contains(&value) {
+                return Err(RiskConfigError::InvalidThreshold { name, value });
+            }
+        }
+        if self.thresholds.medium > self.thresholds.high {
+            return Err(RiskConfigError::InvertedThresholds {
+                medium: self.thresholds.medium,
+                high: self.thresholds.high,
+            });
+        }
+        for (name, value) in [
+            ("min_length_factor", self.min_length_factor),
+            ("max_length_factor", self.max_length_factor),
+        ] {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(RiskConfigError::InvalidLengthFactor { name, value });
+            }
+        }
+        if self.min_length_factor > self.max_length_factor {
+            return Err(RiskConfigError::InvertedLengthFactors {
+                min: self.min_length_factor,
+                max: self.max_length_factor,
+            });
+        }
+        if !(0.0..=1.0).contains(&self.family_dampening) {
+            return Err(RiskConfigError::InvalidDampening(self.family_dampening));
+        }
+        Ok(())
+    }
+
     pub fn length_factor(&self, text_len: usize) -> f32 {
         if self.baseline_chars == 0 {
             return 1.0;
@@ -270,7 +589,15 @@ impl ScanReport {
             findings,
             normalized_len,
             llm_verdict,
+            llm_degraded: None,
+            llm_consulted: None,
+            verdict_adjustment: None,
             score_breakdown,
+            source: None,
+            rule_packs: Vec::new(),
+            metadata: None,
+            redaction: None,
+            truncated_findings: BTreeMap::new(),
         }
     }
 
@@ -288,19 +615,126 @@ impl ScanReport {
             findings,
             normalized_len,
             llm_verdict,
+            llm_degraded: None,
+            llm_consulted: None,
+            verdict_adjustment: None,
             score_breakdown: breakdown,
+            source: None,
+            rule_packs: Vec::new(),
+            metadata: None,
+            redaction: None,
+            truncated_findings: BTreeMap::new(),
         }
     }
+
+    /// Classify the report into the band of `bands` covering its score.
+    pub fn apply_bands(&mut self, bands: &BandSet) {
+        self.risk_band = bands.band(self.risk_score);
+    }
+
+    /// Shift the score by a verdict label's adjustment and re-derive the band under `config`.
+    pub fn apply_verdict_adjustment(&mut self, points: f32, config: &RiskConfig) {
+        self.risk_score = (self.risk_score + points).clamp(0.0, 100.0);
+        self.risk_band = config.band(self.risk_score);
+        self.verdict_adjustment = Some(points);
+    }
+
+    /// Drop findings weighing less than `min_weight`, returning how many were removed.
+    ///
+    /// Scores are left untouched; rescore the report to exclude the dropped findings.
+    pub fn retain_min_weight(&mut self, min_weight: f32) -> usize {
+        let before = self.findings.len();
+        self.findings.retain(|finding| finding.weight >= min_weight);
+        before - self.findings.len()
+    }
+
+    /// Keep only the `max` heaviest findings (in their original order), recording how many
+    /// were dropped per family in `truncated_findings`. Returns the number dropped.
+    ///
+    /// Scores are left untouched so the report still reflects every match.
+    pub fn truncate_findings(&mut self, max: usize) -> usize {
+        if self.findings.len() <= max {
+            return 0;
+        }
+        let mut order: Vec<usize> = (0..self.findings.len()).collect();
+        order.sort_by(|&a, &b| {
+            self.findings[b]
+                .weight
+                .total_cmp(&self.findings[a].weight)
+                .then(a.cmp(&b))
+        });
+        let mut keep = vec![false; self.findings.len()];
+        for &idx in &order[..max] {
+            keep[idx] = true;
+        }
+        let mut keep = keep.into_iter();
+        let mut dropped = 0;
+        let truncated = &mut self.truncated_findings;
+        self.findings.retain(|finding| {
+            let kept = keep.next().unwrap_or(false);
+            if !kept {
+                *truncated
+                    .entry(rule_family(&finding.rule_id).to_string())
+                    .or_default() += 1;
+                dropped += 1;
+            }
+            kept
+        });
+        dropped
+    }
+
+    /// Total findings dropped by [`ScanReport::truncate_findings`].
+    pub fn truncated_count(&self) -> usize {
+        self.truncated_findings.values().sum()
+    }
 }
 
 /// Abstraction over rule loading so different backends (files, HTTP, in-memory) can be swapped transparently.
 #[async_trait]
 pub trait RuleRepository: Send + Sync {
-    /// Retrieve the full rule set currently active.
-    async fn load_rules(&self) -> AnyResult<Vec<Rule>>;
+    /// Retrieve the full rule set currently active, shared rather than copied.
+    async fn load_rules(&self) -> AnyResult<Arc<[Rule]>>;
+
+    /// [`load_rules`](Self::load_rules) together with the generation of the returned set: a
+    /// number that changes whenever the rules may have, so callers can key caches on it instead
+    /// of hashing every rule. `None` when the repository cannot tell.
+    async fn load_rules_with_generation(&self) -> AnyResult<(Arc<[Rule]>, Option<u64>)> {
+        Ok((self.load_rules().await?, None))
+    }
 
     /// Fetch a single rule by identifier if it exists.
     async fn get_rule(&self, rule_id: &str) -> AnyResult<Option<Rule>>;
+
+    /// Rules whose family (id prefix before `_`) matches `family`, case-insensitively.
+    async fn rules_by_family(&self, family: &str) -> AnyResult<Vec<Rule>> {
+        let rules = self.load_rules().await?;
+        Ok(rules
+            .iter()
+            .filter(|rule| rule.family().eq_ignore_ascii_case(family))
+            .cloned()
+            .collect())
+    }
+
+    /// Rules carrying `tag`, case-insensitively.
+    async fn rules_by_tag(&self, tag: &str) -> AnyResult<Vec<Rule>> {
+        let rules = self.load_rules().await?;
+        Ok(rules
+            .iter()
+            .filter(|rule| rule.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+            .cloned()
+            .collect())
+    }
+
+    /// Force a refresh from the backing store (e.g. on SIGHUP), returning the number of rules now
+    /// active. Repositories without a cache simply reload.
+    async fn reload(&self) -> AnyResult<usize> {
+        Ok(self.load_rules().await?.len())
+    }
+
+    /// Provenance of the manifest-backed packs behind the current rule set.
+    fn provenance(&self) -> Vec<PackProvenance> {
+        Vec::new()
+    }
 }
 
 /// Primary scanning interface that transforms raw text into a structured report.
@@ -320,6 +754,7 @@ pub trait VerdictProvider: Send + Sync {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use bands::BandDefinition;
     use proptest::prelude::*;
 
     #[test]
@@ -331,6 +766,9 @@ mod tests {
             pattern: "override".into(),
             weight: 150.0,
             window: None,
+            deprecated: false,
+            replaced_by: None,
+            tags: Vec::new(),
         };
 
         let err = rule.validate().expect_err("should reject weight > 100");
@@ -364,6 +802,8 @@ mod tests {
             span: (10, 2),
             excerpt: "oops".into(),
             weight: 10.0,
+            excerpt_span: None,
+            position: None,
         };
         let err = finding
             .validate()
@@ -381,6 +821,7 @@ mod tests {
             adjusted_total: 120.0,
             length_factor: 1.2,
             family_contributions: Vec::new(),
+            llm_adjustment: None,
         };
         let report = ScanReport::from_breakdown(
             Vec::new(),
@@ -390,15 +831,134 @@ mod tests {
             &RiskThresholds::default(),
         );
         assert!((report.risk_score - 100.0).abs() < f32::EPSILON);
-        assert_eq!(report.risk_band, RiskBand::High);
+        assert_eq!(report.risk_band.name(), "high");
+    }
+
+    #[test]
+    fn retain_min_weight_keeps_score() {
+        let finding = |weight| Finding {
+            rule_id: "TEST".into(),
+            span: (0, 1),
+            excerpt: "x".into(),
+            weight,
+            excerpt_span: None,
+            position: None,
+        };
+        let mut report = ScanReport::new(
+            30.0,
+            vec![finding(20.0), finding(5.0), finding(10.0)],
+            10,
+            None,
+            ScoreBreakdown::default(),
+        );
+        assert_eq!(report.retain_min_weight(10.0), 1);
+        assert_eq!(report.findings.len(), 2);
+        assert_eq!(report.risk_score, 30.0);
+    }
+
+    #[test]
+    fn truncate_findings_keeps_heaviest_and_counts_families() {
+        let finding = |rule_id: &str, weight| Finding {
+            rule_id: rule_id.into(),
+            span: (0, 1),
+            excerpt: "x".into(),
+            weight,
+            excerpt_span: None,
+            position: None,
+        };
+        let mut report = ScanReport::new(
+            60.0,
+            vec![
+                finding("URL_HINT", 5.0),
+                finding("INSTR_OVERRIDE", 20.0),
+                finding("URL_HINT", 5.0),
+                finding("PROMPT_LEAK", 30.0),
+                finding("INSTR_RESET", 10.0),
+            ],
+            10,
+            None,
+            ScoreBreakdown::default(),
+        );
+        assert_eq!(report.truncate_findings(2), 3);
+        let kept: Vec<_> = report.findings.iter().map(|f| f.rule_id.as_str()).collect();
+        assert_eq!(kept, ["INSTR_OVERRIDE", "PROMPT_LEAK"]);
+        assert_eq!(report.truncated_findings["URL"], 2);
+        assert_eq!(report.truncated_findings["INSTR"], 1);
+        assert_eq!(report.truncated_count(), 3);
+        assert_eq!(report.risk_score, 60.0);
+        assert_eq!(report.truncate_findings(5), 0);
+    }
+
+    #[test]
+    fn verdict_adjustment_rebands_and_clamps() {
+        let mut report = ScanReport::new(20.0, Vec::new(), 10, None, ScoreBreakdown::default());
+        let config = RiskConfig {
+            bands: Some(
+                BandSet::new(vec![
+                    BandDefinition::new("info", 0.0, 0),
+                    BandDefinition::new("warning", 50.0, 3),
+                ])
+                .unwrap(),
+            ),
+            ..RiskConfig::default()
+        };
+        report.apply_verdict_adjustment(45.0, &config);
+        assert_eq!(report.risk_score, 65.0);
+        assert_eq!(report.risk_band.name(), "warning");
+        assert_eq!(report.risk_band.index, 1);
+        assert_eq!(report.verdict_adjustment, Some(45.0));
+
+        report.apply_verdict_adjustment(-90.0, &config);
+        assert_eq!(report.risk_score, 0.0);
+        assert_eq!(report.risk_band.name(), "info");
     }
 
     #[test]
     fn risk_band_thresholds_match_spec() {
-        assert_eq!(RiskBand::from_score(10.0), RiskBand::Low);
-        assert_eq!(RiskBand::from_score(25.0), RiskBand::Medium);
-        assert_eq!(RiskBand::from_score(59.9), RiskBand::Medium);
-        assert_eq!(RiskBand::from_score(60.0), RiskBand::High);
+        for (score, name, index) in [
+            (10.0, "low", 0),
+            (25.0, "medium", 1),
+            (59.9, "medium", 1),
+            (60.0, "high", 2),
+        ] {
+            let band = RiskBand::from_score(score);
+            assert_eq!((band.name(), band.index), (name, index));
+        }
+    }
+
+    #[test]
+    fn risk_config_validation_rejects_inverted_and_out_of_range_values() {
+        assert_eq!(RiskConfig::default().validate(), Ok(()));
+        let inverted = RiskConfig {
+            thresholds: RiskThresholds {
+                medium: 70.0,
+                high: 40.0,
+            },
+            ..RiskConfig::default()
+        };
+        assert_eq!(
+            inverted.validate(),
+            Err(RiskConfigError::InvertedThresholds {
+                medium: 70.0,
+                high: 40.0
+            })
+        );
+        let factors = RiskConfig {
+            min_length_factor: 2.0,
+            ..RiskConfig::default()
+        };
+        assert!(matches!(
+            factors.validate(),
+            Err(RiskConfigError::InvertedLengthFactors { .. })
+        ));
+        let dampening = RiskConfig {
+            family_dampening: 1.5,
+            ..RiskConfig::default()
+        };
+        assert_eq!(
+            dampening.validate(),
+            Err(RiskConfigError::InvalidDampening(1.5))
+        );
     }
 
     proptest! {
@@ -412,6 +972,7 @@ mod tests {
                 adjusted_total,
                 length_factor,
                 family_contributions: Vec::new(),
+                llm_adjustment: None,
             };
 
             let score = breakdown.risk_score();
@@ -433,6 +994,7 @@ mod tests {
                 min_length_factor: min_factor,
                 max_length_factor: max_factor,
                 family_dampening: 0.5,
+                bands: None,
             };
 
             let factor = config.length_factor(text_len);
@@ -0,0 +1,111 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "provenance")]
+use super::baseline::finding_fingerprint;
+use super::{Finding, ScanReport};
+
+/// How [`ScanReport::redact_excerpts`] rewrites finding excerpts before a report is shared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ExcerptRedaction {
+    /// Replace the excerpt with `[redacted: N chars]`.
+    Mask,
+    /// Replace the excerpt with `sha256:<fingerprint>`, the same value baselines store, so
+    /// identical findings can still be correlated across reports.
+    #[cfg(feature = "provenance")]
+    Hash,
+}
+
+impl ExcerptRedaction {
+    /// Redacted stand-in for `finding`'s excerpt.
+    pub fn apply(&self, finding: &Finding) -> String {
+        match self {
+            Self::Mask => format!("[redacted: {} chars]", finding.excerpt.chars().count()),
+            #[cfg(feature = "provenance")]
+            Self::Hash => format!("sha256:{}", finding_fingerprint(finding)),
+        }
+    }
+}
+
+impl FromStr for ExcerptRedaction {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "mask" => Ok(Self::Mask),
+            #[cfg(feature = "provenance")]
+            "hash" => Ok(Self::Hash),
+            #[cfg(not(feature = "provenance"))]
+            "hash" => Err("hash redaction requires the `provenance` feature".into()),
+            other => Err(format!(
+                "unknown redaction mode `{other}` (expected mask or hash)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ExcerptRedaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Mask => "mask",
+            #[cfg(feature = "provenance")]
+            Self::Hash => "hash",
+        })
+    }
+}
+
+impl ScanReport {
+    /// Replace every finding excerpt so the report no longer carries scanned text.
+    ///
+    /// Spans, positions, and scores are kept; the report records the mode in `redaction`.
+    pub fn redact_excerpts(&mut self, mode: ExcerptRedaction) {
+        for finding in &mut self.findings {
+            finding.excerpt = mode.apply(finding);
+            finding.excerpt_span = None;
+        }
+        self.redaction = Some(mode);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::ScoreBreakdown;
+
+    fn report() -> ScanReport {
+        let finding = Finding {
+            rule_id: "PROMPT_LEAK".into(),
+            span: (4, 27),
+            excerpt: "reveal the system prompt".into(),
+            weight: 30.0,
+            excerpt_span: Some((0, 6)),
+            position: None,
+        };
+        ScanReport::new(30.0, vec![finding], 40, None, ScoreBreakdown::default())
+    }
+
+    #[test]
+    fn mask_hides_excerpt_text() {
+        let mut report = report();
+        report.redact_excerpts(ExcerptRedaction::Mask);
+        let finding = &report.findings[0];
+        assert_eq!(finding.excerpt, "[redacted: 24 chars]");
+        assert_eq!(finding.excerpt_span, None);
+        assert_eq!(finding.span, (4, 27));
+        assert_eq!(report.redaction, Some(ExcerptRedaction::Mask));
+    }
+
+    #[cfg(feature = "provenance")]
+    #[test]
+    fn hash_matches_baseline_fingerprint() {
+        let mut report = report();
+        let fingerprint = finding_fingerprint(&report.findings[0]);
+        report.redact_excerpts(ExcerptRedaction::Hash);
+        assert_eq!(report.findings[0].excerpt, format!("sha256:{fingerprint}"));
+        assert!("HASH".parse::<ExcerptRedaction>().is_ok());
+        assert!("blur".parse::<ExcerptRedaction>().is_err());
+    }
+}
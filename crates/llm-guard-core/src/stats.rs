@@ -0,0 +1,216 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::scanner::{Rule, ScanReport};
+
+/// Per-rule counters accumulated across scans.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuleHitStats {
+    /// Total number of findings emitted for the rule.
+    pub hits: u64,
+    /// Number of scans in which the rule fired at least once.
+    pub scans_hit: u64,
+    /// Sum of finding weights contributed by the rule.
+    pub total_weight: f64,
+}
+
+/// Opt-in collector recording which rules fired across a batch or tail session.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuleStats {
+    /// Number of reports recorded.
+    pub scans: u64,
+    /// Counters keyed by rule identifier.
+    pub rules: BTreeMap<String, RuleHitStats>,
+}
+
+/// Flattened view of a rule's statistics, including rules that never fired.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleStatsRow {
+    pub rule_id: String,
+    pub hits: u64,
+    pub scans_hit: u64,
+    /// Average weight the rule contributed to scans in which it fired.
+    pub avg_contribution: f32,
+    /// `false` when the rule is no longer part of the active rule set.
+    pub active: bool,
+}
+
+impl RuleStats {
+    /// Record the findings of a single scan report.
+    pub fn record(&mut self, report: &ScanReport) {
+        self.scans += 1;
+        let mut per_rule: HashMap<&str, (u64, f64)> = HashMap::new();
+        for finding in &report.findings {
+            let entry = per_rule.entry(finding.rule_id.as_str()).or_default();
+            entry.0 += 1;
+            entry.1 += f64::from(finding.weight);
+        }
+        for (rule_id, (hits, weight)) in per_rule {
+            let entry = self.rules.entry(rule_id.to_string()).or_default();
+            entry.hits += hits;
+            entry.scans_hit += 1;
+            entry.total_weight += weight;
+        }
+    }
+
+    /// Fold previously persisted statistics into this collector.
+    pub fn merge(&mut self, other: RuleStats) {
+        self.scans += other.scans;
+        for (rule_id, stats) in other.rules {
+            let entry = self.rules.entry(rule_id).or_default();
+            entry.hits += stats.hits;
+            entry.scans_hit += stats.scans_hit;
+            entry.total_weight += stats.total_weight;
+        }
+    }
+
+    /// Load statistics from disk, returning an empty collector when the file does not exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("failed to read rule statistics from {}", path.display()))?;
+        serde_json::from_str(&raw)
+            .with_context(|| format!("invalid rule statistics file at {}", path.display()))
+    }
+
+    /// Merge this session into the statistics stored at `path` and write the result back.
+    pub fn persist(&self, path: &Path) -> Result<()> {
+        let mut combined = Self::load(path)?;
+        combined.merge(self.clone());
+        let raw = serde_json::to_string_pretty(&combined)?;
+        fs::write(path, raw)
+            .with_context(|| format!("failed to write rule statistics to {}", path.display()))
+    }
+
+    /// Produce one row per known rule, sorted by hit count (descending) then id.
+    ///
+    /// Rules in `rules` that never fired are included with zero hits so pack curators can
+    /// spot dead weight; rules recorded in the statistics but missing from `rules` are
+    /// flagged as inactive.
+    pub fn rows(&self, rules: &[Rule]) -> Vec<RuleStatsRow> {
+        let mut rows: Vec<RuleStatsRow> = rules
+            .iter()
+            .map(|rule| self.row_for(&rule.id, true))
+            .collect();
+        for rule_id in self.rules.keys() {
+            if !rules.iter().any(|rule| &rule.id == rule_id) {
+                rows.push(self.row_for(rule_id, false));
+            }
+        }
+        rows.sort_by(|a, b| {
+            b.hits
+                .cmp(&a.hits)
+                .then_with(|| {
+                    b.avg_contribution
+                        .partial_cmp(&a.avg_contribution)
+                        .unwrap_or(Ordering::Equal)
+                })
+                .then_with(|| a.rule_id.cmp(&b.rule_id))
+        });
+        rows
+    }
+
+    fn row_for(&self, rule_id: &str, active: bool) -> RuleStatsRow {
+        let stats = self.rules.get(rule_id).cloned().unwrap_or_default();
+        let avg_contribution = if stats.scans_hit == 0 {
+            0.0
+        } else {
+            (stats.total_weight / stats.scans_hit as f64) as f32
+        };
+        RuleStatsRow {
+            rule_id: rule_id.to_string(),
+            hits: stats.hits,
+            scans_hit: stats.scans_hit,
+            avg_contribution,
+            active,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{Finding, RiskThresholds, RuleKind, ScoreBreakdown};
+
+    fn report_with(rule_ids: &[(&str, f32)]) -> ScanReport {
+        let findings = rule_ids
+            .iter()
+            .enumerate()
+            .map(|(idx, (id, weight))| Finding {
+                rule_id: (*id).to_string(),
+                span: (idx, idx + 1),
+                excerpt: "x".into(),
+                weight: *weight,
+                excerpt_span: None,
+                position: None,
+            })
+            .collect();
+        ScanReport::from_breakdown(
+            findings,
+            10,
+            None,
+            ScoreBreakdown::default(),
+            &RiskThresholds::default(),
+        )
+    }
+
+    fn rule(id: &str) -> Rule {
+        Rule::new(id, "desc", RuleKind::Keyword, "pattern", 10.0, None).unwrap()
+    }
+
+    #[test]
+    fn records_hits_and_scans() {
+        let mut stats = RuleStats::default();
+        stats.record(&report_with(&[("A", 10.0), ("A", 10.0), ("B", 30.0)]));
+        stats.record(&report_with(&[("A", 10.0)]));
+        stats.record(&report_with(&[]));
+
+        assert_eq!(stats.scans, 3);
+        let a = &stats.rules["A"];
+        assert_eq!(a.hits, 3);
+        assert_eq!(a.scans_hit, 2);
+        assert!((a.total_weight - 30.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn rows_include_rules_that_never_fire() {
+        let mut stats = RuleStats::default();
+        stats.record(&report_with(&[("A", 20.0)]));
+        stats.record(&report_with(&[("RETIRED", 5.0)]));
+
+        let rows = stats.rows(&[rule("A"), rule("QUIET")]);
+        let ids: Vec<_> = rows.iter().map(|row| row.rule_id.as_str()).collect();
+        assert_eq!(ids, vec!["A", "RETIRED", "QUIET"]);
+        assert_eq!(rows[2].hits, 0);
+        assert!(!rows[1].active);
+        assert!((rows[0].avg_contribution - 20.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn persist_merges_with_existing_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("stats.json");
+
+        let mut first = RuleStats::default();
+        first.record(&report_with(&[("A", 10.0)]));
+        first.persist(&path).unwrap();
+
+        let mut second = RuleStats::default();
+        second.record(&report_with(&[("A", 10.0), ("B", 5.0)]));
+        second.persist(&path).unwrap();
+
+        let loaded = RuleStats::load(&path).unwrap();
+        assert_eq!(loaded.scans, 2);
+        assert_eq!(loaded.rules["A"].hits, 2);
+        assert_eq!(loaded.rules["B"].scans_hit, 1);
+    }
+}
@@ -34,7 +34,7 @@ async fn scan_fixture(name: &str) -> serde_json::Value {
     json!({
         "fixture": name,
         "risk_score": report.risk_score,
-        "risk_band": report.risk_band,
+        "risk_band": report.risk_band.name(),
         "normalized_len": report.normalized_len,
         "findings": report.findings.iter().map(|finding| json!({
             "rule_id": finding.rule_id,